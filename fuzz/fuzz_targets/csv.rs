@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ypbank_parser::{parse_statements_bytes_with_limits, Format, Limits};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_statements_bytes_with_limits(data, Format::Csv, None, &Limits::default());
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ypbank_parser::{
+    parse_statements_bytes_with_limits, Format, Limits, Mt940Reader, Mt940StatementRef,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let limits = Limits::default();
+    let _ = parse_statements_bytes_with_limits(data, Format::Mt940, None, &limits);
+
+    // Разбор без копирования и потоковое чтение используют собственный код
+    // разбиения на блоки, поэтому проверяются отдельно.
+    if let Ok(content) = std::str::from_utf8(data) {
+        let _ = Mt940StatementRef::parse_with_limits(content, &limits);
+    }
+    for statement in Mt940Reader::new(data).with_limits(limits) {
+        let _ = statement;
+    }
+});
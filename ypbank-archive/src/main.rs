@@ -0,0 +1,191 @@
+//! CLI-утилита для загрузки банковских выписок в архив SQLite.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::Path;
+use std::process;
+
+use ypbank_parser::{Archive, Format, TextEncoding, parse_statements_bytes};
+
+/// Поддерживаемые форматы выписок.
+#[derive(Clone, Copy, ValueEnum)]
+enum FormatArg {
+    /// MT940 (SWIFT)
+    Mt940,
+    /// CAMT.053 (ISO 20022 XML)
+    Camt053,
+    /// CSV
+    Csv,
+    /// 1С:Предприятие (1CClientBankExchange)
+    #[value(name = "1c", alias = "onec")]
+    OneC,
+    /// OFX / QFX (Open Financial Exchange)
+    #[value(alias = "qfx")]
+    Ofx,
+    /// BAI2 (Cash Management Balance Reporting)
+    #[value(name = "bai2", alias = "bai")]
+    Bai2,
+    /// QIF (Quicken Interchange Format)
+    Qif,
+    /// CODA (бельгийский формат)
+    #[value(alias = "cod")]
+    Coda,
+    /// Norma 43 (испанский формат AEB)
+    #[value(name = "norma43", alias = "n43", alias = "aeb43")]
+    Norma43,
+    /// CFONB 120 (французский формат)
+    #[value(name = "cfonb120", alias = "cfonb")]
+    Cfonb120,
+    /// JSON-документ с выписками
+    Json,
+    /// NDJSON: заголовок выписки и транзакции по одной на строку
+    #[value(alias = "jsonl")]
+    Ndjson,
+    /// Книга Excel (XLSX)
+    #[value(alias = "excel")]
+    Xlsx,
+}
+
+impl From<FormatArg> for Format {
+    fn from(arg: FormatArg) -> Self {
+        match arg {
+            FormatArg::Mt940 => Format::Mt940,
+            FormatArg::Camt053 => Format::Camt053,
+            FormatArg::Csv => Format::Csv,
+            FormatArg::OneC => Format::OneC,
+            FormatArg::Ofx => Format::Ofx,
+            FormatArg::Bai2 => Format::Bai2,
+            FormatArg::Qif => Format::Qif,
+            FormatArg::Coda => Format::Coda,
+            FormatArg::Norma43 => Format::Norma43,
+            FormatArg::Cfonb120 => Format::Cfonb120,
+            FormatArg::Json => Format::Json,
+            FormatArg::Ndjson => Format::Ndjson,
+            FormatArg::Xlsx => Format::Xlsx,
+        }
+    }
+}
+
+/// YPBank Archive - архив банковских выписок.
+///
+/// Сохраняет выписки в локальную базу SQLite, пропуская уже загруженные
+/// транзакции и предупреждая о пересекающихся периодах.
+#[derive(Parser)]
+#[command(name = "ypbank-archive")]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Файл базы архива
+    #[arg(long = "db", default_value = "ypbank-archive.sqlite", global = true)]
+    db: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Загрузить выписки из файлов в архив
+    Import {
+        /// Файлы выписок
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Формат файлов (по умолчанию определяется по расширению)
+        #[arg(long = "format", short = 'f', value_enum)]
+        format: Option<FormatArg>,
+
+        /// Кодировка файлов: utf-8, windows-1251, cp866 (по умолчанию определяется автоматически)
+        #[arg(long = "encoding")]
+        encoding: Option<TextEncoding>,
+    },
+}
+
+/// Определяет формат по расширению файла.
+fn format_from_extension(path: &str) -> Option<Format> {
+    let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "sta" | "940" | "mt940" => Some(Format::Mt940),
+        "txt" => None,
+        "xlsx" => Some(Format::Xlsx),
+        other => Format::parse(other),
+    }
+}
+
+fn import_file(
+    archive: &mut Archive,
+    path: &str,
+    format: Option<Format>,
+    encoding: Option<TextEncoding>,
+) -> Result<(), String> {
+    let format = format.or_else(|| format_from_extension(path)).ok_or_else(|| {
+        format!("Не удалось определить формат файла '{}', укажите --format", path)
+    })?;
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Не удалось прочитать файл '{}': {}", path, e))?;
+    let statements = parse_statements_bytes(&bytes, format, encoding)
+        .map_err(|e| format!("Ошибка в файле '{}': {}", path, e))?;
+
+    for statement in &statements {
+        let report = archive
+            .import(statement, Some(path))
+            .map_err(|e| format!("Не удалось загрузить '{}': {}", path, e))?;
+        let period = format!(
+            "{} — {}",
+            statement.opening_balance.date, statement.closing_balance.date
+        );
+
+        if report.new_statement {
+            println!(
+                "{}: счет {}, {}: добавлено {}, дубликатов {}",
+                path, statement.account.number, period, report.inserted, report.duplicates
+            );
+        } else {
+            println!(
+                "{}: счет {}, {}: выписка уже в архиве (#{}), пропущена",
+                path, statement.account.number, period, report.statement_id
+            );
+        }
+        for overlap in &report.overlaps {
+            println!(
+                "  Предупреждение: период пересекается с выпиской #{} ({} — {}{})",
+                overlap.statement_id,
+                overlap.start,
+                overlap.end,
+                overlap.source.as_deref().map(|s| format!(", {}", s)).unwrap_or_default()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut archive = match Archive::open(&args.db) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Ошибка: не удалось открыть архив '{}': {}", args.db, e);
+            process::exit(1);
+        }
+    };
+
+    match args.command {
+        Command::Import {
+            files,
+            format,
+            encoding,
+        } => {
+            let format = format.map(Format::from);
+            let mut failed = false;
+            for path in &files {
+                if let Err(e) = import_file(&mut archive, path, format, encoding) {
+                    eprintln!("Ошибка: {}", e);
+                    failed = true;
+                }
+            }
+            if failed {
+                process::exit(1);
+            }
+        }
+    }
+}
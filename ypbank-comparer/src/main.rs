@@ -5,7 +5,10 @@ use std::fs::File;
 use std::io::Read;
 use std::process;
 
-use ypbank_parser::{Format, Statement, Transaction, parse_statement};
+use ypbank_parser::{
+    Amount, Format, MultiCurrencyTotal, Statement, TextEncoding, Transaction, encoding,
+    parse_statement,
+};
 
 /// Поддерживаемые форматы выписок.
 #[derive(Clone, Copy, ValueEnum)]
@@ -16,6 +19,31 @@ enum FormatArg {
     Camt053,
     /// CSV
     Csv,
+    /// 1С:Предприятие (1CClientBankExchange)
+    #[value(name = "1c", alias = "onec")]
+    OneC,
+    /// OFX / QFX (Open Financial Exchange)
+    #[value(alias = "qfx")]
+    Ofx,
+    /// BAI2 (Cash Management Balance Reporting)
+    #[value(name = "bai2", alias = "bai")]
+    Bai2,
+    /// QIF (Quicken Interchange Format)
+    Qif,
+    /// CODA (бельгийский формат)
+    #[value(alias = "cod")]
+    Coda,
+    /// Norma 43 (испанский формат AEB)
+    #[value(name = "norma43", alias = "n43", alias = "aeb43")]
+    Norma43,
+    /// CFONB 120 (французский формат)
+    #[value(name = "cfonb120", alias = "cfonb")]
+    Cfonb120,
+    /// JSON-документ с выписками
+    Json,
+    /// NDJSON: заголовок выписки и транзакции по одной на строку
+    #[value(alias = "jsonl")]
+    Ndjson,
 }
 
 impl From<FormatArg> for Format {
@@ -24,6 +52,15 @@ impl From<FormatArg> for Format {
             FormatArg::Mt940 => Format::Mt940,
             FormatArg::Camt053 => Format::Camt053,
             FormatArg::Csv => Format::Csv,
+            FormatArg::OneC => Format::OneC,
+            FormatArg::Ofx => Format::Ofx,
+            FormatArg::Bai2 => Format::Bai2,
+            FormatArg::Qif => Format::Qif,
+            FormatArg::Coda => Format::Coda,
+            FormatArg::Norma43 => Format::Norma43,
+            FormatArg::Cfonb120 => Format::Cfonb120,
+            FormatArg::Json => Format::Json,
+            FormatArg::Ndjson => Format::Ndjson,
         }
     }
 }
@@ -54,27 +91,55 @@ struct Args {
     /// Подробный вывод
     #[arg(short, long)]
     verbose: bool,
+
+    /// Кодировка входных файлов: utf-8, windows-1251, cp866 (по умолчанию определяется автоматически)
+    #[arg(long = "encoding")]
+    encoding: Option<TextEncoding>,
 }
 
-fn read_file(path: &str) -> Result<String, String> {
-    let mut content = String::new();
+fn read_file(path: &str, text_encoding: Option<TextEncoding>) -> Result<String, String> {
+    let mut bytes = Vec::new();
     let mut file =
         File::open(path).map_err(|e| format!("Не удалось открыть файл '{}': {}", path, e))?;
-    file.read_to_string(&mut content)
+    file.read_to_end(&mut bytes)
         .map_err(|e| format!("Не удалось прочитать файл '{}': {}", path, e))?;
-    Ok(content)
+    encoding::decode(&bytes, text_encoding)
+        .map_err(|e| format!("Не удалось декодировать файл '{}': {}", path, e))
 }
 
 struct ComparisonResult {
     matched: Vec<(usize, usize)>,
+    /// Пары из `matched`, совпавшие только по сумме в исходной валюте.
+    fx_matched: usize,
     only_in_first: Vec<usize>,
     only_in_second: Vec<usize>,
 }
 
+/// Все суммы транзакции, по которым ее можно сопоставить:
+/// сумма проводки, инструктированная, исходная и встречная суммы.
+fn candidate_amounts(tx: &Transaction) -> impl Iterator<Item = &Amount> {
+    std::iter::once(&tx.amount)
+        .chain(tx.instructed_amount.as_ref())
+        .chain(tx.original_amount.as_ref())
+        .chain(tx.counter_value_amount.as_ref())
+}
+
+/// Совпадение по сумме в исходной валюте для валютных платежей.
+fn fx_amounts_match(tx1: &Transaction, tx2: &Transaction) -> bool {
+    candidate_amounts(tx1).any(|a1| {
+        candidate_amounts(tx2).any(|a2| a1.value == a2.value && a1.currency == a2.currency)
+    })
+}
+
+/// Совпадение суммы проводки с учетом направления.
+fn signed_amounts_match(tx1: &Transaction, tx2: &Transaction) -> bool {
+    tx1.signed_amount().value == tx2.signed_amount().value
+}
+
 fn transactions_match(tx1: &Transaction, tx2: &Transaction) -> bool {
     tx1.date == tx2.date
-        && tx1.amount.value == tx2.amount.value
         && tx1.is_credit == tx2.is_credit
+        && (signed_amounts_match(tx1, tx2) || fx_amounts_match(tx1, tx2))
 }
 
 fn calculate_match_score(tx1: &Transaction, tx2: &Transaction) -> u32 {
@@ -83,8 +148,10 @@ fn calculate_match_score(tx1: &Transaction, tx2: &Transaction) -> u32 {
     if tx1.date == tx2.date {
         score += 10;
     }
-    if tx1.amount.value == tx2.amount.value {
+    if signed_amounts_match(tx1, tx2) {
         score += 10;
+    } else if fx_amounts_match(tx1, tx2) {
+        score += 7;
     }
     if tx1.is_credit == tx2.is_credit {
         score += 5;
@@ -145,8 +212,14 @@ fn compare_statements(stmt1: &Statement, stmt2: &Statement) -> ComparisonResult
         .filter_map(|(i, &used)| if !used { Some(i) } else { None })
         .collect();
 
+    let fx_matched = matched
+        .iter()
+        .filter(|(i, j)| !signed_amounts_match(&stmt1.transactions[*i], &stmt2.transactions[*j]))
+        .count();
+
     ComparisonResult {
         matched,
+        fx_matched,
         only_in_first,
         only_in_second,
     }
@@ -161,15 +234,41 @@ fn format_transaction(tx: &Transaction) -> String {
         tx.description.clone()
     };
 
-    format!(
-        "{} {} {:.2} {} | {} | {}",
-        tx.date,
-        tx_type,
-        tx.amount.as_float(),
-        tx.amount.currency,
-        reference,
-        description
-    )
+    let mut line = format!(
+        "{} {} {} | {} | {}",
+        tx.date, tx_type, tx.amount, reference, description
+    );
+
+    if let Some(original) = tx.instructed_amount.as_ref().or(tx.original_amount.as_ref()) {
+        if original.currency != tx.amount.currency || original.value != tx.amount.value {
+            line.push_str(&format!(" | исх.: {}", original));
+        }
+    }
+
+    for rate in &tx.exchange_rates {
+        let target = rate.target_currency.as_deref().unwrap_or(&tx.amount.currency);
+        line.push_str(&format!(" | курс {}/{}: {}", rate.source_currency, target, rate.rate));
+    }
+
+    if !tx.charges.is_empty() {
+        let charges: Vec<String> = tx
+            .charges
+            .iter()
+            .map(|c| c.amount.to_string())
+            .collect();
+        line.push_str(&format!(" | комиссии: {}", charges.join(", ")));
+    }
+
+    line
+}
+
+/// Сумма операций выписки со знаком по каждой валюте.
+fn net_turnover(stmt: &Statement) -> ypbank_parser::Result<MultiCurrencyTotal> {
+    let mut total = MultiCurrencyTotal::new();
+    for tx in &stmt.transactions {
+        total.add(&tx.signed_amount())?;
+    }
+    Ok(total)
 }
 
 fn percent(part: usize, total: usize) -> f64 {
@@ -189,12 +288,25 @@ fn print_results(result: &ComparisonResult, stmt1: &Statement, stmt2: &Statement
 
     println!("Транзакций в файле 1: {}", total1);
     println!("Транзакций в файле 2: {}", total2);
+    for (label, stmt) in [("1", stmt1), ("2", stmt2)] {
+        match net_turnover(stmt) {
+            Ok(total) if total.is_empty() => {}
+            Ok(total) => println!("Сумма операций в файле {}: {}", label, total),
+            Err(e) => println!("Сумма операций в файле {}: {}", label, e),
+        }
+    }
     println!();
     println!(
         "Совпадающих транзакций: {} ({:.1}%)",
         result.matched.len(),
         percent(result.matched.len(), total1)
     );
+    if result.fx_matched > 0 {
+        println!(
+            "  из них по сумме в исходной валюте: {}",
+            result.fx_matched
+        );
+    }
     println!(
         "Только в файле 1: {} ({:.1}%)",
         result.only_in_first.len(),
@@ -242,7 +354,7 @@ fn print_results(result: &ComparisonResult, stmt1: &Statement, stmt2: &Statement
 fn main() {
     let args = Args::parse();
 
-    let content1 = match read_file(&args.file1) {
+    let content1 = match read_file(&args.file1, args.encoding) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Ошибка: {}", e);
@@ -259,7 +371,7 @@ fn main() {
         }
     };
 
-    let content2 = match read_file(&args.file2) {
+    let content2 = match read_file(&args.file2, args.encoding) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Ошибка: {}", e);
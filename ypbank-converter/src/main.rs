@@ -2,10 +2,16 @@
 
 use clap::{Parser, ValueEnum};
 use std::fs::File;
-use std::io::{self, Read, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 use std::process;
 
-use ypbank_parser::{Camt053Statement, Format, Mt940Statement, Statement, parse_statement};
+use ypbank_parser::{
+    AccountMapping, Bai2File, BeancountWriter, Camt053Statement, Camt053Writer, CsvProfile,
+    CsvStatement, Format, JsonWriter, LedgerWriter, Mt940Reader, Mt940Statement, Mt940Writer,
+    OfxStatement, OfxVersion, OfxWriter, OneCStatement, ParquetWriter, QifDateStyle, QifFile,
+    Statement, StatementReader, TextEncoding, XlsxWorkbook, XlsxWriter, encoding,
+    parse_statements,
+};
 
 /// Поддерживаемые форматы выписок.
 #[derive(Clone, Copy, ValueEnum)]
@@ -16,6 +22,63 @@ enum FormatArg {
     Camt053,
     /// CSV
     Csv,
+    /// 1С:Предприятие (1CClientBankExchange)
+    #[value(name = "1c", alias = "onec")]
+    OneC,
+    /// OFX / QFX (Open Financial Exchange)
+    #[value(alias = "qfx")]
+    Ofx,
+    /// BAI2 (Cash Management Balance Reporting)
+    #[value(name = "bai2", alias = "bai")]
+    Bai2,
+    /// QIF (Quicken Interchange Format)
+    Qif,
+    /// CODA (бельгийский формат) (только входной формат)
+    #[value(alias = "cod")]
+    Coda,
+    /// Norma 43 (испанский формат AEB) (только входной формат)
+    #[value(name = "norma43", alias = "n43", alias = "aeb43")]
+    Norma43,
+    /// CFONB 120 (французский формат) (только входной формат)
+    #[value(name = "cfonb120", alias = "cfonb")]
+    Cfonb120,
+    /// Журнал Beancount (только выходной формат)
+    #[value(alias = "bean")]
+    Beancount,
+    /// Журнал Ledger / hledger (только выходной формат)
+    #[value(alias = "hledger")]
+    Ledger,
+    /// JSON-документ с выписками
+    Json,
+    /// NDJSON: заголовок выписки и транзакции по одной на строку
+    #[value(alias = "jsonl")]
+    Ndjson,
+    /// Книга Excel
+    #[value(alias = "excel")]
+    Xlsx,
+    /// Таблица транзакций Parquet (только выходной формат)
+    #[value(alias = "pq")]
+    Parquet,
+}
+
+/// Версия выходного OFX.
+#[derive(Clone, Copy, ValueEnum)]
+enum OfxVersionArg {
+    /// OFX 1.x (SGML)
+    #[value(name = "1")]
+    V1,
+    /// OFX 2.x (XML)
+    #[value(name = "2")]
+    V2,
+}
+
+impl From<OfxVersionArg> for OfxVersion {
+    fn from(arg: OfxVersionArg) -> Self {
+        match arg {
+            OfxVersionArg::V1 => OfxVersion::V1,
+            OfxVersionArg::V2 => OfxVersion::V2,
+        }
+    }
 }
 
 impl From<FormatArg> for Format {
@@ -24,13 +87,28 @@ impl From<FormatArg> for Format {
             FormatArg::Mt940 => Format::Mt940,
             FormatArg::Camt053 => Format::Camt053,
             FormatArg::Csv => Format::Csv,
+            FormatArg::OneC => Format::OneC,
+            FormatArg::Ofx => Format::Ofx,
+            FormatArg::Bai2 => Format::Bai2,
+            FormatArg::Qif => Format::Qif,
+            FormatArg::Coda => Format::Coda,
+            FormatArg::Norma43 => Format::Norma43,
+            FormatArg::Cfonb120 => Format::Cfonb120,
+            FormatArg::Beancount => Format::Beancount,
+            FormatArg::Ledger => Format::Ledger,
+            FormatArg::Json => Format::Json,
+            FormatArg::Ndjson => Format::Ndjson,
+            FormatArg::Xlsx => Format::Xlsx,
+            FormatArg::Parquet => Format::Parquet,
         }
     }
 }
 
 /// YPBank Converter - конвертер банковских выписок.
 ///
-/// Поддерживает конвертацию между форматами MT940, CAMT.053 и CSV.
+/// Поддерживает конвертацию между форматами MT940, CAMT.053, CSV, 1С, OFX, BAI2, QIF,
+/// JSON, NDJSON и XLSX, чтение CODA, Norma 43 и CFONB 120, а также экспорт в журналы Beancount и Ledger
+/// и в Parquet.
 #[derive(Parser)]
 #[command(name = "ypbank-converter")]
 #[command(author, version, about, long_about = None)]
@@ -50,36 +128,275 @@ struct Args {
     /// Формат выходных данных
     #[arg(short = 't', long = "output-format", value_enum)]
     output_format: FormatArg,
+
+    /// Профиль входного CSV или листа XLSX: sber, tinkoff, alfa, vtb, generic или путь к файлу .toml/.json
+    #[arg(long = "csv-profile")]
+    csv_profile: Option<String>,
+
+    /// Кодировка входного файла: utf-8, windows-1251, cp866 (по умолчанию определяется автоматически)
+    #[arg(long = "encoding")]
+    encoding: Option<TextEncoding>,
+
+    /// Кодировка выходного файла: utf-8, windows-1251, cp866
+    #[arg(long = "output-encoding", default_value = "utf-8")]
+    output_encoding: TextEncoding,
+
+    /// Версия выходного OFX: 1 (SGML) или 2 (XML), по умолчанию 2
+    #[arg(long = "ofx-version", value_enum)]
+    ofx_version: Option<OfxVersionArg>,
+
+    /// Порядок полей даты во входном и выходном QIF: us (MM/DD/YYYY) или eu (DD/MM/YYYY)
+    #[arg(long = "qif-date-style", default_value = "us")]
+    qif_date_style: QifDateStyle,
+
+    /// Файл настройки счетов книги для Beancount/Ledger (.toml/.json)
+    #[arg(long = "account-map")]
+    account_map: Option<String>,
+
+    /// Число потоков разбора выписок MT940 и CAMT.053 (по умолчанию по числу ядер).
+    /// При значении больше 1 MT940 читается целиком, а не потоково
+    #[arg(short = 'j', long = "jobs", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: Option<u16>,
 }
 
-fn read_input(args: &Args) -> Result<String, String> {
-    let mut content = String::new();
+/// Параметры чтения и записи, не зависящие от пары форматов.
+struct ConvertOptions<'a> {
+    csv_profile: Option<&'a CsvProfile>,
+    output_encoding: TextEncoding,
+    ofx_version: Option<OfxVersion>,
+    qif_date_style: QifDateStyle,
+    account_mapping: AccountMapping,
+}
+
+fn load_csv_profile(spec: &str) -> Result<CsvProfile, String> {
+    if let Some(profile) = CsvProfile::builtin(spec) {
+        return Ok(profile);
+    }
+
+    CsvProfile::from_file(spec).map_err(|e| {
+        format!(
+            "Неизвестный профиль CSV '{}' (встроенные: {}): {}",
+            spec,
+            CsvProfile::BUILTIN.join(", "),
+            e
+        )
+    })
+}
+
+fn read_input(args: &Args) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
 
     if let Some(ref path) = args.input {
         let mut file = File::open(path)
             .map_err(|e| format!("Не удалось открыть файл '{}': {}", path, e))?;
-        file.read_to_string(&mut content)
+        file.read_to_end(&mut bytes)
             .map_err(|e| format!("Не удалось прочитать файл '{}': {}", path, e))?;
     } else {
         io::stdin()
-            .read_to_string(&mut content)
+            .read_to_end(&mut bytes)
             .map_err(|e| format!("Не удалось прочитать stdin: {}", e))?;
     }
 
-    Ok(content)
+    Ok(bytes)
+}
+
+/// Входные данные: потоковые форматы читаются построчно, остальные целиком.
+enum Input {
+    Stream(Box<dyn BufRead>),
+    Bytes(Vec<u8>),
+}
+
+fn open_input(args: &Args, input_format: Format) -> Result<Input, String> {
+    // Параллельный разбор MT940 требует всех блоков сразу
+    let parallel_mt940 = input_format == Format::Mt940 && args.jobs.is_some_and(|jobs| jobs > 1);
+    if !input_format.supports_streaming() || parallel_mt940 {
+        return read_input(args).map(Input::Bytes);
+    }
+
+    let reader: Box<dyn BufRead> = match args.input {
+        Some(ref path) => Box::new(BufReader::new(
+            File::open(path).map_err(|e| format!("Не удалось открыть файл '{}': {}", path, e))?,
+        )),
+        None => Box::new(io::stdin().lock()),
+    };
+    Ok(Input::Stream(reader))
+}
+
+/// Конвертирует MT940 и NDJSON по одной выписке, не загружая вход целиком.
+///
+/// Если выходной формат тоже потоковый, выписки записываются по мере чтения,
+/// иначе собираются и передаются общему writer.
+fn convert_stream<W: Write>(
+    reader: Box<dyn BufRead>,
+    input_encoding: Option<TextEncoding>,
+    input_format: Format,
+    output_format: Format,
+    options: &ConvertOptions,
+    writer: &mut W,
+) -> Result<(), String> {
+    let output_encoding = options.output_encoding;
+
+    if input_format == output_format {
+        // Тот же формат копируем построчно с перекодированием
+        let mut lines = encoding::TextReader::new(reader, input_encoding);
+        let mut writer = BufWriter::new(writer);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = lines
+                .read_line(&mut line)
+                .map_err(|e| format!("Ошибка чтения: {}", e))?;
+            if read == 0 {
+                break;
+            }
+            write_text(&line, output_encoding, &mut writer)?;
+        }
+        return writer.flush().map_err(|e| format!("Ошибка записи: {}", e));
+    }
+
+    let written = match (input_format, output_format) {
+        (Format::Mt940, Format::Camt053) => Camt053Writer::write_iter_with_encoding(
+            Mt940Reader::with_encoding(reader, input_encoding),
+            writer,
+            output_encoding,
+        ),
+        (_, Format::Camt053) => Camt053Writer::write_iter_with_encoding(
+            StatementReader::new(reader, input_format, input_encoding)
+                .map_err(|e| format!("Ошибка чтения: {}", e))?,
+            writer,
+            output_encoding,
+        ),
+        (_, Format::Mt940) => Mt940Writer::write_iter_with_encoding(
+            StatementReader::new(reader, input_format, input_encoding)
+                .map_err(|e| format!("Ошибка чтения: {}", e))?,
+            writer,
+            output_encoding,
+        ),
+        (_, Format::Ndjson) => JsonWriter::write_ndjson_iter_with_encoding(
+            StatementReader::new(reader, input_format, input_encoding)
+                .map_err(|e| format!("Ошибка чтения: {}", e))?,
+            writer,
+            output_encoding,
+        ),
+        _ => {
+            let statements = StatementReader::new(reader, input_format, input_encoding)
+                .and_then(|statements| statements.collect::<ypbank_parser::Result<Vec<_>>>())
+                .map_err(|e| format!("Ошибка парсинга {:?}: {}", input_format, e))?;
+            return write_statements(statements, output_format, options, writer);
+        }
+    };
+
+    written
+        .map(drop)
+        .map_err(|e| format!("Ошибка конвертации {:?} в {:?}: {}", input_format, output_format, e))
+}
+
+/// Читает выписки из книги Excel: свою раскладку листов или первый лист по профилю CSV.
+fn read_xlsx(bytes: &[u8], options: &ConvertOptions) -> Result<Vec<Statement>, String> {
+    let workbook = match options.csv_profile {
+        Some(profile) => XlsxWorkbook::parse_bytes_with_profile(bytes, profile),
+        None => XlsxWorkbook::parse_bytes(bytes),
+    };
+    workbook
+        .map(XlsxWorkbook::into_statements)
+        .map_err(|e| format!("Ошибка чтения XLSX: {}", e))
+}
+
+fn convert_input<W: Write>(
+    input: Input,
+    input_encoding: Option<TextEncoding>,
+    input_format: Format,
+    output_format: Format,
+    options: &ConvertOptions,
+    writer: &mut W,
+) -> Result<(), String> {
+    match input {
+        Input::Stream(reader) => {
+            convert_stream(reader, input_encoding, input_format, output_format, options, writer)
+        }
+        Input::Bytes(bytes) => {
+            convert_bytes(&bytes, input_encoding, input_format, output_format, options, writer)
+        }
+    }
+}
+
+/// Конвертирует входные байты: книги Excel читаются напрямую,
+/// остальные форматы предварительно декодируются в текст.
+fn convert_bytes<W: Write>(
+    bytes: &[u8],
+    input_encoding: Option<TextEncoding>,
+    input_format: Format,
+    output_format: Format,
+    options: &ConvertOptions,
+    writer: &mut W,
+) -> Result<(), String> {
+    if input_format == Format::Xlsx {
+        let statements = read_xlsx(bytes, options)?;
+        return write_statements(statements, output_format, options, writer);
+    }
+
+    let input_encoding = input_encoding.unwrap_or_else(|| TextEncoding::detect(bytes));
+    let content = encoding::decode(bytes, Some(input_encoding))
+        .map_err(|e| format!("Ошибка декодирования: {}", e))?;
+    convert_and_write(&content, input_encoding, input_format, output_format, options, writer)
 }
 
 fn convert_and_write<W: Write>(
     content: &str,
+    input_encoding: TextEncoding,
     input_format: Format,
     output_format: Format,
+    options: &ConvertOptions,
     writer: &mut W,
 ) -> Result<(), String> {
-    if input_format == output_format {
-        writer
-            .write_all(content.as_bytes())
-            .map_err(|e| format!("Ошибка записи: {}", e))?;
-        return Ok(());
+    let output_encoding = options.output_encoding;
+    let ofx_version = options.ofx_version;
+
+    // CSV с явным профилем нормализуем, а не копируем как есть
+    let normalize_csv = input_format == Format::Csv && options.csv_profile.is_some();
+    if input_format == output_format && !normalize_csv {
+        // Кодировка указана внутри CAMT.053, 1С и OFX, поэтому при ее смене
+        // документ пересобирается, а не копируется с прежней меткой
+        let reencode = input_encoding != output_encoding;
+        return match output_format {
+            Format::Ofx if ofx_version.is_some() || reencode => {
+                OfxStatement::parse(content)
+                    .and_then(|mut statements| {
+                        for statement in &mut statements {
+                            statement.version = ofx_version.unwrap_or(statement.version);
+                        }
+                        OfxWriter::write_all_to_with_encoding(&statements, writer, output_encoding)
+                    })
+                    .map_err(|e| format!("Ошибка перекодирования OFX: {}", e))
+            }
+            Format::Xlsx => Err("XLSX — двоичный формат и не читается из текста".to_string()),
+            Format::Beancount | Format::Ledger => {
+                Err("Beancount и Ledger поддерживаются только как выходные форматы".to_string())
+            }
+            Format::Parquet => Err("Parquet поддерживается только как выходной формат".to_string()),
+            Format::Camt053 if reencode => Camt053Statement::parse_all(content)
+                .and_then(|statements| {
+                    statements
+                        .iter()
+                        .try_for_each(|camt| camt.write_to_with_encoding(writer, output_encoding))
+                })
+                .map_err(|e| format!("Ошибка перекодирования CAMT.053: {}", e)),
+            Format::OneC if reencode => OneCStatement::parse(content)
+                .and_then(|onec| onec.write_to_with_encoding(writer, output_encoding))
+                .map_err(|e| format!("Ошибка перекодирования 1С: {}", e)),
+            Format::Mt940
+            | Format::Csv
+            | Format::Ofx
+            | Format::Bai2
+            | Format::Qif
+            | Format::Coda
+            | Format::Norma43
+            | Format::Cfonb120
+            | Format::Json
+            | Format::Ndjson
+            | Format::Camt053
+            | Format::OneC => write_text(content, output_encoding, writer),
+        };
     }
 
     match (input_format, output_format) {
@@ -88,76 +405,232 @@ fn convert_and_write<W: Write>(
                 .map_err(|e| format!("Ошибка парсинга MT940: {}", e))?;
             for mt940 in statements {
                 let camt: Camt053Statement = mt940.into();
-                camt.write_to(writer)
+                camt.write_to_with_encoding(writer, output_encoding)
                     .map_err(|e| format!("Ошибка записи CAMT.053: {}", e))?;
             }
         }
         (Format::Camt053, Format::Mt940) => {
-            let camt = Camt053Statement::parse(content)
+            let statements = Camt053Statement::parse_all(content)
                 .map_err(|e| format!("Ошибка парсинга CAMT.053: {}", e))?;
-            let mt940: Mt940Statement = camt
-                .try_into()
-                .map_err(|e| format!("Ошибка конвертации CAMT.053 в MT940: {}", e))?;
-            mt940
-                .write_to(writer)
-                .map_err(|e| format!("Ошибка записи MT940: {}", e))?;
-        }
-        (Format::Mt940, Format::Csv) | (Format::Camt053, Format::Csv) => {
-            let statement = parse_statement(content, input_format)
-                .map_err(|e| format!("Ошибка парсинга: {}", e))?;
-            write_csv(&statement, writer)?;
-        }
-        (Format::Csv, Format::Mt940) | (Format::Csv, Format::Camt053) => {
-            return Err("Конвертация из CSV в MT940/CAMT.053 не поддерживается".to_string());
-        }
-        (Format::Mt940, Format::Mt940)
-        | (Format::Camt053, Format::Camt053)
-        | (Format::Csv, Format::Csv) => {
-            unreachable!("Равные форматы обрабатываются выше");
+            for camt in statements {
+                let mt940: Mt940Statement = camt
+                    .try_into()
+                    .map_err(|e| format!("Ошибка конвертации CAMT.053 в MT940: {}", e))?;
+                mt940
+                    .write_to_with_encoding(writer, output_encoding)
+                    .map_err(|e| format!("Ошибка записи MT940: {}", e))?;
+            }
+        }
+        _ => {
+            let statements = read_statements(content, input_format, options)?;
+            write_statements(statements, output_format, options, writer)?;
         }
     }
 
     Ok(())
 }
 
-fn write_csv<W: Write>(statement: &Statement, writer: &mut W) -> Result<(), String> {
-    writeln!(writer, "Дата,Сумма,Валюта,Тип,Референс,Описание")
-        .map_err(|e| format!("Ошибка записи: {}", e))?;
-
-    for tx in &statement.transactions {
-        let tx_type = if tx.is_credit { "Поступление" } else { "Списание" };
-        let reference = tx.reference.as_deref().unwrap_or("");
-        let description = tx.description.replace(',', ";").replace('\n', " ");
+/// Записывает унифицированные выписки в выходной формат.
+fn write_statements<W: Write>(
+    statements: Vec<Statement>,
+    output_format: Format,
+    options: &ConvertOptions,
+    writer: &mut W,
+) -> Result<(), String> {
+    let output_encoding = options.output_encoding;
 
-        writeln!(
-            writer,
-            "{},{:.2},{},{},{},\"{}\"",
-            tx.date,
-            tx.amount.as_float(),
-            tx.amount.currency,
-            tx_type,
-            reference,
-            description
-        )
-        .map_err(|e| format!("Ошибка записи: {}", e))?;
+    match output_format {
+        Format::Csv => {
+            let statement = statements
+                .into_iter()
+                .next()
+                .ok_or_else(|| "Входные данные не содержат выписок".to_string())?;
+            let csv: CsvStatement = statement.into();
+            csv.write_to_with_encoding(writer, output_encoding)
+                .map_err(|e| format!("Ошибка записи CSV: {}", e))?;
+        }
+        Format::Mt940 => {
+            for statement in statements {
+                let mt940: Mt940Statement = statement.into();
+                mt940
+                    .write_to_with_encoding(writer, output_encoding)
+                    .map_err(|e| format!("Ошибка записи MT940: {}", e))?;
+            }
+        }
+        Format::Camt053 => {
+            for statement in statements {
+                let camt: Camt053Statement = statement.into();
+                camt.write_to_with_encoding(writer, output_encoding)
+                    .map_err(|e| format!("Ошибка записи CAMT.053: {}", e))?;
+            }
+        }
+        Format::OneC => {
+            let onec: OneCStatement = statements.into();
+            onec.write_to_with_encoding(writer, output_encoding)
+                .map_err(|e| format!("Ошибка записи 1С: {}", e))?;
+        }
+        Format::Ofx => {
+            let version = options.ofx_version.unwrap_or_default();
+            let statements: Vec<OfxStatement> = statements
+                .into_iter()
+                .map(|statement| OfxStatement {
+                    version,
+                    ..statement.into()
+                })
+                .collect();
+            OfxWriter::write_all_to_with_encoding(&statements, writer, output_encoding)
+                .map_err(|e| format!("Ошибка записи OFX: {}", e))?;
+        }
+        Format::Bai2 => {
+            let bai2: Bai2File = statements.into();
+            bai2.write_to_with_encoding(writer, output_encoding)
+                .map_err(|e| format!("Ошибка записи BAI2: {}", e))?;
+        }
+        Format::Qif => {
+            let qif = QifFile {
+                date_style: options.qif_date_style,
+                ..statements.into()
+            };
+            qif.write_to_with_encoding(writer, output_encoding)
+                .map_err(|e| format!("Ошибка записи QIF: {}", e))?;
+        }
+        Format::Beancount => {
+            BeancountWriter::write_to_with_encoding(
+                &statements,
+                &options.account_mapping,
+                writer,
+                output_encoding,
+            )
+            .map_err(|e| format!("Ошибка записи Beancount: {}", e))?;
+        }
+        Format::Ledger => {
+            LedgerWriter::write_to_with_encoding(
+                &statements,
+                &options.account_mapping,
+                writer,
+                output_encoding,
+            )
+            .map_err(|e| format!("Ошибка записи Ledger: {}", e))?;
+        }
+        Format::Coda => {
+            return Err("CODA поддерживается только как входной формат".to_string());
+        }
+        Format::Norma43 => {
+            return Err("Norma 43 поддерживается только как входной формат".to_string());
+        }
+        Format::Cfonb120 => {
+            return Err("CFONB 120 поддерживается только как входной формат".to_string());
+        }
+        Format::Json => {
+            JsonWriter::write_to_with_encoding(&statements, writer, output_encoding)
+                .map_err(|e| format!("Ошибка записи JSON: {}", e))?;
+        }
+        Format::Ndjson => {
+            JsonWriter::write_ndjson_to_with_encoding(&statements, writer, output_encoding)
+                .map_err(|e| format!("Ошибка записи NDJSON: {}", e))?;
+        }
+        Format::Xlsx => {
+            // Кодировка к двоичному формату не применяется
+            XlsxWriter::write_to(&statements, writer)
+                .map_err(|e| format!("Ошибка записи XLSX: {}", e))?;
+        }
+        Format::Parquet => {
+            ParquetWriter::write_to(&statements, writer)
+                .map_err(|e| format!("Ошибка записи Parquet: {}", e))?;
+        }
     }
 
     Ok(())
 }
 
+/// Записывает текст в выходной поток в заданной кодировке.
+fn write_text<W: Write>(
+    text: &str,
+    output_encoding: TextEncoding,
+    writer: &mut W,
+) -> Result<(), String> {
+    let bytes = encoding::encode(text, output_encoding)
+        .map_err(|e| format!("Ошибка кодирования: {}", e))?;
+    writer
+        .write_all(&bytes)
+        .map_err(|e| format!("Ошибка записи: {}", e))
+}
+
+/// Разбирает входные данные в унифицированные выписки с учетом профиля CSV
+/// и порядка дат QIF.
+fn read_statements(
+    content: &str,
+    format: Format,
+    options: &ConvertOptions,
+) -> Result<Vec<Statement>, String> {
+    if format == Format::Qif {
+        let qif = QifFile::parse_with_style(content, options.qif_date_style)
+            .map_err(|e| format!("Ошибка парсинга QIF: {}", e))?;
+        return qif
+            .into_statements()
+            .map_err(|e| format!("Ошибка парсинга QIF: {}", e));
+    }
+
+    if let (Format::Csv, Some(profile)) = (format, options.csv_profile) {
+        let csv = CsvStatement::parse_with_profile(content, profile)
+            .map_err(|e| format!("Ошибка парсинга CSV: {}", e))?;
+        let statement: Statement = csv
+            .try_into()
+            .map_err(|e| format!("Ошибка конвертации CSV: {}", e))?;
+        return Ok(vec![statement]);
+    }
+
+    parse_statements(content, format).map_err(|e| format!("Ошибка парсинга: {}", e))
+}
+
 fn main() {
     let args = Args::parse();
 
-    let content = match read_input(&args) {
-        Ok(c) => c,
+    let input_format: Format = args.input_format.into();
+    let output_format: Format = args.output_format.into();
+
+    if let Some(jobs) = args.jobs {
+        if let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs.into())
+            .build_global()
+        {
+            eprintln!("Ошибка: Не удалось создать пул потоков: {}", e);
+            process::exit(1);
+        }
+    }
+
+    let input = match open_input(&args, input_format) {
+        Ok(input) => input,
         Err(e) => {
             eprintln!("Ошибка: {}", e);
             process::exit(1);
         }
     };
 
-    let input_format: Format = args.input_format.into();
-    let output_format: Format = args.output_format.into();
+    let csv_profile = match args.csv_profile.as_deref().map(load_csv_profile).transpose() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Ошибка: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let account_mapping = match args.account_map.as_deref().map(AccountMapping::from_file) {
+        None => AccountMapping::default(),
+        Some(Ok(mapping)) => mapping,
+        Some(Err(e)) => {
+            eprintln!("Ошибка: Не удалось загрузить настройку счетов: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let options = ConvertOptions {
+        csv_profile: csv_profile.as_ref(),
+        output_encoding: args.output_encoding,
+        ofx_version: args.ofx_version.map(OfxVersion::from),
+        qif_date_style: args.qif_date_style,
+        account_mapping,
+    };
 
     let result = if let Some(ref path) = args.output {
         let mut file = match File::create(path) {
@@ -167,10 +640,10 @@ fn main() {
                 process::exit(1);
             }
         };
-        convert_and_write(&content, input_format, output_format, &mut file)
+        convert_input(input, args.encoding, input_format, output_format, &options, &mut file)
     } else {
         let mut stdout = io::stdout();
-        convert_and_write(&content, input_format, output_format, &mut stdout)
+        convert_input(input, args.encoding, input_format, output_format, &options, &mut stdout)
     };
 
     if let Err(e) = result {
@@ -0,0 +1,190 @@
+//! Сравнение владеющего и заимствующего разбора MT940 и замеры разбора
+//! CAMT.053 и CSV, поля которых сканируются без копирования.
+//!
+//! Запуск: `cargo bench -p ypbank-parser --bench parse`. Перед замерами
+//! скорости печатается число выделений памяти на одном проходе.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use ypbank_parser::{Camt053Statement, CsvProfile, CsvStatement, Mt940Statement, Mt940StatementRef};
+
+/// Аллокатор, считающий выделения памяти.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Синтетический файл MT940: `statements` выписок по `transactions` операций.
+fn synthetic_mt940(statements: usize, transactions: usize) -> String {
+    let mut content = String::new();
+    for s in 0..statements {
+        write!(
+            content,
+            "{{1:F01BANKRUMMAXXX0000000000}}{{2:I940BANKRUMMXXXXN}}{{4:\n\
+             :20:STMT{s:06}\n:25:40702810{s:012}\n:28C:{s}/1\n:60F:C240101RUB1000000,00\n"
+        )
+        .unwrap();
+        for t in 0..transactions {
+            write!(
+                content,
+                ":61:2401020102D{}{:02},00NTRFNONREF//TX{s:06}{t:04}\n\
+                 :86:Оплата по счету {t} от 02.01.2024\n",
+                t % 1000 + 1,
+                t % 100
+            )
+            .unwrap();
+        }
+        content.push_str(":62F:C240131RUB1000000,00\n-}\n");
+    }
+    content
+}
+
+/// Синтетический CAMT.053: `statements` выписок по `entries` записей.
+fn synthetic_camt053(statements: usize, entries: usize) -> String {
+    let mut content = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.02\">\n<BkToCstmrStmt>\n\
+         <GrpHdr><MsgId>BENCH</MsgId><CreDtTm>2024-01-31T18:00:00</CreDtTm></GrpHdr>\n",
+    );
+    for s in 0..statements {
+        write!(
+            content,
+            "<Stmt><Id>STMT{s:06}</Id>\
+             <Acct><Id><IBAN>DK80{s:014}</IBAN></Id><Ccy>DKK</Ccy></Acct>\n\
+             <Bal><Tp><CdOrPrtry><Cd>OPBD</Cd></CdOrPrtry></Tp><Amt Ccy=\"DKK\">10000.00</Amt>\
+             <CdtDbtInd>CRDT</CdtDbtInd><Dt><Dt>2024-01-01</Dt></Dt></Bal>\n\
+             <Bal><Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp><Amt Ccy=\"DKK\">10000.00</Amt>\
+             <CdtDbtInd>CRDT</CdtDbtInd><Dt><Dt>2024-01-31</Dt></Dt></Bal>\n"
+        )
+        .unwrap();
+        for e in 0..entries {
+            writeln!(
+                content,
+                "<Ntry><NtryRef>{e}</NtryRef><Amt Ccy=\"DKK\">{}.{:02}</Amt>\
+                 <CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts><BookgDt><Dt>2024-01-15</Dt></BookgDt>\
+                 <NtryDtls><TxDtls><Refs><EndToEndId>E2E{s:06}{e:04}</EndToEndId></Refs>\
+                 <RmtInf><Ustrd>Payment for invoice {e}</Ustrd></RmtInf>\
+                 </TxDtls></NtryDtls></Ntry>",
+                e % 1000 + 1,
+                e % 100
+            )
+            .unwrap();
+        }
+        content.push_str("</Stmt>\n");
+    }
+    content.push_str("</BkToCstmrStmt>\n</Document>\n");
+    content
+}
+
+/// Синтетический CSV в универсальном профиле: `rows` операций.
+fn synthetic_csv(rows: usize) -> String {
+    let mut content = String::from("Дата,Сумма,Номер документа,Счет контрагента,Назначение\n");
+    for r in 0..rows {
+        writeln!(
+            content,
+            "2024-01-{:02},-{}.{:02},{r},40702810{r:012},\"Оплата по счету {r}, без НДС\"",
+            r % 28 + 1,
+            r % 1000 + 1,
+            r % 100
+        )
+        .unwrap();
+    }
+    content
+}
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_mt940(c: &mut Criterion) {
+    let content = synthetic_mt940(200, 500);
+
+    let owned = count_allocations(|| Mt940Statement::parse(&content).unwrap());
+    let borrowed = count_allocations(|| Mt940StatementRef::parse(&content).unwrap());
+    println!(
+        "MT940, {} байт: выделений памяти Mt940Statement — {}, Mt940StatementRef — {}",
+        content.len(),
+        owned,
+        borrowed
+    );
+
+    let mut group = c.benchmark_group("mt940");
+    group.throughput(Throughput::Bytes(content.len() as u64));
+    group.sample_size(20);
+    group.bench_function("owned", |b| {
+        b.iter(|| Mt940Statement::parse(black_box(&content)).unwrap())
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| Mt940StatementRef::parse(black_box(&content)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_camt053(c: &mut Criterion) {
+    let (statements, entries) = (20, 500);
+    let content = synthetic_camt053(statements, entries);
+
+    let allocations = count_allocations(|| Camt053Statement::parse_all(&content).unwrap());
+    println!(
+        "CAMT.053, {} байт: выделений памяти — {}, на запись — {:.1}",
+        content.len(),
+        allocations,
+        allocations as f64 / (statements * entries) as f64
+    );
+
+    let mut group = c.benchmark_group("camt053");
+    group.throughput(Throughput::Bytes(content.len() as u64));
+    group.sample_size(20);
+    group.bench_function("parse_all", |b| {
+        b.iter(|| Camt053Statement::parse_all(black_box(&content)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_csv(c: &mut Criterion) {
+    let rows = 50_000;
+    let content = synthetic_csv(rows);
+    let profile = CsvProfile::generic();
+
+    let allocations =
+        count_allocations(|| CsvStatement::parse_with_profile(&content, &profile).unwrap());
+    println!(
+        "CSV, {} байт: выделений памяти — {}, на строку — {:.1}",
+        content.len(),
+        allocations,
+        allocations as f64 / rows as f64
+    );
+
+    let mut group = c.benchmark_group("csv");
+    group.throughput(Throughput::Bytes(content.len() as u64));
+    group.sample_size(20);
+    group.bench_function("parse", |b| {
+        b.iter(|| CsvStatement::parse_with_profile(black_box(&content), &profile).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_mt940, bench_camt053, bench_csv);
+criterion_main!(benches);
@@ -0,0 +1,462 @@
+//! Денежные суммы и арифметика над ними.
+//!
+//! [`Amount`] хранит значение в минимальных единицах валюты (копейки,
+//! центы), поэтому сложение, вычитание и текстовое представление точные.
+//! Операции над суммами в разных валютах и переполнение возвращают
+//! [`Error::Arithmetic`]; суммы в нескольких валютах накапливает
+//! [`MultiCurrencyTotal`].
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::ops::{Add, Neg, Sub};
+
+/// Денежная сумма с валютой.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Amount {
+    /// Значение в минимальных единицах (копейки, центы).
+    pub value: i64,
+    /// Код валюты (EUR, USD, RUB и т.д.).
+    pub currency: String,
+}
+
+impl Amount {
+    /// Создает новую сумму.
+    pub fn new(value: i64, currency: impl Into<String>) -> Self {
+        Self {
+            value,
+            currency: currency.into(),
+        }
+    }
+
+    /// Нулевая сумма в валюте `currency`.
+    pub fn zero(currency: impl Into<String>) -> Self {
+        Self::new(0, currency)
+    }
+
+    /// Возвращает значение в основных единицах (рубли, евро).
+    ///
+    /// Значение приближенное; для расчетов используйте операции над
+    /// `Amount`, для вывода — [`Display`](std::fmt::Display).
+    pub fn as_float(&self) -> f64 {
+        self.value as f64 / 100.0
+    }
+
+    /// Сумма равна нулю.
+    pub fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    /// Сумма меньше нуля.
+    pub fn is_negative(&self) -> bool {
+        self.value < 0
+    }
+
+    /// Сумма того же значения по модулю.
+    pub fn abs(&self) -> Result<Self> {
+        let value = self.value.checked_abs().ok_or_else(|| self.overflow())?;
+        Ok(Self::new(value, self.currency.as_str()))
+    }
+
+    /// Складывает суммы в одной валюте.
+    pub fn checked_add(&self, other: &Amount) -> Result<Self> {
+        self.check_currency(other)?;
+        let value = self
+            .value
+            .checked_add(other.value)
+            .ok_or_else(|| self.overflow())?;
+        Ok(Self::new(value, self.currency.as_str()))
+    }
+
+    /// Вычитает сумму в той же валюте.
+    pub fn checked_sub(&self, other: &Amount) -> Result<Self> {
+        self.check_currency(other)?;
+        let value = self
+            .value
+            .checked_sub(other.value)
+            .ok_or_else(|| self.overflow())?;
+        Ok(Self::new(value, self.currency.as_str()))
+    }
+
+    /// Сумма с противоположным знаком.
+    pub fn checked_neg(&self) -> Result<Self> {
+        let value = self.value.checked_neg().ok_or_else(|| self.overflow())?;
+        Ok(Self::new(value, self.currency.as_str()))
+    }
+
+    fn check_currency(&self, other: &Amount) -> Result<()> {
+        if self.currency != other.currency {
+            return Err(Error::Arithmetic(format!(
+                "суммы в разных валютах: {} и {}",
+                self.currency, other.currency
+            )));
+        }
+        Ok(())
+    }
+
+    fn overflow(&self) -> Error {
+        Error::Arithmetic(format!("переполнение суммы в {}", self.currency))
+    }
+
+    /// Записывает сумму с кодом валюты по правилам `format`.
+    ///
+    /// ```ignore
+    /// let amount = Amount::new(-123_456, "RUB");
+    /// assert_eq!(amount.format_with(&AmountFormat::RU), "-1 234,56 RUB");
+    /// ```
+    pub fn format_with(&self, format: &AmountFormat) -> String {
+        format!("{} {}", format.format_value(self.value), self.currency)
+    }
+
+    /// Разбирает `<число> <валюта>` по правилам `format`.
+    pub fn parse_with(s: &str, format: &AmountFormat) -> Result<Self> {
+        let s = s.trim();
+        let invalid = || Error::Parse(format!("Некорректная сумма с валютой: '{}'", s));
+
+        let (number, currency) = s.rsplit_once(char::is_whitespace).ok_or_else(invalid)?;
+        if currency.is_empty() || !currency.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(invalid());
+        }
+
+        Ok(Self::new(format.parse_value(number)?, currency))
+    }
+}
+
+impl std::fmt::Display for Amount {
+    /// Выводит `1234.56 EUR` ([`AmountFormat::PLAIN`]).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format_with(&AmountFormat::PLAIN))
+    }
+}
+
+impl std::str::FromStr for Amount {
+    type Err = Error;
+
+    /// Разбирает `1234.56 EUR` ([`AmountFormat::PLAIN`]).
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse_with(s, &AmountFormat::PLAIN)
+    }
+}
+
+impl Add for &Amount {
+    type Output = Result<Amount>;
+
+    fn add(self, other: &Amount) -> Result<Amount> {
+        self.checked_add(other)
+    }
+}
+
+impl Add for Amount {
+    type Output = Result<Amount>;
+
+    fn add(self, other: Amount) -> Result<Amount> {
+        self.checked_add(&other)
+    }
+}
+
+impl Sub for &Amount {
+    type Output = Result<Amount>;
+
+    fn sub(self, other: &Amount) -> Result<Amount> {
+        self.checked_sub(other)
+    }
+}
+
+impl Sub for Amount {
+    type Output = Result<Amount>;
+
+    fn sub(self, other: Amount) -> Result<Amount> {
+        self.checked_sub(&other)
+    }
+}
+
+impl Neg for &Amount {
+    type Output = Result<Amount>;
+
+    fn neg(self) -> Result<Amount> {
+        self.checked_neg()
+    }
+}
+
+impl Neg for Amount {
+    type Output = Result<Amount>;
+
+    fn neg(self) -> Result<Amount> {
+        self.checked_neg()
+    }
+}
+
+/// Правила текстовой записи суммы: десятичный разделитель и
+/// разделитель групп разрядов.
+///
+/// Сумма всегда записывается с двумя знаками после запятой; при разборе
+/// допускается от нуля до двух знаков, больше — ошибка, а не округление.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountFormat {
+    /// Десятичный разделитель.
+    pub decimal_separator: char,
+    /// Разделитель групп по три разряда (`None` — без группировки).
+    pub group_separator: Option<char>,
+}
+
+impl AmountFormat {
+    /// `1234.56` — формат `Display`/`FromStr` и текстовых выгрузок.
+    pub const PLAIN: AmountFormat = AmountFormat {
+        decimal_separator: '.',
+        group_separator: None,
+    };
+    /// `1 234,56` — российская запись.
+    pub const RU: AmountFormat = AmountFormat {
+        decimal_separator: ',',
+        group_separator: Some(' '),
+    };
+    /// `1.234,56` — запись, принятая в Германии и большей части Европы.
+    pub const EU: AmountFormat = AmountFormat {
+        decimal_separator: ',',
+        group_separator: Some('.'),
+    };
+    /// `1,234.56` — англоязычная запись.
+    pub const US: AmountFormat = AmountFormat {
+        decimal_separator: '.',
+        group_separator: Some(','),
+    };
+
+    /// Записывает значение в минимальных единицах без валюты.
+    pub fn format_value(&self, value: i64) -> String {
+        let abs = value.unsigned_abs();
+        let digits = (abs / 100).to_string();
+
+        let mut result = String::with_capacity(digits.len() * 2 + 4);
+        if value < 0 {
+            result.push('-');
+        }
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+                if let Some(separator) = self.group_separator {
+                    result.push(separator);
+                }
+            }
+            result.push(digit);
+        }
+        result.push(self.decimal_separator);
+        result.push_str(&format!("{:02}", abs % 100));
+        result
+    }
+
+    /// Разбирает значение без валюты в минимальные единицы.
+    ///
+    /// Допускается знак `-` или `+`; группы разрядов, если разделитель
+    /// задан, должны быть по три цифры (пробел совпадает и с неразрывным).
+    pub fn parse_value(&self, s: &str) -> Result<i64> {
+        let s = s.trim();
+        let invalid = || Error::Parse(format!("Некорректная сумма: '{}'", s));
+
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (whole, fraction) = match unsigned.split_once(self.decimal_separator) {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (unsigned, ""),
+        };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() || fraction.len() > 2 || !is_digits(fraction) {
+            return Err(invalid());
+        }
+
+        let whole: String = match self.group_separator {
+            Some(separator) => {
+                let is_separator = |c: char| c == separator || (separator == ' ' && c == '\u{a0}');
+                let groups: Vec<&str> = whole.split(is_separator).collect();
+                let first_ok = (1..=3).contains(&groups[0].len()) || groups.len() == 1;
+                if !first_ok || groups[1..].iter().any(|group| group.len() != 3) {
+                    return Err(invalid());
+                }
+                groups.concat()
+            }
+            None => whole.to_string(),
+        };
+        if whole.is_empty() || !is_digits(&whole) {
+            return Err(invalid());
+        }
+
+        let cents: i64 = match fraction.len() {
+            0 => 0,
+            1 => fraction.parse::<i64>().map_err(|_| invalid())? * 10,
+            _ => fraction.parse().map_err(|_| invalid())?,
+        };
+        let value = whole
+            .parse::<i64>()
+            .ok()
+            .and_then(|whole| whole.checked_mul(100))
+            .and_then(|whole| whole.checked_add(cents))
+            .ok_or_else(invalid)?;
+
+        Ok(if negative { -value } else { value })
+    }
+}
+
+impl Default for AmountFormat {
+    fn default() -> Self {
+        Self::PLAIN
+    }
+}
+
+/// Накопитель сумм в нескольких валютах.
+///
+/// Суммы складываются отдельно по каждой валюте без переполнения
+/// и без перевода в числа с плавающей точкой; валюты перечисляются
+/// в алфавитном порядке.
+///
+/// ```ignore
+/// let mut total = MultiCurrencyTotal::new();
+/// for tx in &statement.transactions {
+///     total.add(&tx.signed_amount())?;
+/// }
+/// println!("{}", total); // 150.00 EUR, -20.00 USD
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiCurrencyTotal {
+    totals: BTreeMap<String, i64>,
+}
+
+impl MultiCurrencyTotal {
+    /// Пустой накопитель.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Прибавляет сумму к итогу по ее валюте.
+    pub fn add(&mut self, amount: &Amount) -> Result<()> {
+        let total = self.totals.entry(amount.currency.clone()).or_insert(0);
+        *total = total
+            .checked_add(amount.value)
+            .ok_or_else(|| amount.overflow())?;
+        Ok(())
+    }
+
+    /// Итог в валюте `currency`, если по ней были суммы.
+    pub fn get(&self, currency: &str) -> Option<Amount> {
+        self.totals
+            .get(currency)
+            .map(|&value| Amount::new(value, currency))
+    }
+
+    /// Итоги по всем валютам.
+    pub fn amounts(&self) -> impl Iterator<Item = Amount> + '_ {
+        self.totals
+            .iter()
+            .map(|(currency, &value)| Amount::new(value, currency.as_str()))
+    }
+
+    /// Число валют в итоге.
+    pub fn len(&self) -> usize {
+        self.totals.len()
+    }
+
+    /// Не было ни одной суммы.
+    pub fn is_empty(&self) -> bool {
+        self.totals.is_empty()
+    }
+}
+
+impl std::fmt::Display for MultiCurrencyTotal {
+    /// Итоги через запятую: `150.00 EUR, -20.00 USD`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, amount) in self.amounts().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", amount)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Amount::new(1050, "EUR");
+        let b = Amount::new(-250, "EUR");
+        assert_eq!((&a + &b).unwrap(), Amount::new(800, "EUR"));
+        assert_eq!((&a - &b).unwrap(), Amount::new(1300, "EUR"));
+        assert_eq!((-a.clone()).unwrap(), Amount::new(-1050, "EUR"));
+        assert_eq!(b.abs().unwrap(), Amount::new(250, "EUR"));
+
+        assert!(matches!(&a + &Amount::new(1, "USD"), Err(Error::Arithmetic(_))));
+        assert!(matches!(Amount::new(i64::MAX, "EUR") + a, Err(Error::Arithmetic(_))));
+        assert!(Amount::new(i64::MIN, "EUR").checked_neg().is_err());
+    }
+
+    #[test]
+    fn test_display_and_parse() {
+        assert_eq!(Amount::new(123_456, "EUR").to_string(), "1234.56 EUR");
+        assert_eq!(Amount::new(-5, "EUR").to_string(), "-0.05 EUR");
+        assert_eq!(
+            Amount::new(i64::MIN, "EUR").to_string(),
+            "-92233720368547758.08 EUR"
+        );
+        assert_eq!("1234.56 EUR".parse::<Amount>().unwrap(), Amount::new(123_456, "EUR"));
+        assert_eq!("-0.5 USD".parse::<Amount>().unwrap(), Amount::new(-50, "USD"));
+        assert_eq!("+12 RUB".parse::<Amount>().unwrap(), Amount::new(1200, "RUB"));
+
+        for invalid in ["1234.567 EUR", "1234.56", "12,34 EUR", ". EUR", "1e3 EUR", "-- EUR"] {
+            assert!(invalid.parse::<Amount>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_locale_formats() {
+        let amount = Amount::new(-123_456_789, "RUB");
+        assert_eq!(amount.format_with(&AmountFormat::RU), "-1 234 567,89 RUB");
+        assert_eq!(amount.format_with(&AmountFormat::EU), "-1.234.567,89 RUB");
+        assert_eq!(amount.format_with(&AmountFormat::US), "-1,234,567.89 RUB");
+        assert_eq!(AmountFormat::RU.format_value(99), "0,99");
+
+        for format in [AmountFormat::PLAIN, AmountFormat::RU, AmountFormat::EU, AmountFormat::US] {
+            let text = amount.format_with(&format);
+            assert_eq!(Amount::parse_with(&text, &format).unwrap(), amount);
+        }
+        assert_eq!(AmountFormat::RU.parse_value("1\u{a0}234,5").unwrap(), 123_450);
+        assert_eq!(AmountFormat::RU.parse_value("1234,5").unwrap(), 123_450);
+        assert!(AmountFormat::US.parse_value("1,23,456.00").is_err());
+        assert!(AmountFormat::EU.parse_value("1.234.56").is_err());
+    }
+
+    #[test]
+    fn test_signed_amounts() {
+        use crate::types::{Balance, Date};
+
+        let balance = |value: i64, is_credit: bool| Balance {
+            amount: Amount::new(value, "EUR"),
+            date: Date::new(2024, 1, 1),
+            is_credit,
+        };
+        assert_eq!(balance(12_345, true).signed_amount(), Amount::new(12_345, "EUR"));
+        assert_eq!(balance(12_345, false).signed_amount(), Amount::new(-12_345, "EUR"));
+        // CSV и CAMT.053 хранят дебетовый остаток со знаком
+        assert_eq!(balance(-12_345, false).signed_amount(), Amount::new(-12_345, "EUR"));
+    }
+
+    #[test]
+    fn test_multi_currency_total() {
+        let mut total = MultiCurrencyTotal::new();
+        assert!(total.is_empty());
+        for amount in [
+            Amount::new(10_000, "USD"),
+            Amount::new(15_000, "EUR"),
+            Amount::new(-12_000, "USD"),
+        ] {
+            total.add(&amount).unwrap();
+        }
+
+        assert_eq!(total.len(), 2);
+        assert_eq!(total.get("USD"), Some(Amount::new(-2_000, "USD")));
+        assert_eq!(total.get("RUB"), None);
+        assert_eq!(total.to_string(), "150.00 EUR, -20.00 USD");
+        assert!(total.add(&Amount::new(i64::MAX, "EUR")).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+-- Переход со схемы версии 1: пустой референс становится NULL,
+-- ограничение UNIQUE (account_id, reference, date, amount) снимается.
+
+CREATE TABLE transactions_v2 (
+    id                    INTEGER PRIMARY KEY,
+    account_id            INTEGER NOT NULL REFERENCES accounts (id),
+    statement_id          INTEGER NOT NULL REFERENCES statements (id) ON DELETE CASCADE,
+    date                  TEXT NOT NULL,
+    value_date            TEXT,
+    amount                INTEGER NOT NULL,
+    currency              TEXT NOT NULL,
+    reference             TEXT,
+    transaction_type      TEXT,
+    description           TEXT NOT NULL,
+    counterparty_name     TEXT,
+    counterparty_account  TEXT
+);
+
+INSERT INTO transactions_v2
+SELECT id, account_id, statement_id, date, value_date, amount, currency,
+       NULLIF(reference, ''), transaction_type, description, counterparty_name,
+       counterparty_account
+FROM transactions;
+
+DROP TABLE transactions;
+ALTER TABLE transactions_v2 RENAME TO transactions;
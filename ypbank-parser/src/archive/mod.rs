@@ -0,0 +1,12 @@
+//! Архив выписок в SQLite (feature `archive`).
+//!
+//! Разобранные выписки любого формата сохраняются в локальную базу с
+//! таблицами счетов, выписок, остатков и транзакций, чтобы анализировать
+//! историю без повторного разбора исходных файлов.
+//!
+//! При импорте транзакции дедуплицируются по счету, референсу, дате и сумме,
+//! а выписки, чей период пересекается с уже загруженными, попадают в отчет.
+
+pub mod store;
+
+pub use store::{Archive, ArchivedPeriod, ImportReport, ARCHIVE_SCHEMA_VERSION};
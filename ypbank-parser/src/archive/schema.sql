@@ -0,0 +1,58 @@
+-- Схема архива выписок (версия 2).
+-- Суммы хранятся целым числом минимальных единиц со знаком:
+-- положительные — кредит, отрицательные — дебет. Даты — текст YYYY-MM-DD.
+
+CREATE TABLE IF NOT EXISTS accounts (
+    id          INTEGER PRIMARY KEY,
+    number      TEXT NOT NULL,
+    currency    TEXT NOT NULL,
+    iban        TEXT,
+    name        TEXT,
+    owner       TEXT,
+    tax_id      TEXT,
+    UNIQUE (number, currency)
+);
+
+CREATE TABLE IF NOT EXISTS statements (
+    id                INTEGER PRIMARY KEY,
+    account_id        INTEGER NOT NULL REFERENCES accounts (id),
+    statement_number  TEXT,
+    reference         TEXT,
+    period_start      TEXT NOT NULL,
+    period_end        TEXT NOT NULL,
+    source            TEXT,
+    imported_at       INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS statements_period
+    ON statements (account_id, period_start, period_end);
+
+CREATE TABLE IF NOT EXISTS balances (
+    statement_id  INTEGER NOT NULL REFERENCES statements (id) ON DELETE CASCADE,
+    kind          TEXT NOT NULL CHECK (kind IN ('opening', 'closing')),
+    date          TEXT NOT NULL,
+    amount        INTEGER NOT NULL,
+    currency      TEXT NOT NULL,
+    PRIMARY KEY (statement_id, kind)
+);
+
+CREATE TABLE IF NOT EXISTS transactions (
+    id                    INTEGER PRIMARY KEY,
+    account_id            INTEGER NOT NULL REFERENCES accounts (id),
+    statement_id          INTEGER NOT NULL REFERENCES statements (id) ON DELETE CASCADE,
+    date                  TEXT NOT NULL,
+    value_date            TEXT,
+    amount                INTEGER NOT NULL,
+    currency              TEXT NOT NULL,
+    reference             TEXT,
+    transaction_type      TEXT,
+    description           TEXT NOT NULL,
+    counterparty_name     TEXT,
+    counterparty_account  TEXT
+);
+
+CREATE INDEX IF NOT EXISTS transactions_date ON transactions (account_id, date);
+
+-- Поиск дубликатов: транзакции без референса (NULL) не дедуплицируются.
+CREATE INDEX IF NOT EXISTS transactions_reference
+    ON transactions (account_id, reference, date, amount);
@@ -0,0 +1,590 @@
+//! Хранилище выписок на основе SQLite.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{Error, Result};
+use crate::types::*;
+
+/// Версия схемы базы, записываемая в `PRAGMA user_version`.
+pub const ARCHIVE_SCHEMA_VERSION: i32 = 2;
+
+const SCHEMA: &str = include_str!("schema.sql");
+const MIGRATE_V1: &str = include_str!("migrate_v1.sql");
+
+/// Период ранее загруженной выписки, пересекающийся с импортируемой.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedPeriod {
+    /// Идентификатор выписки в архиве.
+    pub statement_id: i64,
+    /// Начало периода (дата входящего остатка).
+    pub start: Date,
+    /// Конец периода (дата исходящего остатка).
+    pub end: Date,
+    /// Источник выписки (обычно путь к файлу).
+    pub source: Option<String>,
+}
+
+/// Результат импорта одной выписки.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Идентификатор выписки в архиве.
+    pub statement_id: i64,
+    /// `false`, если такая же выписка уже была загружена и импорт пропущен.
+    pub new_statement: bool,
+    /// Количество добавленных транзакций.
+    pub inserted: usize,
+    /// Количество транзакций, уже имевшихся в архиве.
+    pub duplicates: usize,
+    /// Ранее загруженные выписки того же счета с пересекающимся периодом.
+    pub overlaps: Vec<ArchivedPeriod>,
+}
+
+/// Архив выписок.
+///
+/// Суммы хранятся в минимальных единицах со знаком (кредит — плюс),
+/// даты — строками `YYYY-MM-DD`. Счет определяется номером и валютой.
+/// Транзакция считается дубликатом, если на том же счете уже есть
+/// транзакция с тем же референсом, датой и суммой; транзакции без
+/// референса сравниваются по дате и сумме.
+///
+/// # Пример
+///
+/// ```ignore
+/// use ypbank_parser::Archive;
+///
+/// let mut archive = Archive::open("statements.sqlite")?;
+/// let report = archive.import(&statement, Some("march.sta"))?;
+/// println!("добавлено {}, дубликатов {}", report.inserted, report.duplicates);
+/// ```
+pub struct Archive {
+    conn: Connection,
+    clock: Box<dyn Clock>,
+}
+
+impl Archive {
+    /// Открывает (или создает) архив в файле.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::init(Connection::open(path).map_err(map_error)?)
+    }
+
+    /// Создает архив в памяти (для тестов и временной обработки).
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory().map_err(map_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(map_error)?;
+        if version > ARCHIVE_SCHEMA_VERSION {
+            return Err(Error::InvalidFormat(format!(
+                "Версия схемы архива {} новее поддерживаемой {}",
+                version, ARCHIVE_SCHEMA_VERSION
+            )));
+        }
+
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .map_err(map_error)?;
+        if version == 1 {
+            conn.execute_batch(&format!("BEGIN; {} COMMIT;", MIGRATE_V1))
+                .map_err(map_error)?;
+        }
+        conn.execute_batch(SCHEMA).map_err(map_error)?;
+        conn.pragma_update(None, "user_version", ARCHIVE_SCHEMA_VERSION)
+            .map_err(map_error)?;
+
+        Ok(Self {
+            conn,
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    /// Задает часы, по которым записывается время импорта выписок.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Импортирует выписку в архив одной транзакцией базы.
+    ///
+    /// Выписка с тем же счетом, периодом и остатками, что уже есть в архиве,
+    /// повторно не загружается. `source` сохраняется для отчетов.
+    pub fn import(&mut self, statement: &Statement, source: Option<&str>) -> Result<ImportReport> {
+        let tx = self.conn.transaction().map_err(map_error)?;
+
+        let account = &statement.account;
+        tx.execute(
+            "INSERT INTO accounts (number, currency, iban, name, owner, tax_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (number, currency) DO UPDATE SET
+                 iban = COALESCE(accounts.iban, excluded.iban),
+                 name = COALESCE(accounts.name, excluded.name),
+                 owner = COALESCE(accounts.owner, excluded.owner),
+                 tax_id = COALESCE(accounts.tax_id, excluded.tax_id)",
+            params![
+                account.number,
+                account.currency,
+                account.iban,
+                account.name,
+                account.owner,
+                account.tax_id
+            ],
+        )
+        .map_err(map_error)?;
+        let account_id: i64 = tx
+            .query_row(
+                "SELECT id FROM accounts WHERE number = ?1 AND currency = ?2",
+                params![account.number, account.currency],
+                |row| row.get(0),
+            )
+            .map_err(map_error)?;
+
+        let start = statement.opening_balance.date.to_string();
+        let end = statement.closing_balance.date.to_string();
+        let opening = statement.opening_balance.signed_amount().value;
+        let closing = statement.closing_balance.signed_amount().value;
+
+        let existing: Option<i64> = tx
+            .query_row(
+                "SELECT s.id FROM statements s
+                 JOIN balances o ON o.statement_id = s.id AND o.kind = 'opening'
+                 JOIN balances c ON c.statement_id = s.id AND c.kind = 'closing'
+                 WHERE s.account_id = ?1 AND s.period_start = ?2 AND s.period_end = ?3
+                   AND o.amount = ?4 AND c.amount = ?5",
+                params![account_id, start, end, opening, closing],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(map_error)?;
+        if let Some(statement_id) = existing {
+            tracing::warn!(
+                "Выписка по счету {} за {} — {} уже есть в архиве (#{})",
+                account.number,
+                start,
+                end,
+                statement_id
+            );
+            return Ok(ImportReport {
+                statement_id,
+                new_statement: false,
+                inserted: 0,
+                duplicates: statement.transactions.len(),
+                overlaps: Vec::new(),
+            });
+        }
+
+        let overlaps = {
+            let mut query = tx
+                .prepare(
+                    "SELECT id, period_start, period_end, source FROM statements
+                     WHERE account_id = ?1 AND period_start <= ?3 AND period_end >= ?2
+                     ORDER BY period_start, id",
+                )
+                .map_err(map_error)?;
+            let rows = query
+                .query_map(params![account_id, start, end], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                })
+                .map_err(map_error)?;
+            let mut overlaps = Vec::new();
+            for row in rows {
+                let (statement_id, start, end, source) = row.map_err(map_error)?;
+                overlaps.push(ArchivedPeriod {
+                    statement_id,
+                    start: start.parse()?,
+                    end: end.parse()?,
+                    source,
+                });
+            }
+            overlaps
+        };
+
+        tx.execute(
+            "INSERT INTO statements
+                 (account_id, statement_number, reference, period_start, period_end,
+                  source, imported_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                account_id,
+                statement.statement_number,
+                statement.reference,
+                start,
+                end,
+                source,
+                self.clock.now().unix_timestamp()
+            ],
+        )
+        .map_err(map_error)?;
+        let statement_id = tx.last_insert_rowid();
+
+        for (kind, balance) in [
+            ("opening", &statement.opening_balance),
+            ("closing", &statement.closing_balance),
+        ] {
+            tx.execute(
+                "INSERT INTO balances (statement_id, kind, date, amount, currency)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    statement_id,
+                    kind,
+                    balance.date.to_string(),
+                    balance.signed_amount().value,
+                    balance.amount.currency
+                ],
+            )
+            .map_err(map_error)?;
+        }
+
+        let mut inserted = 0;
+        let mut duplicates = 0;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO transactions
+                         (account_id, statement_id, date, value_date, amount, currency,
+                          reference, transaction_type, description, counterparty_name,
+                          counterparty_account)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                )
+                .map_err(map_error)?;
+            // Дубликатом считается только транзакция с референсом, уже загруженная
+            // из другой выписки: одинаковые операции внутри одной выписки сохраняются.
+            let mut existing = tx
+                .prepare(
+                    "SELECT 1 FROM transactions
+                     WHERE account_id = ?1 AND reference = ?2 AND date = ?3 AND amount = ?4
+                       AND statement_id <> ?5
+                     LIMIT 1",
+                )
+                .map_err(map_error)?;
+            for transaction in &statement.transactions {
+                let amount = transaction.signed_amount().value;
+                let date = transaction.date.to_string();
+                let reference = transaction.reference.as_deref().filter(|r| !r.is_empty());
+                if let Some(reference) = reference {
+                    let duplicate = existing
+                        .exists(params![account_id, reference, date, amount, statement_id])
+                        .map_err(map_error)?;
+                    if duplicate {
+                        duplicates += 1;
+                        continue;
+                    }
+                }
+                let counterparty = transaction.counterparty.as_ref();
+                insert
+                    .execute(params![
+                        account_id,
+                        statement_id,
+                        date,
+                        transaction.value_date.as_ref().map(Date::to_string),
+                        amount,
+                        transaction.amount.currency,
+                        reference,
+                        transaction.transaction_type,
+                        transaction.description,
+                        counterparty.and_then(|c| c.name.as_deref()),
+                        counterparty.and_then(|c| c.account.as_deref())
+                    ])
+                    .map_err(map_error)?;
+                inserted += 1;
+            }
+        }
+
+        tx.commit().map_err(map_error)?;
+
+        for overlap in &overlaps {
+            tracing::warn!(
+                "Период выписки {} — {} по счету {} пересекается с выпиской #{} ({} — {})",
+                start,
+                end,
+                account.number,
+                overlap.statement_id,
+                overlap.start,
+                overlap.end
+            );
+        }
+
+        Ok(ImportReport {
+            statement_id,
+            new_statement: true,
+            inserted,
+            duplicates,
+            overlaps,
+        })
+    }
+
+    /// Восстанавливает выписки счета из архива в порядке периодов.
+    ///
+    /// Каждая выписка содержит транзакции, впервые загруженные с ней;
+    /// дубликаты остаются при исходной выписке. Архив хранит основные поля
+    /// транзакций, поэтому курсы, комиссии и реквизиты банка контрагента
+    /// не восстанавливаются.
+    pub fn statements(&self, account_number: &str) -> Result<Vec<Statement>> {
+        let mut query = self
+            .conn
+            .prepare(
+                "SELECT s.id, a.number, a.currency, a.iban, a.name, a.owner, a.tax_id,
+                        s.statement_number, s.reference
+                 FROM statements s JOIN accounts a ON a.id = s.account_id
+                 WHERE a.number = ?1
+                 ORDER BY s.period_start, s.id",
+            )
+            .map_err(map_error)?;
+        let rows = query
+            .query_map(params![account_number], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    Account {
+                        number: row.get(1)?,
+                        currency: row.get(2)?,
+                        iban: row.get(3)?,
+                        name: row.get(4)?,
+                        owner: row.get(5)?,
+                        tax_id: row.get(6)?,
+                    },
+                    row.get::<_, Option<String>>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                ))
+            })
+            .map_err(map_error)?;
+
+        let mut statements = Vec::new();
+        for row in rows {
+            let (statement_id, account, statement_number, reference) = row.map_err(map_error)?;
+            statements.push(Statement {
+                account,
+                opening_balance: self.balance(statement_id, "opening")?,
+                closing_balance: self.balance(statement_id, "closing")?,
+                transactions: self.transactions(statement_id)?,
+                statement_number,
+                reference,
+            });
+        }
+        Ok(statements)
+    }
+
+    fn balance(&self, statement_id: i64, kind: &str) -> Result<Balance> {
+        let (date, amount, currency): (String, i64, String) = self
+            .conn
+            .query_row(
+                "SELECT date, amount, currency FROM balances
+                 WHERE statement_id = ?1 AND kind = ?2",
+                params![statement_id, kind],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(map_error)?;
+        Ok(Balance {
+            amount: Amount::new(amount.abs(), currency),
+            date: date.parse()?,
+            is_credit: amount >= 0,
+        })
+    }
+
+    fn transactions(&self, statement_id: i64) -> Result<Vec<Transaction>> {
+        let mut query = self
+            .conn
+            .prepare(
+                "SELECT date, value_date, amount, currency, reference, transaction_type,
+                        description, counterparty_name, counterparty_account
+                 FROM transactions WHERE statement_id = ?1 ORDER BY id",
+            )
+            .map_err(map_error)?;
+        let rows = query
+            .query_map(params![statement_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, String>(6)?,
+                    row.get::<_, Option<String>>(7)?,
+                    row.get::<_, Option<String>>(8)?,
+                ))
+            })
+            .map_err(map_error)?;
+
+        let mut transactions = Vec::new();
+        for row in rows {
+            let (date, value_date, amount, currency, reference, transaction_type, description,
+                counterparty_name, counterparty_account) = row.map_err(map_error)?;
+            let counterparty = (counterparty_name.is_some() || counterparty_account.is_some())
+                .then(|| Counterparty {
+                    name: counterparty_name,
+                    account: counterparty_account,
+                    ..Default::default()
+                });
+            transactions.push(Transaction {
+                date: date.parse()?,
+                value_date: value_date.as_deref().map(str::parse).transpose()?,
+                amount: Amount::new(amount.abs(), currency),
+                is_credit: amount >= 0,
+                reference,
+                transaction_type,
+                description,
+                counterparty,
+                instructed_amount: None,
+                original_amount: None,
+                counter_value_amount: None,
+                exchange_rates: Vec::new(),
+                charges: Vec::new(),
+            });
+        }
+        Ok(transactions)
+    }
+}
+
+fn map_error(error: rusqlite::Error) -> Error {
+    Error::Database(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement(opening: i64, closing: i64, day: u8, references: &[&str]) -> Statement {
+        let balance = |value: i64, day: u8| Balance {
+            amount: Amount::new(value, "EUR"),
+            date: Date::new(2024, 3, day),
+            is_credit: true,
+        };
+        Statement {
+            account: Account {
+                iban: None,
+                number: "12345678".to_string(),
+                currency: "EUR".to_string(),
+                name: None,
+                owner: None,
+                tax_id: None,
+            },
+            opening_balance: balance(opening, day),
+            closing_balance: balance(closing, day + 1),
+            transactions: references
+                .iter()
+                .map(|reference| Transaction {
+                    date: Date::new(2024, 3, day),
+                    value_date: None,
+                    amount: Amount::new(100, "EUR"),
+                    is_credit: false,
+                    reference: Some(reference.to_string()),
+                    transaction_type: None,
+                    description: "Оплата".to_string(),
+                    counterparty: None,
+                    instructed_amount: None,
+                    original_amount: None,
+                    counter_value_amount: None,
+                    exchange_rates: Vec::new(),
+                    charges: Vec::new(),
+                })
+                .collect(),
+            statement_number: None,
+            reference: None,
+        }
+    }
+
+    #[test]
+    fn test_reimport_is_skipped() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        let first = archive.import(&statement(1000, 800, 1, &["A", "B"]), None).unwrap();
+        assert!(first.new_statement);
+        assert_eq!(first.inserted, 2);
+
+        let second = archive.import(&statement(1000, 800, 1, &["A", "B"]), None).unwrap();
+        assert!(!second.new_statement);
+        assert_eq!(second.statement_id, first.statement_id);
+        assert_eq!(second.duplicates, 2);
+    }
+
+    #[test]
+    fn test_import_time_from_clock() {
+        let clock = crate::clock::FixedClock("2024-04-01T12:00:00+03:00".parse().unwrap());
+        let mut archive = Archive::open_in_memory().unwrap().with_clock(clock);
+        let report = archive.import(&statement(1000, 800, 1, &["A"]), None).unwrap();
+
+        let imported_at: i64 = archive
+            .conn
+            .query_row(
+                "SELECT imported_at FROM statements WHERE id = ?1",
+                [report.statement_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(imported_at, 1_711_962_000);
+    }
+
+    #[test]
+    fn test_overlap_and_duplicates() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        archive.import(&statement(1000, 800, 1, &["A", "B"]), Some("a.sta")).unwrap();
+
+        let report = archive.import(&statement(900, 700, 2, &["C"]), Some("b.sta")).unwrap();
+        assert_eq!(report.inserted, 1);
+        assert_eq!(report.overlaps.len(), 1);
+        assert_eq!(report.overlaps[0].source.as_deref(), Some("a.sta"));
+
+        let mut same_day = statement(900, 800, 1, &["A"]);
+        same_day.closing_balance.date = Date::new(2024, 3, 1);
+        let report = archive.import(&same_day, None).unwrap();
+        assert_eq!((report.inserted, report.duplicates), (0, 1));
+    }
+
+    #[test]
+    fn test_identical_transactions_without_reference_are_kept() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        let mut first = statement(1000, 800, 1, &["", ""]);
+        first.transactions.iter_mut().for_each(|t| t.reference = None);
+        let report = archive.import(&first, None).unwrap();
+        assert_eq!((report.inserted, report.duplicates), (2, 0));
+
+        let mut second = statement(800, 600, 2, &["", ""]);
+        second.transactions.iter_mut().for_each(|t| t.reference = None);
+        second.transactions[0].date = Date::new(2024, 3, 1);
+        let report = archive.import(&second, None).unwrap();
+        assert_eq!((report.inserted, report.duplicates), (2, 0));
+
+        let history = archive.statements("12345678").unwrap();
+        assert_eq!(history[0].transactions.len(), 2);
+        assert!(history[0].transactions.iter().all(|t| t.reference.is_none()));
+    }
+
+    #[test]
+    fn test_same_reference_within_statement_is_kept() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        let report = archive.import(&statement(1000, 800, 1, &["A", "A"]), None).unwrap();
+        assert_eq!((report.inserted, report.duplicates), (2, 0));
+    }
+
+    #[test]
+    fn test_migrates_v1_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        let v1 = SCHEMA
+            .replace("reference             TEXT,", "reference TEXT NOT NULL DEFAULT '',")
+            .replace(
+                "counterparty_account  TEXT\n);",
+                "counterparty_account TEXT,\n    UNIQUE (account_id, reference, date, amount)\n);",
+            );
+        assert!(v1.contains("UNIQUE (account_id, reference") && v1.contains("DEFAULT ''"));
+        conn.execute_batch(&v1).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+
+        let mut archive = Archive::init(conn).unwrap();
+        let mut unreferenced = statement(1000, 800, 1, &["", ""]);
+        unreferenced.transactions.iter_mut().for_each(|t| t.reference = None);
+        let report = archive.import(&unreferenced, None).unwrap();
+        assert_eq!(report.inserted, 2);
+        let version: i32 = archive
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, ARCHIVE_SCHEMA_VERSION);
+    }
+}
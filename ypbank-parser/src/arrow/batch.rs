@@ -0,0 +1,199 @@
+//! Преобразование выписок в record batch Apache Arrow.
+
+use std::sync::Arc;
+
+use arrow_array::builder::{BooleanBuilder, Date32Builder, Decimal128Builder, StringBuilder, UInt32Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+
+use crate::error::{Error, Result};
+use crate::types::{Date, Statement};
+
+/// Точность десятичных колонок сумм.
+pub const ARROW_AMOUNT_PRECISION: u8 = 18;
+
+/// Число знаков после запятой в колонках сумм.
+///
+/// Суммы модели хранятся в минимальных единицах (сотых долях валюты),
+/// поэтому значение записывается в колонку без пересчета.
+pub const ARROW_AMOUNT_SCALE: i8 = 2;
+
+fn amount_type() -> DataType {
+    DataType::Decimal128(ARROW_AMOUNT_PRECISION, ARROW_AMOUNT_SCALE)
+}
+
+/// Схема таблицы транзакций: одна строка на транзакцию.
+pub fn transactions_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("account", DataType::Utf8, false),
+        Field::new("account_iban", DataType::Utf8, true),
+        Field::new("statement_number", DataType::Utf8, true),
+        Field::new("date", DataType::Date32, false),
+        Field::new("value_date", DataType::Date32, true),
+        Field::new("amount", amount_type(), false),
+        Field::new("currency", DataType::Utf8, false),
+        Field::new("is_credit", DataType::Boolean, false),
+        Field::new("transaction_type", DataType::Utf8, true),
+        Field::new("reference", DataType::Utf8, true),
+        Field::new("description", DataType::Utf8, false),
+        Field::new("counterparty_name", DataType::Utf8, true),
+        Field::new("counterparty_account", DataType::Utf8, true),
+        Field::new("counterparty_bank_code", DataType::Utf8, true),
+        Field::new("counterparty_tax_id", DataType::Utf8, true),
+    ]))
+}
+
+/// Схема таблицы выписок: остатки и число операций по каждой выписке.
+pub fn statements_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("account", DataType::Utf8, false),
+        Field::new("account_iban", DataType::Utf8, true),
+        Field::new("account_owner", DataType::Utf8, true),
+        Field::new("currency", DataType::Utf8, false),
+        Field::new("statement_number", DataType::Utf8, true),
+        Field::new("reference", DataType::Utf8, true),
+        Field::new("opening_date", DataType::Date32, false),
+        Field::new("opening_balance", amount_type(), false),
+        Field::new("closing_date", DataType::Date32, false),
+        Field::new("closing_balance", amount_type(), false),
+        Field::new("transaction_count", DataType::UInt32, false),
+    ]))
+}
+
+/// Собирает транзакции всех выписок в один record batch.
+pub fn transactions_batch(statements: &[Statement]) -> Result<RecordBatch> {
+    let rows: usize = statements.iter().map(|s| s.transactions.len()).sum();
+
+    let mut account = StringBuilder::new();
+    let mut account_iban = StringBuilder::new();
+    let mut statement_number = StringBuilder::new();
+    let mut date = Date32Builder::with_capacity(rows);
+    let mut value_date = Date32Builder::with_capacity(rows);
+    let mut amount = decimal_builder(rows)?;
+    let mut currency = StringBuilder::new();
+    let mut is_credit = BooleanBuilder::with_capacity(rows);
+    let mut transaction_type = StringBuilder::new();
+    let mut reference = StringBuilder::new();
+    let mut description = StringBuilder::new();
+    let mut counterparty_name = StringBuilder::new();
+    let mut counterparty_account = StringBuilder::new();
+    let mut counterparty_bank_code = StringBuilder::new();
+    let mut counterparty_tax_id = StringBuilder::new();
+
+    for statement in statements {
+        for tx in &statement.transactions {
+            account.append_value(&statement.account.number);
+            account_iban.append_option(statement.account.iban.as_deref());
+            statement_number.append_option(statement.statement_number.as_deref());
+            date.append_value(days_since_epoch(&tx.date));
+            value_date.append_option(tx.value_date.as_ref().map(days_since_epoch));
+            amount.append_value(i128::from(tx.signed_amount().value));
+            currency.append_value(&tx.amount.currency);
+            is_credit.append_value(tx.is_credit);
+            transaction_type.append_option(tx.transaction_type.as_deref());
+            reference.append_option(tx.reference.as_deref());
+            description.append_value(&tx.description);
+
+            let counterparty = tx.counterparty.as_ref();
+            counterparty_name.append_option(counterparty.and_then(|c| c.name.as_deref()));
+            counterparty_account.append_option(counterparty.and_then(|c| c.account.as_deref()));
+            counterparty_bank_code.append_option(counterparty.and_then(|c| c.bank_code.as_deref()));
+            counterparty_tax_id.append_option(counterparty.and_then(|c| c.tax_id.as_deref()));
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(account.finish()),
+        Arc::new(account_iban.finish()),
+        Arc::new(statement_number.finish()),
+        Arc::new(date.finish()),
+        Arc::new(value_date.finish()),
+        Arc::new(amount.finish()),
+        Arc::new(currency.finish()),
+        Arc::new(is_credit.finish()),
+        Arc::new(transaction_type.finish()),
+        Arc::new(reference.finish()),
+        Arc::new(description.finish()),
+        Arc::new(counterparty_name.finish()),
+        Arc::new(counterparty_account.finish()),
+        Arc::new(counterparty_bank_code.finish()),
+        Arc::new(counterparty_tax_id.finish()),
+    ];
+    RecordBatch::try_new(transactions_schema(), columns).map_err(map_error)
+}
+
+/// Собирает сводку по выпискам (остатки и число операций) в record batch.
+pub fn statements_batch(statements: &[Statement]) -> Result<RecordBatch> {
+    let rows = statements.len();
+
+    let mut account = StringBuilder::new();
+    let mut account_iban = StringBuilder::new();
+    let mut account_owner = StringBuilder::new();
+    let mut currency = StringBuilder::new();
+    let mut statement_number = StringBuilder::new();
+    let mut reference = StringBuilder::new();
+    let mut opening_date = Date32Builder::with_capacity(rows);
+    let mut opening_balance = decimal_builder(rows)?;
+    let mut closing_date = Date32Builder::with_capacity(rows);
+    let mut closing_balance = decimal_builder(rows)?;
+    let mut transaction_count = UInt32Builder::with_capacity(rows);
+
+    for statement in statements {
+        let owner = statement.account.owner.as_deref().or(statement.account.name.as_deref());
+        account.append_value(&statement.account.number);
+        account_iban.append_option(statement.account.iban.as_deref());
+        account_owner.append_option(owner);
+        currency.append_value(&statement.account.currency);
+        statement_number.append_option(statement.statement_number.as_deref());
+        reference.append_option(statement.reference.as_deref());
+        opening_date.append_value(days_since_epoch(&statement.opening_balance.date));
+        opening_balance.append_value(i128::from(statement.opening_balance.signed_amount().value));
+        closing_date.append_value(days_since_epoch(&statement.closing_balance.date));
+        closing_balance.append_value(i128::from(statement.closing_balance.signed_amount().value));
+        transaction_count.append_value(statement.transactions.len() as u32);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(account.finish()),
+        Arc::new(account_iban.finish()),
+        Arc::new(account_owner.finish()),
+        Arc::new(currency.finish()),
+        Arc::new(statement_number.finish()),
+        Arc::new(reference.finish()),
+        Arc::new(opening_date.finish()),
+        Arc::new(opening_balance.finish()),
+        Arc::new(closing_date.finish()),
+        Arc::new(closing_balance.finish()),
+        Arc::new(transaction_count.finish()),
+    ];
+    RecordBatch::try_new(statements_schema(), columns).map_err(map_error)
+}
+
+fn decimal_builder(capacity: usize) -> Result<Decimal128Builder> {
+    Decimal128Builder::with_capacity(capacity)
+        .with_precision_and_scale(ARROW_AMOUNT_PRECISION, ARROW_AMOUNT_SCALE)
+        .map_err(map_error)
+}
+
+/// Количество дней от 1970-01-01 (представление `Date32`).
+fn days_since_epoch(date: &Date) -> i32 {
+    // Годы Date ограничены 1..=9999, поэтому значение помещается в i32
+    date.to_epoch_days() as i32
+}
+
+pub(crate) fn map_error(error: ArrowError) -> Error {
+    Error::InvalidFormat(format!("Ошибка Arrow: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_since_epoch() {
+        assert_eq!(days_since_epoch(&Date::new(1970, 1, 1)), 0);
+        assert_eq!(days_since_epoch(&Date::new(2000, 3, 1)), 11_017);
+        assert_eq!(days_since_epoch(&Date::new(2024, 2, 29)), 19_782);
+        assert_eq!(days_since_epoch(&Date::new(1969, 12, 31)), -1);
+    }
+}
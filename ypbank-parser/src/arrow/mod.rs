@@ -0,0 +1,15 @@
+//! Модуль экспорта выписок в Apache Arrow и Parquet (feature `parquet`).
+//!
+//! Выписки преобразуются в типизированные record batch: даты — `Date32`,
+//! суммы — `Decimal128(18, 2)` со знаком (списания отрицательные),
+//! необязательные поля и реквизиты контрагента — nullable-колонки.
+//! Такие файлы без преобразований читаются DuckDB, Polars и pandas.
+
+pub mod batch;
+pub mod writer;
+
+pub use batch::{
+    statements_batch, statements_schema, transactions_batch, transactions_schema,
+    ARROW_AMOUNT_PRECISION, ARROW_AMOUNT_SCALE,
+};
+pub use writer::ParquetWriter;
@@ -0,0 +1,177 @@
+//! Запись выписок в файлы Parquet.
+
+use std::io::Write;
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+use arrow_array::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+
+use super::batch::{statements_batch, transactions_batch};
+use crate::error::{Error, Result};
+use crate::types::Statement;
+
+/// Writer для файлов Parquet.
+///
+/// Основной файл содержит таблицу транзакций всех выписок
+/// ([`transactions_schema`](super::transactions_schema)); сводка по
+/// выпискам с остатками записывается отдельным файлом. Колонки сжимаются
+/// Snappy, который читают DuckDB, Polars и Spark.
+pub struct ParquetWriter;
+
+impl ParquetWriter {
+    /// Записывает транзакции в любой приемник, реализующий трейт Write.
+    ///
+    /// Parquet — двоичный формат, поэтому кодировка не задается.
+    pub fn write_to<W: Write>(statements: &[Statement], writer: &mut W) -> Result<()> {
+        let buffer = Self::to_bytes(statements)?;
+        writer.write_all(&buffer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает транзакции в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        writer: &mut W,
+    ) -> Result<()> {
+        crate::encoding::write_async(writer, |buffer| Self::write_to(statements, buffer)).await
+    }
+
+    /// Записывает сводку по выпискам (остатки и число операций).
+    pub fn write_statements_to<W: Write>(statements: &[Statement], writer: &mut W) -> Result<()> {
+        let buffer = write_batch(&statements_batch(statements)?)?;
+        writer.write_all(&buffer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает сводку по выпискам (остатки и число операций).
+    #[cfg(feature = "async")]
+    pub async fn write_statements_to_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        writer: &mut W,
+    ) -> Result<()> {
+        crate::encoding::write_async(writer, |buffer| {
+            Self::write_statements_to(statements, buffer)
+        })
+        .await
+    }
+
+    /// Формирует файл Parquet с транзакциями в памяти.
+    pub fn to_bytes(statements: &[Statement]) -> Result<Vec<u8>> {
+        write_batch(&transactions_batch(statements)?)
+    }
+}
+
+fn write_batch(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_created_by(format!("ypbank-parser {}", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    let mut buffer = Vec::new();
+    let mut writer =
+        ArrowWriter::try_new(&mut buffer, batch.schema(), Some(properties)).map_err(map_error)?;
+    writer.write(batch).map_err(map_error)?;
+    writer.close().map_err(map_error)?;
+    Ok(buffer)
+}
+
+fn map_error(error: ParquetError) -> Error {
+    Error::InvalidFormat(format!("Ошибка записи Parquet: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Account, Amount, Balance, Date, Transaction};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Date32Type, Decimal128Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn statement() -> Statement {
+        let balance = |value: i64, day: u8| Balance {
+            amount: Amount::new(value, "EUR"),
+            date: Date::new(2024, 3, day),
+            is_credit: true,
+        };
+        Statement {
+            account: Account {
+                iban: Some("DE89370400440532013000".to_string()),
+                number: "DE89370400440532013000".to_string(),
+                currency: "EUR".to_string(),
+                name: None,
+                owner: None,
+                tax_id: None,
+            },
+            opening_balance: balance(10_000, 1),
+            closing_balance: balance(8_766, 2),
+            transactions: vec![Transaction {
+                date: Date::new(2024, 3, 2),
+                value_date: None,
+                amount: Amount::new(1_234, "EUR"),
+                is_credit: false,
+                reference: Some("REF1".to_string()),
+                transaction_type: Some("NTRF".to_string()),
+                description: "Оплата счета".to_string(),
+                counterparty: None,
+                instructed_amount: None,
+                original_amount: None,
+                counter_value_amount: None,
+                exchange_rates: Vec::new(),
+                charges: Vec::new(),
+            }],
+            statement_number: Some("1".to_string()),
+            reference: None,
+        }
+    }
+
+    #[test]
+    fn test_parquet_roundtrip_types() {
+        let bytes = ParquetWriter::to_bytes(&[statement()]).unwrap();
+        assert!(bytes.starts_with(b"PAR1"));
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(bytes))
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 1);
+
+        let amount = batch.column_by_name("amount").unwrap().as_primitive::<Decimal128Type>();
+        assert_eq!(amount.value(0), -1_234);
+        assert_eq!(amount.scale(), 2);
+        let date = batch.column_by_name("date").unwrap().as_primitive::<Date32Type>();
+        assert_eq!(date.value(0), 19_784);
+        let value_date = batch.column_by_name("value_date").unwrap();
+        assert!(value_date.is_null(0));
+        let counterparty = batch.column_by_name("counterparty_name").unwrap();
+        assert!(counterparty.is_null(0));
+        let description = batch.column_by_name("description").unwrap().as_string::<i32>();
+        assert_eq!(description.value(0), "Оплата счета");
+    }
+
+    #[test]
+    fn test_statements_summary() {
+        let mut output = Vec::new();
+        ParquetWriter::write_statements_to(&[statement()], &mut output).unwrap();
+
+        let mut reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(output))
+            .unwrap()
+            .build()
+            .unwrap();
+        let batch = reader.next().unwrap().unwrap();
+        let closing = batch
+            .column_by_name("closing_balance")
+            .unwrap()
+            .as_primitive::<Decimal128Type>();
+        assert_eq!(closing.value(0), 8_766);
+    }
+}
@@ -0,0 +1,12 @@
+//! Модуль парсинга и сериализации формата BAI2 (Cash Management Balance Reporting).
+//!
+//! Файл состоит из записей, разделенных запятыми и завершаемых `/`:
+//! заголовок файла (01), группы (02 ... 98), счета (03 ... 49),
+//! транзакции (16) и продолжения (88). Трейлеры 49, 98 и 99 содержат
+//! контрольные суммы и количество записей.
+
+pub mod parser;
+pub mod writer;
+
+pub use parser::{Bai2Account, Bai2File, Bai2Group, Bai2Summary, Bai2Transaction};
+pub use writer::Bai2Writer;
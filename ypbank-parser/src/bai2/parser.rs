@@ -0,0 +1,654 @@
+//! Парсер формата BAI2 (Cash Management Balance Reporting Specification).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::types::{Account, Amount, Balance, Date, Statement, Transaction};
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Код остатка: входящий остаток по книгам (Opening Ledger).
+pub const BAI2_OPENING_LEDGER: &str = "010";
+
+/// Код остатка: исходящий остаток по книгам (Closing Ledger).
+pub const BAI2_CLOSING_LEDGER: &str = "015";
+
+/// Код итога: сумма поступлений (Total Credits).
+pub const BAI2_TOTAL_CREDITS: &str = "100";
+
+/// Код итога: сумма списаний (Total Debits).
+pub const BAI2_TOTAL_DEBITS: &str = "400";
+
+/// Код транзакции: прочее поступление (Miscellaneous Credit).
+pub const BAI2_MISC_CREDIT: &str = "399";
+
+/// Код транзакции: прочее списание (Miscellaneous Debit).
+pub const BAI2_MISC_DEBIT: &str = "699";
+
+/// Валюта по умолчанию, если она не указана ни в группе, ни в счете.
+const BAI2_DEFAULT_CURRENCY: &str = "USD";
+
+/// Файл BAI2 (записи 01 ... 99).
+#[derive(Debug, Clone)]
+pub struct Bai2File {
+    /// Идентификатор отправителя (01, Sender Identification).
+    pub sender: String,
+    /// Идентификатор получателя (01, Receiver Identification).
+    pub receiver: String,
+    /// Дата создания файла.
+    pub creation_date: Date,
+    /// Время создания файла (HHMM).
+    pub creation_time: Option<String>,
+    /// Идентификатор файла.
+    pub file_id: String,
+    /// Группы (записи 02 ... 98).
+    pub groups: Vec<Bai2Group>,
+}
+
+/// Группа счетов одного отправителя на одну дату (записи 02 ... 98).
+#[derive(Debug, Clone)]
+pub struct Bai2Group {
+    /// Конечный получатель (Ultimate Receiver Identification).
+    pub ultimate_receiver: Option<String>,
+    /// Банк-отправитель (Originator Identification).
+    pub originator: String,
+    /// Статус группы: 1 — новая, 2 — замена, 3 — изменение, 4 — удаление.
+    pub status: String,
+    /// Дата, на которую сформированы данные (As-of-Date).
+    pub as_of_date: Date,
+    /// Время, на которое сформированы данные (HHMM).
+    pub as_of_time: Option<String>,
+    /// Валюта группы по умолчанию.
+    pub currency: Option<String>,
+    /// Счета (записи 03 ... 49).
+    pub accounts: Vec<Bai2Account>,
+}
+
+/// Счет (записи 03, 16 и 49).
+#[derive(Debug, Clone)]
+pub struct Bai2Account {
+    /// Номер счета.
+    pub account_number: String,
+    /// Валюта счета (если отличается от валюты группы).
+    pub currency: Option<String>,
+    /// Остатки и итоги из записи 03.
+    pub summaries: Vec<Bai2Summary>,
+    /// Транзакции (записи 16).
+    pub transactions: Vec<Bai2Transaction>,
+}
+
+/// Остаток или итог из записи 03.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bai2Summary {
+    /// Код типа (010, 015, 100, 400 и т.д.).
+    pub type_code: String,
+    /// Сумма со знаком в минимальных единицах.
+    pub amount: Option<i64>,
+    /// Количество операций.
+    pub item_count: Option<u32>,
+    /// Тип доступности средств (Funds Type).
+    pub funds_type: Option<String>,
+}
+
+/// Транзакция (запись 16).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bai2Transaction {
+    /// Код типа транзакции.
+    pub type_code: String,
+    /// Сумма в минимальных единицах.
+    pub amount: i64,
+    /// Тип доступности средств (Funds Type).
+    pub funds_type: Option<String>,
+    /// Референс банка.
+    pub bank_reference: Option<String>,
+    /// Референс клиента.
+    pub customer_reference: Option<String>,
+    /// Текстовое описание (с учетом записей-продолжений 88).
+    pub text: String,
+}
+
+impl Bai2Transaction {
+    /// Направление операции по коду типа: 100–399 — поступление, 400–699 — списание.
+    ///
+    /// Для остальных кодов (кредиты, пользовательские коды) возвращает None.
+    pub fn is_credit(&self) -> Option<bool> {
+        match self.type_code.parse::<u16>().ok()? {
+            100..=399 => Some(true),
+            400..=699 => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl Bai2Account {
+    /// Находит остаток или итог по коду типа.
+    pub fn summary(&self, type_code: &str) -> Option<&Bai2Summary> {
+        self.summaries.iter().find(|s| s.type_code == type_code)
+    }
+}
+
+/// Логическая запись: физическая запись вместе с продолжениями 88.
+struct Record {
+    code: String,
+    content: String,
+    lines: usize,
+}
+
+impl Record {
+    /// Поля записи без завершающего `/`.
+    fn fields(&self) -> Vec<&str> {
+        let content = self.content.trim_end();
+        content.strip_suffix('/').unwrap_or(content).split(',').collect()
+    }
+}
+
+/// Промежуточное состояние открытого счета при разборе.
+struct AccountState {
+    account: Bai2Account,
+    records: usize,
+    total: i64,
+}
+
+/// Промежуточное состояние открытой группы при разборе.
+struct GroupState {
+    group: Bai2Group,
+    records: usize,
+    total: i64,
+}
+
+impl Bai2File {
+    /// Парсит BAI2 из любого источника, реализующего трейт Read.
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит BAI2 из любого источника, реализующего трейт AsyncRead.
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит BAI2 из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Self> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
+    /// Парсит BAI2 из строки.
+    ///
+    /// Проверяет контрольные суммы и количество записей в трейлерах 49, 98 и 99.
+    /// Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Self> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит BAI2 из строки с заданными ограничениями.
+    ///
+    /// Длина логической записи ограничена `max_field_len`, число записей
+    /// продолжения 88 — `max_continuation_lines`, число счетов в файле —
+    /// `max_statements`, число записей 16 в счете — `max_transactions`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Self> {
+        limits.check_input(content.len())?;
+        let records = Self::records(content, limits)?;
+        let mut records = records.into_iter();
+
+        let header = records
+            .next()
+            .filter(|r| r.code == "01")
+            .ok_or_else(|| Error::InvalidFormat("Файл BAI2 не начинается с записи 01".into()))?;
+        let mut file = Self::parse_file_header(&header)?;
+        let mut file_records = header.lines;
+        let mut file_total = 0i64;
+
+        let mut group: Option<GroupState> = None;
+        let mut account: Option<AccountState> = None;
+        let mut accounts = 0;
+
+        for record in records {
+            file_records += record.lines;
+            if let Some(ref mut state) = group {
+                state.records += record.lines;
+            }
+            if let Some(ref mut state) = account {
+                state.records += record.lines;
+            }
+
+            let fields = record.fields();
+            match record.code.as_str() {
+                "02" => {
+                    if group.is_some() {
+                        return Err(Error::InvalidFormat(
+                            "Запись 02 внутри незакрытой группы".into(),
+                        ));
+                    }
+                    group = Some(GroupState {
+                        group: Self::parse_group_header(&fields)?,
+                        records: record.lines,
+                        total: 0,
+                    });
+                }
+                "03" => {
+                    if group.is_none() || account.is_some() {
+                        return Err(Error::InvalidFormat(
+                            "Запись 03 вне группы или внутри незакрытого счета".into(),
+                        ));
+                    }
+                    accounts += 1;
+                    limits.check_statements(accounts)?;
+                    let parsed = Self::parse_account_header(&fields)?;
+                    let total = parsed
+                        .summaries
+                        .iter()
+                        .filter_map(|s| s.amount)
+                        .fold(0i64, i64::saturating_add);
+                    account = Some(AccountState {
+                        account: parsed,
+                        records: record.lines,
+                        total,
+                    });
+                }
+                "16" => {
+                    let state = account.as_mut().ok_or_else(|| {
+                        Error::InvalidFormat("Запись 16 вне счета".into())
+                    })?;
+                    let tx = Self::parse_transaction(&fields)?;
+                    state.total = state.total.saturating_add(tx.amount);
+                    state.account.transactions.push(tx);
+                    limits.check_transactions(state.account.transactions.len())?;
+                }
+                "49" => {
+                    let state = account.take().ok_or_else(|| {
+                        Error::InvalidFormat("Запись 49 без записи 03".into())
+                    })?;
+                    Self::check_trailer(
+                        "49",
+                        &state.account.account_number,
+                        &fields,
+                        state.total,
+                        &[state.records],
+                    )?;
+                    let group_state = group
+                        .as_mut()
+                        .ok_or_else(|| Error::InvalidFormat("Запись 49 вне группы".into()))?;
+                    group_state.total = group_state.total.saturating_add(state.total);
+                    group_state.group.accounts.push(state.account);
+                }
+                "98" => {
+                    if account.is_some() {
+                        return Err(Error::InvalidFormat(
+                            "Запись 98 внутри незакрытого счета".into(),
+                        ));
+                    }
+                    let state = group.take().ok_or_else(|| {
+                        Error::InvalidFormat("Запись 98 без записи 02".into())
+                    })?;
+                    Self::check_trailer(
+                        "98",
+                        &state.group.originator,
+                        &fields,
+                        state.total,
+                        &[state.group.accounts.len(), state.records],
+                    )?;
+                    file_total = file_total.saturating_add(state.total);
+                    file.groups.push(state.group);
+                }
+                "99" => {
+                    if group.is_some() {
+                        return Err(Error::InvalidFormat(
+                            "Запись 99 внутри незакрытой группы".into(),
+                        ));
+                    }
+                    Self::check_trailer(
+                        "99",
+                        &file.file_id,
+                        &fields,
+                        file_total,
+                        &[file.groups.len(), file_records],
+                    )?;
+                    return Ok(file);
+                }
+                other => {
+                    return Err(Error::InvalidFormat(format!(
+                        "Неизвестный тип записи BAI2: {}",
+                        other
+                    )));
+                }
+            }
+        }
+
+        Err(Error::InvalidFormat("Файл BAI2 не завершен записью 99".into()))
+    }
+
+    /// Собирает логические записи, присоединяя продолжения 88 к предыдущей записи.
+    ///
+    /// Для записи 16 продолжение относится к текстовому полю и присоединяется
+    /// через пробел, для остальных — как следующие поля.
+    fn records(content: &str, limits: &Limits) -> Result<Vec<Record>> {
+        let mut records: Vec<Record> = Vec::new();
+
+        for line in content.trim_start_matches('\u{feff}').lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            limits.check_field("запись BAI2", line)?;
+
+            let (code, rest) = line.split_once(',').unwrap_or((line.trim_end_matches('/'), ""));
+
+            if code != "88" {
+                records.push(Record {
+                    code: code.to_string(),
+                    content: line.to_string(),
+                    lines: 1,
+                });
+                continue;
+            }
+
+            let previous = records.last_mut().ok_or_else(|| {
+                Error::InvalidFormat("Запись 88 в начале файла".into())
+            })?;
+            let base = previous.content.trim_end();
+            let base = base.strip_suffix('/').unwrap_or(base);
+            let separator = match previous.code.as_str() {
+                "16" if base.ends_with(',') => "",
+                "16" => " ",
+                _ => ",",
+            };
+            previous.content = format!("{}{}{}", base, separator, rest);
+            previous.lines += 1;
+            limits.check_continuation_lines("записи 88", previous.lines - 1)?;
+            limits.check_field("запись BAI2", &previous.content)?;
+        }
+
+        Ok(records)
+    }
+
+    fn parse_file_header(record: &Record) -> Result<Self> {
+        let fields = record.fields();
+
+        if let Some(version) = Self::field(&fields, 8) {
+            if version != "2" {
+                tracing::warn!("Версия BAI {} отличается от 2", version);
+            }
+        }
+
+        Ok(Bai2File {
+            sender: Self::field(&fields, 1).unwrap_or_default(),
+            receiver: Self::field(&fields, 2).unwrap_or_default(),
+            creation_date: Self::parse_date(&Self::required(&fields, 3, "дата создания файла")?)?,
+            creation_time: Self::field(&fields, 4),
+            file_id: Self::field(&fields, 5).unwrap_or_default(),
+            groups: Vec::new(),
+        })
+    }
+
+    fn parse_group_header(fields: &[&str]) -> Result<Bai2Group> {
+        Ok(Bai2Group {
+            ultimate_receiver: Self::field(fields, 1),
+            originator: Self::field(fields, 2).unwrap_or_default(),
+            status: Self::field(fields, 3).unwrap_or_else(|| "1".to_string()),
+            as_of_date: Self::parse_date(&Self::required(fields, 4, "дата группы")?)?,
+            as_of_time: Self::field(fields, 5),
+            currency: Self::field(fields, 6),
+            accounts: Vec::new(),
+        })
+    }
+
+    fn parse_account_header(fields: &[&str]) -> Result<Bai2Account> {
+        let mut account = Bai2Account {
+            account_number: Self::required(fields, 1, "номер счета")?,
+            currency: Self::field(fields, 2),
+            summaries: Vec::new(),
+            transactions: Vec::new(),
+        };
+
+        let mut idx = 3;
+        while idx < fields.len() {
+            let Some(type_code) = Self::field(fields, idx) else {
+                idx += 4;
+                continue;
+            };
+            let funds_type = Self::field(fields, idx + 3);
+
+            account.summaries.push(Bai2Summary {
+                type_code,
+                amount: Self::field(fields, idx + 1)
+                    .map(|a| Self::parse_amount(&a))
+                    .transpose()?,
+                item_count: Self::field(fields, idx + 2)
+                    .map(|c| {
+                        c.parse()
+                            .map_err(|_| Error::Parse(format!("Некорректное количество: {}", c)))
+                    })
+                    .transpose()?,
+                funds_type: funds_type.clone(),
+            });
+
+            idx = Self::skip_funds_details(fields, funds_type.as_deref(), idx + 4)?;
+        }
+
+        Ok(account)
+    }
+
+    fn parse_transaction(fields: &[&str]) -> Result<Bai2Transaction> {
+        let funds_type = Self::field(fields, 3);
+        let idx = Self::skip_funds_details(fields, funds_type.as_deref(), 4)?;
+
+        // Текст — последнее поле, он может содержать запятые
+        let text = fields.get(idx + 2..).map(|rest| rest.join(",")).unwrap_or_default();
+
+        Ok(Bai2Transaction {
+            type_code: Self::required(fields, 1, "код транзакции")?,
+            amount: Self::parse_amount(&Self::required(fields, 2, "сумма транзакции")?)?,
+            funds_type,
+            bank_reference: Self::field(fields, idx),
+            customer_reference: Self::field(fields, idx + 1),
+            text: text.trim().to_string(),
+        })
+    }
+
+    /// Пропускает дополнительные поля типа доступности средств S, V и D.
+    fn skip_funds_details(fields: &[&str], funds_type: Option<&str>, idx: usize) -> Result<usize> {
+        Ok(match funds_type {
+            Some("S") => idx + 3,
+            Some("V") => idx + 2,
+            Some("D") => {
+                let count: usize = Self::field(fields, idx)
+                    .unwrap_or_default()
+                    .parse()
+                    .map_err(|_| {
+                        Error::Parse("Некорректное количество распределений Funds Type D".into())
+                    })?;
+                idx + 1 + count * 2
+            }
+            _ => idx,
+        })
+    }
+
+    /// Сверяет контрольную сумму и счетчики трейлера с подсчитанными при разборе.
+    fn check_trailer(
+        code: &str,
+        owner: &str,
+        fields: &[&str],
+        total: i64,
+        counts: &[usize],
+    ) -> Result<()> {
+        let declared_total = Self::parse_amount(&Self::required(fields, 1, "контрольная сумма")?)?;
+        if declared_total != total {
+            return Err(Error::InvalidFormat(format!(
+                "Контрольная сумма в записи {} ({}) не совпадает: указано {}, подсчитано {}",
+                code, owner, declared_total, total
+            )));
+        }
+
+        for (offset, expected) in counts.iter().enumerate() {
+            let declared = Self::field(fields, 2 + offset)
+                .and_then(|c| c.parse::<usize>().ok())
+                .ok_or_else(|| {
+                    Error::MissingField(format!("Отсутствует счетчик в записи {}", code))
+                })?;
+            if declared != *expected {
+                return Err(Error::InvalidFormat(format!(
+                    "Счетчик в записи {} ({}) не совпадает: указано {}, подсчитано {}",
+                    code, owner, declared, expected
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn field(fields: &[&str], idx: usize) -> Option<String> {
+        fields
+            .get(idx)
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+    }
+
+    fn required(fields: &[&str], idx: usize, name: &str) -> Result<String> {
+        Self::field(fields, idx).ok_or_else(|| Error::MissingField(format!("Отсутствует {}", name)))
+    }
+
+    /// Парсит дату в формате YYMMDD.
+    fn parse_date(date_str: &str) -> Result<Date> {
+        Date::parse_from_str(date_str, "%y%m%d")
+            .map_err(|_| Error::Parse(format!("Некорректная дата BAI2: {}", date_str)))
+    }
+
+    /// Парсит сумму: целое число минимальных единиц с необязательным знаком.
+    fn parse_amount(amount_str: &str) -> Result<i64> {
+        let amount_str = amount_str.trim();
+        let (is_negative, digits) = match amount_str.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, amount_str.strip_prefix('+').unwrap_or(amount_str)),
+        };
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(Error::Parse(format!("Некорректная сумма: {}", amount_str)));
+        }
+
+        let amount: i64 = digits
+            .parse()
+            .map_err(|_| Error::Parse("Переполнение при парсинге суммы".to_string()))?;
+
+        Ok(if is_negative { -amount } else { amount })
+    }
+
+    /// Преобразует файл в выписки: по одной на каждый счет каждой группы.
+    ///
+    /// Остатки берутся из кодов 010 и 015; если один из них отсутствует,
+    /// он восстанавливается по оборотам. Транзакции с кодами вне диапазонов
+    /// поступлений и списаний пропускаются.
+    pub fn into_statements(self) -> Vec<Statement> {
+        let mut statements = Vec::new();
+
+        for group in self.groups {
+            for account in group.accounts {
+                let currency = account
+                    .currency
+                    .clone()
+                    .or_else(|| group.currency.clone())
+                    .unwrap_or_else(|| BAI2_DEFAULT_CURRENCY.to_string());
+
+                let mut turnover = 0i64;
+                let mut transactions = Vec::new();
+                for tx in &account.transactions {
+                    let Some(is_credit) = tx.is_credit() else {
+                        tracing::warn!(
+                            "Пропущена транзакция BAI2 с кодом {} по счету {}",
+                            tx.type_code,
+                            account.account_number
+                        );
+                        continue;
+                    };
+
+                    let signed = if is_credit { tx.amount } else { -tx.amount };
+                    turnover = turnover.saturating_add(signed);
+
+                    transactions.push(Transaction {
+                        date: group.as_of_date,
+                        value_date: None,
+                        amount: Amount::new(tx.amount.abs(), &currency),
+                        is_credit,
+                        reference: tx.bank_reference.clone().or_else(|| tx.customer_reference.clone()),
+                        transaction_type: None,
+                        description: tx.text.clone(),
+                        counterparty: None,
+                        instructed_amount: None,
+                        original_amount: None,
+                        counter_value_amount: None,
+                        exchange_rates: Vec::new(),
+                        charges: Vec::new(),
+                    });
+                }
+
+                let amount_of = |code: &str| account.summary(code).and_then(|s| s.amount);
+                let (opening, closing) =
+                    match (amount_of(BAI2_OPENING_LEDGER), amount_of(BAI2_CLOSING_LEDGER)) {
+                        (Some(opening), Some(closing)) => (opening, closing),
+                        (Some(opening), None) => (opening, opening.saturating_add(turnover)),
+                        (None, Some(closing)) => (closing.saturating_sub(turnover), closing),
+                        (None, None) => (0, turnover),
+                    };
+
+                let balance = |amount: i64| Balance {
+                    amount: Amount::new(amount.abs(), &currency),
+                    date: group.as_of_date,
+                    is_credit: amount >= 0,
+                };
+
+                statements.push(Statement {
+                    account: Account {
+                        iban: None,
+                        number: account.account_number,
+                        currency: currency.clone(),
+                        name: None,
+                        owner: None,
+                        tax_id: None,
+                    },
+                    opening_balance: balance(opening),
+                    closing_balance: balance(closing),
+                    transactions,
+                    statement_number: None,
+                    reference: Some(self.file_id.clone()).filter(|id| !id.is_empty()),
+                });
+            }
+        }
+
+        statements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_and_amount() {
+        assert_eq!(Bai2File::parse_date("240131").unwrap(), Date::new(2024, 1, 31));
+        assert!(Bai2File::parse_date("241331").is_err());
+
+        assert_eq!(Bai2File::parse_amount("150050").unwrap(), 150050);
+        assert_eq!(Bai2File::parse_amount("-2500").unwrap(), -2500);
+        assert!(Bai2File::parse_amount("12.50").is_err());
+    }
+
+    #[test]
+    fn test_continuation_records() {
+        let content = "16,195,100,Z,REF1,,Wire from/\n88,ACME CORP\n03,1,USD,010,5,,/\n88,015,6,,/\n";
+        let records = Bai2File::records(content, &Limits::default()).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].lines, 2);
+        assert_eq!(records[0].content, "16,195,100,Z,REF1,,Wire from ACME CORP");
+        assert_eq!(
+            records[1].fields(),
+            vec!["03", "1", "USD", "010", "5", "", "", "015", "6", "", ""]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Сериализация формата BAI2.
+
+use crate::bai2::parser::{Bai2Account, Bai2File, Bai2Group, Bai2Transaction};
+use crate::encoding::{self, TextEncoding};
+use crate::error::Result;
+use crate::types::Date;
+use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Максимальная длина физической записи; длинные записи продолжаются записями 88.
+const BAI2_MAX_RECORD_LEN: usize = 80;
+
+/// Writer для формата BAI2.
+///
+/// Контрольные суммы и количество записей в трейлерах 49, 98 и 99
+/// вычисляются при записи.
+pub struct Bai2Writer;
+
+impl Bai2Writer {
+    /// Записывает файл BAI2 в любой приемник, реализующий трейт Write.
+    ///
+    /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
+    pub fn write_to<W: Write>(file: &Bai2File, writer: &mut W) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_to_buffered(file, &mut buf_writer)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает файл BAI2 в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        file: &Bai2File,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(file, buffer)).await
+    }
+
+    /// Записывает файл BAI2 в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        file: &Bai2File,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(file, buffer)
+        })
+    }
+
+    /// Асинхронно записывает файл BAI2 в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        file: &Bai2File,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(file, buffer, encoding)
+        })
+        .await
+    }
+
+    fn write_to_buffered<W: Write>(file: &Bai2File, writer: &mut W) -> Result<()> {
+        writeln!(
+            writer,
+            "01,{},{},{},{},{},,,2/",
+            Self::sanitize(&file.sender),
+            Self::sanitize(&file.receiver),
+            Self::format_date(&file.creation_date),
+            file.creation_time.as_deref().unwrap_or("0000"),
+            Self::sanitize(&file.file_id)
+        )?;
+
+        let mut file_records = 1;
+        let mut file_total = 0i64;
+
+        for group in &file.groups {
+            let (records, total) = Self::write_group(writer, group)?;
+            file_records += records;
+            file_total = file_total.saturating_add(total);
+        }
+
+        file_records += 1;
+        writeln!(writer, "99,{},{},{}/", file_total, file.groups.len(), file_records)?;
+
+        Ok(())
+    }
+
+    /// Записывает группу и возвращает количество записей и контрольную сумму.
+    fn write_group<W: Write>(writer: &mut W, group: &Bai2Group) -> Result<(usize, i64)> {
+        writeln!(
+            writer,
+            "02,{},{},{},{},{},{},/",
+            Self::sanitize(group.ultimate_receiver.as_deref().unwrap_or_default()),
+            Self::sanitize(&group.originator),
+            group.status,
+            Self::format_date(&group.as_of_date),
+            group.as_of_time.as_deref().unwrap_or_default(),
+            group.currency.as_deref().unwrap_or_default()
+        )?;
+
+        let mut records = 1;
+        let mut total = 0i64;
+
+        for account in &group.accounts {
+            let (account_records, account_total) = Self::write_account(writer, account)?;
+            records += account_records;
+            total = total.saturating_add(account_total);
+        }
+
+        records += 1;
+        writeln!(writer, "98,{},{},{}/", total, group.accounts.len(), records)?;
+
+        Ok((records, total))
+    }
+
+    /// Записывает счет и возвращает количество записей и контрольную сумму.
+    fn write_account<W: Write>(writer: &mut W, account: &Bai2Account) -> Result<(usize, i64)> {
+        let mut fields = vec![
+            "03".to_string(),
+            Self::sanitize(&account.account_number),
+            account.currency.clone().unwrap_or_default(),
+        ];
+        let mut total = 0i64;
+
+        for summary in &account.summaries {
+            fields.push(summary.type_code.clone());
+            fields.push(summary.amount.map(|a| a.to_string()).unwrap_or_default());
+            fields.push(summary.item_count.map(|c| c.to_string()).unwrap_or_default());
+            fields.push(Self::funds_type(summary.funds_type.as_deref()));
+            total = total.saturating_add(summary.amount.unwrap_or(0));
+        }
+
+        let mut lines = Self::wrap_fields(fields);
+        for tx in &account.transactions {
+            lines.extend(Self::transaction_lines(tx));
+            total = total.saturating_add(tx.amount);
+        }
+
+        for line in &lines {
+            writeln!(writer, "{}", line)?;
+        }
+
+        let records = lines.len() + 1;
+        writeln!(writer, "49,{},{}/", total, records)?;
+
+        Ok((records, total))
+    }
+
+    /// Формирует запись 16; длинный текст переносится в записи 88 по словам.
+    fn transaction_lines(tx: &Bai2Transaction) -> Vec<String> {
+        let prefix = format!(
+            "16,{},{},{},{},{},",
+            tx.type_code,
+            tx.amount,
+            Self::funds_type(tx.funds_type.as_deref()),
+            Self::sanitize(tx.bank_reference.as_deref().unwrap_or_default()),
+            Self::sanitize(tx.customer_reference.as_deref().unwrap_or_default())
+        );
+
+        let text = tx.text.replace(['\r', '\n'], " ");
+        let mut words = text.split_whitespace();
+        let Some(first) = words.next() else {
+            return vec![format!("{}/", prefix)];
+        };
+
+        let mut lines = Vec::new();
+        let mut current = format!("{}{}", prefix, first);
+        for word in words {
+            if current.len() + 1 + word.len() > BAI2_MAX_RECORD_LEN {
+                lines.push(current);
+                current = format!("88,{}", word);
+            } else {
+                current.push(' ');
+                current.push_str(word);
+            }
+        }
+        lines.push(current);
+
+        lines
+    }
+
+    /// Разбивает поля записи на физические записи с продолжениями 88.
+    fn wrap_fields(fields: Vec<String>) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut fields = fields.into_iter();
+        let mut current = fields.next().unwrap_or_default();
+        let mut has_fields = false;
+
+        for field in fields {
+            // Запятая перед полем и завершающий `/`
+            if has_fields && current.len() + field.len() + 2 > BAI2_MAX_RECORD_LEN {
+                lines.push(format!("{}/", current));
+                current = "88".to_string();
+            }
+            current.push(',');
+            current.push_str(&field);
+            has_fields = true;
+        }
+        lines.push(format!("{}/", current));
+
+        lines
+    }
+
+    /// Тип доступности средств для записи.
+    ///
+    /// Дополнительные поля типов S, V и D не сохраняются при разборе,
+    /// поэтому такие типы записываются пустыми.
+    fn funds_type(funds_type: Option<&str>) -> String {
+        match funds_type {
+            Some("S" | "V" | "D") | None => String::new(),
+            Some(other) => other.to_string(),
+        }
+    }
+
+    /// Удаляет символы-разделители BAI2 из значения поля.
+    fn sanitize(value: &str) -> String {
+        value.replace([',', '/', '\r', '\n'], " ").trim().to_string()
+    }
+
+    fn format_date(date: &Date) -> String {
+        format!("{:02}{:02}{:02}", date.year() % 100, date.month(), date.day())
+    }
+}
+
+impl Bai2File {
+    /// Записывает файл BAI2 в любой приемник, реализующий трейт Write.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        Bai2Writer::write_to(self, writer)
+    }
+
+    /// Асинхронно записывает файл BAI2 в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает файл BAI2 в указанной кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        Bai2Writer::write_to_with_encoding(self, writer, encoding)
+    }
+
+    /// Асинхронно записывает файл BAI2 в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to_with_encoding(buffer, encoding)).await
+    }
+}
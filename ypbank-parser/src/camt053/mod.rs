@@ -4,7 +4,8 @@ pub mod parser;
 pub mod writer;
 
 pub use parser::{
-    Camt053Account, Camt053Balance, Camt053Entry, Camt053Statement, Camt053TransactionDetails,
+    Camt053Account, Camt053Agent, Camt053AmountDetail, Camt053AmountDetails, Camt053Balance,
+    Camt053Charge, Camt053Entry, Camt053Party, Camt053Statement, Camt053TransactionDetails,
 };
 pub use writer::Camt053Writer;
 
@@ -1,11 +1,17 @@
 //! Парсер формата CAMT.053 (ISO 20022 XML).
 
+use crate::encoding::{self, TextEncoding};
 use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::parallel;
 use crate::types::{
-    Account, Amount, Balance, BalanceType, Counterparty, CreditDebit, Date, Statement, Transaction,
-    CREDIT_INDICATOR,
+    Account, Amount, Balance, BalanceType, Charge, Counterparty, CreditDebit, Date, DateTime,
+    ExchangeRate, PostalAddress, Statement, Transaction, CREDIT_INDICATOR, DEBIT_INDICATOR,
+    TAX_ID_SCHEME,
 };
 use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
 
 /// Выписка в формате CAMT.053.
 #[derive(Debug, Clone)]
@@ -13,7 +19,7 @@ pub struct Camt053Statement {
     /// Идентификатор сообщения (MsgId).
     pub message_id: String,
     /// Дата и время создания (CreDtTm).
-    pub creation_date_time: String,
+    pub creation_date_time: DateTime,
     /// Идентификатор выписки (Id в Stmt).
     pub statement_id: String,
     /// Информация о счете.
@@ -35,6 +41,8 @@ pub struct Camt053Account {
     pub name: Option<String>,
     /// Имя владельца счета.
     pub owner_name: Option<String>,
+    /// ИНН владельца счета (Ownr/Id/OrgId/Othr со схемой TXID).
+    pub owner_tax_id: Option<String>,
 }
 
 /// Баланс в формате CAMT.053.
@@ -69,10 +77,92 @@ pub struct Camt053Entry {
     pub value_date: Option<Date>,
     /// Референс от банка (AcctSvcrRef).
     pub account_servicer_ref: Option<String>,
+    /// Детализация сумм уровня записи (AmtDtls).
+    pub amount_details: Camt053AmountDetails,
+    /// Комиссии уровня записи (Chrgs).
+    pub charges: Vec<Camt053Charge>,
     /// Детали транзакций.
     pub transaction_details: Vec<Camt053TransactionDetails>,
 }
 
+/// Участник платежа (Dbtr, Cdtr, UltmtDbtr, UltmtCdtr).
+#[derive(Debug, Clone, Default)]
+pub struct Camt053Party {
+    /// Наименование (Nm).
+    pub name: Option<String>,
+    /// Почтовый адрес (PstlAdr).
+    pub postal_address: Option<PostalAddress>,
+    /// BIC или BEI организации (Id/OrgId/BICOrBEI, AnyBIC).
+    pub bic: Option<String>,
+    /// Иной идентификатор организации (Id/OrgId/Othr/Id).
+    pub organisation_id: Option<String>,
+    /// ИНН (Id/OrgId/Othr/Id со схемой TXID).
+    pub tax_id: Option<String>,
+    /// Идентификатор физического лица (Id/PrvtId/Othr/Id).
+    pub private_id: Option<String>,
+}
+
+/// Обслуживающий банк участника (DbtrAgt, CdtrAgt).
+#[derive(Debug, Clone, Default)]
+pub struct Camt053Agent {
+    /// BIC банка (FinInstnId/BIC, BICFI).
+    pub bic: Option<String>,
+    /// Идентификатор в клиринговой системе (ClrSysMmbId/MmbId), например БИК ЦБ РФ.
+    pub clearing_member_id: Option<String>,
+    /// Наименование банка.
+    pub name: Option<String>,
+    /// Почтовый адрес банка.
+    pub postal_address: Option<PostalAddress>,
+}
+
+/// Детализация сумм (AmtDtls): исходная, инструктированная и встречная суммы.
+#[derive(Debug, Clone, Default)]
+pub struct Camt053AmountDetails {
+    /// Сумма, указанная инициатором платежа (InstdAmt).
+    pub instructed_amount: Option<Camt053AmountDetail>,
+    /// Сумма транзакции (TxAmt).
+    pub transaction_amount: Option<Camt053AmountDetail>,
+    /// Встречная сумма после конвертации (CntrValAmt).
+    pub counter_value_amount: Option<Camt053AmountDetail>,
+}
+
+impl Camt053AmountDetails {
+    /// Возвращает true, если ни одна сумма не указана.
+    pub fn is_empty(&self) -> bool {
+        self.instructed_amount.is_none()
+            && self.transaction_amount.is_none()
+            && self.counter_value_amount.is_none()
+    }
+}
+
+/// Сумма с необязательным курсом конвертации (AmtDtls/*).
+#[derive(Debug, Clone)]
+pub struct Camt053AmountDetail {
+    /// Сумма в минимальных единицах.
+    pub amount: i64,
+    /// Код валюты.
+    pub currency: String,
+    /// Курс конвертации (CcyXchg).
+    pub currency_exchange: Option<ExchangeRate>,
+}
+
+/// Комиссия в формате CAMT.053 (Chrgs).
+#[derive(Debug, Clone)]
+pub struct Camt053Charge {
+    /// Сумма в минимальных единицах.
+    pub amount: i64,
+    /// Код валюты.
+    pub currency: String,
+    /// Индикатор кредит/дебет.
+    pub credit_debit: CreditDebit,
+    /// Код типа комиссии (Tp/Cd).
+    pub charge_type: Option<String>,
+    /// Плательщик комиссии (Br).
+    pub bearer: Option<String>,
+    /// Включена ли комиссия в сумму записи (ChrgInclInd).
+    pub included: Option<bool>,
+}
+
 /// Детали транзакции.
 #[derive(Debug, Clone)]
 pub struct Camt053TransactionDetails {
@@ -84,29 +174,106 @@ pub struct Camt053TransactionDetails {
     pub amount: Option<i64>,
     /// Код валюты.
     pub currency: Option<String>,
-    /// Имя плательщика.
-    pub debtor_name: Option<String>,
+    /// Плательщик (RltdPties/Dbtr).
+    pub debtor: Option<Camt053Party>,
     /// Счет плательщика.
     pub debtor_account: Option<String>,
-    /// Имя получателя.
-    pub creditor_name: Option<String>,
+    /// Конечный плательщик (RltdPties/UltmtDbtr).
+    pub ultimate_debtor: Option<Camt053Party>,
+    /// Получатель (RltdPties/Cdtr).
+    pub creditor: Option<Camt053Party>,
     /// Счет получателя.
     pub creditor_account: Option<String>,
+    /// Конечный получатель (RltdPties/UltmtCdtr).
+    pub ultimate_creditor: Option<Camt053Party>,
+    /// Банк плательщика (RltdAgts/DbtrAgt).
+    pub debtor_agent: Option<Camt053Agent>,
+    /// Банк получателя (RltdAgts/CdtrAgt).
+    pub creditor_agent: Option<Camt053Agent>,
     /// Информация о назначении платежа.
     pub remittance_info: Vec<String>,
+    /// Детализация сумм (AmtDtls).
+    pub amount_details: Camt053AmountDetails,
+    /// Комиссии (Chrgs).
+    pub charges: Vec<Camt053Charge>,
 }
 
 impl Camt053Statement {
     /// Парсит CAMT.053 из любого источника, реализующего трейт Read.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
     pub fn from_read<R: Read>(reader: &mut R) -> Result<Self> {
-        let mut content = String::new();
-        reader.read_to_string(&mut content)?;
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит CAMT.053 из любого источника, реализующего трейт AsyncRead.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string_async(reader, None).await?;
         Self::parse(&content)
     }
 
+    /// Парсит CAMT.053 из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Self> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
     /// Парсит CAMT.053 из строки.
+    ///
+    /// Если документ содержит несколько элементов `<Stmt>`, возвращается
+    /// первый; все выписки возвращает [`Camt053Statement::parse_all`].
     pub fn parse(content: &str) -> Result<Self> {
-        let content = content.trim();
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит первую выписку CAMT.053 с заданными ограничениями.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Self> {
+        let (message_id, creation_date_time) = Self::parse_group_header(content, limits)?;
+        let blocks = Self::split_statements(content)?;
+
+        Self::parse_stmt(blocks[0], &message_id, creation_date_time, limits)
+    }
+
+    /// Парсит все элементы `<Stmt>` документа CAMT.053.
+    ///
+    /// Выписки независимы: с feature `parallel` они разбираются параллельно.
+    /// Порядок выписок сохраняется, ошибка содержит номер элемента `<Stmt>`.
+    pub fn parse_all(content: &str) -> Result<Vec<Self>> {
+        Self::parse_all_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит все элементы `<Stmt>` с заданными ограничениями.
+    pub fn parse_all_with_limits(content: &str, limits: &Limits) -> Result<Vec<Self>> {
+        let (message_id, creation_date_time) = Self::parse_group_header(content, limits)?;
+        let blocks = Self::split_statements(content)?;
+        limits.check_statements(blocks.len())?;
+
+        parallel::map_ordered(&blocks, |block| {
+            Self::parse_stmt(block, &message_id, creation_date_time, limits)
+        })
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            result.map_err(|e| match e {
+                Error::LimitExceeded(e) => {
+                    Error::LimitExceeded(format!("Stmt №{}: {}", index + 1, e))
+                }
+                e => Error::InvalidFormat(format!("Stmt №{}: {}", index + 1, e)),
+            })
+        })
+        .collect()
+    }
+
+    /// Проверяет размер, вложенность и корневой элемент документа
+    /// и читает MsgId и CreDtTm из GrpHdr.
+    fn parse_group_header(content: &str, limits: &Limits) -> Result<(String, DateTime)> {
+        limits.check_input(content.len())?;
+        limits.check_xml_depth(content)?;
 
         if !content.contains("<BkToCstmrStmt>") {
             return Err(Error::InvalidFormat(
@@ -117,27 +284,52 @@ impl Camt053Statement {
         let message_id = Self::extract_element_value(content, "MsgId").ok_or_else(|| {
             Error::MissingField("Не найден обязательный элемент MsgId".to_string())
         })?;
-        let creation_date_time = Self::extract_element_value(content, "CreDtTm").ok_or_else(|| {
+        let creation_date_time = Self::element_value(content, "CreDtTm").ok_or_else(|| {
             Error::MissingField("Не найден обязательный элемент CreDtTm".to_string())
         })?;
+        let creation_date_time = creation_date_time.parse()?;
 
-        let stmt_start = content.find("<Stmt>").ok_or_else(|| {
-            Error::InvalidFormat("Не найден элемент Stmt".to_string())
-        })?;
-        let stmt_end = content.find("</Stmt>").ok_or_else(|| {
-            Error::InvalidFormat("Не найден закрывающий тег Stmt".to_string())
-        })?;
-        let stmt_content = &content[stmt_start..stmt_end + 7];
+        Ok((message_id, creation_date_time))
+    }
+
+    /// Элементы `<Stmt>` документа в порядке следования (хотя бы один).
+    fn split_statements(content: &str) -> Result<Vec<&str>> {
+        let mut blocks = Vec::new();
+        let mut rest = content;
+
+        while let Some(start) = rest.find("<Stmt>") {
+            let end = rest[start..].find("</Stmt>").ok_or_else(|| {
+                Error::InvalidFormat("Не найден закрывающий тег Stmt".to_string())
+            })?;
+            let end = start + end + "</Stmt>".len();
+            blocks.push(&rest[start..end]);
+            rest = &rest[end..];
+        }
+
+        if blocks.is_empty() {
+            return Err(Error::InvalidFormat("Не найден элемент Stmt".to_string()));
+        }
+
+        Ok(blocks)
+    }
+
+    fn parse_stmt(
+        stmt_content: &str,
+        message_id: &str,
+        creation_date_time: DateTime,
+        limits: &Limits,
+    ) -> Result<Self> {
+        limits.check_transactions(stmt_content.matches("<Ntry>").count())?;
 
         let statement_id = Self::extract_element_value(stmt_content, "Id").ok_or_else(|| {
             Error::MissingField("Не найден обязательный элемент Id в Stmt".to_string())
         })?;
         let account = Self::parse_account(stmt_content)?;
         let balances = Self::parse_balances(stmt_content)?;
-        let entries = Self::parse_entries(stmt_content)?;
+        let entries = Self::parse_entries(stmt_content, creation_date_time.date())?;
 
         Ok(Camt053Statement {
-            message_id,
+            message_id: message_id.to_string(),
             creation_date_time,
             statement_id,
             account,
@@ -147,30 +339,53 @@ impl Camt053Statement {
     }
 
     fn extract_element_value(content: &str, tag: &str) -> Option<String> {
-        let open_tag = format!("<{}>", tag);
-        let close_tag = format!("</{}>", tag);
+        Self::element_value(content, tag).map(str::to_string)
+    }
 
-        let start = content.find(&open_tag)?;
-        let value_start = start + open_tag.len();
-        let end = content[value_start..].find(&close_tag)?;
+    /// Значение первого элемента `tag` как срез входного буфера, без копирования.
+    fn element_value<'a>(content: &'a str, tag: &str) -> Option<&'a str> {
+        let (_, value_start) = Self::find_tag(content, "<", tag)?;
+        let (end, _) = Self::find_tag(&content[value_start..], "</", tag)?;
+
+        Some(content[value_start..value_start + end].trim())
+    }
 
-        Some(content[value_start..value_start + end].trim().to_string())
+    /// Начало и конец первого тега `{prefix}{tag}>` без сборки строки тега.
+    fn find_tag(content: &str, prefix: &str, tag: &str) -> Option<(usize, usize)> {
+        let mut from = 0;
+        while let Some(pos) = content[from..].find(prefix) {
+            let start = from + pos;
+            let name_start = start + prefix.len();
+            let rest = &content[name_start..];
+            if rest.starts_with(tag) && rest[tag.len()..].starts_with('>') {
+                return Some((start, name_start + tag.len() + 1));
+            }
+            from = name_start;
+        }
+        None
     }
 
     fn parse_account(content: &str) -> Result<Camt053Account> {
         let acct_start = content.find("<Acct>").unwrap_or(0);
-        let acct_end = content.find("</Acct>").unwrap_or(content.len());
+        let acct_end = content[acct_start..]
+            .find("</Acct>")
+            .map_or(content.len(), |end| acct_start + end);
         let acct_content = &content[acct_start..acct_end];
 
         let iban = Self::extract_element_value(acct_content, "IBAN");
         let currency = Self::extract_element_value(acct_content, "Ccy").unwrap_or_else(|| "EUR".to_string());
         let name = Self::extract_element_value(acct_content, "Nm");
 
-        let owner_name = if let Some(ownr_start) = acct_content.find("<Ownr>") {
-            let ownr_end = acct_content.find("</Ownr>").unwrap_or(acct_content.len());
-            Self::extract_element_value(&acct_content[ownr_start..ownr_end], "Nm")
+        let (owner_name, owner_tax_id) = if let Some(ownr_start) = acct_content.find("<Ownr>") {
+            let ownr_end = acct_content[ownr_start..]
+                .find("</Ownr>")
+                .map_or(acct_content.len(), |end| ownr_start + end);
+            let ownr = &acct_content[ownr_start..ownr_end];
+            let tax_id = Self::extract_block(ownr, "OrgId")
+                .and_then(|org| Self::parse_org_identifiers(org).1);
+            (Self::extract_element_value(ownr, "Nm"), tax_id)
         } else {
-            None
+            (None, None)
         };
 
         Ok(Camt053Account {
@@ -178,6 +393,7 @@ impl Camt053Statement {
             currency,
             name,
             owner_name,
+            owner_tax_id,
         })
     }
 
@@ -187,8 +403,10 @@ impl Camt053Statement {
 
         while let Some(bal_start) = content[pos..].find("<Bal>") {
             let abs_start = pos + bal_start;
-            let bal_end = content[abs_start..].find("</Bal>").unwrap_or(content.len() - abs_start);
-            let bal_content = &content[abs_start..abs_start + bal_end + 6];
+            let bal_end = content[abs_start..]
+                .find("</Bal>")
+                .map_or(content.len(), |end| abs_start + end + 6);
+            let bal_content = &content[abs_start..bal_end];
 
             match Self::parse_single_balance(bal_content) {
                 Ok(balance) => balances.push(balance),
@@ -197,19 +415,19 @@ impl Camt053Statement {
                 }
             }
 
-            pos = abs_start + bal_end + 6;
+            pos = bal_end;
         }
 
         Ok(balances)
     }
 
     fn parse_single_balance(content: &str) -> Result<Camt053Balance> {
-        let balance_type_str = Self::extract_element_value(content, "Cd").unwrap_or_default();
-        let balance_type = BalanceType::from_code(&balance_type_str);
+        let balance_type =
+            BalanceType::from_code(Self::element_value(content, "Cd").unwrap_or_default());
         let (amount, currency) = Self::parse_amount_with_currency(content, "Amt")?;
-        let credit_debit_str =
-            Self::extract_element_value(content, "CdtDbtInd").unwrap_or_else(|| CREDIT_INDICATOR.to_string());
-        let credit_debit = CreditDebit::from_code(&credit_debit_str);
+        let credit_debit = CreditDebit::from_code(
+            Self::element_value(content, "CdtDbtInd").unwrap_or(CREDIT_INDICATOR),
+        );
         let date = Self::parse_date_element(content)?;
 
         Ok(Camt053Balance {
@@ -234,9 +452,8 @@ impl Camt053Statement {
 
         let tag_content = &content[start..start + tag_end];
         let currency = if let Some(ccy_pos) = tag_content.find("Ccy=\"") {
-            let ccy_start = ccy_pos + 5;
-            let ccy_end = tag_content[ccy_start..].find('"').unwrap_or(3);
-            tag_content[ccy_start..ccy_start + ccy_end].to_string()
+            let ccy = &tag_content[ccy_pos + 5..];
+            ccy[..ccy.find('"').unwrap_or(ccy.len())].to_string()
         } else {
             "EUR".to_string()
         };
@@ -288,7 +505,7 @@ impl Camt053Statement {
                 0 => "00".to_string(),
                 1 => format!("{}0", frac_str),
                 2 => frac_str.to_string(),
-                _ => frac_str[..2].to_string(),
+                _ => frac_str.get(..2).unwrap_or_default().to_string(),
             };
             frac_padded.parse().map_err(|_| {
                 Error::Parse(format!("Некорректная дробная часть суммы: {}", frac_str))
@@ -304,31 +521,25 @@ impl Camt053Statement {
     }
 
     fn parse_date_element(content: &str) -> Result<Date> {
-        // Ищем самый внутренний <Dt> элемент, который содержит только дату
-        let mut date_str = Self::extract_element_value(content, "Dt").ok_or_else(|| {
-            Error::MissingField("Не найден элемент Dt".to_string())
-        })?;
+        // Самый внутренний <Dt> или <DtTm> — последний открывающий тег перед
+        // первым закрывающим; находится за один проход при любой вложенности
+        let missing = || Error::MissingField("Не найден элемент Dt или DtTm".to_string());
+        let (end, open_tag) = match (content.find("</Dt>"), content.find("</DtTm>")) {
+            (Some(dt), Some(dt_tm)) if dt_tm < dt => (dt_tm, "<DtTm>"),
+            (Some(dt), _) => (dt, "<Dt>"),
+            (None, Some(dt_tm)) => (dt_tm, "<DtTm>"),
+            (None, None) => return Err(missing()),
+        };
+        let start = content[..end].rfind(open_tag).ok_or_else(missing)? + open_tag.len();
+        let value = &content[start..end];
 
-        // Если внутри есть вложенный <Dt>, извлекаем его рекурсивно
-        while date_str.contains("<Dt>") {
-            if let Some(inner) = Self::extract_element_value(&date_str, "Dt") {
-                date_str = inner;
-            } else {
-                // Если не можем извлечь вложенный тег, извлекаем дату напрямую
-                // из содержимого после тега
-                if let Some(start) = date_str.find("<Dt>") {
-                    let value_start = start + 4;
-                    if let Some(end) = date_str[value_start..].find('<') {
-                        date_str = date_str[value_start..value_start + end].trim().to_string();
-                    } else {
-                        date_str = date_str[value_start..].trim().to_string();
-                    }
-                }
-                break;
-            }
+        if open_tag == "<DtTm>" {
+            // Дата берется по местному времени банка, указанному в DtTm
+            let date_time: DateTime = value.trim().parse()?;
+            Ok(date_time.date())
+        } else {
+            Self::parse_iso_date(value)
         }
-
-        Self::parse_iso_date(&date_str)
     }
 
     fn parse_iso_date(date_str: &str) -> Result<Date> {
@@ -351,53 +562,66 @@ impl Camt053Statement {
             Error::Parse(format!("Некорректный день: {}", parts[2]))
         })?;
 
-        Ok(Date::new(year, month, day))
+        Date::from_ymd(year, month, day)
     }
 
-    fn parse_entries(content: &str) -> Result<Vec<Camt053Entry>> {
+    /// Разбирает записи `<Ntry>`; запись без BookgDt датируется датой
+    /// валютирования, а без нее — датой создания выписки.
+    fn parse_entries(content: &str, creation_date: Date) -> Result<Vec<Camt053Entry>> {
         let mut entries = Vec::new();
         let mut pos = 0;
 
         while let Some(ntry_start) = content[pos..].find("<Ntry>") {
             let abs_start = pos + ntry_start;
-            let ntry_end = content[abs_start..].find("</Ntry>").unwrap_or(content.len() - abs_start);
-            let ntry_content = &content[abs_start..abs_start + ntry_end + 7];
+            let ntry_end = content[abs_start..]
+                .find("</Ntry>")
+                .map_or(content.len(), |end| abs_start + end + 7);
+            let ntry_content = &content[abs_start..ntry_end];
 
-            match Self::parse_single_entry(ntry_content) {
+            match Self::parse_single_entry(ntry_content, creation_date) {
                 Ok(entry) => entries.push(entry),
                 Err(e) => {
                     tracing::warn!("Не удалось распарсить запись: {}", e);
                 }
             }
 
-            pos = abs_start + ntry_end + 7;
+            pos = ntry_end;
         }
 
         Ok(entries)
     }
 
-    fn parse_single_entry(content: &str) -> Result<Camt053Entry> {
+    fn parse_single_entry(content: &str, creation_date: Date) -> Result<Camt053Entry> {
         let entry_ref = Self::extract_element_value(content, "NtryRef");
         let (amount, currency) = Self::parse_amount_with_currency(content, "Amt")?;
-        let credit_debit_str =
-            Self::extract_element_value(content, "CdtDbtInd").unwrap_or_else(|| CREDIT_INDICATOR.to_string());
-        let credit_debit = CreditDebit::from_code(&credit_debit_str);
-
-        let booking_date = if let Some(bookg_start) = content.find("<BookgDt>") {
-            let bookg_end = content.find("</BookgDt>").unwrap_or(content.len());
-            Self::parse_date_element(&content[bookg_start..bookg_end])?
-        } else {
-            Date::new(2024, 1, 1)
-        };
+        let credit_debit = CreditDebit::from_code(
+            Self::element_value(content, "CdtDbtInd").unwrap_or(CREDIT_INDICATOR),
+        );
 
         let value_date = if let Some(val_start) = content.find("<ValDt>") {
-            let val_end = content.find("</ValDt>").unwrap_or(content.len());
+            let val_end = content[val_start..]
+                .find("</ValDt>")
+                .map_or(content.len(), |end| val_start + end);
             Self::parse_date_element(&content[val_start..val_end]).ok()
         } else {
             None
         };
 
+        let booking_date = if let Some(bookg_start) = content.find("<BookgDt>") {
+            let bookg_end = content[bookg_start..]
+                .find("</BookgDt>")
+                .map_or(content.len(), |end| bookg_start + end);
+            Self::parse_date_element(&content[bookg_start..bookg_end])?
+        } else {
+            value_date.unwrap_or(creation_date)
+        };
+
         let account_servicer_ref = Self::extract_element_value(content, "AcctSvcrRef");
+
+        // Детализация сумм и комиссии уровня записи расположены до NtryDtls
+        let entry_level = &content[..content.find("<NtryDtls>").unwrap_or(content.len())];
+        let amount_details = Self::parse_amount_details(entry_level)?;
+        let charges = Self::parse_charges(entry_level)?;
         let transaction_details = Self::parse_transaction_details(content)?;
 
         Ok(Camt053Entry {
@@ -408,6 +632,8 @@ impl Camt053Statement {
             booking_date,
             value_date,
             account_servicer_ref,
+            amount_details,
+            charges,
             transaction_details,
         })
     }
@@ -418,18 +644,30 @@ impl Camt053Statement {
 
         while let Some(tx_start) = content[pos..].find("<TxDtls>") {
             let abs_start = pos + tx_start;
-            let tx_end = content[abs_start..].find("</TxDtls>").unwrap_or(content.len() - abs_start);
-            let tx_content = &content[abs_start..abs_start + tx_end + 9];
+            let tx_end = content[abs_start..]
+                .find("</TxDtls>")
+                .map_or(content.len(), |end| abs_start + end + 9);
+            let tx_content = &content[abs_start..tx_end];
 
             let end_to_end_id = Self::extract_element_value(tx_content, "EndToEndId");
             let transaction_id = Self::extract_element_value(tx_content, "TxId");
 
-            let (amount, currency) = Self::parse_amount_with_currency(tx_content, "Amt")
-                .map(|(a, c)| (Some(a), Some(c)))
-                .unwrap_or((None, None));
+            let amount_details = Self::parse_amount_details(tx_content)?;
+            let charges = Self::parse_charges(tx_content)?;
+
+            let (amount, currency) = match amount_details.transaction_amount {
+                Some(ref tx_amt) => (Some(tx_amt.amount), Some(tx_amt.currency.clone())),
+                None => {
+                    // Сумма вне AmtDtls (camt.053.001.08+), комиссии не учитываем
+                    let direct = Self::strip_block(tx_content, "Chrgs");
+                    Self::parse_amount_with_currency(&direct, "Amt")
+                        .map(|(a, c)| (Some(a), Some(c)))
+                        .unwrap_or((None, None))
+                }
+            };
 
-            let (debtor_name, debtor_account) = Self::parse_party_info(tx_content, "Dbtr");
-            let (creditor_name, creditor_account) = Self::parse_party_info(tx_content, "Cdtr");
+            let parties = Self::extract_block(tx_content, "RltdPties").unwrap_or("");
+            let agents = Self::extract_block(tx_content, "RltdAgts").unwrap_or("");
             let remittance_info = Self::parse_remittance_info(tx_content);
 
             details.push(Camt053TransactionDetails {
@@ -437,42 +675,254 @@ impl Camt053Statement {
                 transaction_id,
                 amount,
                 currency,
-                debtor_name,
-                debtor_account,
-                creditor_name,
-                creditor_account,
+                debtor: Self::parse_party(parties, "Dbtr"),
+                debtor_account: Self::parse_party_account(parties, "DbtrAcct"),
+                ultimate_debtor: Self::parse_party(parties, "UltmtDbtr"),
+                creditor: Self::parse_party(parties, "Cdtr"),
+                creditor_account: Self::parse_party_account(parties, "CdtrAcct"),
+                ultimate_creditor: Self::parse_party(parties, "UltmtCdtr"),
+                debtor_agent: Self::parse_agent(agents, "DbtrAgt"),
+                creditor_agent: Self::parse_agent(agents, "CdtrAgt"),
                 remittance_info,
+                amount_details,
+                charges,
             });
 
-            pos = abs_start + tx_end + 9;
+            pos = tx_end;
         }
 
         Ok(details)
     }
 
-    fn parse_party_info(content: &str, party_tag: &str) -> (Option<String>, Option<String>) {
-        let open_tag = format!("<{}>", party_tag);
-        let close_tag = format!("</{}>", party_tag);
+    /// Возвращает содержимое первого блока `<tag>...</tag>` (включая теги).
+    ///
+    /// Учитывает вложенные одноименные элементы (например, `<Id>` внутри `<Othr>`).
+    fn extract_block<'a>(content: &'a str, tag: &str) -> Option<&'a str> {
+        let open_tag = format!("<{}>", tag);
+        let close_tag = format!("</{}>", tag);
 
-        if let Some(start) = content.find(&open_tag) {
-            let end = content.find(&close_tag).unwrap_or(content.len());
-            let party_content = &content[start..end];
+        let start = content.find(&open_tag)?;
+        let mut depth = 0usize;
+        let mut pos = start;
+
+        loop {
+            let next_close = pos + content[pos..].find(&close_tag)?;
+            match content[pos..].find(&open_tag).map(|p| pos + p) {
+                Some(next_open) if next_open < next_close => {
+                    depth += 1;
+                    pos = next_open + open_tag.len();
+                }
+                _ => {
+                    depth -= 1;
+                    pos = next_close + close_tag.len();
+                    if depth == 0 {
+                        return Some(&content[start..pos]);
+                    }
+                }
+            }
+        }
+    }
 
-            let name = Self::extract_element_value(party_content, "Nm");
+    /// Возвращает содержимое без всех блоков `<tag>...</tag>`.
+    fn strip_block(content: &str, tag: &str) -> String {
+        let mut content = content.to_string();
+        while let Some(block) = Self::extract_block(&content, tag) {
+            content = content.replacen(block, "", 1);
+        }
+        content
+    }
 
-            let account = Self::extract_element_value(party_content, "IBAN")
-                .or_else(|| {
-                    if let Some(id_start) = party_content.find("<Id>") {
-                        let id_end = party_content.find("</Id>").unwrap_or(party_content.len());
-                        Self::extract_element_value(&party_content[id_start..id_end], "Id")
-                    } else {
-                        None
-                    }
-                });
+    fn parse_amount_details(content: &str) -> Result<Camt053AmountDetails> {
+        let Some(amt_dtls) = Self::extract_block(content, "AmtDtls") else {
+            return Ok(Camt053AmountDetails::default());
+        };
 
-            (name, account)
-        } else {
-            (None, None)
+        Ok(Camt053AmountDetails {
+            instructed_amount: Self::parse_amount_detail(amt_dtls, "InstdAmt")?,
+            transaction_amount: Self::parse_amount_detail(amt_dtls, "TxAmt")?,
+            counter_value_amount: Self::parse_amount_detail(amt_dtls, "CntrValAmt")?,
+        })
+    }
+
+    fn parse_amount_detail(content: &str, tag: &str) -> Result<Option<Camt053AmountDetail>> {
+        let Some(block) = Self::extract_block(content, tag) else {
+            return Ok(None);
+        };
+
+        let (amount, currency) = Self::parse_amount_with_currency(block, "Amt")?;
+        let currency_exchange = Self::extract_block(block, "CcyXchg").and_then(|xchg| {
+            Some(ExchangeRate {
+                source_currency: Self::extract_element_value(xchg, "SrcCcy")?,
+                target_currency: Self::extract_element_value(xchg, "TrgtCcy"),
+                unit_currency: Self::extract_element_value(xchg, "UnitCcy"),
+                rate: Self::extract_element_value(xchg, "XchgRate")?,
+                contract_id: Self::extract_element_value(xchg, "CtrctId"),
+            })
+        });
+
+        Ok(Some(Camt053AmountDetail {
+            amount,
+            currency,
+            currency_exchange,
+        }))
+    }
+
+    /// Парсит комиссии.
+    ///
+    /// Поддерживает как плоский список `<Chrgs>` (camt.053.001.02),
+    /// так и записи `<Chrgs><Rcrd>` (camt.053.001.04+).
+    fn parse_charges(content: &str) -> Result<Vec<Camt053Charge>> {
+        let mut charges = Vec::new();
+        let mut pos = 0;
+
+        while let Some(start) = content[pos..].find("<Chrgs>") {
+            let abs_start = pos + start;
+            let Some(end) = content[abs_start..].find("</Chrgs>") else {
+                break;
+            };
+            let chrgs_content = &content[abs_start..abs_start + end + 8];
+
+            if chrgs_content.contains("<Rcrd>") {
+                let mut rcrd_pos = 0;
+                while let Some(rcrd) = Self::extract_block(&chrgs_content[rcrd_pos..], "Rcrd") {
+                    charges.push(Self::parse_single_charge(rcrd)?);
+                    rcrd_pos = chrgs_content[rcrd_pos..]
+                        .find("</Rcrd>")
+                        .map(|p| rcrd_pos + p + 7)
+                        .unwrap_or(chrgs_content.len());
+                }
+            } else {
+                charges.push(Self::parse_single_charge(chrgs_content)?);
+            }
+
+            pos = abs_start + end + 8;
+        }
+
+        Ok(charges)
+    }
+
+    fn parse_single_charge(content: &str) -> Result<Camt053Charge> {
+        let (amount, currency) = Self::parse_amount_with_currency(content, "Amt")?;
+        let credit_debit = CreditDebit::from_code(
+            Self::element_value(content, "CdtDbtInd").unwrap_or(DEBIT_INDICATOR),
+        );
+        let charge_type = Self::extract_block(content, "Tp")
+            .and_then(|tp| {
+                Self::extract_element_value(tp, "Cd")
+                    .or_else(|| Self::extract_element_value(tp, "Id"))
+            });
+        let bearer = Self::extract_element_value(content, "Br");
+        let included = Self::element_value(content, "ChrgInclInd").map(|v| v == "true");
+
+        Ok(Camt053Charge {
+            amount,
+            currency,
+            credit_debit,
+            charge_type,
+            bearer,
+            included,
+        })
+    }
+
+    fn parse_party(content: &str, party_tag: &str) -> Option<Camt053Party> {
+        let block = Self::extract_block(content, party_tag)?;
+        // В camt.053.001.08+ участник вложен в <Pty>
+        let block = Self::extract_block(block, "Pty").unwrap_or(block);
+
+        let id_block = Self::extract_block(block, "Id");
+        let org_id = id_block.and_then(|id| Self::extract_block(id, "OrgId"));
+        let prvt_id = id_block.and_then(|id| Self::extract_block(id, "PrvtId"));
+
+        // Nm ищем вне блока идентификации, чтобы не спутать с SchmeNm
+        let name_scope = match id_block {
+            Some(id) => block.replacen(id, "", 1),
+            None => block.to_string(),
+        };
+
+        let (organisation_id, tax_id) = org_id
+            .map(Self::parse_org_identifiers)
+            .unwrap_or_default();
+
+        Some(Camt053Party {
+            name: Self::extract_element_value(&name_scope, "Nm"),
+            postal_address: Self::extract_block(block, "PstlAdr").map(Self::parse_postal_address),
+            bic: org_id.and_then(|org| {
+                Self::extract_element_value(org, "AnyBIC")
+                    .or_else(|| Self::extract_element_value(org, "BICOrBEI"))
+            }),
+            organisation_id,
+            tax_id,
+            private_id: prvt_id
+                .and_then(|prvt| Self::extract_block(prvt, "Othr"))
+                .and_then(|othr| Self::extract_element_value(othr, "Id")),
+        })
+    }
+
+    /// Разбирает блоки Othr в OrgId: первый идентификатор и ИНН (схема TXID).
+    fn parse_org_identifiers(org: &str) -> (Option<String>, Option<String>) {
+        let mut organisation_id = None;
+        let mut tax_id = None;
+        let mut rest = org;
+
+        while let Some(othr) = Self::extract_block(rest, "Othr") {
+            let id = Self::extract_element_value(othr, "Id");
+            let scheme = Self::extract_block(othr, "SchmeNm")
+                .and_then(|scheme| Self::extract_element_value(scheme, "Cd"));
+
+            if scheme.as_deref() == Some(TAX_ID_SCHEME) && tax_id.is_none() {
+                tax_id = id.clone();
+            }
+            organisation_id = organisation_id.or(id);
+
+            let consumed = rest.find(othr).map_or(rest.len(), |p| p + othr.len());
+            rest = &rest[consumed..];
+        }
+
+        (organisation_id, tax_id)
+    }
+
+    fn parse_party_account(content: &str, account_tag: &str) -> Option<String> {
+        let block = Self::extract_block(content, account_tag)?;
+
+        Self::extract_element_value(block, "IBAN").or_else(|| {
+            Self::extract_block(block, "Othr").and_then(|othr| Self::extract_element_value(othr, "Id"))
+        })
+    }
+
+    fn parse_agent(content: &str, agent_tag: &str) -> Option<Camt053Agent> {
+        let block = Self::extract_block(content, agent_tag)?;
+        let fin_instn = Self::extract_block(block, "FinInstnId").unwrap_or(block);
+
+        Some(Camt053Agent {
+            bic: Self::extract_element_value(fin_instn, "BICFI")
+                .or_else(|| Self::extract_element_value(fin_instn, "BIC")),
+            clearing_member_id: Self::extract_block(fin_instn, "ClrSysMmbId")
+                .and_then(|clr| Self::extract_element_value(clr, "MmbId")),
+            name: Self::extract_element_value(fin_instn, "Nm"),
+            postal_address: Self::extract_block(fin_instn, "PstlAdr").map(Self::parse_postal_address),
+        })
+    }
+
+    fn parse_postal_address(content: &str) -> PostalAddress {
+        let mut address_lines = Vec::new();
+        let mut pos = 0;
+
+        while let Some(line) = Self::extract_element_value(&content[pos..], "AdrLine") {
+            address_lines.push(line);
+            pos = content[pos..]
+                .find("</AdrLine>")
+                .map(|p| pos + p + 10)
+                .unwrap_or(content.len());
+        }
+
+        PostalAddress {
+            street_name: Self::extract_element_value(content, "StrtNm"),
+            building_number: Self::extract_element_value(content, "BldgNb"),
+            post_code: Self::extract_element_value(content, "PstCd"),
+            town_name: Self::extract_element_value(content, "TwnNm"),
+            country_subdivision: Self::extract_element_value(content, "CtrySubDvsn"),
+            country: Self::extract_element_value(content, "Ctry"),
+            address_lines,
         }
     }
 
@@ -495,6 +945,47 @@ impl Camt053Statement {
     }
 }
 
+impl Camt053Party {
+    /// Создает участника только с наименованием.
+    pub fn with_name(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    fn to_counterparty(&self) -> Counterparty {
+        Counterparty {
+            name: self.name.clone(),
+            postal_address: self.postal_address.clone(),
+            organisation_id: self.organisation_id.clone().or_else(|| self.bic.clone()),
+            tax_id: self.tax_id.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Camt053Statement {
+    /// Собирает контрагента из участника, его счета, банка и конечного участника.
+    fn build_counterparty(
+        party: Option<&Camt053Party>,
+        account: Option<&String>,
+        agent: Option<&Camt053Agent>,
+        ultimate: Option<&Camt053Party>,
+    ) -> Counterparty {
+        let mut counterparty = party.map(Camt053Party::to_counterparty).unwrap_or_default();
+
+        counterparty.account = account.cloned();
+        if let Some(agent) = agent {
+            counterparty.bank_code = agent.bic.clone().or_else(|| agent.clearing_member_id.clone());
+            counterparty.bank_name = agent.name.clone();
+        }
+        counterparty.ultimate_party = ultimate.map(|u| Box::new(u.to_counterparty()));
+
+        counterparty
+    }
+}
+
 impl From<Camt053Statement> for Statement {
     fn from(camt: Camt053Statement) -> Self {
         let account = Account {
@@ -503,6 +994,7 @@ impl From<Camt053Statement> for Statement {
             currency: camt.account.currency.clone(),
             name: camt.account.name.clone(),
             owner: camt.account.owner_name.clone(),
+            tax_id: camt.account.owner_tax_id.clone(),
         };
 
         let opening_balance = camt
@@ -518,7 +1010,7 @@ impl From<Camt053Statement> for Statement {
                     },
                     &b.currency,
                 ),
-                date: b.date.clone(),
+                date: b.date,
                 is_credit: b.credit_debit.is_credit(),
             })
             .unwrap_or_else(|| Balance {
@@ -540,7 +1032,7 @@ impl From<Camt053Statement> for Statement {
                     },
                     &b.currency,
                 ),
-                date: b.date.clone(),
+                date: b.date,
                 is_credit: b.credit_debit.is_credit(),
             })
             .unwrap_or_else(|| Balance {
@@ -557,19 +1049,19 @@ impl From<Camt053Statement> for Statement {
 
                 let (counterparty, description) = if let Some(details) = entry.transaction_details.first() {
                     let counterparty = if is_credit {
-                        Counterparty {
-                            name: details.debtor_name.clone(),
-                            account: details.debtor_account.clone(),
-                            bank_code: None,
-                            bank_name: None,
-                        }
+                        Camt053Statement::build_counterparty(
+                            details.debtor.as_ref(),
+                            details.debtor_account.as_ref(),
+                            details.debtor_agent.as_ref(),
+                            details.ultimate_debtor.as_ref(),
+                        )
                     } else {
-                        Counterparty {
-                            name: details.creditor_name.clone(),
-                            account: details.creditor_account.clone(),
-                            bank_code: None,
-                            bank_name: None,
-                        }
+                        Camt053Statement::build_counterparty(
+                            details.creditor.as_ref(),
+                            details.creditor_account.as_ref(),
+                            details.creditor_agent.as_ref(),
+                            details.ultimate_creditor.as_ref(),
+                        )
                     };
 
                     let description = details.remittance_info.join(" ");
@@ -579,14 +1071,51 @@ impl From<Camt053Statement> for Statement {
                     (None, String::new())
                 };
 
+                // Детализация уровня транзакции приоритетнее детализации уровня записи
+                let (amount_details, charges) = match entry.transaction_details.first() {
+                    Some(details) if !details.amount_details.is_empty() || !details.charges.is_empty() => {
+                        (&details.amount_details, &details.charges)
+                    }
+                    _ => (&entry.amount_details, &entry.charges),
+                };
+
+                let to_amount = |detail: &Camt053AmountDetail| Amount::new(detail.amount, &detail.currency);
+
+                let exchange_rates = [
+                    &amount_details.instructed_amount,
+                    &amount_details.transaction_amount,
+                    &amount_details.counter_value_amount,
+                ]
+                .into_iter()
+                .flatten()
+                .filter_map(|detail| detail.currency_exchange.clone())
+                .collect();
+
+                let charges = charges
+                    .iter()
+                    .map(|charge| Charge {
+                        amount: Amount::new(charge.amount, &charge.currency),
+                        is_credit: charge.credit_debit.is_credit(),
+                        charge_type: charge.charge_type.clone(),
+                        bearer: charge.bearer.clone(),
+                        included: charge.included,
+                    })
+                    .collect();
+
                 Transaction {
                     date: entry.booking_date,
                     value_date: entry.value_date,
                     amount: Amount::new(entry.amount, &entry.currency),
                     is_credit,
                     reference: entry.account_servicer_ref,
+                    transaction_type: None,
                     description,
                     counterparty,
+                    instructed_amount: amount_details.instructed_amount.as_ref().map(to_amount),
+                    original_amount: amount_details.transaction_amount.as_ref().map(to_amount),
+                    counter_value_amount: amount_details.counter_value_amount.as_ref().map(to_amount),
+                    exchange_rates,
+                    charges,
                 }
             })
             .collect();
@@ -1,8 +1,15 @@
 //! Сериализация формата CAMT.053 (ISO 20022 XML).
 
-use crate::camt053::parser::{Camt053Balance, Camt053Entry, Camt053Statement, Camt053TransactionDetails};
+use crate::camt053::parser::{
+    Camt053Agent, Camt053AmountDetail, Camt053AmountDetails, Camt053Balance, Camt053Charge,
+    Camt053Entry, Camt053Party, Camt053Statement, Camt053TransactionDetails,
+};
+use crate::encoding::{self, TextEncoding};
 use crate::error::Result;
+use crate::types::{PostalAddress, TAX_ID_SCHEME};
 use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
 
 /// Writer для формата CAMT.053.
 pub struct Camt053Writer;
@@ -13,13 +20,79 @@ impl Camt053Writer {
     /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
     pub fn write_to<W: Write>(statement: &Camt053Statement, writer: &mut W) -> Result<()> {
         let mut buf_writer = BufWriter::new(writer);
-        Self::write_to_buffered(statement, &mut buf_writer)?;
+        Self::write_to_buffered(statement, &mut buf_writer, TextEncoding::Utf8)?;
         buf_writer.flush()?;
         Ok(())
     }
 
-    fn write_to_buffered<W: Write>(statement: &Camt053Statement, writer: &mut W) -> Result<()> {
-        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    /// Асинхронно записывает выписку CAMT.053 в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statement: &Camt053Statement,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statement, buffer)).await
+    }
+
+    /// Записывает выписку CAMT.053 в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        statement: &Camt053Statement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(statement, buffer, encoding)
+        })
+    }
+
+    /// Асинхронно записывает выписку CAMT.053 в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statement: &Camt053Statement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statement, buffer, encoding)
+        })
+        .await
+    }
+
+    /// Записывает выписки из итератора по мере их поступления,
+    /// каждую отдельным документом.
+    ///
+    /// В памяти находится только текущая выписка. При ошибке во входном
+    /// итераторе запись прекращается. Возвращает число записанных выписок.
+    pub fn write_iter_with_encoding<W, I, S>(
+        statements: I,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<usize>
+    where
+        W: Write,
+        I: IntoIterator<Item = Result<S>>,
+        S: Into<Camt053Statement>,
+    {
+        let mut written = 0;
+        for statement in statements {
+            Self::write_to_with_encoding(&statement?.into(), writer, encoding)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    fn write_to_buffered<W: Write>(
+        statement: &Camt053Statement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        writeln!(
+            writer,
+            "<?xml version=\"1.0\" encoding=\"{}\"?>",
+            encoding.label()
+        )?;
         writeln!(
             writer,
             "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.02\">"
@@ -28,7 +101,7 @@ impl Camt053Writer {
 
         writeln!(writer, "<GrpHdr>")?;
         writeln!(writer, "<MsgId>{}</MsgId>", Self::escape_xml(&statement.message_id))?;
-        writeln!(writer, "<CreDtTm>{}</CreDtTm>", Self::escape_xml(&statement.creation_date_time))?;
+        writeln!(writer, "<CreDtTm>{}</CreDtTm>", statement.creation_date_time)?;
         writeln!(writer, "</GrpHdr>")?;
 
         writeln!(writer, "<Stmt>")?;
@@ -66,9 +139,18 @@ impl Camt053Writer {
             writeln!(writer, "<Nm>{}</Nm>", Self::escape_xml(name))?;
         }
 
-        if let Some(ref owner) = statement.account.owner_name {
+        if statement.account.owner_name.is_some() || statement.account.owner_tax_id.is_some() {
             writeln!(writer, "<Ownr>")?;
-            writeln!(writer, "<Nm>{}</Nm>", Self::escape_xml(owner))?;
+            if let Some(ref owner) = statement.account.owner_name {
+                writeln!(writer, "<Nm>{}</Nm>", Self::escape_xml(owner))?;
+            }
+            if let Some(ref tax_id) = statement.account.owner_tax_id {
+                writeln!(writer, "<Id>")?;
+                writeln!(writer, "<OrgId>")?;
+                Self::write_tax_id(writer, tax_id)?;
+                writeln!(writer, "</OrgId>")?;
+                writeln!(writer, "</Id>")?;
+            }
             writeln!(writer, "</Ownr>")?;
         }
 
@@ -143,6 +225,12 @@ impl Camt053Writer {
             writeln!(writer, "<AcctSvcrRef>{}</AcctSvcrRef>", Self::escape_xml(acct_ref))?;
         }
 
+        if !entry.amount_details.is_empty() {
+            Self::write_amount_details(writer, &entry.amount_details)?;
+        }
+
+        Self::write_charges(writer, &entry.charges)?;
+
         if !entry.transaction_details.is_empty() {
             writeln!(writer, "<NtryDtls>")?;
             for details in &entry.transaction_details {
@@ -171,7 +259,9 @@ impl Camt053Writer {
         }
         writeln!(writer, "</Refs>")?;
 
-        if let (Some(amount), Some(ref currency)) = (details.amount, &details.currency) {
+        if !details.amount_details.is_empty() {
+            Self::write_amount_details(writer, &details.amount_details)?;
+        } else if let (Some(amount), Some(ref currency)) = (details.amount, &details.currency) {
             writeln!(writer, "<AmtDtls>")?;
             writeln!(writer, "<TxAmt>")?;
             writeln!(
@@ -184,42 +274,47 @@ impl Camt053Writer {
             writeln!(writer, "</AmtDtls>")?;
         }
 
+        Self::write_charges(writer, &details.charges)?;
+
         writeln!(writer, "<RltdPties>")?;
 
-        if details.debtor_name.is_some() || details.debtor_account.is_some() {
-            writeln!(writer, "<Dbtr>")?;
-            if let Some(ref name) = details.debtor_name {
-                writeln!(writer, "<Nm>{}</Nm>", Self::escape_xml(name))?;
-            }
-            writeln!(writer, "</Dbtr>")?;
+        if details.debtor.is_some() || details.debtor_account.is_some() {
+            Self::write_party(writer, "Dbtr", details.debtor.as_ref())?;
 
             if let Some(ref account) = details.debtor_account {
-                writeln!(writer, "<DbtrAcct>")?;
-                writeln!(writer, "<Id>")?;
-                writeln!(writer, "<IBAN>{}</IBAN>", Self::escape_xml(account))?;
-                writeln!(writer, "</Id>")?;
-                writeln!(writer, "</DbtrAcct>")?;
+                Self::write_party_account(writer, "DbtrAcct", account)?;
             }
         }
 
-        if details.creditor_name.is_some() || details.creditor_account.is_some() {
-            writeln!(writer, "<Cdtr>")?;
-            if let Some(ref name) = details.creditor_name {
-                writeln!(writer, "<Nm>{}</Nm>", Self::escape_xml(name))?;
-            }
-            writeln!(writer, "</Cdtr>")?;
+        if details.ultimate_debtor.is_some() {
+            Self::write_party(writer, "UltmtDbtr", details.ultimate_debtor.as_ref())?;
+        }
+
+        if details.creditor.is_some() || details.creditor_account.is_some() {
+            Self::write_party(writer, "Cdtr", details.creditor.as_ref())?;
 
             if let Some(ref account) = details.creditor_account {
-                writeln!(writer, "<CdtrAcct>")?;
-                writeln!(writer, "<Id>")?;
-                writeln!(writer, "<IBAN>{}</IBAN>", Self::escape_xml(account))?;
-                writeln!(writer, "</Id>")?;
-                writeln!(writer, "</CdtrAcct>")?;
+                Self::write_party_account(writer, "CdtrAcct", account)?;
             }
         }
 
+        if details.ultimate_creditor.is_some() {
+            Self::write_party(writer, "UltmtCdtr", details.ultimate_creditor.as_ref())?;
+        }
+
         writeln!(writer, "</RltdPties>")?;
 
+        if details.debtor_agent.is_some() || details.creditor_agent.is_some() {
+            writeln!(writer, "<RltdAgts>")?;
+            if let Some(ref agent) = details.debtor_agent {
+                Self::write_agent(writer, "DbtrAgt", agent)?;
+            }
+            if let Some(ref agent) = details.creditor_agent {
+                Self::write_agent(writer, "CdtrAgt", agent)?;
+            }
+            writeln!(writer, "</RltdAgts>")?;
+        }
+
         if !details.remittance_info.is_empty() {
             writeln!(writer, "<RmtInf>")?;
             for info in &details.remittance_info {
@@ -233,6 +328,229 @@ impl Camt053Writer {
         Ok(())
     }
 
+    fn write_party<W: Write>(writer: &mut W, tag: &str, party: Option<&Camt053Party>) -> Result<()> {
+        writeln!(writer, "<{}>", tag)?;
+
+        if let Some(party) = party {
+            if let Some(ref name) = party.name {
+                writeln!(writer, "<Nm>{}</Nm>", Self::escape_xml(name))?;
+            }
+
+            if let Some(ref address) = party.postal_address {
+                Self::write_postal_address(writer, address)?;
+            }
+
+            let has_org_id =
+                party.bic.is_some() || party.organisation_id.is_some() || party.tax_id.is_some();
+            if has_org_id || party.private_id.is_some() {
+                writeln!(writer, "<Id>")?;
+                if has_org_id {
+                    writeln!(writer, "<OrgId>")?;
+                    if let Some(ref bic) = party.bic {
+                        writeln!(writer, "<BICOrBEI>{}</BICOrBEI>", Self::escape_xml(bic))?;
+                    }
+                    // Идентификатор, совпадающий с ИНН, пишем один раз со схемой TXID
+                    if let Some(org_id) = party
+                        .organisation_id
+                        .as_ref()
+                        .filter(|id| party.tax_id.as_ref() != Some(*id))
+                    {
+                        writeln!(writer, "<Othr>")?;
+                        writeln!(writer, "<Id>{}</Id>", Self::escape_xml(org_id))?;
+                        writeln!(writer, "</Othr>")?;
+                    }
+                    if let Some(ref tax_id) = party.tax_id {
+                        Self::write_tax_id(writer, tax_id)?;
+                    }
+                    writeln!(writer, "</OrgId>")?;
+                } else if let Some(ref private_id) = party.private_id {
+                    writeln!(writer, "<PrvtId>")?;
+                    writeln!(writer, "<Othr>")?;
+                    writeln!(writer, "<Id>{}</Id>", Self::escape_xml(private_id))?;
+                    writeln!(writer, "</Othr>")?;
+                    writeln!(writer, "</PrvtId>")?;
+                }
+                writeln!(writer, "</Id>")?;
+            }
+        }
+
+        writeln!(writer, "</{}>", tag)?;
+
+        Ok(())
+    }
+
+    fn write_tax_id<W: Write>(writer: &mut W, tax_id: &str) -> Result<()> {
+        writeln!(writer, "<Othr>")?;
+        writeln!(writer, "<Id>{}</Id>", Self::escape_xml(tax_id))?;
+        writeln!(writer, "<SchmeNm>")?;
+        writeln!(writer, "<Cd>{}</Cd>", TAX_ID_SCHEME)?;
+        writeln!(writer, "</SchmeNm>")?;
+        writeln!(writer, "</Othr>")?;
+        Ok(())
+    }
+
+    fn write_party_account<W: Write>(writer: &mut W, tag: &str, account: &str) -> Result<()> {
+        writeln!(writer, "<{}>", tag)?;
+        writeln!(writer, "<Id>")?;
+
+        // IBAN начинается с двухбуквенного кода страны, прочие счета пишем в Othr
+        if account.len() > 2 && account.chars().take(2).all(|c| c.is_ascii_alphabetic()) {
+            writeln!(writer, "<IBAN>{}</IBAN>", Self::escape_xml(account))?;
+        } else {
+            writeln!(writer, "<Othr>")?;
+            writeln!(writer, "<Id>{}</Id>", Self::escape_xml(account))?;
+            writeln!(writer, "</Othr>")?;
+        }
+
+        writeln!(writer, "</Id>")?;
+        writeln!(writer, "</{}>", tag)?;
+
+        Ok(())
+    }
+
+    fn write_agent<W: Write>(writer: &mut W, tag: &str, agent: &Camt053Agent) -> Result<()> {
+        writeln!(writer, "<{}>", tag)?;
+        writeln!(writer, "<FinInstnId>")?;
+
+        if let Some(ref bic) = agent.bic {
+            writeln!(writer, "<BIC>{}</BIC>", Self::escape_xml(bic))?;
+        }
+
+        if let Some(ref member_id) = agent.clearing_member_id {
+            writeln!(writer, "<ClrSysMmbId>")?;
+            writeln!(writer, "<MmbId>{}</MmbId>", Self::escape_xml(member_id))?;
+            writeln!(writer, "</ClrSysMmbId>")?;
+        }
+
+        if let Some(ref name) = agent.name {
+            writeln!(writer, "<Nm>{}</Nm>", Self::escape_xml(name))?;
+        }
+
+        if let Some(ref address) = agent.postal_address {
+            Self::write_postal_address(writer, address)?;
+        }
+
+        writeln!(writer, "</FinInstnId>")?;
+        writeln!(writer, "</{}>", tag)?;
+
+        Ok(())
+    }
+
+    fn write_postal_address<W: Write>(writer: &mut W, address: &PostalAddress) -> Result<()> {
+        writeln!(writer, "<PstlAdr>")?;
+
+        let fields = [
+            ("StrtNm", &address.street_name),
+            ("BldgNb", &address.building_number),
+            ("PstCd", &address.post_code),
+            ("TwnNm", &address.town_name),
+            ("CtrySubDvsn", &address.country_subdivision),
+            ("Ctry", &address.country),
+        ];
+
+        for (tag, value) in fields {
+            if let Some(value) = value {
+                writeln!(writer, "<{}>{}</{}>", tag, Self::escape_xml(value), tag)?;
+            }
+        }
+
+        for line in &address.address_lines {
+            writeln!(writer, "<AdrLine>{}</AdrLine>", Self::escape_xml(line))?;
+        }
+
+        writeln!(writer, "</PstlAdr>")?;
+
+        Ok(())
+    }
+
+    fn write_amount_details<W: Write>(
+        writer: &mut W,
+        amount_details: &Camt053AmountDetails,
+    ) -> Result<()> {
+        writeln!(writer, "<AmtDtls>")?;
+
+        let parts = [
+            ("InstdAmt", &amount_details.instructed_amount),
+            ("TxAmt", &amount_details.transaction_amount),
+            ("CntrValAmt", &amount_details.counter_value_amount),
+        ];
+
+        for (tag, detail) in parts {
+            if let Some(detail) = detail {
+                Self::write_amount_detail(writer, tag, detail)?;
+            }
+        }
+
+        writeln!(writer, "</AmtDtls>")?;
+
+        Ok(())
+    }
+
+    fn write_amount_detail<W: Write>(
+        writer: &mut W,
+        tag: &str,
+        detail: &Camt053AmountDetail,
+    ) -> Result<()> {
+        writeln!(writer, "<{}>", tag)?;
+        writeln!(
+            writer,
+            "<Amt Ccy=\"{}\">{}</Amt>",
+            Self::escape_xml(&detail.currency),
+            Self::format_amount(detail.amount)
+        )?;
+
+        if let Some(ref xchg) = detail.currency_exchange {
+            writeln!(writer, "<CcyXchg>")?;
+            writeln!(writer, "<SrcCcy>{}</SrcCcy>", Self::escape_xml(&xchg.source_currency))?;
+            if let Some(ref target) = xchg.target_currency {
+                writeln!(writer, "<TrgtCcy>{}</TrgtCcy>", Self::escape_xml(target))?;
+            }
+            if let Some(ref unit) = xchg.unit_currency {
+                writeln!(writer, "<UnitCcy>{}</UnitCcy>", Self::escape_xml(unit))?;
+            }
+            writeln!(writer, "<XchgRate>{}</XchgRate>", Self::escape_xml(&xchg.rate))?;
+            if let Some(ref contract_id) = xchg.contract_id {
+                writeln!(writer, "<CtrctId>{}</CtrctId>", Self::escape_xml(contract_id))?;
+            }
+            writeln!(writer, "</CcyXchg>")?;
+        }
+
+        writeln!(writer, "</{}>", tag)?;
+
+        Ok(())
+    }
+
+    fn write_charges<W: Write>(writer: &mut W, charges: &[Camt053Charge]) -> Result<()> {
+        for charge in charges {
+            writeln!(writer, "<Chrgs>")?;
+            writeln!(
+                writer,
+                "<Amt Ccy=\"{}\">{}</Amt>",
+                Self::escape_xml(&charge.currency),
+                Self::format_amount(charge.amount)
+            )?;
+            writeln!(
+                writer,
+                "<CdtDbtInd>{}</CdtDbtInd>",
+                charge.credit_debit.as_code()
+            )?;
+            if let Some(ref charge_type) = charge.charge_type {
+                writeln!(writer, "<Tp>")?;
+                writeln!(writer, "<Cd>{}</Cd>", Self::escape_xml(charge_type))?;
+                writeln!(writer, "</Tp>")?;
+            }
+            if let Some(ref bearer) = charge.bearer {
+                writeln!(writer, "<Br>{}</Br>", Self::escape_xml(bearer))?;
+            }
+            if let Some(included) = charge.included {
+                writeln!(writer, "<ChrgInclInd>{}</ChrgInclInd>", included)?;
+            }
+            writeln!(writer, "</Chrgs>")?;
+        }
+
+        Ok(())
+    }
+
     fn format_amount(amount: i64) -> String {
         let whole = amount / 100;
         let frac = (amount % 100).abs();
@@ -240,7 +558,7 @@ impl Camt053Writer {
     }
 
     fn format_date(date: &crate::types::Date) -> String {
-        format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)
+        format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day())
     }
 
     fn escape_xml(s: &str) -> String {
@@ -257,4 +575,29 @@ impl Camt053Statement {
     pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
         Camt053Writer::write_to(self, writer)
     }
+
+    /// Асинхронно записывает выписку в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает выписку CAMT.053 в указанной кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        Camt053Writer::write_to_with_encoding(self, writer, encoding)
+    }
+
+    /// Асинхронно записывает выписку CAMT.053 в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to_with_encoding(buffer, encoding)).await
+    }
 }
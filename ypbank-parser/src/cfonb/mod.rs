@@ -0,0 +1,9 @@
+//! Модуль парсинга французского формата CFONB 120 (relevé de comptes).
+//!
+//! Файл состоит из записей фиксированной длины 120 символов: входящий остаток (01),
+//! движения (04) с дополнительной информацией (05) и исходящий остаток (07).
+//! Последний символ суммы несет ее знак. Формат поддерживается только для чтения.
+
+pub mod parser;
+
+pub use parser::{CfonbMovement, CfonbStatement};
@@ -0,0 +1,443 @@
+//! Парсер французского формата CFONB 120 (relevé de comptes).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::record::{self, RecordFormat};
+use crate::types::{
+    Account, Amount, Balance, Counterparty, Date, Statement, Transaction, TRANSACTION_TYPE_CHEQUE,
+    TRANSACTION_TYPE_MISC, TRANSACTION_TYPE_TRANSFER,
+};
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Длина записи CFONB 120.
+pub const CFONB_RECORD_LEN: usize = 120;
+
+/// Выписка CFONB 120 (записи 01 ... 07).
+///
+/// Суммы хранятся со знаком в минимальных единицах валюты.
+#[derive(Debug, Clone)]
+pub struct CfonbStatement {
+    /// Код банка.
+    pub bank_code: String,
+    /// Код отделения (guichet).
+    pub branch_code: String,
+    /// Номер счета.
+    pub account_number: String,
+    /// Код валюты.
+    pub currency: String,
+    /// Входящий остаток (запись 01).
+    pub opening_balance: i64,
+    /// Дата входящего остатка.
+    pub opening_date: Date,
+    /// Исходящий остаток (запись 07).
+    pub closing_balance: i64,
+    /// Дата исходящего остатка.
+    pub closing_date: Date,
+    /// Движения (записи 04 с дополнительными записями 05).
+    pub movements: Vec<CfonbMovement>,
+}
+
+/// Движение по счету (запись 04 с дополнительными записями 05).
+#[derive(Debug, Clone)]
+pub struct CfonbMovement {
+    /// Внутренний код операции банка.
+    pub internal_code: String,
+    /// Межбанковский код операции.
+    pub interbank_code: String,
+    /// Дата проводки.
+    pub booking_date: Date,
+    /// Код причины отказа.
+    pub reject_code: Option<String>,
+    /// Дата валютирования.
+    pub value_date: Date,
+    /// Назначение операции.
+    pub label: String,
+    /// Номер проводки.
+    pub entry_number: Option<String>,
+    /// Сумма со знаком.
+    pub amount: i64,
+    /// Референс операции.
+    pub reference: Option<String>,
+    /// Дополнительная информация (записи 05): квалификатор и текст.
+    pub complements: Vec<(String, String)>,
+}
+
+impl CfonbMovement {
+    /// Код типа транзакции SWIFT для межбанковского кода операции.
+    pub fn transaction_type(&self) -> &'static str {
+        match self.interbank_code.as_str() {
+            "01" | "02" => TRANSACTION_TYPE_CHEQUE,
+            "05" | "06" => TRANSACTION_TYPE_TRANSFER,
+            _ => TRANSACTION_TYPE_MISC,
+        }
+    }
+
+    /// Текст дополнительной записи 05 с заданным квалификатором.
+    pub fn complement(&self, qualifier: &str) -> Option<&str> {
+        self.complements
+            .iter()
+            .find(|(q, _)| q == qualifier)
+            .map(|(_, text)| text.as_str())
+    }
+}
+
+type Record = record::Record<CfonbStatement>;
+
+impl RecordFormat for CfonbStatement {
+    const NAME: &'static str = "CFONB";
+    const RECORD_LEN: usize = CFONB_RECORD_LEN;
+
+    fn parse_date(value: &str) -> Result<Date> {
+        CfonbStatement::parse_date(value)
+    }
+}
+
+impl Record {
+    /// Сумма (позиции 91–104) в минимальных единицах валюты.
+    fn amount(&self) -> Result<i64> {
+        let decimals = self.raw(20, 20);
+        let decimals: u32 = decimals.parse().map_err(|_| {
+            Error::Parse(format!(
+                "Строка {}: некорректное количество десятичных знаков '{}'",
+                self.line, decimals
+            ))
+        })?;
+        CfonbStatement::parse_amount(&self.raw(91, 104), decimals)
+            .map_err(|e| Error::Parse(format!("Строка {}: {}", self.line, e)))
+    }
+
+    /// Счет записи: банк, отделение, валюта и номер.
+    fn account(&self) -> (String, String, String, String) {
+        (
+            self.raw(3, 7),
+            self.raw(12, 16),
+            self.raw(17, 19),
+            self.raw(22, 32).trim().to_string(),
+        )
+    }
+}
+
+impl CfonbStatement {
+    /// Парсит CFONB 120 из любого источника, реализующего трейт Read.
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит CFONB 120 из любого источника, реализующего трейт AsyncRead.
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит CFONB 120 из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Vec<Self>> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
+    /// Парсит CFONB 120 из строки.
+    ///
+    /// Файл может содержать несколько выписок. Для каждой проверяется,
+    /// что входящий остаток вместе с движениями дает исходящий остаток
+    /// и что записи 04, 05 и 07 относятся к счету из записи 01.
+    /// Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Vec<Self>> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит CFONB 120 из строки с заданными ограничениями.
+    ///
+    /// Число выписок ограничено `max_statements`, число движений в выписке —
+    /// `max_transactions`, число записей 05 у движения — `max_continuation_lines`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Vec<Self>> {
+        limits.check_input(content.len())?;
+        let mut statements = Vec::new();
+        let mut current: Option<(CfonbStatement, (String, String, String, String))> = None;
+
+        for (idx, line) in content.trim_start_matches('\u{feff}').lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = Record::new(line, idx + 1)?;
+            let kind = record.raw(1, 2);
+
+            if kind == "01" {
+                if current.is_some() {
+                    return Err(Error::InvalidFormat(format!(
+                        "Строка {}: запись 01 внутри незакрытой выписки",
+                        record.line
+                    )));
+                }
+                let account = record.account();
+                let opening_date = record.date(35, "дата входящего остатка")?;
+                let statement = CfonbStatement {
+                    bank_code: account.0.clone(),
+                    branch_code: account.1.clone(),
+                    currency: account.2.clone(),
+                    account_number: account.3.clone(),
+                    opening_balance: record.amount()?,
+                    closing_date: opening_date,
+                    opening_date,
+                    closing_balance: 0,
+                    movements: Vec::new(),
+                };
+                current = Some((statement, account));
+                continue;
+            }
+
+            let (statement, account) = current.as_mut().ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "Строка {}: запись {} без записи 01",
+                    record.line, kind
+                ))
+            })?;
+            if record.account() != *account {
+                return Err(Error::InvalidFormat(format!(
+                    "Строка {}: запись {} относится к другому счету",
+                    record.line, kind
+                )));
+            }
+
+            match kind.as_str() {
+                "04" => {
+                    statement.movements.push(CfonbMovement {
+                        internal_code: record.raw(8, 11),
+                        interbank_code: record.raw(33, 34),
+                        booking_date: record.date(35, "дата проводки")?,
+                        reject_code: record.field(41, 42),
+                        value_date: record.date(43, "дата валютирования")?,
+                        label: record.field(49, 79).unwrap_or_default(),
+                        entry_number: record.field(82, 88),
+                        amount: record.amount()?,
+                        reference: record.field(105, 120),
+                        complements: Vec::new(),
+                    });
+                    limits.check_transactions(statement.movements.len())?;
+                }
+                "05" => {
+                    let movement = statement.movements.last_mut().ok_or_else(|| {
+                        Error::InvalidFormat(format!(
+                            "Строка {}: запись 05 без записи 04",
+                            record.line
+                        ))
+                    })?;
+                    movement.complements.push((
+                        record.raw(46, 48).trim().to_string(),
+                        record.field(49, 118).unwrap_or_default(),
+                    ));
+                    limits.check_continuation_lines("записи 05", movement.complements.len())?;
+                }
+                "07" => {
+                    statement.closing_balance = record.amount()?;
+                    statement.closing_date = record.date(35, "дата исходящего остатка")?;
+                    statement.check_balance()?;
+                }
+                other => {
+                    return Err(Error::InvalidFormat(format!(
+                        "Строка {}: неизвестный тип записи CFONB: {}",
+                        record.line, other
+                    )));
+                }
+            }
+
+            if kind == "07" {
+                statements.extend(current.take().map(|(statement, _)| statement));
+                limits.check_statements(statements.len())?;
+            }
+        }
+
+        if current.is_some() {
+            return Err(Error::InvalidFormat(
+                "Выписка CFONB не завершена записью 07".into(),
+            ));
+        }
+        if statements.is_empty() {
+            return Err(Error::InvalidFormat(
+                "Файл CFONB не содержит выписок".into(),
+            ));
+        }
+
+        Ok(statements)
+    }
+
+    /// Сверяет исходящий остаток с суммой входящего остатка и движений.
+    fn check_balance(&self) -> Result<()> {
+        let expected = self
+            .movements
+            .iter()
+            .fold(self.opening_balance, |acc, m| acc.saturating_add(m.amount));
+        if expected != self.closing_balance {
+            return Err(Error::InvalidFormat(format!(
+                "Исходящий остаток CFONB ({}) не совпадает: указано {}, подсчитано {}",
+                self.account_number, self.closing_balance, expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// Парсит дату в формате DDMMYY.
+    fn parse_date(date_str: &str) -> Result<Date> {
+        Date::parse_from_str(date_str, "%d%m%y")
+            .map_err(|_| Error::Parse(format!("Некорректная дата CFONB: {}", date_str)))
+    }
+
+    /// Парсит сумму из 14 символов, последний из которых несет знак
+    /// (`{`, `A`–`I` — положительная, `}`, `J`–`R` — отрицательная),
+    /// и приводит ее к двум знакам после запятой.
+    fn parse_amount(amount_str: &str, decimals: u32) -> Result<i64> {
+        let invalid = || Error::Parse(format!("Некорректная сумма CFONB: {}", amount_str));
+        if amount_str.len() != 14 || !amount_str.is_ascii() {
+            return Err(invalid());
+        }
+        let (digits, last) = amount_str.split_at(13);
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let (last_digit, negative) = match last.chars().next().ok_or_else(invalid)? {
+            '{' => (0, false),
+            '}' => (0, true),
+            c @ 'A'..='I' => (c as i64 - 'A' as i64 + 1, false),
+            c @ 'J'..='R' => (c as i64 - 'J' as i64 + 1, true),
+            _ => return Err(invalid()),
+        };
+
+        let value: i64 = digits
+            .parse::<i64>()
+            .ok()
+            .and_then(|v| v.checked_mul(10))
+            .and_then(|v| v.checked_add(last_digit))
+            .ok_or_else(|| Error::Parse("Переполнение при парсинге суммы".to_string()))?;
+
+        let value = match decimals {
+            0..=2 => value
+                .checked_mul(10i64.pow(2 - decimals))
+                .ok_or_else(|| Error::Parse("Переполнение при парсинге суммы".to_string()))?,
+            _ => {
+                let divisor = 10i64.checked_pow(decimals - 2).ok_or_else(invalid)?;
+                if value % divisor != 0 {
+                    return Err(Error::Parse(format!(
+                        "Сумма {} задана точнее минимальной единицы валюты",
+                        amount_str
+                    )));
+                }
+                value / divisor
+            }
+        };
+
+        Ok(if negative { -value } else { value })
+    }
+}
+
+impl From<CfonbStatement> for Statement {
+    fn from(cfonb: CfonbStatement) -> Self {
+        let currency = cfonb.currency;
+
+        let transactions = cfonb
+            .movements
+            .into_iter()
+            .map(|m| {
+                // Записи 05 с квалификатором LIB продолжают назначение
+                let description = std::iter::once(m.label.as_str())
+                    .chain(
+                        m.complements
+                            .iter()
+                            .filter(|(q, _)| q == "LIB")
+                            .map(|(_, text)| text.as_str()),
+                    )
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                // NPY — плательщик, NBE — получатель
+                let counterparty_name = if m.amount >= 0 {
+                    m.complement("NPY")
+                } else {
+                    m.complement("NBE")
+                };
+                let counterparty = counterparty_name.map(|name| Counterparty {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                });
+
+                Transaction {
+                    value_date: Some(m.value_date).filter(|d| *d != m.booking_date),
+                    date: m.booking_date,
+                    amount: Amount::new(m.amount.abs(), &currency),
+                    is_credit: m.amount >= 0,
+                    transaction_type: Some(m.transaction_type().to_string()),
+                    reference: m.reference.clone().or_else(|| m.entry_number.clone()),
+                    description,
+                    counterparty,
+                    instructed_amount: None,
+                    original_amount: None,
+                    counter_value_amount: None,
+                    exchange_rates: Vec::new(),
+                    charges: Vec::new(),
+                }
+            })
+            .collect();
+
+        let balance = |amount: i64, date: Date| Balance {
+            amount: Amount::new(amount.abs(), &currency),
+            date,
+            is_credit: amount >= 0,
+        };
+
+        Statement {
+            account: Account {
+                iban: None,
+                number: format!(
+                    "{}{}{}",
+                    cfonb.bank_code, cfonb.branch_code, cfonb.account_number
+                ),
+                currency: currency.clone(),
+                name: None,
+                owner: None,
+                tax_id: None,
+            },
+            opening_balance: balance(cfonb.opening_balance, cfonb.opening_date),
+            closing_balance: balance(cfonb.closing_balance, cfonb.closing_date),
+            transactions,
+            statement_number: None,
+            reference: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount_overpunch() {
+        assert_eq!(
+            CfonbStatement::parse_amount("0000000012345E", 2).unwrap(),
+            123455
+        );
+        assert_eq!(
+            CfonbStatement::parse_amount("0000000012345}", 2).unwrap(),
+            -123450
+        );
+        assert_eq!(
+            CfonbStatement::parse_amount("0000000000010J", 0).unwrap(),
+            -10100
+        );
+        assert!(CfonbStatement::parse_amount("0000000012345X", 2).is_err());
+        assert!(CfonbStatement::parse_amount("000000005234Ж", 2).is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            CfonbStatement::parse_date("310124").unwrap(),
+            Date::new(2024, 1, 31)
+        );
+    }
+}
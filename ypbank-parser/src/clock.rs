@@ -0,0 +1,59 @@
+//! Источник текущего времени.
+//!
+//! Конвертации, которым нужно время создания документа, и архив выписок
+//! берут его через [`Clock`]; в тестах вместо [`SystemClock`] передается
+//! [`FixedClock`], и результат не зависит от момента запуска.
+
+use crate::date::{Date, DateTime, UtcOffset};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Источник текущего времени.
+pub trait Clock: Send + Sync {
+    /// Текущий момент.
+    fn now(&self) -> DateTime;
+}
+
+/// Системные часы: текущее время UTC с точностью до секунды.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        DateTime::from_unix_timestamp(secs, 0).unwrap_or_else(|| {
+            DateTime::midnight(Date::new(1970, 1, 1)).with_offset(Some(UtcOffset::UTC))
+        })
+    }
+}
+
+/// Часы, всегда возвращающие заданный момент.
+///
+/// ```ignore
+/// let clock = FixedClock("2024-01-31T18:00:00Z".parse()?);
+/// let camt = Camt053Statement::from_mt940_with_clock(mt940, &clock);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub DateTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clocks() {
+        let fixed = FixedClock("2024-01-31T18:00:00Z".parse().unwrap());
+        assert_eq!(fixed.now().to_string(), "2024-01-31T18:00:00Z");
+
+        let now = SystemClock.now();
+        assert_eq!(now.offset(), Some(UtcOffset::UTC));
+        assert!(now.date().year() >= 2024);
+    }
+}
@@ -0,0 +1,10 @@
+//! Модуль парсинга бельгийского формата CODA (Coded Statement of Account).
+//!
+//! Файл состоит из записей фиксированной длины 128 символов: заголовок (0),
+//! входящий остаток (1), движения (21, 22, 23), дополнительная информация
+//! (31, 32, 33), свободные сообщения (4), исходящий остаток (8) и трейлер (9)
+//! с количеством записей и оборотами. Формат поддерживается только для чтения.
+
+pub mod parser;
+
+pub use parser::{CodaMovement, CodaStatement};
@@ -0,0 +1,680 @@
+//! Парсер бельгийского формата CODA (версия 2).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::record::{self, RecordFormat};
+use crate::types::{Account, Amount, Balance, Counterparty, Date, Statement, Transaction};
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Длина записи CODA.
+pub const CODA_RECORD_LEN: usize = 128;
+
+/// Тип структурированного сообщения: бельгийская OGM (`+++123/4567/89012+++`).
+pub const CODA_COMMUNICATION_OGM: &str = "101";
+
+/// Тип структурированного сообщения: OGM с признаком повторного платежа.
+pub const CODA_COMMUNICATION_OGM_REPEAT: &str = "102";
+
+/// Выписка CODA (записи 0 ... 9).
+///
+/// Суммы хранятся со знаком в минимальных единицах валюты.
+#[derive(Debug, Clone)]
+pub struct CodaStatement {
+    /// Дата создания файла (запись 0).
+    pub creation_date: Date,
+    /// Идентификационный номер банка.
+    pub bank_id: String,
+    /// Признак дубликата ранее отправленного файла.
+    pub duplicate: bool,
+    /// Референс файла.
+    pub file_reference: Option<String>,
+    /// Наименование получателя файла.
+    pub addressee: Option<String>,
+    /// BIC банка.
+    pub bic: Option<String>,
+    /// Номер счета или IBAN.
+    pub account_number: String,
+    /// Номер счета указан в формате IBAN (структура 2 или 3).
+    pub is_iban: bool,
+    /// Код валюты счета.
+    pub currency: String,
+    /// Номер бумажной выписки.
+    pub paper_sequence: String,
+    /// Порядковый номер выписки CODA.
+    pub coda_sequence: String,
+    /// Владелец счета.
+    pub account_holder: Option<String>,
+    /// Описание счета.
+    pub account_description: Option<String>,
+    /// Входящий остаток (запись 1).
+    pub opening_balance: i64,
+    /// Дата входящего остатка.
+    pub opening_date: Date,
+    /// Исходящий остаток (запись 8).
+    pub closing_balance: i64,
+    /// Дата исходящего остатка.
+    pub closing_date: Date,
+    /// Движения по счету (записи 21, 22, 23 и 31, 32, 33).
+    pub movements: Vec<CodaMovement>,
+    /// Свободные сообщения банка (записи 4).
+    pub free_communications: Vec<String>,
+}
+
+/// Движение по счету (записи 21, 22 и 23).
+#[derive(Debug, Clone)]
+pub struct CodaMovement {
+    /// Сквозной номер движения в выписке.
+    pub sequence: String,
+    /// Номер детализации; `0000` — основное движение.
+    pub detail: String,
+    /// Референс банка.
+    pub bank_reference: Option<String>,
+    /// Сумма со знаком.
+    pub amount: i64,
+    /// Дата валютирования.
+    pub value_date: Date,
+    /// Дата проводки.
+    pub entry_date: Date,
+    /// Код операции: тип, семейство, операция и категория (8 символов).
+    pub transaction_code: String,
+    /// Тип структурированного сообщения (`101` — OGM), если сообщение структурировано.
+    pub communication_type: Option<String>,
+    /// Сообщение; OGM приводится к виду `+++123/4567/89012+++`.
+    pub communication: String,
+    /// Референс клиента (запись 22).
+    pub customer_reference: Option<String>,
+    /// BIC банка контрагента (запись 22).
+    pub counterparty_bic: Option<String>,
+    /// Счет контрагента (запись 23).
+    pub counterparty_account: Option<String>,
+    /// Валюта счета контрагента (запись 23).
+    pub counterparty_currency: Option<String>,
+    /// Наименование контрагента (запись 23).
+    pub counterparty_name: Option<String>,
+    /// Дополнительная информация (записи 31, 32, 33).
+    pub information: Vec<String>,
+}
+
+impl CodaMovement {
+    /// Является ли движение детализацией глобализированной суммы.
+    pub fn is_detail(&self) -> bool {
+        self.detail != "0000"
+    }
+
+    /// Возвращает OGM, если сообщение — структурированная бельгийская ссылка.
+    pub fn ogm(&self) -> Option<&str> {
+        match self.communication_type.as_deref() {
+            Some(CODA_COMMUNICATION_OGM | CODA_COMMUNICATION_OGM_REPEAT) => {
+                Some(&self.communication)
+            }
+            _ => None,
+        }
+    }
+}
+
+type Record = record::Record<CodaStatement>;
+
+impl RecordFormat for CodaStatement {
+    const NAME: &'static str = "CODA";
+    const RECORD_LEN: usize = CODA_RECORD_LEN;
+
+    fn parse_date(value: &str) -> Result<Date> {
+        CodaStatement::parse_date(value)
+    }
+}
+
+impl Record {
+    fn kind(&self) -> String {
+        match self.char_at(1) {
+            '2' | '3' => self.raw(1, 2),
+            c => c.to_string(),
+        }
+    }
+
+    /// Сумма: признак (0 — кредит, 1 — дебет) и 15 цифр с тремя знаками после запятой.
+    fn amount(&self, sign_pos: usize, name: &str) -> Result<i64> {
+        let amount = CodaStatement::parse_amount(&self.raw(sign_pos + 1, sign_pos + 15))
+            .map_err(|e| Error::Parse(format!("Строка {}: {}: {}", self.line, name, e)))?;
+        match self.char_at(sign_pos) {
+            '0' => Ok(amount),
+            '1' => Ok(-amount),
+            other => Err(Error::Parse(format!(
+                "Строка {}: некорректный признак суммы '{}'",
+                self.line, other
+            ))),
+        }
+    }
+}
+
+/// Заголовок из записи 0 до появления записи 1.
+struct Header {
+    creation_date: Date,
+    bank_id: String,
+    duplicate: bool,
+    file_reference: Option<String>,
+    addressee: Option<String>,
+    bic: Option<String>,
+}
+
+/// Промежуточное состояние выписки при разборе.
+struct StatementState {
+    header: Header,
+    statement: Option<CodaStatement>,
+    records: usize,
+}
+
+impl CodaStatement {
+    /// Парсит CODA из любого источника, реализующего трейт Read.
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит CODA из любого источника, реализующего трейт AsyncRead.
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит CODA из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Vec<Self>> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
+    /// Парсит CODA из строки.
+    ///
+    /// Файл может содержать несколько выписок, каждая от записи 0 до записи 9.
+    /// Количество записей и обороты из записи 9 сверяются с разобранными.
+    /// Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Vec<Self>> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит CODA из строки с заданными ограничениями.
+    ///
+    /// Число выписок ограничено `max_statements`, число движений в выписке —
+    /// `max_transactions`, длина собранной из записей 3 информации —
+    /// `max_field_len`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Vec<Self>> {
+        limits.check_input(content.len())?;
+        let mut statements = Vec::new();
+        let mut state: Option<StatementState> = None;
+
+        for (idx, line) in content.trim_start_matches('\u{feff}').lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = Record::new(line, idx + 1)?;
+            let kind = record.kind();
+
+            if kind == "0" {
+                if state.is_some() {
+                    return Err(Error::InvalidFormat(format!(
+                        "Строка {}: запись 0 внутри незавершенной выписки",
+                        record.line
+                    )));
+                }
+                state = Some(StatementState {
+                    header: Self::parse_header(&record)?,
+                    statement: None,
+                    records: 0,
+                });
+                continue;
+            }
+
+            let current = state.as_mut().ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "Строка {}: запись {} до записи 0",
+                    record.line, kind
+                ))
+            })?;
+
+            if kind == "9" {
+                let statement = current.statement.take().ok_or_else(|| {
+                    Error::InvalidFormat(format!("Строка {}: запись 9 без записи 1", record.line))
+                })?;
+                Self::check_trailer(&record, &statement, current.records)?;
+                statements.push(statement);
+                limits.check_statements(statements.len())?;
+                state = None;
+                continue;
+            }
+
+            current.records += 1;
+            if kind == "1" {
+                if current.statement.is_some() {
+                    return Err(Error::InvalidFormat(format!(
+                        "Строка {}: повторная запись 1",
+                        record.line
+                    )));
+                }
+                current.statement = Some(Self::parse_old_balance(&record, &current.header)?);
+                continue;
+            }
+
+            let statement = current.statement.as_mut().ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "Строка {}: запись {} до записи 1",
+                    record.line, kind
+                ))
+            })?;
+
+            match kind.as_str() {
+                "21" => {
+                    statement.movements.push(Self::parse_movement(&record)?);
+                    limits.check_transactions(statement.movements.len())?;
+                }
+                "22" | "23" => {
+                    let movement = Self::find_movement(statement, &record)?;
+                    if kind == "22" {
+                        Self::apply_movement_part2(movement, &record);
+                    } else {
+                        Self::apply_movement_part3(movement, &record);
+                    }
+                }
+                "31" | "32" | "33" => {
+                    Self::apply_information(statement, &record, &kind, limits)?
+                }
+                "4" => {
+                    if let Some(text) = record.field(33, 112) {
+                        statement.free_communications.push(text);
+                    }
+                }
+                "8" => {
+                    statement.closing_balance = record.amount(42, "исходящий остаток")?;
+                    statement.closing_date = record.date(58, "дата исходящего остатка")?;
+                }
+                other => {
+                    tracing::warn!(
+                        "Пропущена запись CODA неизвестного типа {} (строка {})",
+                        other,
+                        record.line
+                    );
+                }
+            }
+        }
+
+        if state.is_some() {
+            return Err(Error::InvalidFormat(
+                "Выписка CODA не завершена записью 9".into(),
+            ));
+        }
+        if statements.is_empty() {
+            return Err(Error::InvalidFormat(
+                "Не найдено ни одной выписки CODA".into(),
+            ));
+        }
+
+        Ok(statements)
+    }
+
+    fn parse_header(record: &Record) -> Result<Header> {
+        let version = record.raw(128, 128);
+        if version != "2" {
+            return Err(Error::InvalidFormat(format!(
+                "Неподдерживаемая версия CODA '{}', поддерживается только версия 2",
+                version.trim()
+            )));
+        }
+
+        Ok(Header {
+            creation_date: record.date(6, "дата создания файла")?,
+            bank_id: record.raw(12, 14),
+            duplicate: record.raw(17, 17) == "D",
+            file_reference: record.field(25, 34),
+            addressee: record.field(35, 60),
+            bic: record.field(61, 71),
+        })
+    }
+
+    /// Запись 1: счет и входящий остаток.
+    fn parse_old_balance(record: &Record, header: &Header) -> Result<Self> {
+        let (account_number, currency, is_iban) = match record.raw(2, 2).as_str() {
+            "0" => (record.field(6, 17), record.field(19, 21), false),
+            "1" => (record.field(6, 39), record.field(40, 42), false),
+            "2" => (record.field(6, 21), record.field(40, 42), true),
+            "3" => (record.field(6, 39), record.field(40, 42), true),
+            other => {
+                return Err(Error::InvalidFormat(format!(
+                    "Строка {}: неизвестная структура счета '{}'",
+                    record.line, other
+                )));
+            }
+        };
+
+        let opening_date = record.date(59, "дата входящего остатка")?;
+
+        Ok(CodaStatement {
+            creation_date: header.creation_date,
+            bank_id: header.bank_id.clone(),
+            duplicate: header.duplicate,
+            file_reference: header.file_reference.clone(),
+            addressee: header.addressee.clone(),
+            bic: header.bic.clone(),
+            account_number: account_number
+                .ok_or_else(|| Error::MissingField("Отсутствует номер счета".into()))?,
+            is_iban,
+            currency: currency
+                .ok_or_else(|| Error::MissingField("Отсутствует валюта счета".into()))?,
+            paper_sequence: record.raw(3, 5),
+            coda_sequence: record.raw(126, 128),
+            account_holder: record.field(65, 90),
+            account_description: record.field(91, 125),
+            opening_balance: record.amount(43, "входящий остаток")?,
+            opening_date,
+            closing_balance: 0,
+            closing_date: opening_date,
+            movements: Vec::new(),
+            free_communications: Vec::new(),
+        })
+    }
+
+    /// Запись 21: сумма, даты, код операции и начало сообщения.
+    fn parse_movement(record: &Record) -> Result<CodaMovement> {
+        let structured = record.raw(62, 62) == "1";
+        let (communication_type, communication) = if structured {
+            let type_code = record.raw(63, 65);
+            let communication = match type_code.as_str() {
+                CODA_COMMUNICATION_OGM | CODA_COMMUNICATION_OGM_REPEAT => {
+                    Self::format_ogm(&record.raw(66, 77), record.line)
+                }
+                _ => record.raw(66, 115),
+            };
+            (Some(type_code), communication)
+        } else {
+            (None, record.raw(63, 115))
+        };
+
+        Ok(CodaMovement {
+            sequence: record.raw(3, 6),
+            detail: record.raw(7, 10),
+            bank_reference: record.field(11, 31),
+            amount: record.amount(32, "сумма движения")?,
+            value_date: record.date(48, "дата валютирования")?,
+            entry_date: record.date(116, "дата проводки")?,
+            transaction_code: record.raw(54, 61),
+            communication_type,
+            communication,
+            customer_reference: None,
+            counterparty_bic: None,
+            counterparty_account: None,
+            counterparty_currency: None,
+            counterparty_name: None,
+            information: Vec::new(),
+        })
+    }
+
+    /// Записи 22 и 23 продолжают последнее движение с тем же номером.
+    fn find_movement<'a>(
+        statement: &'a mut CodaStatement,
+        record: &Record,
+    ) -> Result<&'a mut CodaMovement> {
+        let (sequence, detail) = (record.raw(3, 6), record.raw(7, 10));
+        statement
+            .movements
+            .last_mut()
+            .filter(|m| m.sequence == sequence && m.detail == detail)
+            .ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "Строка {}: запись {} без записи 21 для движения {}/{}",
+                    record.line,
+                    record.kind(),
+                    sequence,
+                    detail
+                ))
+            })
+    }
+
+    /// Запись 22: продолжение сообщения, референс клиента и BIC контрагента.
+    fn apply_movement_part2(movement: &mut CodaMovement, record: &Record) {
+        if movement.ogm().is_none() {
+            movement.communication.push_str(&record.raw(11, 63));
+        }
+        movement.customer_reference = record.field(64, 98);
+        movement.counterparty_bic = record.field(99, 109);
+    }
+
+    /// Запись 23: счет и наименование контрагента, окончание сообщения.
+    fn apply_movement_part3(movement: &mut CodaMovement, record: &Record) {
+        movement.counterparty_account = record.field(11, 44);
+        movement.counterparty_currency = record.field(45, 47);
+        movement.counterparty_name = record.field(48, 82);
+        if movement.ogm().is_none() {
+            movement.communication.push_str(&record.raw(83, 125));
+        }
+    }
+
+    /// Записи 31, 32 и 33: дополнительная информация к движению.
+    fn apply_information(
+        statement: &mut CodaStatement,
+        record: &Record,
+        kind: &str,
+        limits: &Limits,
+    ) -> Result<()> {
+        let sequence = record.raw(3, 6);
+        let Some(movement) = statement
+            .movements
+            .iter_mut()
+            .rev()
+            .find(|m| m.sequence == sequence)
+        else {
+            tracing::warn!(
+                "Пропущена запись CODA {} без движения {} (строка {})",
+                kind,
+                sequence,
+                record.line
+            );
+            return Ok(());
+        };
+
+        let text = match kind {
+            "31" => record.raw(41, 113),
+            "32" => record.raw(11, 115),
+            _ => record.raw(11, 100),
+        };
+
+        match movement.information.last_mut() {
+            Some(last) if kind != "31" => {
+                last.push_str(&text);
+                limits.check_field("информация CODA", last)
+            }
+            _ => {
+                movement.information.push(text);
+                Ok(())
+            }
+        }
+    }
+
+    /// Запись 9: количество записей 1 ... 8 и обороты по основным движениям.
+    fn check_trailer(record: &Record, statement: &CodaStatement, records: usize) -> Result<()> {
+        let declared_records: usize = record.raw(17, 22).trim().parse().map_err(|_| {
+            Error::Parse(format!(
+                "Строка {}: некорректное количество записей",
+                record.line
+            ))
+        })?;
+        if declared_records != records {
+            return Err(Error::InvalidFormat(format!(
+                "Количество записей в записи 9 не совпадает: указано {}, подсчитано {}",
+                declared_records, records
+            )));
+        }
+
+        let (mut debit, mut credit) = (0i64, 0i64);
+        for movement in statement.movements.iter().filter(|m| !m.is_detail()) {
+            if movement.amount < 0 {
+                debit = debit.saturating_sub(movement.amount);
+            } else {
+                credit = credit.saturating_add(movement.amount);
+            }
+        }
+
+        let declared_debit = Self::parse_amount(&record.raw(23, 37))?;
+        let declared_credit = Self::parse_amount(&record.raw(38, 52))?;
+        if declared_debit != debit || declared_credit != credit {
+            return Err(Error::InvalidFormat(format!(
+                "Обороты в записи 9 ({}) не совпадают: указано дебет {} / кредит {}, \
+                 подсчитано дебет {} / кредит {}",
+                statement.account_number, declared_debit, declared_credit, debit, credit
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Приводит 12 цифр OGM к виду `+++123/4567/89012+++` и проверяет контрольное число.
+    fn format_ogm(digits: &str, line: usize) -> String {
+        if digits.len() != 12 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            tracing::warn!(
+                "Некорректная структурированная ссылка '{}' (строка {})",
+                digits,
+                line
+            );
+            return digits.trim().to_string();
+        }
+
+        // Контрольное число — остаток от деления первых 10 цифр на 97 (0 заменяется на 97)
+        let base: u64 = digits[..10].parse().unwrap_or_default();
+        let check: u64 = digits[10..].parse().unwrap_or_default();
+        let expected = match base % 97 {
+            0 => 97,
+            rest => rest,
+        };
+        if check != expected {
+            tracing::warn!(
+                "Неверное контрольное число структурированной ссылки {} (строка {})",
+                digits,
+                line
+            );
+        }
+
+        format!("+++{}/{}/{}+++", &digits[..3], &digits[3..7], &digits[7..])
+    }
+
+    /// Парсит дату в формате DDMMYY.
+    fn parse_date(date_str: &str) -> Result<Date> {
+        Date::parse_from_str(date_str, "%d%m%y")
+            .map_err(|_| Error::Parse(format!("Некорректная дата CODA: {}", date_str)))
+    }
+
+    /// Парсит сумму из 15 цифр с тремя знаками после запятой в минимальные единицы.
+    fn parse_amount(amount_str: &str) -> Result<i64> {
+        if amount_str.len() != 15 || !amount_str.chars().all(|c| c.is_ascii_digit()) {
+            return Err(Error::Parse(format!("Некорректная сумма: {}", amount_str)));
+        }
+
+        let thousandths: i64 = amount_str
+            .parse()
+            .map_err(|_| Error::Parse("Переполнение при парсинге суммы".to_string()))?;
+        if thousandths % 10 != 0 {
+            return Err(Error::Parse(format!(
+                "Сумма {} задана точнее минимальной единицы валюты",
+                amount_str
+            )));
+        }
+
+        Ok(thousandths / 10)
+    }
+}
+
+impl From<CodaStatement> for Statement {
+    /// Детализации глобализированных сумм не переносятся: в выписку попадают
+    /// только основные движения, из которых складываются остатки.
+    fn from(coda: CodaStatement) -> Self {
+        let currency = coda.currency;
+
+        let transactions = coda
+            .movements
+            .into_iter()
+            .filter(|m| !m.is_detail())
+            .map(|m| {
+                let reference = m
+                    .ogm()
+                    .map(str::to_string)
+                    .or_else(|| m.customer_reference.clone())
+                    .or_else(|| m.bank_reference.clone());
+                let counterparty = (m.counterparty_name.is_some()
+                    || m.counterparty_account.is_some()
+                    || m.counterparty_bic.is_some())
+                .then(|| Counterparty {
+                    name: m.counterparty_name,
+                    account: m.counterparty_account,
+                    bank_code: m.counterparty_bic,
+                    ..Default::default()
+                });
+
+                Transaction {
+                    value_date: Some(m.value_date).filter(|d| *d != m.entry_date),
+                    date: m.entry_date,
+                    amount: Amount::new(m.amount.abs(), &currency),
+                    is_credit: m.amount >= 0,
+                    reference,
+                    transaction_type: None,
+                    description: m.communication.trim().to_string(),
+                    counterparty,
+                    instructed_amount: None,
+                    original_amount: None,
+                    counter_value_amount: None,
+                    exchange_rates: Vec::new(),
+                    charges: Vec::new(),
+                }
+            })
+            .collect();
+
+        let balance = |amount: i64, date: Date| Balance {
+            amount: Amount::new(amount.abs(), &currency),
+            date,
+            is_credit: amount >= 0,
+        };
+
+        Statement {
+            account: Account {
+                iban: coda.is_iban.then(|| coda.account_number.clone()),
+                number: coda.account_number,
+                currency: currency.clone(),
+                name: coda.account_description,
+                owner: coda.account_holder,
+                tax_id: None,
+            },
+            opening_balance: balance(coda.opening_balance, coda.opening_date),
+            closing_balance: balance(coda.closing_balance, coda.closing_date),
+            transactions,
+            statement_number: Some(coda.paper_sequence),
+            reference: coda.file_reference,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_and_amount() {
+        assert_eq!(
+            CodaStatement::parse_date("310124").unwrap(),
+            Date::new(2024, 1, 31)
+        );
+        assert!(CodaStatement::parse_date("311324").is_err());
+
+        assert_eq!(
+            CodaStatement::parse_amount("000000001500500").unwrap(),
+            150050
+        );
+        assert!(CodaStatement::parse_amount("000000001500501").is_err());
+    }
+
+    #[test]
+    fn test_format_ogm() {
+        assert_eq!(
+            CodaStatement::format_ogm("123456789002", 1),
+            "+++123/4567/89002+++"
+        );
+    }
+}
@@ -1,16 +1,44 @@
 //! Модуль конвертации между форматами.
 
+use crate::bai2::parser::{
+    Bai2Account, Bai2File, Bai2Group, Bai2Summary, Bai2Transaction, BAI2_CLOSING_LEDGER,
+    BAI2_MISC_CREDIT, BAI2_MISC_DEBIT, BAI2_OPENING_LEDGER, BAI2_TOTAL_CREDITS,
+    BAI2_TOTAL_DEBITS,
+};
 use crate::camt053::parser::{
-    Camt053Account, Camt053Balance, Camt053Entry, Camt053Statement, Camt053TransactionDetails,
+    Camt053Account, Camt053Agent, Camt053AmountDetail, Camt053AmountDetails, Camt053Balance,
+    Camt053Charge, Camt053Entry, Camt053Party, Camt053Statement, Camt053TransactionDetails,
 };
+use crate::clock::{Clock, SystemClock};
+use crate::csv::parser::{CsvAccountDetails, CsvStatement, CsvTransaction};
 use crate::error::Error;
+use std::collections::HashSet;
 use crate::mt940::parser::{Mt940Balance, Mt940Statement, Mt940Transaction};
+use crate::ofx::parser::{
+    OfxBalance, OfxStatement, OfxTransaction, OfxVersion, OFX_ACCOUNT_TYPE_CHECKING,
+    OFX_TRNTYPE_CREDIT, OFX_TRNTYPE_DEBIT,
+};
+use crate::onec::parser::{
+    OneCAccountSection, OneCDocument, OneCParty, OneCStatement, ONEC_PAYMENT_ORDER,
+};
+use crate::qif::parser::{
+    QifAccount, QifDateStyle, QifFile, QifTransaction, QIF_OPENING_BALANCE, QIF_TYPE_BANK,
+};
 use crate::types::{
-    BalanceType, CreditDebit, END_TO_END_NOT_PROVIDED, TRANSACTION_TYPE_TRANSFER,
+    Amount, Balance, BalanceType, Counterparty, CreditDebit, Statement, Transaction,
+    END_TO_END_NOT_PROVIDED, TRANSACTION_TYPE_TRANSFER,
 };
 
 impl From<Mt940Statement> for Camt053Statement {
+    /// Время создания (CreDtTm) берется из системных часов.
     fn from(mt940: Mt940Statement) -> Self {
+        Self::from_mt940_with_clock(mt940, &SystemClock)
+    }
+}
+
+impl Camt053Statement {
+    /// Конвертирует MT940 в CAMT.053 со временем создания из `clock`.
+    pub fn from_mt940_with_clock(mt940: Mt940Statement, clock: &dyn Clock) -> Self {
         let currency = mt940.opening_balance.currency.clone();
 
         let account = Camt053Account {
@@ -22,6 +50,7 @@ impl From<Mt940Statement> for Camt053Statement {
             currency: currency.clone(),
             name: None,
             owner_name: None,
+            owner_tax_id: None,
         };
 
         let opening_balance = Camt053Balance {
@@ -77,11 +106,17 @@ impl From<Mt940Statement> for Camt053Statement {
                     transaction_id: tx.reference.clone(),
                     amount: Some(tx.amount),
                     currency: Some(currency.clone()),
-                    debtor_name,
+                    debtor: debtor_name.map(Camt053Party::with_name),
                     debtor_account,
-                    creditor_name,
+                    ultimate_debtor: None,
+                    creditor: creditor_name.map(Camt053Party::with_name),
                     creditor_account,
+                    ultimate_creditor: None,
+                    debtor_agent: None,
+                    creditor_agent: None,
                     remittance_info,
+                    amount_details: Camt053AmountDetails::default(),
+                    charges: Vec::new(),
                 }];
 
                 Camt053Entry {
@@ -92,6 +127,8 @@ impl From<Mt940Statement> for Camt053Statement {
                     booking_date: tx.date,
                     value_date: tx.value_date,
                     account_servicer_ref: tx.reference,
+                    amount_details: Camt053AmountDetails::default(),
+                    charges: Vec::new(),
                     transaction_details,
                 }
             })
@@ -99,7 +136,7 @@ impl From<Mt940Statement> for Camt053Statement {
 
         Camt053Statement {
             message_id: format!("MT940-{}", mt940.reference),
-            creation_date_time: "2024-01-01T00:00:00".to_string(),
+            creation_date_time: clock.now(),
             statement_id: mt940.statement_number,
             account,
             balances: vec![opening_balance, closing_balance],
@@ -156,10 +193,10 @@ impl TryFrom<Camt053Statement> for Mt940Statement {
 
                     let mut details_parts: Vec<String> = Vec::new();
 
-                    if let Some(name) = tx_details.debtor_name {
+                    if let Some(name) = tx_details.debtor.and_then(|p| p.name) {
                         details_parts.push(name);
                     }
-                    if let Some(name) = tx_details.creditor_name {
+                    if let Some(name) = tx_details.creditor.and_then(|p| p.name) {
                         details_parts.push(name);
                     }
                     if let Some(acct) = tx_details.debtor_account {
@@ -201,10 +238,652 @@ impl TryFrom<Camt053Statement> for Mt940Statement {
     }
 }
 
+impl From<Statement> for Mt940Statement {
+    fn from(statement: Statement) -> Self {
+        let to_balance = |balance: &Balance| Mt940Balance {
+            credit_debit: if balance.is_credit { 'C' } else { 'D' },
+            date: balance.date,
+            currency: balance.amount.currency.clone(),
+            amount: balance.amount.value.abs(),
+        };
+
+        let transactions = statement
+            .transactions
+            .into_iter()
+            .map(|tx| Mt940Transaction {
+                details: mt940_details(tx.counterparty.as_ref(), &tx.description),
+                date: tx.date,
+                value_date: tx.value_date,
+                credit_debit: if tx.is_credit { 'C' } else { 'D' },
+                amount: tx.amount.value,
+                transaction_type: tx
+                    .transaction_type
+                    .unwrap_or_else(|| TRANSACTION_TYPE_TRANSFER.to_string()),
+                reference: tx.reference,
+            })
+            .collect();
+
+        Mt940Statement {
+            reference: statement.reference.unwrap_or_else(|| "NONREF".to_string()),
+            account_id: statement.account.iban.unwrap_or(statement.account.number),
+            statement_number: statement.statement_number.unwrap_or_else(|| "1/1".to_string()),
+            opening_balance: to_balance(&statement.opening_balance),
+            closing_balance: to_balance(&statement.closing_balance),
+            transactions,
+        }
+    }
+}
+
+/// Формирует поле :86: из реквизитов контрагента (наименование, ИНН, КПП) и назначения.
+fn mt940_details(counterparty: Option<&Counterparty>, description: &str) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(counterparty) = counterparty {
+        if let Some(ref name) = counterparty.name {
+            // Наименование уже может быть в назначении (например, после MT940 -> Statement)
+            if name != description {
+                parts.push(name.clone());
+            }
+        }
+        if let Some(ref tax_id) = counterparty.tax_id {
+            parts.push(format!("ИНН {}", tax_id));
+        }
+        if let Some(ref kpp) = counterparty.tax_reason_code {
+            parts.push(format!("КПП {}", kpp));
+        }
+    }
+
+    if !description.is_empty() {
+        parts.push(description.to_string());
+    }
+
+    parts.join(" ")
+}
+
+impl From<Statement> for Camt053Statement {
+    /// Время создания (CreDtTm) берется из системных часов.
+    fn from(statement: Statement) -> Self {
+        Self::from_statement_with_clock(statement, &SystemClock)
+    }
+}
+
+impl Camt053Statement {
+    /// Конвертирует унифицированную выписку в CAMT.053 со временем
+    /// создания из `clock`.
+    pub fn from_statement_with_clock(statement: Statement, clock: &dyn Clock) -> Self {
+        let currency = statement.account.currency.clone();
+
+        let to_balance = |balance: &Balance, balance_type: BalanceType| Camt053Balance {
+            balance_type,
+            amount: balance.amount.value.abs(),
+            currency: balance.amount.currency.clone(),
+            credit_debit: if balance.is_credit {
+                CreditDebit::Credit
+            } else {
+                CreditDebit::Debit
+            },
+            date: balance.date,
+        };
+
+        let balances = vec![
+            to_balance(&statement.opening_balance, BalanceType::Opening),
+            to_balance(&statement.closing_balance, BalanceType::Closing),
+        ];
+
+        let entries = statement
+            .transactions
+            .into_iter()
+            .enumerate()
+            .map(|(idx, tx)| transaction_to_camt_entry(idx, tx))
+            .collect();
+
+        Camt053Statement {
+            message_id: statement
+                .reference
+                .unwrap_or_else(|| format!("STMT-{}", statement.account.number)),
+            creation_date_time: clock.now(),
+            statement_id: statement
+                .statement_number
+                .unwrap_or_else(|| statement.account.number.clone()),
+            account: Camt053Account {
+                // Без IBAN передаем номер счета, иначе он будет потерян
+                iban: statement.account.iban.or(Some(statement.account.number)),
+                currency,
+                name: statement.account.name,
+                owner_name: statement.account.owner,
+                owner_tax_id: statement.account.tax_id,
+            },
+            balances,
+            entries,
+        }
+    }
+}
+
+fn transaction_to_camt_entry(idx: usize, tx: Transaction) -> Camt053Entry {
+    let credit_debit = if tx.is_credit {
+        CreditDebit::Credit
+    } else {
+        CreditDebit::Debit
+    };
+
+    let counterparty = tx.counterparty.unwrap_or_default();
+    let party = counterparty_to_camt_party(&counterparty);
+    let ultimate = counterparty
+        .ultimate_party
+        .as_deref()
+        .and_then(counterparty_to_camt_party);
+    let agent = counterparty_to_camt_agent(&counterparty);
+    let account = counterparty.account.clone();
+
+    // Курс относим к сумме транзакции, а при ее отсутствии — к инструктированной сумме
+    let mut rates = tx.exchange_rates.into_iter();
+    let to_detail = |amount: Amount| Camt053AmountDetail {
+        amount: amount.value,
+        currency: amount.currency,
+        currency_exchange: None,
+    };
+    let mut amount_details = Camt053AmountDetails {
+        instructed_amount: tx.instructed_amount.map(to_detail),
+        transaction_amount: tx.original_amount.map(to_detail),
+        counter_value_amount: tx.counter_value_amount.map(to_detail),
+    };
+    if let Some(detail) = amount_details
+        .transaction_amount
+        .as_mut()
+        .or(amount_details.instructed_amount.as_mut())
+    {
+        detail.currency_exchange = rates.next();
+    }
+
+    let charges = tx
+        .charges
+        .into_iter()
+        .map(|charge| Camt053Charge {
+            amount: charge.amount.value,
+            currency: charge.amount.currency,
+            credit_debit: if charge.is_credit {
+                CreditDebit::Credit
+            } else {
+                CreditDebit::Debit
+            },
+            charge_type: charge.charge_type,
+            bearer: charge.bearer,
+            included: charge.included,
+        })
+        .collect();
+
+    let (debtor, debtor_account, ultimate_debtor, debtor_agent) = if tx.is_credit {
+        (party.clone(), account.clone(), ultimate.clone(), agent.clone())
+    } else {
+        (None, None, None, None)
+    };
+    let (creditor, creditor_account, ultimate_creditor, creditor_agent) = if tx.is_credit {
+        (None, None, None, None)
+    } else {
+        (party, account, ultimate, agent)
+    };
+
+    let transaction_details = vec![Camt053TransactionDetails {
+        end_to_end_id: Some(END_TO_END_NOT_PROVIDED.to_string()),
+        transaction_id: tx.reference.clone(),
+        amount: Some(tx.amount.value),
+        currency: Some(tx.amount.currency.clone()),
+        debtor,
+        debtor_account,
+        ultimate_debtor,
+        creditor,
+        creditor_account,
+        ultimate_creditor,
+        debtor_agent,
+        creditor_agent,
+        remittance_info: if tx.description.is_empty() {
+            vec![]
+        } else {
+            vec![tx.description]
+        },
+        amount_details,
+        charges,
+    }];
+
+    Camt053Entry {
+        entry_ref: Some(format!("{}", idx + 1)),
+        amount: tx.amount.value,
+        currency: tx.amount.currency,
+        credit_debit,
+        booking_date: tx.date,
+        value_date: tx.value_date,
+        account_servicer_ref: tx.reference,
+        amount_details: Camt053AmountDetails::default(),
+        charges: Vec::new(),
+        transaction_details,
+    }
+}
+
+fn counterparty_to_camt_party(counterparty: &Counterparty) -> Option<Camt053Party> {
+    if counterparty.name.is_none()
+        && counterparty.postal_address.is_none()
+        && counterparty.organisation_id.is_none()
+        && counterparty.tax_id.is_none()
+    {
+        return None;
+    }
+
+    Some(Camt053Party {
+        name: counterparty.name.clone(),
+        postal_address: counterparty.postal_address.clone(),
+        organisation_id: counterparty.organisation_id.clone(),
+        tax_id: counterparty.tax_id.clone(),
+        ..Default::default()
+    })
+}
+
+fn counterparty_to_camt_agent(counterparty: &Counterparty) -> Option<Camt053Agent> {
+    if counterparty.bank_code.is_none() && counterparty.bank_name.is_none() {
+        return None;
+    }
+
+    // Российский БИК (9 цифр) передается как идентификатор клиринговой системы
+    let (bic, clearing_member_id) = match counterparty.bank_code {
+        Some(ref code) if code.chars().all(|c| c.is_ascii_digit()) => (None, Some(code.clone())),
+        ref code => (code.clone(), None),
+    };
+
+    Some(Camt053Agent {
+        bic,
+        clearing_member_id,
+        name: counterparty.bank_name.clone(),
+        postal_address: None,
+    })
+}
+
+impl From<Vec<Statement>> for OneCStatement {
+    fn from(statements: Vec<Statement>) -> Self {
+        let date_from = statements
+            .iter()
+            .map(|s| s.opening_balance.date)
+            .min();
+        let date_to = statements
+            .iter()
+            .map(|s| s.closing_balance.date)
+            .max();
+
+        let mut account_numbers = Vec::new();
+        let mut accounts = Vec::new();
+        let mut documents = Vec::new();
+
+        for statement in statements {
+            let number = statement.account.number.clone();
+            let own = OneCParty {
+                account: Some(number.clone()),
+                name: statement.account.owner.clone().or(statement.account.name.clone()),
+                inn: statement.account.tax_id.clone(),
+                settlement_account: Some(number.clone()),
+                ..Default::default()
+            };
+
+            let mut total_credit = 0i64;
+            let mut total_debit = 0i64;
+
+            for (idx, tx) in statement.transactions.into_iter().enumerate() {
+                let amount = tx.amount.value.abs();
+                let other = tx
+                    .counterparty
+                    .map(|c| OneCParty {
+                        settlement_account: c.account.clone(),
+                        account: c.account,
+                        name: c.name,
+                        inn: c.tax_id,
+                        kpp: c.tax_reason_code,
+                        bank_name: c.bank_name,
+                        bik: c.bank_code,
+                        correspondent_account: c.bank_correspondent_account,
+                        ..Default::default()
+                    })
+                    .unwrap_or_default();
+
+                let (payer, payee, debited_date, credited_date) = if tx.is_credit {
+                    total_credit = total_credit.saturating_add(amount);
+                    (other, own.clone(), None, Some(tx.date))
+                } else {
+                    total_debit = total_debit.saturating_add(amount);
+                    (own.clone(), other, Some(tx.date), None)
+                };
+
+                documents.push(OneCDocument {
+                    document_type: ONEC_PAYMENT_ORDER.to_string(),
+                    number: tx.reference.unwrap_or_else(|| format!("{}", idx + 1)),
+                    date: tx.date,
+                    amount,
+                    payer,
+                    payee,
+                    debited_date,
+                    credited_date,
+                    payment_kind: None,
+                    priority: None,
+                    purpose: tx.description,
+                    other_fields: Vec::new(),
+                });
+            }
+
+            accounts.push(OneCAccountSection {
+                date_from: Some(statement.opening_balance.date),
+                date_to: Some(statement.closing_balance.date),
+                account: number.clone(),
+                opening_balance: statement.opening_balance.signed_amount().value,
+                total_credit,
+                total_debit,
+                closing_balance: statement.closing_balance.signed_amount().value,
+            });
+            account_numbers.push(number);
+        }
+
+        OneCStatement {
+            format_version: "1.03".to_string(),
+            encoding: "Windows".to_string(),
+            sender: Some("YPBank".to_string()),
+            receiver: None,
+            creation_date: None,
+            creation_time: None,
+            date_from,
+            date_to,
+            account_numbers,
+            accounts,
+            documents,
+        }
+    }
+}
+
+impl From<Statement> for CsvStatement {
+    fn from(statement: Statement) -> Self {
+        let own_account = statement.account.number.clone();
+        let own_details = CsvAccountDetails {
+            tax_id: statement.account.tax_id.clone(),
+            tax_reason_code: None,
+            name: statement.account.owner.clone().or(statement.account.name.clone()),
+        };
+
+        let transactions = statement
+            .transactions
+            .into_iter()
+            .map(|tx| {
+                let counterparty = tx.counterparty.unwrap_or_default();
+                let amount = tx.amount.value.abs();
+
+                // БИК дописываем к наименованию банка, если его там еще нет
+                let bank_info = match (counterparty.bank_code, counterparty.bank_name) {
+                    (Some(code), Some(name)) if name.contains(&code) => name,
+                    (Some(code), Some(name)) => format!("БИК {} {}", code, name),
+                    (Some(code), None) => format!("БИК {}", code),
+                    (None, name) => name.unwrap_or_default(),
+                };
+
+                let counterparty_details = CsvAccountDetails {
+                    tax_id: counterparty.tax_id,
+                    tax_reason_code: counterparty.tax_reason_code,
+                    name: counterparty.name,
+                };
+
+                let (debit_account, credit_account, debit_amount, credit_amount) = if tx.is_credit {
+                    (counterparty.account, Some(own_account.clone()), None, Some(amount))
+                } else {
+                    (Some(own_account.clone()), counterparty.account, Some(amount), None)
+                };
+                let (debit_details, credit_details) = if tx.is_credit {
+                    (counterparty_details, own_details.clone())
+                } else {
+                    (own_details.clone(), counterparty_details)
+                };
+
+                CsvTransaction {
+                    date: tx.date,
+                    debit_account,
+                    credit_account,
+                    debit_amount,
+                    credit_amount,
+                    document_number: tx.reference.unwrap_or_default(),
+                    bank_info,
+                    description: tx.description,
+                    debit_details,
+                    credit_details,
+                }
+            })
+            .collect();
+
+        CsvStatement {
+            account_number: own_account,
+            account_name: statement
+                .account
+                .owner
+                .or(statement.account.name)
+                .unwrap_or_default(),
+            currency: statement.account.currency,
+            bank_name: None,
+            period_start: Some(statement.opening_balance.date),
+            period_end: Some(statement.closing_balance.date),
+            opening_balance: Some(statement.opening_balance.signed_amount().value),
+            closing_balance: Some(statement.closing_balance.signed_amount().value),
+            transactions,
+        }
+    }
+}
+
+/// Максимальная длина поля NAME в OFX.
+const OFX_NAME_MAX_LEN: usize = 32;
+
+impl From<Statement> for OfxStatement {
+    fn from(statement: Statement) -> Self {
+        // FITID должен быть уникален в пределах счета
+        let mut seen_fitids = HashSet::new();
+        let transactions = statement
+            .transactions
+            .into_iter()
+            .enumerate()
+            .map(|(idx, tx)| {
+                let amount = tx.signed_amount().value;
+                let date = &tx.date;
+                let generated = || format!("{}{:04}", date.format("%Y%m%d"), idx + 1);
+                let mut fitid = tx
+                    .reference
+                    .clone()
+                    .filter(|r| !r.is_empty() && r != END_TO_END_NOT_PROVIDED)
+                    .unwrap_or_else(generated);
+                if !seen_fitids.insert(fitid.clone()) {
+                    fitid = format!("{}-{}", fitid, idx + 1);
+                    seen_fitids.insert(fitid.clone());
+                }
+
+                let name = tx
+                    .counterparty
+                    .and_then(|c| c.name)
+                    .map(|name| name.chars().take(OFX_NAME_MAX_LEN).collect());
+
+                OfxTransaction {
+                    transaction_type: if tx.is_credit {
+                        OFX_TRNTYPE_CREDIT
+                    } else {
+                        OFX_TRNTYPE_DEBIT
+                    }
+                    .to_string(),
+                    date_posted: tx.date,
+                    amount,
+                    fitid,
+                    check_number: None,
+                    name,
+                    memo: Some(tx.description).filter(|d| !d.is_empty()),
+                }
+            })
+            .collect();
+
+        let ledger_balance = OfxBalance {
+            amount: statement.closing_balance.signed_amount().value,
+            date: statement.closing_balance.date,
+        };
+
+        OfxStatement {
+            version: OfxVersion::default(),
+            currency: statement.account.currency,
+            bank_id: None,
+            account_id: statement.account.iban.unwrap_or(statement.account.number),
+            account_type: OFX_ACCOUNT_TYPE_CHECKING.to_string(),
+            date_start: Some(statement.opening_balance.date),
+            date_end: Some(statement.closing_balance.date),
+            transactions,
+            ledger_balance,
+            // Доступный остаток в унифицированной модели неизвестен
+            available_balance: None,
+        }
+    }
+}
+
+impl From<Vec<Statement>> for Bai2File {
+    /// Дата и время создания файла (запись 01) берутся из системных часов.
+    fn from(statements: Vec<Statement>) -> Self {
+        Self::from_statements_with_clock(statements, &SystemClock)
+    }
+}
+
+impl Bai2File {
+    /// Группирует выписки по дате исходящего остатка: одна группа BAI2 на дату.
+    /// Дата и время создания файла берутся из `clock`.
+    pub fn from_statements_with_clock(statements: Vec<Statement>, clock: &dyn Clock) -> Self {
+        let now = clock.now();
+        let file_id = statements
+            .iter()
+            .find_map(|s| s.reference.clone())
+            .unwrap_or_else(|| "1".to_string());
+
+        let mut groups: Vec<Bai2Group> = Vec::new();
+
+        for statement in statements {
+            let total = |is_credit: bool| {
+                statement
+                    .transactions
+                    .iter()
+                    .filter(|tx| tx.is_credit == is_credit)
+                    .fold((0i64, 0u32), |(sum, count), tx| {
+                        (sum.saturating_add(tx.amount.value.abs()), count + 1)
+                    })
+            };
+            let (credits, credit_count) = total(true);
+            let (debits, debit_count) = total(false);
+
+            let summary = |type_code: &str, amount: i64, item_count: Option<u32>| Bai2Summary {
+                type_code: type_code.to_string(),
+                amount: Some(amount),
+                item_count,
+                funds_type: None,
+            };
+            let opening = statement.opening_balance.signed_amount().value;
+            let closing = statement.closing_balance.signed_amount().value;
+
+            let account = Bai2Account {
+                account_number: statement.account.number,
+                currency: Some(statement.account.currency),
+                summaries: vec![
+                    summary(BAI2_OPENING_LEDGER, opening, None),
+                    summary(BAI2_CLOSING_LEDGER, closing, None),
+                    summary(BAI2_TOTAL_CREDITS, credits, Some(credit_count)),
+                    summary(BAI2_TOTAL_DEBITS, debits, Some(debit_count)),
+                ],
+                transactions: statement
+                    .transactions
+                    .into_iter()
+                    .map(|tx| Bai2Transaction {
+                        type_code: if tx.is_credit {
+                            BAI2_MISC_CREDIT
+                        } else {
+                            BAI2_MISC_DEBIT
+                        }
+                        .to_string(),
+                        amount: tx.amount.value.abs(),
+                        funds_type: None,
+                        bank_reference: tx.reference,
+                        customer_reference: None,
+                        text: tx.description,
+                    })
+                    .collect(),
+            };
+
+            let as_of_date = statement.closing_balance.date;
+            match groups.iter_mut().find(|g| g.as_of_date == as_of_date) {
+                Some(group) => group.accounts.push(account),
+                None => groups.push(Bai2Group {
+                    ultimate_receiver: None,
+                    originator: "YPBANK".to_string(),
+                    status: "1".to_string(),
+                    as_of_date,
+                    as_of_time: None,
+                    currency: None,
+                    accounts: vec![account],
+                }),
+            }
+        }
+
+        Bai2File {
+            sender: "YPBANK".to_string(),
+            receiver: "YPBANK".to_string(),
+            creation_date: now.date(),
+            creation_time: Some(format!("{:02}{:02}", now.hour(), now.minute())),
+            file_id,
+            groups,
+        }
+    }
+}
+
+impl From<Vec<Statement>> for QifFile {
+    /// Входящий остаток каждой выписки записывается операцией `Opening Balance`.
+    fn from(statements: Vec<Statement>) -> Self {
+        let accounts = statements
+            .into_iter()
+            .map(|statement| {
+                let number = statement.account.number;
+                let opening = &statement.opening_balance;
+                let opening_amount = opening.signed_amount().value;
+
+                let mut transactions = vec![QifTransaction {
+                    date: opening.date,
+                    amount: opening_amount,
+                    number: None,
+                    payee: Some(QIF_OPENING_BALANCE.to_string()),
+                    memo: None,
+                    category: Some(format!("[{}]", number)),
+                    cleared: Some("X".to_string()),
+                }];
+
+                transactions.extend(statement.transactions.into_iter().map(|tx| {
+                    let amount = tx.signed_amount().value;
+                    QifTransaction {
+                        date: tx.date,
+                        amount,
+                        number: tx.reference,
+                        payee: tx.counterparty.and_then(|c| c.name),
+                        memo: Some(tx.description).filter(|d| !d.is_empty()),
+                        category: None,
+                        cleared: None,
+                    }
+                }));
+
+                QifAccount {
+                    name: Some(number),
+                    account_type: QIF_TYPE_BANK.to_string(),
+                    description: statement.account.owner.or(statement.account.name),
+                    transactions,
+                }
+            })
+            .collect();
+
+        QifFile {
+            date_style: QifDateStyle::default(),
+            accounts,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Date;
+    use crate::clock::FixedClock;
+    use crate::types::{Date, DateTime};
 
     #[test]
     fn test_mt940_to_camt053_conversion() {
@@ -235,8 +914,10 @@ mod tests {
             }],
         };
 
-        let camt: Camt053Statement = mt940.into();
+        let clock = FixedClock("2024-02-01T09:30:00+01:00".parse().unwrap());
+        let camt = Camt053Statement::from_mt940_with_clock(mt940, &clock);
 
+        assert_eq!(camt.creation_date_time.to_string(), "2024-02-01T09:30:00+01:00");
         assert_eq!(camt.account.iban, Some("NL81ASNB9999999999".to_string()));
         assert_eq!(camt.balances.len(), 2);
         assert_eq!(camt.entries.len(), 1);
@@ -247,13 +928,14 @@ mod tests {
     fn test_camt053_to_mt940_conversion() {
         let camt = Camt053Statement {
             message_id: "MSG001".to_string(),
-            creation_date_time: "2024-01-01T00:00:00".to_string(),
+            creation_date_time: DateTime::midnight(Date::new(2024, 1, 1)),
             statement_id: "STMT001".to_string(),
             account: Camt053Account {
                 iban: Some("DK8030000001234567".to_string()),
                 currency: "DKK".to_string(),
                 name: Some("Test Account".to_string()),
                 owner_name: Some("Test Owner".to_string()),
+                owner_tax_id: None,
             },
             balances: vec![
                 Camt053Balance {
@@ -285,13 +967,14 @@ mod tests {
     fn test_camt053_to_mt940_missing_balance() {
         let camt = Camt053Statement {
             message_id: "MSG001".to_string(),
-            creation_date_time: "2024-01-01T00:00:00".to_string(),
+            creation_date_time: DateTime::midnight(Date::new(2024, 1, 1)),
             statement_id: "STMT001".to_string(),
             account: Camt053Account {
                 iban: Some("DK8030000001234567".to_string()),
                 currency: "DKK".to_string(),
                 name: None,
                 owner_name: None,
+                owner_tax_id: None,
             },
             balances: vec![],
             entries: vec![],
@@ -300,4 +983,13 @@ mod tests {
         let result: Result<Mt940Statement, _> = camt.try_into();
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_bai2_creation_time_from_clock() {
+        let clock = FixedClock("2024-03-05T09:07:00".parse().unwrap());
+        let bai2 = Bai2File::from_statements_with_clock(Vec::new(), &clock);
+        assert_eq!(bai2.creation_date, Date::new(2024, 3, 5));
+        assert_eq!(bai2.creation_time.as_deref(), Some("0907"));
+        assert!(bai2.groups.is_empty());
+    }
 }
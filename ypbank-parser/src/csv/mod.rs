@@ -1,8 +1,10 @@
 //! Модуль парсинга и сериализации формата CSV банковских выписок.
 
 pub mod parser;
+pub mod profile;
 pub mod writer;
 
-pub use parser::{CsvStatement, CsvTransaction};
+pub use parser::{CsvAccountDetails, CsvStatement, CsvTransaction};
+pub use profile::{CsvColumns, CsvHeader, CsvProfile};
 pub use writer::CsvWriter;
 
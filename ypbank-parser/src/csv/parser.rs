@@ -1,11 +1,19 @@
 //! Парсер CSV формата банковских выписок.
 
+use crate::csv::profile::CsvProfile;
+use crate::encoding::{self, TextEncoding};
 use crate::error::{Error, Result};
-use crate::types::{Account, Amount, Balance, Counterparty, Date, Statement, Transaction};
+use crate::limits::Limits;
+use crate::types::{
+    is_valid_inn, Account, Amount, Balance, Counterparty, Date, Statement, Transaction,
+};
+use std::borrow::Cow;
 use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
 
 /// Выписка в формате CSV.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CsvStatement {
     /// Номер счета (20 цифр).
     pub account_number: String,
@@ -13,12 +21,22 @@ pub struct CsvStatement {
     pub account_name: String,
     /// Код валюты (RUB по умолчанию).
     pub currency: String,
+    /// Наименование обслуживающего банка (строка `Банк:`).
+    pub bank_name: Option<String>,
+    /// Начало периода выписки (строка `Период:`).
+    pub period_start: Option<Date>,
+    /// Конец периода выписки.
+    pub period_end: Option<Date>,
+    /// Входящий остаток в минимальных единицах.
+    pub opening_balance: Option<i64>,
+    /// Исходящий остаток в минимальных единицах.
+    pub closing_balance: Option<i64>,
     /// Список транзакций.
     pub transactions: Vec<CsvTransaction>,
 }
 
 /// Транзакция в формате CSV.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CsvTransaction {
     /// Дата операции.
     pub date: Date,
@@ -36,89 +54,238 @@ pub struct CsvTransaction {
     pub bank_info: String,
     /// Назначение платежа.
     pub description: String,
+    /// ИНН, КПП и наименование из ячейки счета дебета.
+    pub debit_details: CsvAccountDetails,
+    /// ИНН, КПП и наименование из ячейки счета кредита.
+    pub credit_details: CsvAccountDetails,
+}
+
+/// Реквизиты владельца счета из многострочной ячейки `счет\nИНН\nНаименование`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CsvAccountDetails {
+    /// ИНН (с проверенной контрольной суммой).
+    pub tax_id: Option<String>,
+    /// КПП.
+    pub tax_reason_code: Option<String>,
+    /// Наименование владельца счета.
+    pub name: Option<String>,
+}
+
+impl CsvAccountDetails {
+    /// Возвращает true, если ни один реквизит не указан.
+    pub fn is_empty(&self) -> bool {
+        self.tax_id.is_none() && self.tax_reason_code.is_none() && self.name.is_none()
+    }
 }
 
 impl CsvStatement {
     /// Парсит CSV из любого источника, реализующего трейт Read.
     pub fn from_read<R: Read>(reader: &mut R) -> Result<Self> {
-        let mut content = String::new();
-        reader.read_to_string(&mut content)?;
-        Self::parse(&content)
+        Self::from_read_with_profile(reader, &CsvProfile::sber())
+    }
+
+    /// Асинхронно парсит CSV из любого источника, реализующего трейт AsyncRead.
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        Self::from_async_read_with_profile(reader, &CsvProfile::sber()).await
+    }
+
+    /// Парсит CSV с заданным профилем банка из любого источника, реализующего трейт Read.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    pub fn from_read_with_profile<R: Read>(reader: &mut R, profile: &CsvProfile) -> Result<Self> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse_with_profile(&content, profile)
+    }
+
+    /// Асинхронно парсит CSV с заданным профилем банка из любого источника,
+    /// реализующего трейт AsyncRead.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    #[cfg(feature = "async")]
+    pub async fn from_async_read_with_profile<R: AsyncRead + Unpin>(
+        reader: &mut R,
+        profile: &CsvProfile,
+    ) -> Result<Self> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse_with_profile(&content, profile)
+    }
+
+    /// Парсит CSV из байтов в заданной кодировке с заданным профилем банка.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes_with_profile(
+        bytes: &[u8],
+        encoding: Option<TextEncoding>,
+        profile: &CsvProfile,
+    ) -> Result<Self> {
+        Self::parse_with_profile(&encoding::decode(bytes, encoding)?, profile)
     }
 
-    /// Парсит CSV из строки.
+    /// Парсит CSV из строки (профиль СберБизнес). Применяются [`Limits::default`].
     pub fn parse(content: &str) -> Result<Self> {
-        let lines: Vec<&str> = content.lines().collect();
+        Self::parse_with_profile(content, &CsvProfile::sber())
+    }
 
-        if lines.len() < 12 {
-            return Err(Error::InvalidFormat(
-                "CSV файл слишком короткий, ожидается минимум 12 строк".to_string(),
-            ));
-        }
+    /// Парсит CSV из строки (профиль СберБизнес) с заданными ограничениями.
+    ///
+    /// Длина строки и записи, продолженной на следующих строках из-за
+    /// кавычек, ограничена `max_field_len`, число транзакций — `max_transactions`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Self> {
+        Self::parse_lines(content, &CsvProfile::sber(), limits)
+    }
 
-        let (account_number, account_name) = Self::parse_header(&lines)?;
-        let currency = "RUB".to_string();
-        let transactions = Self::parse_transactions(&lines[12..])?;
+    /// Парсит CSV из строки с заданным профилем банка.
+    pub fn parse_with_profile(content: &str, profile: &CsvProfile) -> Result<Self> {
+        Self::parse_lines(content, profile, &Limits::default())
+    }
 
-        Ok(CsvStatement {
-            account_number,
-            account_name,
-            currency,
-            transactions,
-        })
+    pub(crate) fn parse_lines(
+        content: &str,
+        profile: &CsvProfile,
+        limits: &Limits,
+    ) -> Result<Self> {
+        profile.validate()?;
+        limits.check_input(content.len())?;
+
+        let lines: Vec<&str> = content.lines().collect();
+        for line in &lines {
+            limits.check_field("строка CSV", line)?;
+        }
+        let data_start = profile.data_start(&lines)?;
+
+        let mut statement = CsvStatement {
+            account_number: String::new(),
+            account_name: String::new(),
+            currency: profile.currency.clone(),
+            bank_name: None,
+            period_start: None,
+            period_end: None,
+            opening_balance: None,
+            closing_balance: None,
+            transactions: Vec::new(),
+        };
+        statement.parse_header(&lines[..data_start], profile);
+        statement.transactions = Self::parse_transactions(&lines[data_start..], profile, limits)?;
+        statement.check_footer(&lines[data_start..], profile);
+
+        Ok(statement)
     }
 
-    fn parse_header(lines: &[&str]) -> Result<(String, String)> {
-        let mut account_number = String::new();
-        let mut account_name = String::new();
-
-        for line in lines.iter().take(10) {
-            if line.contains("40702") || line.contains("40703") || line.contains("40817") {
-                let parts: Vec<&str> = line.split(',').collect();
-                for part in parts {
-                    let trimmed = part.trim().trim_matches('"');
-                    if trimmed.len() == 20 && trimmed.chars().all(|c| c.is_ascii_digit()) {
-                        account_number = trimmed.to_string();
-                        break;
+    fn parse_header(&mut self, lines: &[&str], profile: &CsvProfile) {
+        let mut fallback_name = None;
+
+        for line in lines {
+            for cell in Self::parse_csv_fields(line, profile) {
+                if let Some(name) = cell.strip_prefix("Клиент:") {
+                    self.account_name = name.trim().to_string();
+                } else if let Some(bank) = cell.strip_prefix("Банк:") {
+                    let bank = bank.trim();
+                    if !bank.is_empty() {
+                        self.bank_name = Some(bank.to_string());
+                    }
+                } else if let Some(period) = cell.strip_prefix("Период:") {
+                    if let Some((from, to)) = period.split_once(" - ") {
+                        self.period_start = profile.parse_date(from).ok();
+                        self.period_end = profile.parse_date(to).ok();
                     }
+                } else if let Some(balance) = cell.strip_prefix("Входящий остаток:") {
+                    self.opening_balance = self.parse_header_balance(balance, profile);
+                } else if let Some(balance) = cell.strip_prefix("Исходящий остаток:") {
+                    self.closing_balance = self.parse_header_balance(balance, profile);
+                } else if fallback_name.is_none()
+                    && (cell.contains("ООО") || cell.contains("ИП") || cell.contains("АО"))
+                {
+                    fallback_name = Some(cell.to_string());
                 }
-            }
 
-            if line.contains("ООО") || line.contains("ИП") || line.contains("АО") {
-                let parts: Vec<&str> = line.split(',').collect();
-                for part in parts {
-                    let trimmed = part.trim().trim_matches('"');
-                    if trimmed.contains("ООО") || trimmed.contains("ИП") || trimmed.contains("АО") {
-                        account_name = trimmed.to_string();
-                        break;
+                if self.account_number.is_empty() {
+                    if let Some(number) = cell.split_whitespace().find(|word| {
+                        word.len() == 20 && word.chars().all(|c| c.is_ascii_digit())
+                    }) {
+                        self.account_number = number.to_string();
                     }
                 }
             }
         }
 
-        if account_number.is_empty() && lines.len() > 5 {
-            let parts: Vec<&str> = lines[5].split(',').collect();
-            for part in parts {
-                let trimmed = part.trim().trim_matches('"');
-                if trimmed.len() == 20 && trimmed.chars().all(|c| c.is_ascii_digit()) {
-                    account_number = trimmed.to_string();
-                    break;
-                }
-            }
+        if self.account_number.is_empty() {
+            self.account_number = "UNKNOWN".to_string();
         }
 
-        if account_number.is_empty() {
-            account_number = "UNKNOWN".to_string();
+        if self.account_name.is_empty() {
+            self.account_name = fallback_name.unwrap_or_else(|| "Неизвестно".to_string());
         }
+    }
 
-        if account_name.is_empty() {
-            account_name = "Неизвестно".to_string();
+    /// Разбирает остаток вида `100000.00 RUB`; валюта из строки заменяет валюту профиля.
+    fn parse_header_balance(&mut self, value: &str, profile: &CsvProfile) -> Option<i64> {
+        let mut parts = value.split_whitespace();
+        let amount = Self::parse_amount_field(&profile.normalize_amount(parts.next()?))?;
+
+        if let Some(currency) = parts.next() {
+            if currency.len() == 3 && currency.chars().all(|c| c.is_ascii_uppercase()) {
+                self.currency = currency.to_string();
+            }
         }
 
-        Ok((account_number, account_name))
+        Some(amount)
     }
 
-    fn parse_transactions(lines: &[&str]) -> Result<Vec<CsvTransaction>> {
+    /// Сверяет количество операций и обороты из итоговых строк с разобранными транзакциями.
+    fn check_footer(&self, lines: &[&str], profile: &CsvProfile) {
+        for line in lines {
+            let cell = Self::parse_csv_fields(line, profile).into_iter().next().unwrap_or_default();
+
+            if let Some(count) = cell.strip_prefix("Количество операций:") {
+                if count.trim().parse::<usize>().ok() != Some(self.transactions.len()) {
+                    tracing::warn!(
+                        "Количество операций в итогах ({}) не совпадает с разобранным ({})",
+                        count.trim(),
+                        self.transactions.len()
+                    );
+                }
+            } else if let Some(totals) = cell.strip_prefix("Итого оборотов:") {
+                let mut words = totals.split_whitespace();
+                let mut expected = (None, None);
+                while let Some(word) = words.next() {
+                    let amount = words
+                        .next()
+                        .and_then(|a| Self::parse_amount_field(&profile.normalize_amount(a)));
+                    match word {
+                        "Дебет" => expected.0 = amount,
+                        "Кредит" => expected.1 = amount,
+                        _ => {}
+                    }
+                }
+
+                let (debit, credit) = self.turnover();
+                if expected != (Some(debit), Some(credit)) {
+                    tracing::warn!(
+                        "Обороты в итогах не совпадают с суммой операций: дебет {}, кредит {}",
+                        debit,
+                        credit
+                    );
+                }
+            }
+        }
+    }
+
+    /// Обороты по дебету и кредиту в минимальных единицах.
+    pub fn turnover(&self) -> (i64, i64) {
+        self.transactions.iter().fold((0i64, 0i64), |(debit, credit), tx| {
+            (
+                debit.saturating_add(tx.debit_amount.unwrap_or(0)),
+                credit.saturating_add(tx.credit_amount.unwrap_or(0)),
+            )
+        })
+    }
+
+    fn parse_transactions(
+        lines: &[&str],
+        profile: &CsvProfile,
+        limits: &Limits,
+    ) -> Result<Vec<CsvTransaction>> {
         let mut transactions = Vec::new();
         let mut i = 0;
 
@@ -135,21 +302,25 @@ impl CsvStatement {
                 continue;
             }
 
-            let parts: Vec<&str> = line.split(',').collect();
-            if parts.len() > 1 {
-                let date_str = parts[1].trim().trim_matches('"');
-                if Self::is_valid_date(date_str) {
+            let date_field = line.split(profile.delimiter).nth(profile.columns.date);
+            if let Some(date_field) = date_field {
+                let date_str = Self::unquote(date_field.trim(), profile);
+                if profile.parse_date(date_str).is_ok() {
                     let mut full_record = line.to_string();
                     let mut j = i + 1;
 
-                    while j < lines.len() && Self::has_unclosed_quotes(&full_record) {
+                    while j < lines.len() && Self::has_unclosed_quotes(&full_record, profile) {
                         full_record.push('\n');
                         full_record.push_str(lines[j]);
+                        limits.check_field("запись CSV", &full_record)?;
                         j += 1;
                     }
 
-                    match Self::parse_transaction_record(&full_record) {
-                        Ok(tx) => transactions.push(tx),
+                    match Self::parse_transaction_record(&full_record, profile) {
+                        Ok(tx) => {
+                            transactions.push(tx);
+                            limits.check_transactions(transactions.len())?;
+                        }
                         Err(e) => {
                             tracing::warn!("Не удалось распарсить транзакцию: {}", e);
                         }
@@ -166,75 +337,68 @@ impl CsvStatement {
         Ok(transactions)
     }
 
-    fn is_valid_date(s: &str) -> bool {
-        if s.len() != 10 {
-            return false;
+    fn unquote<'a>(s: &'a str, profile: &CsvProfile) -> &'a str {
+        match profile.quote {
+            Some(quote) => s.trim_matches(quote),
+            None => s,
         }
-
-        let parts: Vec<&str> = s.split('.').collect();
-        if parts.len() != 3 {
-            return false;
-        }
-
-        parts[0].len() == 2
-            && parts[1].len() == 2
-            && parts[2].len() == 4
-            && parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit()))
     }
 
-    fn has_unclosed_quotes(s: &str) -> bool {
-        !s.matches('"').count().is_multiple_of(2)
+    fn has_unclosed_quotes(s: &str, profile: &CsvProfile) -> bool {
+        profile
+            .quote
+            .is_some_and(|quote| !s.matches(quote).count().is_multiple_of(2))
     }
 
-    fn parse_transaction_record(record: &str) -> Result<CsvTransaction> {
-        let fields = Self::parse_csv_fields(record);
+    fn parse_transaction_record(record: &str, profile: &CsvProfile) -> Result<CsvTransaction> {
+        let fields = Self::parse_csv_fields(record, profile);
 
-        if fields.len() < 20 {
+        if fields.len() < profile.min_fields {
             return Err(Error::Parse(format!(
-                "Недостаточно полей в записи: {} (ожидается >= 20)",
-                fields.len()
+                "Недостаточно полей в записи: {} (ожидается >= {})",
+                fields.len(),
+                profile.min_fields
             )));
         }
 
-        let date = Self::parse_date(&fields[1])?;
-
-        let debit_account = if !fields[4].is_empty() {
-            Some(fields[4].lines().next().unwrap_or("").to_string())
-        } else {
-            None
-        };
-
-        let credit_account = if fields.len() > 8 && !fields[8].is_empty() {
-            Some(fields[8].lines().next().unwrap_or("").to_string())
-        } else {
-            None
+        let columns = &profile.columns;
+        let field = |index: Option<usize>| -> &str {
+            index
+                .and_then(|i| fields.get(i))
+                .map(Cow::as_ref)
+                .unwrap_or("")
         };
-
-        let debit_amount = Self::parse_amount_field(&fields[9]);
-
-        let credit_amount = if fields.len() > 13 {
-            Self::parse_amount_field(&fields[13])
-        } else {
-            None
+        let account = |index: Option<usize>| Self::parse_account_cell(field(index));
+        let amount = |index: Option<usize>| -> Option<i64> {
+            let normalized = profile.normalize_amount(field(index));
+            Self::parse_amount_field(&normalized)
         };
 
-        let document_number = if fields.len() > 14 {
-            fields[14].clone()
-        } else {
-            String::new()
-        };
+        let date = profile.parse_date(field(Some(columns.date)))?;
 
-        let bank_info = if fields.len() > 17 {
-            fields[17].clone()
-        } else {
-            String::new()
-        };
+        let (mut debit_amount, mut credit_amount) = (amount(columns.debit_amount), amount(columns.credit_amount));
+        if let Some(signed) = amount(columns.amount) {
+            if signed < 0 {
+                debit_amount = Some(-signed);
+            } else {
+                credit_amount = Some(signed);
+            }
+        }
 
-        let description = if fields.len() > 20 {
-            fields[20].clone()
-        } else {
-            String::new()
-        };
+        let (mut debit_account, mut debit_details) = account(columns.debit_account);
+        let (mut credit_account, mut credit_details) = account(columns.credit_account);
+        if let (Some(counterparty_account), details) = account(columns.counterparty_account) {
+            // Для поступления контрагент — плательщик (счет дебета), иначе — получатель
+            let (target_account, target_details) = if credit_amount.is_some() {
+                (&mut debit_account, &mut debit_details)
+            } else {
+                (&mut credit_account, &mut credit_details)
+            };
+            if target_account.is_none() {
+                *target_account = Some(counterparty_account);
+                *target_details = details;
+            }
+        }
 
         Ok(CsvTransaction {
             date,
@@ -242,63 +406,107 @@ impl CsvStatement {
             credit_account,
             debit_amount,
             credit_amount,
-            document_number,
-            bank_info,
-            description,
+            document_number: field(columns.document_number).to_string(),
+            bank_info: field(columns.bank_info).to_string(),
+            description: field(columns.description).to_string(),
+            debit_details,
+            credit_details,
         })
     }
 
-    fn parse_csv_fields(record: &str) -> Vec<String> {
+    /// Разбирает ячейку счета: первая строка — номер счета, далее ИНН, КПП и наименование.
+    ///
+    /// ИНН и КПП распознаются как отдельные строки или в виде `ИНН/КПП`,
+    /// в том числе с подписями `ИНН`/`КПП`. ИНН с неверной контрольной суммой отбрасывается.
+    fn parse_account_cell(value: &str) -> (Option<String>, CsvAccountDetails) {
+        let mut lines = value.lines().map(str::trim).filter(|l| !l.is_empty());
+        let Some(account) = lines.next() else {
+            return (None, CsvAccountDetails::default());
+        };
+
+        let mut details = CsvAccountDetails::default();
+        let mut name_parts = Vec::new();
+
+        for line in lines {
+            let tokens: Vec<&str> = line
+                .split(|c: char| c.is_whitespace() || c == '/' || c == ':')
+                .filter(|t| !t.is_empty())
+                .collect();
+            let is_identifier_line = tokens
+                .iter()
+                .all(|t| *t == "ИНН" || *t == "КПП" || t.chars().all(|c| c.is_ascii_digit()));
+
+            if !is_identifier_line {
+                name_parts.push(line);
+                continue;
+            }
+
+            for token in tokens.iter().filter(|t| t.chars().all(|c| c.is_ascii_digit())) {
+                match token.len() {
+                    10 | 12 if is_valid_inn(token) => {
+                        details.tax_id.get_or_insert_with(|| token.to_string());
+                    }
+                    10 | 12 => {
+                        tracing::warn!("Некорректная контрольная сумма ИНН: {}", token);
+                    }
+                    9 => {
+                        details.tax_reason_code.get_or_insert_with(|| token.to_string());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if !name_parts.is_empty() {
+            details.name = Some(name_parts.join(" "));
+        }
+
+        (Some(account.to_string()), details)
+    }
+
+    /// Разбивает запись на поля. Поле без кавычек ссылается на запись,
+    /// копия создается только для поля, в котором встретились кавычки.
+    fn parse_csv_fields<'a>(record: &'a str, profile: &CsvProfile) -> Vec<Cow<'a, str>> {
         let mut fields = Vec::new();
-        let mut current_field = String::new();
+        let mut field_start = 0;
+        let mut unquoted: Option<String> = None;
         let mut in_quotes = false;
-        let mut chars = record.chars().peekable();
+        let mut chars = record.char_indices().peekable();
 
-        while let Some(c) = chars.next() {
+        while let Some((pos, c)) = chars.next() {
             match c {
-                '"' => {
-                    if in_quotes && chars.peek() == Some(&'"') {
-                        current_field.push('"');
+                c if Some(c) == profile.quote => {
+                    let buffer =
+                        unquoted.get_or_insert_with(|| record[field_start..pos].to_string());
+                    if in_quotes && chars.peek().map(|&(_, next)| next) == Some(c) {
+                        buffer.push(c);
                         chars.next();
                     } else {
                         in_quotes = !in_quotes;
                     }
                 }
-                ',' if !in_quotes => {
-                    fields.push(current_field.trim().to_string());
-                    current_field = String::new();
+                c if c == profile.delimiter && !in_quotes => {
+                    fields.push(Self::finish_field(&record[field_start..pos], unquoted.take()));
+                    field_start = pos + c.len_utf8();
                 }
                 _ => {
-                    current_field.push(c);
+                    if let Some(buffer) = unquoted.as_mut() {
+                        buffer.push(c);
+                    }
                 }
             }
         }
 
-        fields.push(current_field.trim().to_string());
+        fields.push(Self::finish_field(&record[field_start..], unquoted.take()));
         fields
     }
 
-    fn parse_date(date_str: &str) -> Result<Date> {
-        let date_str = date_str.trim();
-
-        let parts: Vec<&str> = date_str.split('.').collect();
-        if parts.len() != 3 {
-            return Err(Error::Parse(format!("Некорректный формат даты: {}", date_str)));
+    fn finish_field(raw: &str, unquoted: Option<String>) -> Cow<'_, str> {
+        match unquoted {
+            Some(buffer) if buffer.trim().len() == buffer.len() => Cow::Owned(buffer),
+            Some(buffer) => Cow::Owned(buffer.trim().to_string()),
+            None => Cow::Borrowed(raw.trim()),
         }
-
-        let day: u8 = parts[0]
-            .parse()
-            .map_err(|_| Error::Parse(format!("Некорректный день: {}", parts[0])))?;
-
-        let month: u8 = parts[1]
-            .parse()
-            .map_err(|_| Error::Parse(format!("Некорректный месяц: {}", parts[1])))?;
-
-        let year: u16 = parts[2]
-            .parse()
-            .map_err(|_| Error::Parse(format!("Некорректный год: {}", parts[2])))?;
-
-        Ok(Date::new(year, month, day))
     }
 
     fn parse_amount_field(s: &str) -> Option<i64> {
@@ -349,7 +557,7 @@ impl CsvStatement {
                 0 => "00".to_string(),
                 1 => format!("{}0", frac_str),
                 2 => frac_str.to_string(),
-                _ => frac_str[..2].to_string(),
+                _ => frac_str.get(..2).unwrap_or_default().to_string(),
             };
             frac_padded.parse().map_err(|_| {
                 Error::Parse(format!("Некорректная дробная часть суммы: {}", frac_str))
@@ -369,75 +577,87 @@ impl TryFrom<CsvStatement> for Statement {
     type Error = Error;
 
     fn try_from(csv: CsvStatement) -> Result<Self> {
-        let account = Account {
+        let mut account = Account {
             iban: None,
             number: csv.account_number,
             currency: csv.currency.clone(),
             name: Some(csv.account_name),
             owner: None,
+            tax_id: None,
         };
 
-        let mut balance: i128 = 0;
+        let opening = csv.opening_balance.unwrap_or(0);
+        let mut balance = Amount::new(opening, &csv.currency);
         let first_date = csv
-            .transactions
-            .first()
-            .map(|t| t.date.clone())
+            .period_start
+            .or_else(|| csv.transactions.first().map(|t| t.date))
             .unwrap_or_else(|| Date::new(2024, 1, 1));
         let last_date = csv
-            .transactions
-            .last()
-            .map(|t| t.date.clone())
+            .period_end
+            .or_else(|| csv.transactions.last().map(|t| t.date))
             .unwrap_or_else(|| Date::new(2024, 12, 31));
 
         let mut transactions: Vec<Transaction> = Vec::with_capacity(csv.transactions.len());
 
         for tx in csv.transactions.iter() {
-            let (amount, is_credit) = if let Some(credit) = tx.credit_amount {
-                balance = balance.checked_add(credit as i128).ok_or_else(|| {
-                    Error::Parse("Переполнение при расчёте баланса".to_string())
-                })?;
-                (credit, true)
-            } else if let Some(debit) = tx.debit_amount {
-                balance = balance.checked_sub(debit as i128).ok_or_else(|| {
-                    Error::Parse("Переполнение при расчёте баланса".to_string())
-                })?;
-                (debit, false)
-            } else {
-                (0, true)
+            let (amount, is_credit) = match (tx.credit_amount, tx.debit_amount) {
+                (Some(credit), _) => (credit, true),
+                (None, Some(debit)) => (debit, false),
+                (None, None) => (0, true),
             };
 
-            let counterparty_account = if is_credit {
-                tx.debit_account.clone()
+            let (counterparty_account, counterparty_details, own_details) = if is_credit {
+                (&tx.debit_account, &tx.debit_details, &tx.credit_details)
             } else {
-                tx.credit_account.clone()
+                (&tx.credit_account, &tx.credit_details, &tx.debit_details)
             };
 
+            if account.tax_id.is_none() {
+                account.tax_id = own_details.tax_id.clone();
+            }
+
             let counterparty = Some(Counterparty {
-                name: None,
-                account: counterparty_account,
+                name: counterparty_details.name.clone(),
+                account: counterparty_account.clone(),
                 bank_code: CsvStatement::extract_bik(&tx.bank_info),
                 bank_name: Some(tx.bank_info.clone()),
+                tax_id: counterparty_details.tax_id.clone(),
+                tax_reason_code: counterparty_details.tax_reason_code.clone(),
+                ..Default::default()
             });
 
-            transactions.push(Transaction {
-                date: tx.date.clone(),
+            let transaction = Transaction {
+                date: tx.date,
                 value_date: None,
                 amount: Amount::new(amount, &csv.currency),
                 is_credit,
                 reference: Some(tx.document_number.clone()),
+                transaction_type: None,
                 description: tx.description.clone(),
                 counterparty,
-            });
+                instructed_amount: None,
+                original_amount: None,
+                counter_value_amount: None,
+                exchange_rates: Vec::new(),
+                charges: Vec::new(),
+            };
+            balance = balance.checked_add(&transaction.signed_amount())?;
+            transactions.push(transaction);
         }
 
-        let final_balance: i64 = balance.try_into().map_err(|_| {
-            Error::Parse("Итоговый баланс превышает допустимый диапазон i64".to_string())
-        })?;
+        let computed_balance = balance.value;
+        if csv.closing_balance.is_some_and(|closing| closing != computed_balance) {
+            tracing::warn!(
+                "Исходящий остаток CSV не совпадает с рассчитанным: {}",
+                computed_balance
+            );
+        }
+        let final_balance = csv.closing_balance.unwrap_or(computed_balance);
 
         let opening_balance = Balance {
-            amount: Amount::new(0, &csv.currency),
+            amount: Amount::new(opening, &csv.currency),
             date: first_date,
-            is_credit: true,
+            is_credit: opening >= 0,
         };
 
         let closing_balance = Balance {
@@ -459,6 +679,11 @@ impl TryFrom<CsvStatement> for Statement {
 
 impl CsvStatement {
     fn extract_bik(bank_info: &str) -> Option<String> {
+        let trimmed = bank_info.trim();
+        if trimmed.len() == 9 && trimmed.chars().all(|c| c.is_ascii_digit()) {
+            return Some(trimmed.to_string());
+        }
+
         if let Some(pos) = bank_info.find("БИК") {
             let start = pos + 4;
             let bik: String = bank_info[start..]
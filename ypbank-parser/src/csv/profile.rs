@@ -0,0 +1,401 @@
+//! Профили CSV-выгрузок различных банков.
+//!
+//! Профиль описывает, как найти таблицу операций в файле, как разбить строку
+//! на поля и в каких колонках находятся дата, суммы, счета и назначение платежа.
+
+use crate::error::{Error, Result};
+use crate::types::Date;
+
+/// Способ определения начала таблицы операций.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "profile-files",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CsvHeader {
+    /// Фиксированное число строк заголовка перед данными.
+    Lines(usize),
+    /// Данные начинаются после строки, содержащей маркер.
+    Marker {
+        /// Текст, по которому распознается строка с названиями колонок.
+        text: String,
+        /// Сколько строк пропустить после строки с маркером.
+        #[cfg_attr(feature = "profile-files", serde(default))]
+        skip: usize,
+    },
+}
+
+/// Номера колонок (с нуля) с данными операции.
+///
+/// Сумма задается либо парой колонок дебета и кредита,
+/// либо одной колонкой `amount` со знаком (отрицательная сумма — списание).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "profile-files",
+    derive(serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct CsvColumns {
+    /// Дата операции.
+    pub date: usize,
+    /// Сумма по дебету (списание).
+    pub debit_amount: Option<usize>,
+    /// Сумма по кредиту (поступление).
+    pub credit_amount: Option<usize>,
+    /// Сумма со знаком.
+    pub amount: Option<usize>,
+    /// Счет дебета.
+    pub debit_account: Option<usize>,
+    /// Счет кредита.
+    pub credit_account: Option<usize>,
+    /// Счет контрагента (если банк не разделяет счета дебета и кредита).
+    pub counterparty_account: Option<usize>,
+    /// Номер документа.
+    pub document_number: Option<usize>,
+    /// Банк контрагента (БИК и наименование).
+    pub bank_info: Option<usize>,
+    /// Назначение платежа.
+    pub description: Option<usize>,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        Self {
+            date: 0,
+            debit_amount: None,
+            credit_amount: None,
+            amount: Some(1),
+            debit_account: None,
+            credit_account: None,
+            counterparty_account: Some(3),
+            document_number: Some(2),
+            bank_info: None,
+            description: Some(4),
+        }
+    }
+}
+
+/// Профиль CSV-выгрузки банка.
+///
+/// Встроенные профили доступны через [`CsvProfile::builtin`], собственный профиль
+/// можно загрузить из TOML или JSON (feature `profile-files`). Незаданные в файле
+/// параметры берутся из профиля [`CsvProfile::generic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "profile-files",
+    derive(serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct CsvProfile {
+    /// Название профиля.
+    pub name: String,
+    /// Определение начала таблицы операций.
+    pub header: CsvHeader,
+    /// Разделитель полей.
+    pub delimiter: char,
+    /// Символ кавычек (None — поля не экранируются).
+    pub quote: Option<char>,
+    /// Формат даты: `%d`, `%m`, `%Y`, `%y` и символы-разделители.
+    pub date_format: String,
+    /// Десятичный разделитель в суммах.
+    pub decimal_separator: char,
+    /// Разделитель разрядов в суммах.
+    pub thousands_separator: Option<char>,
+    /// Код валюты счета.
+    pub currency: String,
+    /// Минимальное число полей в записи операции.
+    pub min_fields: usize,
+    /// Расположение колонок.
+    pub columns: CsvColumns,
+}
+
+impl Default for CsvProfile {
+    fn default() -> Self {
+        Self::generic()
+    }
+}
+
+impl CsvProfile {
+    /// Названия встроенных профилей.
+    pub const BUILTIN: [&'static str; 5] = ["sber", "tinkoff", "alfa", "vtb", "generic"];
+
+    /// Возвращает встроенный профиль по названию.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sber" | "sberbank" => Some(Self::sber()),
+            "tinkoff" | "tbank" => Some(Self::tinkoff()),
+            "alfa" | "alfabank" => Some(Self::alfa()),
+            "vtb" => Some(Self::vtb()),
+            "generic" => Some(Self::generic()),
+            _ => None,
+        }
+    }
+
+    /// СберБизнес: 12 строк заголовка, счета и суммы дебета/кредита в отдельных колонках.
+    pub fn sber() -> Self {
+        Self {
+            name: "sber".to_string(),
+            header: CsvHeader::Lines(12),
+            delimiter: ',',
+            quote: Some('"'),
+            date_format: "%d.%m.%Y".to_string(),
+            decimal_separator: '.',
+            thousands_separator: None,
+            currency: "RUB".to_string(),
+            min_fields: 20,
+            columns: CsvColumns {
+                date: 1,
+                debit_amount: Some(9),
+                credit_amount: Some(13),
+                amount: None,
+                debit_account: Some(4),
+                credit_account: Some(8),
+                counterparty_account: None,
+                document_number: Some(14),
+                bank_info: Some(17),
+                description: Some(20),
+            },
+        }
+    }
+
+    /// Т-Бизнес (Тинькофф): `Дата операции;Номер документа;Списание;Поступление;
+    /// Счет контрагента;БИК банка контрагента;Назначение платежа`.
+    pub fn tinkoff() -> Self {
+        Self {
+            name: "tinkoff".to_string(),
+            header: CsvHeader::Marker {
+                text: "Дата операции".to_string(),
+                skip: 0,
+            },
+            delimiter: ';',
+            quote: Some('"'),
+            date_format: "%d.%m.%Y".to_string(),
+            decimal_separator: ',',
+            thousands_separator: None,
+            currency: "RUB".to_string(),
+            min_fields: 0,
+            columns: CsvColumns {
+                date: 0,
+                debit_amount: Some(2),
+                credit_amount: Some(3),
+                amount: None,
+                debit_account: None,
+                credit_account: None,
+                counterparty_account: Some(4),
+                document_number: Some(1),
+                bank_info: Some(5),
+                description: Some(6),
+            },
+        }
+    }
+
+    /// Альфа-Бизнес: `Дата проводки;Номер документа;Счет дебета;Счет кредита;
+    /// Дебет;Кредит;Банк контрагента;Назначение платежа`, разряды через пробел.
+    pub fn alfa() -> Self {
+        Self {
+            name: "alfa".to_string(),
+            header: CsvHeader::Marker {
+                text: "Дата проводки".to_string(),
+                skip: 0,
+            },
+            delimiter: ';',
+            quote: Some('"'),
+            date_format: "%d.%m.%Y".to_string(),
+            decimal_separator: ',',
+            thousands_separator: Some(' '),
+            currency: "RUB".to_string(),
+            min_fields: 0,
+            columns: CsvColumns {
+                date: 0,
+                debit_amount: Some(4),
+                credit_amount: Some(5),
+                amount: None,
+                debit_account: Some(2),
+                credit_account: Some(3),
+                counterparty_account: None,
+                document_number: Some(1),
+                bank_info: Some(6),
+                description: Some(7),
+            },
+        }
+    }
+
+    /// ВТБ Бизнес: `Дата;Номер документа;Сумма;Счет контрагента;Банк контрагента;
+    /// Назначение платежа`, сумма со знаком.
+    pub fn vtb() -> Self {
+        Self {
+            name: "vtb".to_string(),
+            header: CsvHeader::Marker {
+                text: "Номер документа".to_string(),
+                skip: 0,
+            },
+            delimiter: ';',
+            quote: Some('"'),
+            date_format: "%d.%m.%Y".to_string(),
+            decimal_separator: ',',
+            thousands_separator: None,
+            currency: "RUB".to_string(),
+            min_fields: 0,
+            columns: CsvColumns {
+                date: 0,
+                debit_amount: None,
+                credit_amount: None,
+                amount: Some(2),
+                debit_account: None,
+                credit_account: None,
+                counterparty_account: Some(3),
+                document_number: Some(1),
+                bank_info: Some(4),
+                description: Some(5),
+            },
+        }
+    }
+
+    /// Универсальный профиль: одна строка заголовка,
+    /// `Дата,Сумма,Номер документа,Счет контрагента,Назначение`, даты ISO 8601.
+    pub fn generic() -> Self {
+        Self {
+            name: "generic".to_string(),
+            header: CsvHeader::Lines(1),
+            delimiter: ',',
+            quote: Some('"'),
+            date_format: "%Y-%m-%d".to_string(),
+            decimal_separator: '.',
+            thousands_separator: None,
+            currency: "RUB".to_string(),
+            min_fields: 0,
+            columns: CsvColumns::default(),
+        }
+    }
+
+    /// Загружает профиль из TOML.
+    #[cfg(feature = "profile-files")]
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let profile: Self = toml::from_str(content)
+            .map_err(|e| Error::Parse(format!("Некорректный TOML профиля CSV: {}", e)))?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Загружает профиль из JSON.
+    #[cfg(feature = "profile-files")]
+    pub fn from_json_str(content: &str) -> Result<Self> {
+        let profile: Self = serde_json::from_str(content)
+            .map_err(|e| Error::Parse(format!("Некорректный JSON профиля CSV: {}", e)))?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Загружает профиль из файла; формат определяется по расширению (`.toml` или `.json`).
+    #[cfg(feature = "profile-files")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json_str(&content),
+            _ => Self::from_toml_str(&content),
+        }
+    }
+
+    /// Проверяет согласованность профиля.
+    pub fn validate(&self) -> Result<()> {
+        let columns = &self.columns;
+        let has_pair = columns.debit_amount.is_some() && columns.credit_amount.is_some();
+
+        if columns.amount.is_none() && !has_pair {
+            return Err(Error::InvalidFormat(format!(
+                "Профиль CSV '{}': нужна колонка amount или пара debit_amount/credit_amount",
+                self.name
+            )));
+        }
+
+        if Some(self.delimiter) == self.quote || self.delimiter == self.decimal_separator {
+            return Err(Error::InvalidFormat(format!(
+                "Профиль CSV '{}': разделитель полей совпадает с кавычками или десятичным разделителем",
+                self.name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Возвращает индекс первой строки данных.
+    pub(crate) fn data_start(&self, lines: &[&str]) -> Result<usize> {
+        match self.header {
+            CsvHeader::Lines(count) => {
+                if lines.len() < count {
+                    return Err(Error::InvalidFormat(format!(
+                        "CSV файл слишком короткий, ожидается минимум {} строк",
+                        count
+                    )));
+                }
+                Ok(count)
+            }
+            CsvHeader::Marker { ref text, skip } => lines
+                .iter()
+                .position(|line| line.contains(text.as_str()))
+                .map(|pos| (pos + 1 + skip).min(lines.len()))
+                .ok_or_else(|| {
+                    Error::InvalidFormat(format!("Не найдена строка заголовка '{}'", text))
+                }),
+        }
+    }
+
+    /// Парсит дату по формату профиля.
+    pub fn parse_date(&self, s: &str) -> Result<Date> {
+        Date::parse_from_str(s.trim(), &self.date_format)
+    }
+
+    /// Форматирует дату по формату профиля.
+    pub fn format_date(&self, date: &Date) -> String {
+        date.format(&self.date_format)
+    }
+
+    /// Нормализует сумму к виду `-1234.56` с учетом разделителей профиля.
+    pub(crate) fn normalize_amount(&self, s: &str) -> String {
+        s.chars()
+            .filter(|c| Some(*c) != self.thousands_separator && !c.is_whitespace())
+            .map(|c| if c == self.decimal_separator { '.' } else { c })
+            .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_with_format() {
+        let profile = CsvProfile::generic();
+        assert_eq!(profile.parse_date("2024-01-15").unwrap(), Date::new(2024, 1, 15));
+        assert!(profile.parse_date("15.01.2024").is_err());
+
+        let sber = CsvProfile::sber();
+        assert_eq!(sber.parse_date("15.01.2024").unwrap(), Date::new(2024, 1, 15));
+        assert!(sber.parse_date("15.13.2024").is_err());
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = Date::new(2024, 1, 5);
+        assert_eq!(CsvProfile::generic().format_date(&date), "2024-01-05");
+        assert_eq!(CsvProfile::sber().format_date(&date), "05.01.2024");
+    }
+
+    #[test]
+    fn test_normalize_amount() {
+        let alfa = CsvProfile::alfa();
+        assert_eq!(alfa.normalize_amount("1 540,00"), "1540.00");
+        assert_eq!(alfa.normalize_amount("-12\u{a0}300,5"), "-12300.5");
+    }
+
+    #[test]
+    fn test_builtin_profiles_are_valid() {
+        for name in CsvProfile::BUILTIN {
+            CsvProfile::builtin(name).unwrap().validate().unwrap();
+        }
+    }
+}
@@ -1,66 +1,216 @@
 //! Сериализация формата CSV.
 
-use crate::csv::parser::{CsvStatement, CsvTransaction};
+use crate::csv::parser::{CsvAccountDetails, CsvStatement, CsvTransaction};
+use crate::encoding::{self, TextEncoding};
 use crate::error::Result;
+use crate::types::{AmountFormat, Date};
 use std::io::Write;
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
 
 /// Writer для формата CSV.
 pub struct CsvWriter;
 
+/// Число колонок в раскладке СберБизнес.
+const COLUMN_COUNT: usize = 24;
+
+/// Позиции колонок транзакции в раскладке СберБизнес (см. [`CsvProfile::sber`]).
+///
+/// [`CsvProfile::sber`]: crate::csv::CsvProfile::sber
+const DATE_COLUMN: usize = 1;
+const DEBIT_ACCOUNT_COLUMN: usize = 4;
+const CREDIT_ACCOUNT_COLUMN: usize = 8;
+const DEBIT_AMOUNT_COLUMN: usize = 9;
+const CREDIT_AMOUNT_COLUMN: usize = 13;
+const DOCUMENT_NUMBER_COLUMN: usize = 14;
+const OPERATION_TYPE_COLUMN: usize = 16;
+const BANK_COLUMN: usize = 17;
+const DESCRIPTION_COLUMN: usize = 20;
+
+/// Вид операции по умолчанию (платежное поручение).
+const DEFAULT_OPERATION_TYPE: &str = "01";
+
 impl CsvWriter {
     /// Записывает выписку CSV в любой приемник, реализующий трейт Write.
+    ///
+    /// Формирует полную раскладку СберБизнес: 12 строк заголовка с периодом,
+    /// банком, счетом, клиентом и остатками, строки операций и итоги.
+    /// Результат читается обратно через [`CsvStatement::parse`].
     pub fn write_to<W: Write>(statement: &CsvStatement, writer: &mut W) -> Result<()> {
-        writeln!(
-            writer,
-            "Дата,Счет дебета,Счет кредита,Сумма дебета,Сумма кредита,№ документа,Банк,Назначение платежа"
-        )?;
+        Self::write_header(writer, statement)?;
 
         for tx in &statement.transactions {
             Self::write_transaction(writer, tx)?;
         }
 
+        Self::write_footer(writer, statement)?;
+
         Ok(())
     }
 
-    fn write_transaction<W: Write>(writer: &mut W, tx: &CsvTransaction) -> Result<()> {
-        let date_str = format!("{:02}.{:02}.{}", tx.date.day, tx.date.month, tx.date.year);
+    /// Асинхронно записывает выписку CSV в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statement: &CsvStatement,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statement, buffer)).await
+    }
 
-        let debit_account = tx.debit_account.as_deref().unwrap_or("");
-        let credit_account = tx.credit_account.as_deref().unwrap_or("");
+    /// Записывает выписку CSV в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        statement: &CsvStatement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| Self::write_to(statement, buffer))
+    }
 
-        let debit_amount = tx
-            .debit_amount
-            .map(Self::format_amount)
-            .unwrap_or_default();
+    /// Асинхронно записывает выписку CSV в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statement: &CsvStatement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statement, buffer, encoding)
+        })
+        .await
+    }
+
+    fn write_header<W: Write>(writer: &mut W, statement: &CsvStatement) -> Result<()> {
+        let period = match (&statement.period_start, &statement.period_end) {
+            (Some(from), Some(to)) => {
+                format!("Период: {} - {}", Self::format_date(from), Self::format_date(to))
+            }
+            _ => String::new(),
+        };
+        let bank = match statement.bank_name {
+            Some(ref bank) => format!("Банк: {}", bank),
+            None => String::new(),
+        };
+        let balance = |title: &str, amount: Option<i64>| match amount {
+            Some(amount) => format!(
+                "{}: {} {}",
+                title,
+                Self::format_amount(amount),
+                statement.currency
+            ),
+            None => String::new(),
+        };
+
+        Self::write_cell_row(writer, "Выписка по счету")?;
+        Self::write_cell_row(writer, &period)?;
+        Self::write_cell_row(writer, "")?;
+        Self::write_cell_row(writer, &bank)?;
+        Self::write_cell_row(writer, "")?;
+        Self::write_cell_row(
+            writer,
+            &format!(
+                "ВЫПИСКА ОПЕРАЦИЙ ПО ЛИЦЕВОМУ СЧЕТУ № {}",
+                statement.account_number
+            ),
+        )?;
+        Self::write_cell_row(writer, &format!("Клиент: {}", statement.account_name))?;
+        Self::write_cell_row(writer, "")?;
+        Self::write_cell_row(writer, &balance("Входящий остаток", statement.opening_balance))?;
+        Self::write_cell_row(writer, &balance("Исходящий остаток", statement.closing_balance))?;
+
+        let mut titles = vec![String::new(); COLUMN_COUNT];
+        titles[DATE_COLUMN] = "Дата проводки".to_string();
+        titles[DEBIT_ACCOUNT_COLUMN] = "Счет".to_string();
+        titles[DEBIT_AMOUNT_COLUMN] = "Сумма по дебету".to_string();
+        titles[CREDIT_AMOUNT_COLUMN] = "Сумма по кредиту".to_string();
+        titles[DOCUMENT_NUMBER_COLUMN] = "№ документа".to_string();
+        titles[OPERATION_TYPE_COLUMN] = "ВО".to_string();
+        titles[BANK_COLUMN] = "Банк (БИК и наименование)".to_string();
+        titles[DESCRIPTION_COLUMN] = "Назначение платежа".to_string();
+        Self::write_row(writer, &titles)?;
 
-        let credit_amount = tx
+        let mut subtitles = vec![String::new(); COLUMN_COUNT];
+        subtitles[DEBIT_ACCOUNT_COLUMN] = "Дебет".to_string();
+        subtitles[CREDIT_ACCOUNT_COLUMN] = "Кредит".to_string();
+        Self::write_row(writer, &subtitles)?;
+
+        Ok(())
+    }
+
+    fn write_transaction<W: Write>(writer: &mut W, tx: &CsvTransaction) -> Result<()> {
+        let mut fields = vec![String::new(); COLUMN_COUNT];
+
+        fields[DATE_COLUMN] = Self::format_date(&tx.date);
+        fields[DEBIT_ACCOUNT_COLUMN] = Self::format_account_cell(&tx.debit_account, &tx.debit_details);
+        fields[CREDIT_ACCOUNT_COLUMN] =
+            Self::format_account_cell(&tx.credit_account, &tx.credit_details);
+        fields[DEBIT_AMOUNT_COLUMN] = tx.debit_amount.map(Self::format_amount).unwrap_or_default();
+        fields[CREDIT_AMOUNT_COLUMN] = tx
             .credit_amount
             .map(Self::format_amount)
             .unwrap_or_default();
+        fields[DOCUMENT_NUMBER_COLUMN] = tx.document_number.clone();
+        fields[OPERATION_TYPE_COLUMN] = DEFAULT_OPERATION_TYPE.to_string();
+        fields[BANK_COLUMN] = tx.bank_info.clone();
+        fields[DESCRIPTION_COLUMN] = tx.description.clone();
+
+        Self::write_row(writer, &fields)
+    }
+
+    /// Формирует многострочную ячейку `счет\nИНН\nКПП\nНаименование`.
+    fn format_account_cell(account: &Option<String>, details: &CsvAccountDetails) -> String {
+        let Some(account) = account else {
+            return String::new();
+        };
+
+        let lines: Vec<&str> = std::iter::once(account.as_str())
+            .chain(details.tax_id.as_deref())
+            .chain(details.tax_reason_code.as_deref())
+            .chain(details.name.as_deref())
+            .collect();
+        lines.join("\n")
+    }
 
-        let description = Self::escape_csv_field(&tx.description);
-        let bank_info = Self::escape_csv_field(&tx.bank_info);
+    fn write_footer<W: Write>(writer: &mut W, statement: &CsvStatement) -> Result<()> {
+        let (debit, credit) = statement.turnover();
 
-        writeln!(
+        Self::write_cell_row(writer, "")?;
+        Self::write_cell_row(
             writer,
-            "{},{},{},{},{},{},{},{}",
-            date_str,
-            debit_account,
-            credit_account,
-            debit_amount,
-            credit_amount,
-            tx.document_number,
-            bank_info,
-            description
+            &format!("Количество операций: {}", statement.transactions.len()),
+        )?;
+        Self::write_cell_row(
+            writer,
+            &format!(
+                "Итого оборотов: Дебет {} Кредит {}",
+                Self::format_amount(debit),
+                Self::format_amount(credit)
+            ),
         )?;
 
         Ok(())
     }
 
+    /// Записывает строку из одной заполненной ячейки, дополненную пустыми колонками.
+    fn write_cell_row<W: Write>(writer: &mut W, cell: &str) -> Result<()> {
+        let mut fields = vec![String::new(); COLUMN_COUNT];
+        fields[0] = cell.to_string();
+        Self::write_row(writer, &fields)
+    }
+
+    fn write_row<W: Write>(writer: &mut W, fields: &[String]) -> Result<()> {
+        let row: Vec<String> = fields.iter().map(|f| Self::escape_csv_field(f)).collect();
+        writeln!(writer, "{}", row.join(","))?;
+        Ok(())
+    }
+
+    fn format_date(date: &Date) -> String {
+        format!("{:02}.{:02}.{}", date.day(), date.month(), date.year())
+    }
+
     fn format_amount(amount: i64) -> String {
-        let whole = amount / 100;
-        let frac = (amount % 100).abs();
-        format!("{}.{:02}", whole, frac)
+        AmountFormat::PLAIN.format_value(amount)
     }
 
     fn escape_csv_field(s: &str) -> String {
@@ -77,4 +227,29 @@ impl CsvStatement {
     pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
         CsvWriter::write_to(self, writer)
     }
+
+    /// Асинхронно записывает выписку в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает выписку CSV в указанной кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        CsvWriter::write_to_with_encoding(self, writer, encoding)
+    }
+
+    /// Асинхронно записывает выписку CSV в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to_with_encoding(buffer, encoding)).await
+    }
 }
@@ -0,0 +1,937 @@
+//! Календарная дата и время выписок.
+//!
+//! [`Date`] всегда содержит существующую дату григорианского календаря
+//! в диапазоне 0001-01-01..=9999-12-31: месяц и день проверяются при
+//! создании с учетом високосных лет. Арифметика по дням выполняется через
+//! число дней от 1970-01-01 (алгоритм days_from_civil Г. Хиннанта).
+//!
+//! [`DateTime`] — момент времени ISO 8601 (`CreDtTm`, `DtTm` в CAMT.053)
+//! с необязательным смещением [`UtcOffset`].
+
+use crate::error::{Error, Result};
+
+/// Граница века для двузначных годов по умолчанию.
+///
+/// Годы `00..=50` относятся к 2000-м, `51..=99` — к 1900-м.
+pub const DEFAULT_CENTURY_PIVOT: u8 = 50;
+
+/// Дата в формате год-месяц-день.
+///
+/// Поля закрыты: дату можно получить только через проверяющие
+/// конструкторы, поэтому `Date` никогда не бывает 31 февраля.
+/// Сравнение дат хронологическое.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+/// День недели.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    /// Понедельник
+    Monday,
+    /// Вторник
+    Tuesday,
+    /// Среда
+    Wednesday,
+    /// Четверг
+    Thursday,
+    /// Пятница
+    Friday,
+    /// Суббота
+    Saturday,
+    /// Воскресенье
+    Sunday,
+}
+
+impl Weekday {
+    /// Суббота или воскресенье.
+    pub fn is_weekend(self) -> bool {
+        matches!(self, Weekday::Saturday | Weekday::Sunday)
+    }
+}
+
+impl Date {
+    /// Наименьшая представимая дата.
+    pub const MIN: Date = Date::new(1, 1, 1);
+    /// Наибольшая представимая дата.
+    pub const MAX: Date = Date::new(9999, 12, 31);
+
+    /// Создает дату из заведомо корректных значений.
+    ///
+    /// Предназначена для констант и тестов; данные из входных файлов
+    /// разбирайте через [`Date::from_ymd`].
+    ///
+    /// # Panics
+    ///
+    /// Если такой даты нет в календаре.
+    pub const fn new(year: u16, month: u8, day: u8) -> Self {
+        assert!(Self::is_valid(year, month, day), "Некорректная дата");
+        Self { year, month, day }
+    }
+
+    /// Создает дату, проверяя год, месяц и день по календарю.
+    pub fn from_ymd(year: u16, month: u8, day: u8) -> Result<Self> {
+        if !Self::is_valid(year, month, day) {
+            return Err(Error::Parse(format!(
+                "Некорректная дата: {:04}-{:02}-{:02}",
+                year, month, day
+            )));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    const fn is_valid(year: u16, month: u8, day: u8) -> bool {
+        year >= 1
+            && year <= 9999
+            && month >= 1
+            && month <= 12
+            && day >= 1
+            && day <= Self::days_in_month(year, month)
+    }
+
+    /// Год (например, 2024).
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Месяц (1-12).
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// День месяца (1-31).
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Високосный ли год.
+    pub const fn is_leap_year(year: u16) -> bool {
+        year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+    }
+
+    /// Число дней в месяце; для месяца вне 1-12 возвращает 0.
+    pub const fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            _ => 0,
+        }
+    }
+
+    /// Восстанавливает полный год по двузначному.
+    ///
+    /// Годы не больше `pivot` относятся к 2000-м, остальные — к 1900-м
+    /// (см. [`DEFAULT_CENTURY_PIVOT`]).
+    pub fn expand_year(year: u8, pivot: u8) -> u16 {
+        let year = u16::from(year % 100);
+        if year > u16::from(pivot) {
+            1900 + year
+        } else {
+            2000 + year
+        }
+    }
+
+    /// Число дней от 1970-01-01 (отрицательное для более ранних дат).
+    pub fn to_epoch_days(&self) -> i64 {
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Дата через `days` дней от 1970-01-01, если она в диапазоне `Date`.
+    pub fn from_epoch_days(days: i64) -> Option<Self> {
+        if days < Self::MIN.to_epoch_days() || days > Self::MAX.to_epoch_days() {
+            return None;
+        }
+
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Some(Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+        })
+    }
+
+    /// Сдвигает дату на `days` дней (назад при отрицательном значении).
+    ///
+    /// Возвращает `None`, если результат выходит за [`Date::MIN`]..=[`Date::MAX`].
+    pub fn checked_add_days(&self, days: i64) -> Option<Self> {
+        Self::from_epoch_days(self.to_epoch_days().checked_add(days)?)
+    }
+
+    /// Сдвигает дату на `days` дней, останавливаясь на [`Date::MIN`]
+    /// и [`Date::MAX`].
+    pub fn add_days(&self, days: i64) -> Self {
+        self.checked_add_days(days)
+            .unwrap_or(if days < 0 { Self::MIN } else { Self::MAX })
+    }
+
+    /// Число дней от `earlier` до этой даты.
+    pub fn days_since(&self, earlier: &Date) -> i64 {
+        self.to_epoch_days() - earlier.to_epoch_days()
+    }
+
+    /// День недели.
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 — четверг
+        match (self.to_epoch_days() + 3).rem_euclid(7) {
+            0 => Weekday::Monday,
+            1 => Weekday::Tuesday,
+            2 => Weekday::Wednesday,
+            3 => Weekday::Thursday,
+            4 => Weekday::Friday,
+            5 => Weekday::Saturday,
+            _ => Weekday::Sunday,
+        }
+    }
+
+    /// Рабочий ли день: не суббота, не воскресенье и не праздник из `holidays`.
+    pub fn is_business_day(&self, holidays: &[Date]) -> bool {
+        !self.weekday().is_weekend() && !holidays.contains(self)
+    }
+
+    /// Эта дата, если она рабочая, иначе ближайший следующий рабочий день.
+    pub fn roll_forward(&self, holidays: &[Date]) -> Self {
+        self.next_business_day(0, 1, holidays)
+    }
+
+    /// Сдвигает дату на `days` рабочих дней (назад при отрицательном
+    /// значении), пропуская выходные и праздники из `holidays`.
+    ///
+    /// У границ диапазона дат сдвиг останавливается на [`Date::MIN`]
+    /// или [`Date::MAX`].
+    pub fn add_business_days(&self, days: i64, holidays: &[Date]) -> Self {
+        let step = days.signum();
+        let mut date = *self;
+        for _ in 0..days.unsigned_abs() {
+            let next = date.next_business_day(step, step, holidays);
+            if next == date {
+                break;
+            }
+            date = next;
+        }
+        date
+    }
+
+    /// Первый рабочий день начиная с даты через `offset` дней, двигаясь
+    /// с шагом `step`; у границы диапазона возвращает саму границу.
+    fn next_business_day(&self, offset: i64, step: i64, holidays: &[Date]) -> Self {
+        let mut date = match self.checked_add_days(offset) {
+            Some(date) => date,
+            None => return *self,
+        };
+        while !date.is_business_day(holidays) {
+            match date.checked_add_days(step) {
+                Some(next) => date = next,
+                None => break,
+            }
+        }
+        date
+    }
+
+    /// Разбирает дату по шаблону с полями `%d`, `%m`, `%Y` и `%y`.
+    ///
+    /// Двузначный год `%y` восстанавливается по [`DEFAULT_CENTURY_PIVOT`].
+    ///
+    /// ```ignore
+    /// let date = Date::parse_from_str("15.01.2024", "%d.%m.%Y")?;
+    /// ```
+    pub fn parse_from_str(s: &str, format: &str) -> Result<Self> {
+        let invalid = || {
+            Error::Parse(format!(
+                "Некорректная дата '{}' (ожидается формат {})",
+                s, format
+            ))
+        };
+
+        let mut input = s.chars().peekable();
+        let mut spec = format.chars();
+        let (mut year, mut month, mut day) = (None, None, None);
+
+        while let Some(c) = spec.next() {
+            if c != '%' {
+                if input.next() != Some(c) {
+                    return Err(invalid());
+                }
+                continue;
+            }
+
+            let (width, field) = match spec.next() {
+                Some('d') => (2, &mut day),
+                Some('m') => (2, &mut month),
+                Some('Y') => (4, &mut year),
+                Some('y') => (2, &mut year),
+                _ => return Err(invalid()),
+            };
+
+            let digits: String = (0..width)
+                .map_while(|_| input.next_if(char::is_ascii_digit))
+                .collect();
+            if digits.len() != width {
+                return Err(invalid());
+            }
+            *field = Some(digits.parse::<u16>().map_err(|_| invalid())?);
+        }
+
+        if input.next().is_some() {
+            return Err(invalid());
+        }
+
+        let (Some(year), Some(month), Some(day)) = (year, month, day) else {
+            return Err(invalid());
+        };
+        let year = match u8::try_from(year) {
+            Ok(year) if year < 100 => Self::expand_year(year, DEFAULT_CENTURY_PIVOT),
+            _ => year,
+        };
+        let month = u8::try_from(month).map_err(|_| invalid())?;
+        let day = u8::try_from(day).map_err(|_| invalid())?;
+
+        Self::from_ymd(year, month, day).map_err(|_| invalid())
+    }
+
+    /// Форматирует дату по шаблону с полями `%d`, `%m`, `%Y` и `%y`.
+    ///
+    /// Остальные символы копируются как есть.
+    pub fn format(&self, format: &str) -> String {
+        let mut result = String::with_capacity(format.len() + 4);
+        let mut spec = format.chars();
+
+        while let Some(c) = spec.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            match spec.next() {
+                Some('d') => result.push_str(&format!("{:02}", self.day)),
+                Some('m') => result.push_str(&format!("{:02}", self.month)),
+                Some('Y') => result.push_str(&format!("{:04}", self.year)),
+                Some('y') => result.push_str(&format!("{:02}", self.year % 100)),
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                }
+                None => result.push('%'),
+            }
+        }
+
+        result
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = Error;
+
+    /// Разбирает дату в формате ISO 8601 `YYYY-MM-DD`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Parse(format!("Некорректная дата '{}', ожидается YYYY-MM-DD", s));
+
+        let mut parts = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+
+        let year: u16 = year.parse().map_err(|_| invalid())?;
+        let month: u8 = month.parse().map_err(|_| invalid())?;
+        let day: u8 = day.parse().map_err(|_| invalid())?;
+
+        Date::from_ymd(year, month, day).map_err(|_| invalid())
+    }
+}
+
+/// Смещение местного времени от UTC с точностью до минуты.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UtcOffset {
+    minutes: i16,
+}
+
+impl UtcOffset {
+    /// Нулевое смещение (UTC).
+    pub const UTC: UtcOffset = UtcOffset { minutes: 0 };
+
+    /// Создает смещение в минутах; допустимы значения меньше суток по модулю.
+    pub fn from_minutes(minutes: i16) -> Result<Self> {
+        if minutes.unsigned_abs() >= 24 * 60 {
+            return Err(Error::Parse(format!(
+                "Некорректное смещение от UTC: {} мин",
+                minutes
+            )));
+        }
+        Ok(Self { minutes })
+    }
+
+    /// Смещение в минутах (положительное к востоку от Гринвича).
+    pub const fn minutes(&self) -> i16 {
+        self.minutes
+    }
+}
+
+impl std::fmt::Display for UtcOffset {
+    /// Нулевое смещение выводится как `Z`, остальные — как `+HH:MM`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.minutes == 0 {
+            return f.write_str("Z");
+        }
+        let sign = if self.minutes < 0 { '-' } else { '+' };
+        let minutes = self.minutes.unsigned_abs();
+        write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+}
+
+impl std::str::FromStr for UtcOffset {
+    type Err = Error;
+
+    /// Разбирает `Z`, `+HH:MM` или `+HHMM`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Parse(format!("Некорректное смещение от UTC '{}'", s));
+
+        if s == "Z" {
+            return Ok(Self::UTC);
+        }
+        let (sign, rest) = match s.chars().next() {
+            Some('+') => (1, &s[1..]),
+            Some('-') => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some(parts) => parts,
+            None if rest.len() == 4 && rest.is_ascii() => rest.split_at(2),
+            None => return Err(invalid()),
+        };
+        if hours.len() != 2 || minutes.len() != 2 {
+            return Err(invalid());
+        }
+
+        let hours: i16 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i16 = minutes.parse().map_err(|_| invalid())?;
+        if hours < 0 || !(0..60).contains(&minutes) {
+            return Err(invalid());
+        }
+
+        Self::from_minutes(sign * (hours * 60 + minutes)).map_err(|_| invalid())
+    }
+}
+
+/// Дата и время ISO 8601 с необязательным смещением от UTC.
+///
+/// Время без смещения (`2024-01-31T18:00:00`) считается местным временем
+/// банка; при переводе в Unix-время оно трактуется как UTC. Сравнение
+/// через `==` поэлементное: одинаковые моменты с разными смещениями
+/// не равны, для них сравнивайте [`DateTime::unix_timestamp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    date: Date,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+    offset: Option<UtcOffset>,
+}
+
+impl DateTime {
+    /// Создает время без смещения, проверяя часы, минуты и секунды.
+    pub fn new(date: Date, hour: u8, minute: u8, second: u8) -> Result<Self> {
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(Error::Parse(format!(
+                "Некорректное время: {:02}:{:02}:{:02}",
+                hour, minute, second
+            )));
+        }
+        Ok(Self {
+            date,
+            hour,
+            minute,
+            second,
+            nanosecond: 0,
+            offset: None,
+        })
+    }
+
+    /// Начало суток `date` без смещения.
+    pub const fn midnight(date: Date) -> Self {
+        Self {
+            date,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            offset: None,
+        }
+    }
+
+    /// Задает доли секунды в наносекундах (меньше 10⁹).
+    pub fn with_nanosecond(self, nanosecond: u32) -> Result<Self> {
+        if nanosecond >= 1_000_000_000 {
+            return Err(Error::Parse(format!(
+                "Некорректная доля секунды: {} нс",
+                nanosecond
+            )));
+        }
+        Ok(Self { nanosecond, ..self })
+    }
+
+    /// Задает смещение, не меняя показания часов.
+    ///
+    /// Чтобы выразить тот же момент в другом поясе, используйте
+    /// [`DateTime::to_offset`].
+    pub fn with_offset(self, offset: Option<UtcOffset>) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Момент `secs` секунд и `nanos` наносекунд от 1970-01-01T00:00:00Z.
+    ///
+    /// Результат имеет смещение [`UtcOffset::UTC`]; `None`, если дата
+    /// выходит за диапазон [`Date`] или `nanos` не меньше 10⁹.
+    pub fn from_unix_timestamp(secs: i64, nanos: u32) -> Option<Self> {
+        Self::from_local_seconds(secs, nanos, UtcOffset::UTC)
+    }
+
+    fn from_local_seconds(secs: i64, nanos: u32, offset: UtcOffset) -> Option<Self> {
+        if nanos >= 1_000_000_000 {
+            return None;
+        }
+        let date = Date::from_epoch_days(secs.div_euclid(86_400))?;
+        let seconds = secs.rem_euclid(86_400);
+
+        Some(Self {
+            date,
+            hour: (seconds / 3600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+            nanosecond: nanos,
+            offset: Some(offset),
+        })
+    }
+
+    /// Дата по местному времени.
+    pub const fn date(&self) -> Date {
+        self.date
+    }
+
+    /// Час (0-23).
+    pub const fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Минута (0-59).
+    pub const fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Секунда (0-59).
+    pub const fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Доли секунды в наносекундах.
+    pub const fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    /// Смещение от UTC, если оно указано.
+    pub const fn offset(&self) -> Option<UtcOffset> {
+        self.offset
+    }
+
+    /// Секунды от 1970-01-01T00:00:00Z (время без смещения считается UTC).
+    pub fn unix_timestamp(&self) -> i64 {
+        let offset = self.offset.map_or(0, |o| i64::from(o.minutes));
+        self.local_seconds() - offset * 60
+    }
+
+    fn local_seconds(&self) -> i64 {
+        self.date.to_epoch_days() * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    /// Тот же момент времени в поясе `offset`.
+    ///
+    /// `None`, если местная дата в новом поясе выходит за диапазон [`Date`].
+    pub fn to_offset(&self, offset: UtcOffset) -> Option<Self> {
+        let local = self.unix_timestamp() + i64::from(offset.minutes) * 60;
+        Self::from_local_seconds(local, self.nanosecond, offset)
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    /// Выводит `YYYY-MM-DDTHH:MM:SS`, затем доли секунды (3, 6 или 9 цифр),
+    /// если они есть, и смещение, если оно указано.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}T{:02}:{:02}:{:02}",
+            self.date, self.hour, self.minute, self.second
+        )?;
+        match self.nanosecond {
+            0 => {}
+            ns if ns % 1_000_000 == 0 => write!(f, ".{:03}", ns / 1_000_000)?,
+            ns if ns % 1000 == 0 => write!(f, ".{:06}", ns / 1000)?,
+            ns => write!(f, ".{:09}", ns)?,
+        }
+        if let Some(offset) = self.offset {
+            write!(f, "{}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for DateTime {
+    type Err = Error;
+
+    /// Разбирает `YYYY-MM-DDTHH:MM:SS` с необязательными долями секунды
+    /// (до 9 цифр) и смещением `Z`, `+HH:MM` или `+HHMM`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::Parse(format!(
+                "Некорректные дата и время '{}', ожидается YYYY-MM-DDTHH:MM:SS",
+                s
+            ))
+        };
+
+        let (date, time) = s.split_once('T').ok_or_else(invalid)?;
+        let date: Date = date.parse().map_err(|_| invalid())?;
+
+        let (time, offset) = match time.find(['Z', '+', '-']) {
+            Some(pos) => {
+                let offset: UtcOffset = time[pos..].parse().map_err(|_| invalid())?;
+                (&time[..pos], Some(offset))
+            }
+            None => (time, None),
+        };
+        let (time, fraction) = match time.split_once('.') {
+            Some((time, fraction)) => (time, Some(fraction)),
+            None => (time, None),
+        };
+
+        let field = |part: Option<&str>| -> Option<u8> {
+            let part = part?;
+            if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            part.parse().ok()
+        };
+        let mut parts = time.split(':');
+        let (Some(hour), Some(minute), Some(second), None) = (
+            field(parts.next()),
+            field(parts.next()),
+            field(parts.next()),
+            parts.next(),
+        ) else {
+            return Err(invalid());
+        };
+
+        let nanosecond = match fraction {
+            None => 0,
+            Some(f) if (1..=9).contains(&f.len()) && f.bytes().all(|b| b.is_ascii_digit()) => {
+                let value: u32 = f.parse().map_err(|_| invalid())?;
+                value * 10u32.pow(9 - f.len() as u32)
+            }
+            Some(_) => return Err(invalid()),
+        };
+
+        DateTime::new(date, hour, minute, second)
+            .and_then(|dt| dt.with_nanosecond(nanosecond))
+            .map(|dt| dt.with_offset(offset))
+            .map_err(|_| invalid())
+    }
+}
+
+/// В JSON дата представлена строкой `YYYY-MM-DD`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Date {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Date {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// В JSON дата и время представлены строкой ISO 8601.
+#[cfg(feature = "serde")]
+impl serde::Serialize for DateTime {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DateTime {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Date> for chrono::NaiveDate {
+    fn from(date: Date) -> Self {
+        chrono::NaiveDate::from_ymd_opt(
+            i32::from(date.year),
+            u32::from(date.month),
+            u32::from(date.day),
+        )
+        .expect("Date всегда содержит существующую дату")
+    }
+}
+
+/// Даты chrono вне диапазона 0001..=9999 годов не преобразуются.
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDate> for Date {
+    type Error = Error;
+
+    fn try_from(date: chrono::NaiveDate) -> Result<Self> {
+        use chrono::Datelike;
+
+        let year = u16::try_from(date.year())
+            .map_err(|_| Error::Parse(format!("Год вне диапазона: {}", date)))?;
+        Date::from_ymd(year, date.month() as u8, date.day() as u8)
+    }
+}
+
+/// Местное время без смещения.
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::NaiveDateTime {
+    fn from(dt: DateTime) -> Self {
+        chrono::NaiveDate::from(dt.date)
+            .and_hms_nano_opt(
+                u32::from(dt.hour),
+                u32::from(dt.minute),
+                u32::from(dt.second),
+                dt.nanosecond,
+            )
+            .expect("DateTime всегда содержит корректное время")
+    }
+}
+
+/// Время chrono с секундой координации (`:60`) не преобразуется.
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::FixedOffset>> for DateTime {
+    type Error = Error;
+
+    fn try_from(dt: chrono::DateTime<chrono::FixedOffset>) -> Result<Self> {
+        use chrono::Timelike;
+
+        let local = dt.naive_local();
+        let offset = UtcOffset::from_minutes((dt.offset().local_minus_utc() / 60) as i16)?;
+        DateTime::new(
+            Date::try_from(local.date())?,
+            local.hour() as u8,
+            local.minute() as u8,
+            local.second() as u8,
+        )?
+        .with_nanosecond(local.nanosecond())
+        .map(|dt| dt.with_offset(Some(offset)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation() {
+        assert!(Date::from_ymd(2024, 2, 29).is_ok());
+        assert!(Date::from_ymd(2023, 2, 29).is_err());
+        assert!(Date::from_ymd(2024, 2, 31).is_err());
+        assert!(Date::from_ymd(2100, 2, 29).is_err());
+        assert!(Date::from_ymd(2000, 2, 29).is_ok());
+        assert!(Date::from_ymd(2024, 13, 1).is_err());
+        assert!(Date::from_ymd(0, 1, 1).is_err());
+        assert!(matches!("2024-04-31".parse::<Date>(), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Некорректная дата")]
+    fn test_new_panics_on_invalid() {
+        let _ = Date::new(2024, 2, 31);
+    }
+
+    #[test]
+    fn test_epoch_days() {
+        assert_eq!(Date::new(1970, 1, 1).to_epoch_days(), 0);
+        assert_eq!(Date::new(2000, 3, 1).to_epoch_days(), 11_017);
+        assert_eq!(Date::new(2024, 2, 29).to_epoch_days(), 19_782);
+        assert_eq!(Date::new(1969, 12, 31).to_epoch_days(), -1);
+        for date in [Date::MIN, Date::new(1600, 2, 29), Date::new(2024, 12, 31), Date::MAX] {
+            assert_eq!(Date::from_epoch_days(date.to_epoch_days()), Some(date));
+        }
+        assert_eq!(Date::from_epoch_days(Date::MAX.to_epoch_days() + 1), None);
+    }
+
+    #[test]
+    fn test_add_days() {
+        assert_eq!(Date::new(2024, 1, 1).add_days(-1), Date::new(2023, 12, 31));
+        assert_eq!(Date::new(2024, 2, 28).add_days(1), Date::new(2024, 2, 29));
+        assert_eq!(Date::new(2023, 2, 28).add_days(1), Date::new(2023, 3, 1));
+        assert_eq!(Date::new(2024, 12, 31).add_days(1), Date::new(2025, 1, 1));
+        assert_eq!(Date::MAX.checked_add_days(1), None);
+        assert_eq!(Date::MIN.add_days(-1), Date::MIN);
+        assert_eq!(Date::new(2024, 3, 1).days_since(&Date::new(2024, 2, 1)), 29);
+    }
+
+    #[test]
+    fn test_weekday_and_business_days() {
+        assert_eq!(Date::new(1970, 1, 1).weekday(), Weekday::Thursday);
+        assert_eq!(Date::new(2024, 1, 15).weekday(), Weekday::Monday);
+        assert_eq!(Date::new(1969, 12, 28).weekday(), Weekday::Sunday);
+
+        let friday = Date::new(2024, 5, 10);
+        let holidays = [Date::new(2024, 5, 13)];
+        assert!(friday.is_business_day(&[]));
+        assert!(!Date::new(2024, 5, 11).is_business_day(&[]));
+        assert_eq!(Date::new(2024, 5, 11).roll_forward(&holidays), Date::new(2024, 5, 14));
+        assert_eq!(friday.add_business_days(1, &[]), Date::new(2024, 5, 13));
+        assert_eq!(friday.add_business_days(1, &holidays), Date::new(2024, 5, 14));
+        assert_eq!(Date::new(2024, 5, 14).add_business_days(-1, &holidays), friday);
+        assert_eq!(friday.add_business_days(0, &holidays), friday);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut dates = vec![
+            Date::new(2024, 2, 1),
+            Date::new(2023, 12, 31),
+            Date::new(2024, 1, 15),
+        ];
+        dates.sort();
+        assert_eq!(
+            dates,
+            vec![Date::new(2023, 12, 31), Date::new(2024, 1, 15), Date::new(2024, 2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_expand_year() {
+        assert_eq!(Date::expand_year(24, DEFAULT_CENTURY_PIVOT), 2024);
+        assert_eq!(Date::expand_year(50, DEFAULT_CENTURY_PIVOT), 2050);
+        assert_eq!(Date::expand_year(51, DEFAULT_CENTURY_PIVOT), 1951);
+        assert_eq!(Date::expand_year(51, 79), 2051);
+        assert_eq!(Date::expand_year(0, 0), 2000);
+        assert_eq!(Date::expand_year(1, 0), 1901);
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        let date = Date::new(2024, 1, 5);
+        assert_eq!(Date::parse_from_str("05.01.2024", "%d.%m.%Y").unwrap(), date);
+        assert_eq!(Date::parse_from_str("240105", "%y%m%d").unwrap(), date);
+        assert!(Date::parse_from_str("30.02.2024", "%d.%m.%Y").is_err());
+        assert!(Date::parse_from_str("05.01.2024x", "%d.%m.%Y").is_err());
+        assert_eq!(date.format("%d/%m/%y"), "05/01/24");
+        assert_eq!(date.format("%Y-%m-%d"), date.to_string());
+    }
+
+    #[test]
+    fn test_date_time_parse_and_display() {
+        let dt: DateTime = "2024-01-31T18:30:05.250+03:00".parse().unwrap();
+        assert_eq!(dt.date(), Date::new(2024, 1, 31));
+        assert_eq!((dt.hour(), dt.minute(), dt.second()), (18, 30, 5));
+        assert_eq!(dt.nanosecond(), 250_000_000);
+        assert_eq!(dt.offset().map(|o| o.minutes()), Some(180));
+        assert_eq!(dt.to_string(), "2024-01-31T18:30:05.250+03:00");
+
+        let local: DateTime = "2024-01-31T18:30:05".parse().unwrap();
+        assert_eq!(local.offset(), None);
+        assert_eq!(local.to_string(), "2024-01-31T18:30:05");
+        for (input, expected) in [
+            ("2024-01-31T18:30:05+0000", "2024-01-31T18:30:05Z"),
+            ("2024-01-31T18:30:05-05:30", "2024-01-31T18:30:05-05:30"),
+            ("2024-01-31T18:30:05.000001Z", "2024-01-31T18:30:05.000001Z"),
+        ] {
+            assert_eq!(input.parse::<DateTime>().unwrap().to_string(), expected);
+        }
+
+        for invalid in [
+            "2024-01-31",
+            "2024-01-31T24:00:00",
+            "2024-01-31T18:30",
+            "2024-01-31T18:30:05.",
+            "2024-01-31T18:30:05.1234567890",
+            "2024-02-30T00:00:00",
+            "2024-01-31T18:30:05+24:00",
+            "2024-01-31T18:30:05+3",
+            "2024-01-31T18:30:05Zx",
+        ] {
+            assert!(matches!(invalid.parse::<DateTime>(), Err(Error::Parse(_))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_date_time_unix_timestamp() {
+        let epoch = DateTime::from_unix_timestamp(0, 0).unwrap();
+        assert_eq!(epoch.to_string(), "1970-01-01T00:00:00Z");
+
+        let dt: DateTime = "2024-01-01T02:00:00+03:00".parse().unwrap();
+        assert_eq!(dt.unix_timestamp(), 1_704_063_600);
+        let utc = dt.to_offset(UtcOffset::UTC).unwrap();
+        assert_eq!(utc.to_string(), "2023-12-31T23:00:00Z");
+        assert_eq!(utc.unix_timestamp(), dt.unix_timestamp());
+        assert_eq!(DateTime::from_unix_timestamp(1_704_063_600, 0), Some(utc));
+        assert_eq!(DateTime::from_unix_timestamp(-1, 0).unwrap().date(), Date::new(1969, 12, 31));
+        assert_eq!(DateTime::from_unix_timestamp(0, 1_000_000_000), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_roundtrip() {
+        let date = Date::new(2024, 2, 29);
+        let naive = chrono::NaiveDate::from(date);
+        assert_eq!(naive, chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert_eq!(Date::try_from(naive).unwrap(), date);
+        assert!(Date::try_from(chrono::NaiveDate::from_ymd_opt(10_000, 1, 1).unwrap()).is_err());
+
+        let dt: DateTime = "2024-01-31T18:30:05.5+03:00".parse().unwrap();
+        let fixed = chrono::DateTime::parse_from_rfc3339("2024-01-31T18:30:05.5+03:00").unwrap();
+        assert_eq!(DateTime::try_from(fixed).unwrap(), dt);
+        assert_eq!(chrono::NaiveDateTime::from(dt), fixed.naive_local());
+    }
+}
@@ -0,0 +1,439 @@
+//! Модуль определения и преобразования кодировок входных и выходных файлов.
+//!
+//! Российские банки и 1С часто выгружают файлы в Windows-1251 или CP866,
+//! поэтому все парсеры читают байты и декодируют их через [`decode`].
+
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use encoding_rs::{Encoding, IBM866, UTF_8, WINDOWS_1251};
+use std::io::{BufRead, Read, Write};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Метка порядка байтов UTF-8.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Поддерживаемые текстовые кодировки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    /// UTF-8
+    #[default]
+    Utf8,
+    /// Windows-1251 (кириллица Windows)
+    Windows1251,
+    /// CP866 (кириллица DOS)
+    Cp866,
+}
+
+impl TextEncoding {
+    /// Имя кодировки для XML-декларации и сообщений.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Windows1251 => "windows-1251",
+            TextEncoding::Cp866 => "cp866",
+        }
+    }
+
+    fn encoding(&self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 => UTF_8,
+            TextEncoding::Windows1251 => WINDOWS_1251,
+            TextEncoding::Cp866 => IBM866,
+        }
+    }
+
+    /// Определяет кодировку по содержимому.
+    ///
+    /// Порядок: BOM, декларация `<?xml encoding=...?>`, поле `CHARSET`
+    /// заголовка OFX 1.x, корректный UTF-8, затем эвристика выбора
+    /// между Windows-1251 и CP866.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(UTF8_BOM) {
+            return TextEncoding::Utf8;
+        }
+
+        if let Some(encoding) = Self::detect_xml_declaration(bytes) {
+            return encoding;
+        }
+
+        if let Some(encoding) = Self::detect_ofx_header(bytes) {
+            return encoding;
+        }
+
+        if std::str::from_utf8(bytes).is_ok() {
+            return TextEncoding::Utf8;
+        }
+
+        Self::detect_cyrillic(bytes)
+    }
+
+    /// Извлекает кодировку из XML-декларации в начале файла.
+    fn detect_xml_declaration(bytes: &[u8]) -> Option<Self> {
+        let head = &bytes[..bytes.len().min(200)];
+        if !head.starts_with(b"<?xml") {
+            return None;
+        }
+
+        let end = head.windows(2).position(|w| w == b"?>")?;
+        let declaration = std::str::from_utf8(&head[..end]).ok()?;
+        let pos = declaration.find("encoding")?;
+        let rest = declaration[pos + "encoding".len()..].trim_start();
+        let rest = rest.strip_prefix('=')?.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = rest[1..].split(quote).next()?;
+
+        value.parse().ok()
+    }
+
+    /// Извлекает кодировку из поля `CHARSET` заголовка OFX 1.x.
+    fn detect_ofx_header(bytes: &[u8]) -> Option<Self> {
+        let head = &bytes[..bytes.len().min(400)];
+        if !head.starts_with(b"OFXHEADER:") {
+            return None;
+        }
+
+        let pos = head.windows(8).position(|w| w == b"CHARSET:")? + "CHARSET:".len();
+        let value: String = head[pos..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || **b == b'-')
+            .map(|b| *b as char)
+            .collect();
+
+        // NONE и прочие значения оставляем на общее определение
+        value.parse().ok()
+    }
+
+    /// Выбирает однобайтовую кириллическую кодировку по числу букв,
+    /// которые дает декодирование в каждой из кандидатов.
+    fn detect_cyrillic(bytes: &[u8]) -> Self {
+        let score = |encoding: &'static Encoding| {
+            let (text, _) = encoding.decode_without_bom_handling(bytes);
+            text.chars()
+                .filter(|c| matches!(c, 'а'..='я' | 'А'..='Я' | 'ё' | 'Ё'))
+                .count()
+        };
+
+        if score(IBM866) > score(WINDOWS_1251) {
+            TextEncoding::Cp866
+        } else {
+            TextEncoding::Windows1251
+        }
+    }
+}
+
+impl std::str::FromStr for TextEncoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(TextEncoding::Utf8),
+            "windows-1251" | "cp1251" | "1251" | "win1251" | "windows" => {
+                Ok(TextEncoding::Windows1251)
+            }
+            "cp866" | "866" | "ibm866" | "dos" => Ok(TextEncoding::Cp866),
+            other => Err(Error::InvalidFormat(format!(
+                "Неподдерживаемая кодировка: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Декодирует байты в строку.
+///
+/// Если кодировка не указана, она определяется через [`TextEncoding::detect`].
+/// BOM UTF-8 удаляется.
+pub fn decode(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<String> {
+    let encoding = encoding.unwrap_or_else(|| TextEncoding::detect(bytes));
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+
+    match encoding {
+        TextEncoding::Utf8 => String::from_utf8(bytes.to_vec()).map_err(|e| {
+            Error::Parse(format!(
+                "Некорректная последовательность UTF-8 в позиции {}",
+                e.utf8_error().valid_up_to()
+            ))
+        }),
+        // Однобайтовые кодировки декодируют любую последовательность байтов
+        _ => Ok(encoding
+            .encoding()
+            .decode_without_bom_handling(bytes)
+            .0
+            .into_owned()),
+    }
+}
+
+/// Читает все данные из источника и декодирует их в строку.
+///
+/// Читается не больше [`Limits::default`]`.max_input_bytes` байтов,
+/// более длинный вход дает [`Error::LimitExceeded`].
+pub fn read_to_string<R: Read>(reader: &mut R, encoding: Option<TextEncoding>) -> Result<String> {
+    decode(&read_limited(reader, &Limits::default())?, encoding)
+}
+
+/// Читает источник целиком, но не больше `limits.max_input_bytes` байтов.
+pub(crate) fn read_limited<R: Read>(reader: &mut R, limits: &Limits) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(read_bound(limits)).read_to_end(&mut bytes)?;
+    limits.check_input(bytes.len())?;
+    Ok(bytes)
+}
+
+/// Сколько байтов читать, чтобы заметить превышение `max_input_bytes`.
+fn read_bound(limits: &Limits) -> u64 {
+    u64::try_from(limits.max_input_bytes).unwrap_or(u64::MAX).saturating_add(1)
+}
+
+/// Построчное чтение текста из источника с декодированием.
+///
+/// Держит в памяти только текущую строку, поэтому подходит для файлов
+/// любого размера. Если кодировка не указана, она определяется по первому
+/// заполнению буфера источника (для `BufReader` — до 8 КиБ).
+pub struct TextReader<R> {
+    reader: R,
+    encoding: Option<TextEncoding>,
+    raw: Vec<u8>,
+    line: usize,
+}
+
+impl<R: BufRead> TextReader<R> {
+    /// Создает читатель; `None` включает автоматическое определение кодировки.
+    pub fn new(reader: R, encoding: Option<TextEncoding>) -> Self {
+        Self {
+            reader,
+            encoding,
+            raw: Vec::new(),
+            line: 0,
+        }
+    }
+
+    /// Кодировка текста (после чтения первой строки — определенная).
+    pub fn encoding(&self) -> Option<TextEncoding> {
+        self.encoding
+    }
+
+    /// Номер последней прочитанной строки (с единицы).
+    pub fn line_number(&self) -> usize {
+        self.line
+    }
+
+    /// Читает строку вместе с переводом строки и добавляет ее к `buf`.
+    ///
+    /// Возвращает число прочитанных байтов; `0` означает конец данных.
+    pub fn read_line(&mut self, buf: &mut String) -> Result<usize> {
+        if self.encoding.is_none() {
+            self.encoding = Some(detect_prefix(self.reader.fill_buf()?));
+        }
+
+        self.raw.clear();
+        let read = self.reader.read_until(b'\n', &mut self.raw)?;
+        if read == 0 {
+            return Ok(0);
+        }
+        self.line += 1;
+
+        let mut bytes = self.raw.as_slice();
+        if self.line == 1 {
+            bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+        }
+        match self.encoding.unwrap_or_default() {
+            TextEncoding::Utf8 => buf.push_str(std::str::from_utf8(bytes).map_err(|e| {
+                Error::Parse(format!(
+                    "Некорректная последовательность UTF-8 в строке {}, позиция {}",
+                    self.line,
+                    e.valid_up_to()
+                ))
+            })?),
+            encoding => buf.push_str(&encoding.encoding().decode_without_bom_handling(bytes).0),
+        }
+
+        Ok(read)
+    }
+}
+
+/// Определяет кодировку по началу данных, которое может обрываться
+/// посреди многобайтового символа UTF-8.
+fn detect_prefix(bytes: &[u8]) -> TextEncoding {
+    match std::str::from_utf8(bytes) {
+        Err(e) if e.error_len().is_none() => TextEncoding::detect(&bytes[..e.valid_up_to()]),
+        _ => TextEncoding::detect(bytes),
+    }
+}
+
+/// Кодирует строку в указанную кодировку.
+///
+/// Возвращает ошибку, если строка содержит символы, не представимые
+/// в целевой кодировке.
+pub fn encode(text: &str, encoding: TextEncoding) -> Result<Vec<u8>> {
+    if encoding == TextEncoding::Utf8 {
+        return Ok(text.as_bytes().to_vec());
+    }
+
+    let (bytes, _, had_errors) = encoding.encoding().encode(text);
+    if had_errors {
+        let symbol = text
+            .chars()
+            .find(|c| encoding.encoding().encode(c.encode_utf8(&mut [0; 4])).2)
+            .unwrap_or('?');
+        return Err(Error::InvalidFormat(format!(
+            "Символ '{}' не представим в кодировке {}",
+            symbol, encoding
+        )));
+    }
+
+    Ok(bytes.into_owned())
+}
+
+/// Асинхронно читает источник целиком, но не больше
+/// [`Limits::default`]`.max_input_bytes` байтов.
+///
+/// Разбор затем выполняет тот же парсер, что и для `Read`, в текущей задаче,
+/// без блокирующих потоков.
+#[cfg(feature = "async")]
+pub(crate) async fn read_async<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>> {
+    let limits = Limits::default();
+    let mut bytes = Vec::new();
+    reader.take(read_bound(&limits)).read_to_end(&mut bytes).await?;
+    limits.check_input(bytes.len())?;
+    Ok(bytes)
+}
+
+/// Асинхронно читает источник целиком и декодирует его в строку.
+///
+/// Если кодировка не указана, она определяется автоматически.
+#[cfg(feature = "async")]
+pub async fn read_to_string_async<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    encoding: Option<TextEncoding>,
+) -> Result<String> {
+    decode(&read_async(reader).await?, encoding)
+}
+
+/// Формирует вывод синхронным writer в памяти и асинхронно записывает его
+/// в приемник.
+#[cfg(feature = "async")]
+pub(crate) async fn write_async<W, F>(writer: &mut W, write: F) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    F: FnOnce(&mut Vec<u8>) -> Result<()>,
+{
+    let mut buffer = Vec::new();
+    write(&mut buffer)?;
+    writer.write_all(&buffer).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+/// Формирует вывод в UTF-8 и записывает его в приемник в нужной кодировке.
+pub(crate) fn write_encoded<W, F>(writer: &mut W, encoding: TextEncoding, write: F) -> Result<()>
+where
+    W: Write,
+    F: FnOnce(&mut Vec<u8>) -> Result<()>,
+{
+    let mut buffer = Vec::new();
+    write(&mut buffer)?;
+
+    let text = String::from_utf8(buffer)
+        .map_err(|_| Error::InvalidFormat("Writer сформировал не UTF-8 данные".to_string()))?;
+    writer.write_all(&encode(&text, encoding)?)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "Оплата по договору поставки, НДС не облагается";
+
+    #[test]
+    fn test_read_limited() {
+        let limits = Limits {
+            max_input_bytes: 8,
+            ..Limits::default()
+        };
+        assert_eq!(read_limited(&mut &b"12345678"[..], &limits).unwrap().len(), 8);
+        assert!(matches!(
+            read_limited(&mut &b"123456789"[..], &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn test_detect_utf8_and_bom() {
+        assert_eq!(TextEncoding::detect(SAMPLE.as_bytes()), TextEncoding::Utf8);
+
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice(SAMPLE.as_bytes());
+        assert_eq!(TextEncoding::detect(&bytes), TextEncoding::Utf8);
+        assert_eq!(decode(&bytes, None).unwrap(), SAMPLE);
+    }
+
+    #[test]
+    fn test_detect_single_byte_cyrillic() {
+        let cp1251 = encode(SAMPLE, TextEncoding::Windows1251).unwrap();
+        assert_eq!(TextEncoding::detect(&cp1251), TextEncoding::Windows1251);
+        assert_eq!(decode(&cp1251, None).unwrap(), SAMPLE);
+
+        let cp866 = encode(SAMPLE, TextEncoding::Cp866).unwrap();
+        assert_eq!(TextEncoding::detect(&cp866), TextEncoding::Cp866);
+        assert_eq!(decode(&cp866, None).unwrap(), SAMPLE);
+    }
+
+    #[test]
+    fn test_detect_xml_declaration() {
+        let xml = b"<?xml version=\"1.0\" encoding='windows-1251'?><Document/>";
+        assert_eq!(TextEncoding::detect(xml), TextEncoding::Windows1251);
+    }
+
+    #[test]
+    fn test_detect_ofx_charset() {
+        let mut ofx = b"OFXHEADER:100\nDATA:OFXSGML\nCHARSET:866\n\n<OFX>".to_vec();
+        ofx.extend(encode("Оплата", TextEncoding::Cp866).unwrap());
+        assert_eq!(TextEncoding::detect(&ofx), TextEncoding::Cp866);
+    }
+
+    #[test]
+    fn test_text_reader_lines() {
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice("Первая\r\nВторая".as_bytes());
+        let mut reader = TextReader::new(bytes.as_slice(), None);
+
+        let mut line = String::new();
+        assert!(reader.read_line(&mut line).unwrap() > 0);
+        assert_eq!(line, "Первая\r\n");
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "Вторая");
+        assert_eq!(reader.read_line(&mut line).unwrap(), 0);
+        assert_eq!(reader.line_number(), 2);
+
+        let cp866 = encode("Оплата\nпо договору\n", TextEncoding::Cp866).unwrap();
+        let mut reader = TextReader::new(cp866.as_slice(), None);
+        let mut text = String::new();
+        while reader.read_line(&mut text).unwrap() > 0 {}
+        assert_eq!(text, "Оплата\nпо договору\n");
+        assert_eq!(reader.encoding(), Some(TextEncoding::Cp866));
+    }
+
+    #[test]
+    fn test_detect_prefix_cut_utf8() {
+        let bytes = "Оплата".as_bytes();
+        assert_eq!(detect_prefix(&bytes[..3]), TextEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_encode_unmappable() {
+        assert!(encode("€ 100", TextEncoding::Cp866).is_err());
+        assert!("koi8-r".parse::<TextEncoding>().is_err());
+    }
+}
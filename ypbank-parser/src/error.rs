@@ -20,6 +20,18 @@ pub enum Error {
     /// Отсутствует обязательное поле
     #[error("Отсутствует обязательное поле: {0}")]
     MissingField(String),
+
+    /// Ошибка базы данных архива
+    #[error("Ошибка базы данных: {0}")]
+    Database(String),
+
+    /// Превышено ограничение на размер или сложность входа
+    #[error("Превышен лимит: {0}")]
+    LimitExceeded(String),
+
+    /// Недопустимая операция над суммами (разные валюты, переполнение)
+    #[error("Ошибка денежной арифметики: {0}")]
+    Arithmetic(String),
 }
 
 /// Тип Result с ошибкой библиотеки.
@@ -0,0 +1,187 @@
+//! Сопоставление счетов выписки и контрагентов со счетами бухгалтерской книги.
+
+use crate::error::{Error, Result};
+use crate::types::Transaction;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// Правило выбора счета книги для второй стороны проводки.
+///
+/// Правило срабатывает, если совпадают все заданные в нем условия.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "profile-files",
+    derive(serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct MappingRule {
+    /// Точный номер счета контрагента.
+    #[cfg_attr(feature = "profile-files", serde(default))]
+    pub counterparty_account: Option<String>,
+    /// Регулярное выражение для назначения платежа.
+    #[cfg_attr(
+        feature = "profile-files",
+        serde(default, deserialize_with = "deserialize_regex")
+    )]
+    pub description: Option<Regex>,
+    /// Счет книги, например `Expenses:Rent`.
+    pub account: String,
+}
+
+impl MappingRule {
+    /// Правило по номеру счета контрагента.
+    pub fn counterparty_account(number: impl Into<String>, account: impl Into<String>) -> Self {
+        MappingRule {
+            counterparty_account: Some(number.into()),
+            description: None,
+            account: account.into(),
+        }
+    }
+
+    /// Правило по регулярному выражению для назначения платежа.
+    pub fn description(pattern: &str, account: impl Into<String>) -> Result<Self> {
+        Ok(MappingRule {
+            counterparty_account: None,
+            description: Some(Self::compile(pattern)?),
+            account: account.into(),
+        })
+    }
+
+    /// Проверяет, подходит ли правило для транзакции.
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        let account_matches = self.counterparty_account.as_ref().is_none_or(|number| {
+            tx.counterparty
+                .as_ref()
+                .and_then(|c| c.account.as_ref())
+                .is_some_and(|account| account == number)
+        });
+        let description_matches = self
+            .description
+            .as_ref()
+            .is_none_or(|regex| regex.is_match(&tx.description));
+
+        account_matches && description_matches
+    }
+
+    fn compile(pattern: &str) -> Result<Regex> {
+        Regex::new(pattern).map_err(|e| {
+            Error::InvalidFormat(format!("Некорректное регулярное выражение '{}': {}", pattern, e))
+        })
+    }
+}
+
+#[cfg(feature = "profile-files")]
+fn deserialize_regex<'de, D>(deserializer: D) -> std::result::Result<Option<Regex>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    Option::<String>::deserialize(deserializer)?
+        .map(|pattern| MappingRule::compile(&pattern).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Настройка счетов для экспорта в Beancount и Ledger.
+///
+/// Собственный счет выписки ищется в `accounts` по номеру или IBAN,
+/// счет второй стороны — по первому подходящему правилу из `rules`,
+/// иначе используются счета доходов и расходов по умолчанию.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "profile-files",
+    derive(serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct AccountMapping {
+    /// Счет книги для выписок, не найденных в `accounts`.
+    pub default_account: String,
+    /// Счет для поступлений без подходящего правила.
+    pub default_income: String,
+    /// Счет для списаний без подходящего правила.
+    pub default_expense: String,
+    /// Счет капитала для входящих остатков.
+    pub opening_balances: String,
+    /// Номер счета или IBAN выписки -> счет книги.
+    pub accounts: BTreeMap<String, String>,
+    /// Правила для второй стороны проводки.
+    pub rules: Vec<MappingRule>,
+}
+
+impl Default for AccountMapping {
+    fn default() -> Self {
+        AccountMapping {
+            default_account: "Assets:Bank".to_string(),
+            default_income: "Income:Uncategorized".to_string(),
+            default_expense: "Expenses:Uncategorized".to_string(),
+            opening_balances: "Equity:Opening-Balances".to_string(),
+            accounts: BTreeMap::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl AccountMapping {
+    /// Загружает настройку из TOML.
+    #[cfg(feature = "profile-files")]
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let mapping: Self = toml::from_str(content)
+            .map_err(|e| Error::Parse(format!("Некорректный TOML настройки счетов: {}", e)))?;
+        mapping.validate()?;
+        Ok(mapping)
+    }
+
+    /// Загружает настройку из JSON.
+    #[cfg(feature = "profile-files")]
+    pub fn from_json_str(content: &str) -> Result<Self> {
+        let mapping: Self = serde_json::from_str(content)
+            .map_err(|e| Error::Parse(format!("Некорректный JSON настройки счетов: {}", e)))?;
+        mapping.validate()?;
+        Ok(mapping)
+    }
+
+    /// Загружает настройку из файла; формат определяется по расширению (`.toml` или `.json`).
+    #[cfg(feature = "profile-files")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::from_json_str(&content),
+            _ => Self::from_toml_str(&content),
+        }
+    }
+
+    /// Проверяет, что у каждого правила есть хотя бы одно условие.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|r| r.counterparty_account.is_none() && r.description.is_none())
+        {
+            return Err(Error::InvalidFormat(format!(
+                "Правило для счета '{}' не содержит условий counterparty_account или description",
+                rule.account
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Счет книги для собственного счета выписки.
+    pub fn own_account(&self, number: &str, iban: Option<&str>) -> &str {
+        self.accounts
+            .get(number)
+            .or_else(|| iban.and_then(|iban| self.accounts.get(iban)))
+            .unwrap_or(&self.default_account)
+    }
+
+    /// Счет книги для второй стороны проводки.
+    pub fn counter_account(&self, tx: &Transaction) -> &str {
+        match self.rules.iter().find(|rule| rule.matches(tx)) {
+            Some(rule) => &rule.account,
+            None if tx.is_credit => &self.default_income,
+            None => &self.default_expense,
+        }
+    }
+}
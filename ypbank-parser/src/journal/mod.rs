@@ -0,0 +1,12 @@
+//! Модуль экспорта выписок в журналы учета в простом тексте:
+//! Beancount и Ledger (совместим с hledger).
+//!
+//! Счета книги для собственного счета выписки и второй стороны проводки
+//! задаются настройкой [`AccountMapping`]; остатки выписки записываются
+//! как утверждения баланса, референсы транзакций — как метаданные.
+
+pub mod mapping;
+pub mod writer;
+
+pub use mapping::{AccountMapping, MappingRule};
+pub use writer::{BeancountWriter, LedgerWriter};
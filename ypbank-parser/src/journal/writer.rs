@@ -0,0 +1,375 @@
+//! Сериализация выписок в журналы Beancount и Ledger (hledger).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::Result;
+use crate::journal::mapping::AccountMapping;
+use crate::types::{Amount, AmountFormat, Balance, Date, Statement, Transaction};
+use std::collections::HashSet;
+use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Ширина колонки счета в проводках.
+const ACCOUNT_WIDTH: usize = 40;
+
+/// Writer журнала Beancount.
+///
+/// Входящий остаток устанавливается директивой `pad` со счета капитала
+/// и проверяется директивой `balance`; исходящий остаток проверяется
+/// на следующий день после даты выписки, так как `balance` в Beancount
+/// относится к началу дня.
+pub struct BeancountWriter;
+
+impl BeancountWriter {
+    /// Записывает журнал в любой приемник, реализующий трейт Write.
+    ///
+    /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
+    pub fn write_to<W: Write>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+    ) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_to_buffered(statements, mapping, &mut buf_writer)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает журнал в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statements, mapping, buffer)).await
+    }
+
+    /// Записывает журнал в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(statements, mapping, buffer)
+        })
+    }
+
+    /// Асинхронно записывает журнал в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statements, mapping, buffer, encoding)
+        })
+        .await
+    }
+
+    fn write_to_buffered<W: Write>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+    ) -> Result<()> {
+        Self::write_open_directives(statements, mapping, writer)?;
+
+        let mut padded = HashSet::new();
+        for statement in statements {
+            let account = own_account(statement, mapping);
+            let currency = &statement.account.currency;
+            let opening = &statement.opening_balance;
+
+            if padded.insert(account) {
+                writeln!(
+                    writer,
+                    "{} pad {} {}",
+                    Self::format_date(&opening.date.add_days(-1)),
+                    account,
+                    mapping.opening_balances
+                )?;
+            }
+            Self::write_balance(writer, &opening.date, account, opening, currency)?;
+            writeln!(writer)?;
+
+            for tx in &statement.transactions {
+                Self::write_transaction(writer, tx, account, mapping)?;
+            }
+
+            let closing = &statement.closing_balance;
+            Self::write_balance(writer, &closing.date.add_days(1), account, closing, currency)?;
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Открывает все используемые счета за день до самой ранней выписки.
+    fn write_open_directives<W: Write>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+    ) -> Result<()> {
+        let Some(earliest) = statements
+            .iter()
+            .map(|s| &s.opening_balance.date)
+            .min()
+        else {
+            return Ok(());
+        };
+        let date = Self::format_date(&earliest.add_days(-1));
+
+        let mut opened = HashSet::new();
+        for statement in statements {
+            let account = own_account(statement, mapping);
+            if opened.insert(account) {
+                writeln!(writer, "{} open {} {}", date, account, statement.account.currency)?;
+            }
+        }
+
+        let counter_accounts = statements
+            .iter()
+            .flat_map(|s| s.transactions.iter().map(|tx| mapping.counter_account(tx)))
+            .chain(std::iter::once(mapping.opening_balances.as_str()));
+        for account in counter_accounts {
+            if opened.insert(account) {
+                writeln!(writer, "{} open {}", date, account)?;
+            }
+        }
+        writeln!(writer)?;
+
+        Ok(())
+    }
+
+    fn write_balance<W: Write>(
+        writer: &mut W,
+        date: &Date,
+        account: &str,
+        balance: &Balance,
+        currency: &str,
+    ) -> Result<()> {
+        writeln!(
+            writer,
+            "{} balance {} {} {}",
+            Self::format_date(date),
+            account,
+            format_amount(&balance.signed_amount()),
+            currency
+        )?;
+        Ok(())
+    }
+
+    fn write_transaction<W: Write>(
+        writer: &mut W,
+        tx: &Transaction,
+        account: &str,
+        mapping: &AccountMapping,
+    ) -> Result<()> {
+        let narration = Self::quote(&tx.description);
+
+        match payee(tx) {
+            Some(payee) => writeln!(
+                writer,
+                "{} * {} {}",
+                Self::format_date(&tx.date),
+                Self::quote(payee),
+                narration
+            )?,
+            None => writeln!(writer, "{} * {}", Self::format_date(&tx.date), narration)?,
+        }
+
+        if let Some(ref reference) = tx.reference {
+            writeln!(writer, "  reference: {}", Self::quote(reference))?;
+        }
+
+        let amount = format!("{} {}", format_amount(&tx.signed_amount()), tx.amount.currency);
+        writeln!(writer, "  {:<width$} {:>16}", account, amount, width = ACCOUNT_WIDTH)?;
+        writeln!(writer, "  {}", mapping.counter_account(tx))?;
+        writeln!(writer)?;
+
+        Ok(())
+    }
+
+    fn quote(s: &str) -> String {
+        let escaped = s
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace(['\r', '\n'], " ");
+        format!("\"{}\"", escaped)
+    }
+
+    fn format_date(date: &Date) -> String {
+        format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day())
+    }
+}
+
+/// Writer журнала Ledger, совместимый с hledger.
+///
+/// Входящий остаток первой выписки по счету задается присваиванием
+/// баланса (`= сумма`) со счета капитала, остальные остатки проверяются
+/// утверждениями в нулевых проводках.
+pub struct LedgerWriter;
+
+impl LedgerWriter {
+    /// Записывает журнал в любой приемник, реализующий трейт Write.
+    ///
+    /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
+    pub fn write_to<W: Write>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+    ) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_to_buffered(statements, mapping, &mut buf_writer)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает журнал в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statements, mapping, buffer)).await
+    }
+
+    /// Записывает журнал в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(statements, mapping, buffer)
+        })
+    }
+
+    /// Асинхронно записывает журнал в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statements, mapping, buffer, encoding)
+        })
+        .await
+    }
+
+    fn write_to_buffered<W: Write>(
+        statements: &[Statement],
+        mapping: &AccountMapping,
+        writer: &mut W,
+    ) -> Result<()> {
+        let mut assigned = HashSet::new();
+
+        for statement in statements {
+            let account = own_account(statement, mapping);
+            let currency = &statement.account.currency;
+            let opening = &statement.opening_balance;
+            let opening_amount =
+                format!("{} {}", format_amount(&opening.signed_amount()), currency);
+
+            writeln!(writer, "{} * Opening balance", Self::format_date(&opening.date))?;
+            if assigned.insert(account) {
+                Self::write_posting(writer, account, &format!("= {}", opening_amount))?;
+                writeln!(writer, "    {}", mapping.opening_balances)?;
+            } else {
+                Self::write_posting(writer, account, &format!("0 {} = {}", currency, opening_amount))?;
+            }
+            writeln!(writer)?;
+
+            for tx in &statement.transactions {
+                Self::write_transaction(writer, tx, account, mapping)?;
+            }
+
+            let closing = &statement.closing_balance;
+            writeln!(writer, "{} * Closing balance", Self::format_date(&closing.date))?;
+            Self::write_posting(
+                writer,
+                account,
+                &format!(
+                    "0 {} = {} {}",
+                    currency,
+                    format_amount(&closing.signed_amount()),
+                    currency
+                ),
+            )?;
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_transaction<W: Write>(
+        writer: &mut W,
+        tx: &Transaction,
+        account: &str,
+        mapping: &AccountMapping,
+    ) -> Result<()> {
+        let description = Self::clean(&tx.description);
+
+        // Разделитель `|` — соглашение hledger о получателе и примечании
+        let title = match payee(tx) {
+            Some(payee) if !description.is_empty() => {
+                format!("{} | {}", Self::clean(payee), description)
+            }
+            Some(payee) => Self::clean(payee),
+            None => description,
+        };
+        writeln!(writer, "{} * {}", Self::format_date(&tx.date), title)?;
+
+        if let Some(ref reference) = tx.reference {
+            writeln!(writer, "    ; reference: {}", Self::clean(reference))?;
+        }
+
+        let amount = format!("{} {}", format_amount(&tx.signed_amount()), tx.amount.currency);
+        Self::write_posting(writer, account, &amount)?;
+        writeln!(writer, "    {}", mapping.counter_account(tx))?;
+        writeln!(writer)?;
+
+        Ok(())
+    }
+
+    fn write_posting<W: Write>(writer: &mut W, account: &str, amount: &str) -> Result<()> {
+        writeln!(writer, "    {:<width$} {:>16}", account, amount, width = ACCOUNT_WIDTH)?;
+        Ok(())
+    }
+
+    fn clean(s: &str) -> String {
+        s.replace(['\r', '\n'], " ").trim().to_string()
+    }
+
+    fn format_date(date: &Date) -> String {
+        format!("{:04}/{:02}/{:02}", date.year(), date.month(), date.day())
+    }
+}
+
+fn own_account<'a>(statement: &Statement, mapping: &'a AccountMapping) -> &'a str {
+    mapping.own_account(&statement.account.number, statement.account.iban.as_deref())
+}
+
+/// Наименование контрагента, если оно не повторяет назначение платежа.
+fn payee(tx: &Transaction) -> Option<&str> {
+    tx.counterparty
+        .as_ref()
+        .and_then(|c| c.name.as_deref())
+        .filter(|name| !name.is_empty() && *name != tx.description)
+}
+
+fn format_amount(amount: &Amount) -> String {
+    AmountFormat::PLAIN.format_value(amount.value)
+}
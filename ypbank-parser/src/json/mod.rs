@@ -0,0 +1,18 @@
+//! Модуль сериализации унифицированной модели в JSON и NDJSON (feature `serde`).
+//!
+//! Документ JSON — объект `{"version": 1, "statements": [...]}` с выписками
+//! [`Statement`](crate::types::Statement). В NDJSON каждая строка — отдельная
+//! запись: заголовок выписки (`"record": "statement"`) и следующие за ним
+//! транзакции (`"record": "transaction"`), по одной на строку.
+//!
+//! Даты записываются строками `YYYY-MM-DD`, суммы — целым числом минимальных
+//! единиц валюты. Схема описана в [`JSON_SCHEMA`]; при несовместимых
+//! изменениях увеличивается [`JSON_SCHEMA_VERSION`].
+
+pub mod parser;
+pub mod reader;
+pub mod writer;
+
+pub use parser::{JSON_SCHEMA, JSON_SCHEMA_VERSION, JsonDocument};
+pub use reader::{NdjsonReader, NdjsonTransactions};
+pub use writer::JsonWriter;
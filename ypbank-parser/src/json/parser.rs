@@ -0,0 +1,152 @@
+//! Разбор документов JSON и NDJSON с выписками.
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::json::reader::NdjsonReader;
+use crate::limits::Limits;
+use crate::types::Statement;
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Версия схемы JSON, которую записывает и принимает библиотека.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema (draft 2020-12) документа и записей NDJSON.
+pub const JSON_SCHEMA: &str = include_str!("../../schema/statement-v1.schema.json");
+
+/// Документ JSON: версия схемы и список выписок.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct JsonDocument {
+    /// Версия схемы.
+    pub version: u32,
+    /// Выписки.
+    pub statements: Vec<Statement>,
+}
+
+impl From<Vec<Statement>> for JsonDocument {
+    fn from(statements: Vec<Statement>) -> Self {
+        JsonDocument {
+            version: JSON_SCHEMA_VERSION,
+            statements,
+        }
+    }
+}
+
+impl JsonDocument {
+    /// Парсит документ JSON из любого источника, реализующего трейт Read.
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит документ JSON из любого источника, реализующего трейт AsyncRead.
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит документ JSON из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Self> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
+    /// Парсит документ JSON из строки.
+    ///
+    /// Версия схемы проверяется до разбора выписок, чтобы документ
+    /// более новой версии давал понятную ошибку. Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Self> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит документ JSON из строки с заданными ограничениями.
+    ///
+    /// Число выписок и транзакций проверяется по массивам документа
+    /// до построения выписок, длины полей — в построенных выписках.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Self> {
+        limits.check_input(content.len())?;
+        let value: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| Error::Parse(format!("Некорректный JSON: {}", e)))?;
+
+        let version = value
+            .get("version")
+            .ok_or_else(|| Error::MissingField("version".to_string()))?;
+        Self::check_version(version.as_u64())?;
+
+        if let Some(statements) = value.get("statements").and_then(|s| s.as_array()) {
+            limits.check_statements(statements.len())?;
+            for statement in statements {
+                if let Some(transactions) = statement["transactions"].as_array() {
+                    limits.check_transactions(transactions.len())?;
+                }
+            }
+        }
+
+        let document: Self = serde_json::from_value(value)
+            .map_err(|e| Error::InvalidFormat(format!("Документ не соответствует схеме: {}", e)))?;
+        limits.check_parsed(&document.statements)?;
+        Ok(document)
+    }
+
+    /// Парсит NDJSON: заголовок выписки, за которым следуют ее транзакции.
+    ///
+    /// Пустые строки пропускаются. Для больших файлов используйте
+    /// [`NdjsonReader`](crate::json::NdjsonReader). Применяются [`Limits::default`].
+    pub fn parse_ndjson(content: &str) -> Result<Self> {
+        Self::parse_ndjson_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит NDJSON с заданными ограничениями (см. [`NdjsonReader::with_limits`]).
+    pub fn parse_ndjson_with_limits(content: &str, limits: &Limits) -> Result<Self> {
+        limits.check_input(content.len())?;
+        let mut statements = Vec::new();
+        let reader = NdjsonReader::with_encoding(content.as_bytes(), Some(TextEncoding::Utf8))
+            .with_limits(*limits);
+        for statement in reader {
+            statements.push(statement?);
+            limits.check_statements(statements.len())?;
+        }
+        Ok(statements.into())
+    }
+
+    /// Возвращает выписки документа.
+    pub fn into_statements(self) -> Vec<Statement> {
+        self.statements
+    }
+
+    pub(crate) fn check_version(version: Option<u64>) -> Result<()> {
+        match version {
+            Some(v) if v == u64::from(JSON_SCHEMA_VERSION) => Ok(()),
+            Some(v) => Err(Error::InvalidFormat(format!(
+                "Неподдерживаемая версия схемы JSON {} (поддерживается {})",
+                v, JSON_SCHEMA_VERSION
+            ))),
+            None => Err(Error::InvalidFormat(
+                "Поле version должно быть целым числом".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_is_valid_json() {
+        let schema: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["version"]["const"],
+            JSON_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let err = JsonDocument::parse(r#"{"version": 2, "statements": []}"#).unwrap_err();
+        assert!(matches!(err, Error::InvalidFormat(_)));
+    }
+}
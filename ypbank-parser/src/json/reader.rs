@@ -0,0 +1,262 @@
+//! Потоковое чтение NDJSON.
+
+use crate::encoding::{TextEncoding, TextReader};
+use crate::error::{Error, Result};
+use crate::json::parser::JsonDocument;
+use crate::limits::Limits;
+use crate::types::{Account, Balance, Statement, Transaction};
+use std::io::BufRead;
+
+/// Строка NDJSON: заголовок выписки или транзакция.
+#[derive(serde::Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum NdjsonRecord {
+    Statement(Box<NdjsonHeader>),
+    Transaction(Box<Transaction>),
+}
+
+/// Заголовок выписки в NDJSON: все поля Statement, кроме транзакций.
+#[derive(serde::Deserialize)]
+struct NdjsonHeader {
+    version: u32,
+    account: Account,
+    opening_balance: Balance,
+    closing_balance: Balance,
+    statement_number: Option<String>,
+    reference: Option<String>,
+}
+
+impl From<NdjsonHeader> for Statement {
+    fn from(header: NdjsonHeader) -> Self {
+        Statement {
+            account: header.account,
+            opening_balance: header.opening_balance,
+            closing_balance: header.closing_balance,
+            transactions: Vec::new(),
+            statement_number: header.statement_number,
+            reference: header.reference,
+        }
+    }
+}
+
+/// Итератор по выпискам NDJSON из любого источника `BufRead`.
+///
+/// Выписка возвращается, когда встречается заголовок следующей или
+/// заканчивается вход, поэтому в памяти находится только текущая выписка.
+/// Пустые строки пропускаются.
+pub struct NdjsonReader<R> {
+    lines: TextReader<R>,
+    line: String,
+    current: Option<Statement>,
+    finished: bool,
+    limits: Limits,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    /// Создает итератор с автоматическим определением кодировки.
+    pub fn new(reader: R) -> Self {
+        Self::with_encoding(reader, None)
+    }
+
+    /// Создает итератор для входа в заданной кодировке.
+    pub fn with_encoding(reader: R, encoding: Option<TextEncoding>) -> Self {
+        Self {
+            lines: TextReader::new(reader, encoding),
+            line: String::new(),
+            current: None,
+            finished: false,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Задает ограничения разбора.
+    ///
+    /// `max_field_len` ограничивает длину строки NDJSON, `max_transactions` —
+    /// число транзакций в выписке. Число выписок в потоке не ограничивается.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Превращает итератор выписок в итератор транзакций.
+    ///
+    /// Транзакции возвращаются по мере чтения строк, поэтому в памяти
+    /// не накапливается даже текущая выписка.
+    pub fn into_transactions(mut self) -> NdjsonTransactions<R> {
+        // Транзакции выписки, заголовок которой уже прочитан
+        let (pending, count) = match self.current.take() {
+            Some(statement) => {
+                let count = statement.transactions.len();
+                (statement.transactions.into_iter(), Some(count))
+            }
+            None => (Vec::new().into_iter(), None),
+        };
+        NdjsonTransactions {
+            reader: self,
+            pending,
+            count,
+        }
+    }
+
+    /// Следующая непустая строка NDJSON с ее номером.
+    fn next_record(&mut self) -> Result<Option<(usize, NdjsonRecord)>> {
+        loop {
+            self.line.clear();
+            if self.lines.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+
+            let line = self.line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            self.limits.check_field("строка NDJSON", line)?;
+
+            let number = self.lines.line_number();
+            let record = serde_json::from_str(line)
+                .map_err(|e| Error::Parse(format!("Строка {}: {}", number, e)))?;
+            return Ok(Some((number, record)));
+        }
+    }
+
+    fn next_statement(&mut self) -> Result<Option<Statement>> {
+        loop {
+            let (number, record) = match self.next_record()? {
+                Some(record) => record,
+                None => return Ok(self.current.take()),
+            };
+
+            match record {
+                NdjsonRecord::Statement(header) => {
+                    JsonDocument::check_version(Some(header.version.into()))?;
+                    let previous = self.current.replace((*header).into());
+                    if previous.is_some() {
+                        return Ok(previous);
+                    }
+                }
+                NdjsonRecord::Transaction(tx) => match self.current.as_mut() {
+                    Some(statement) => {
+                        statement.transactions.push(*tx);
+                        self.limits.check_transactions(statement.transactions.len())?;
+                    }
+                    None => return Err(orphan_transaction(number)),
+                },
+            }
+        }
+    }
+}
+
+fn orphan_transaction(line: usize) -> Error {
+    Error::InvalidFormat(format!("Строка {}: транзакция до заголовка выписки", line))
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<Statement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let result = self.next_statement().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.finished = true;
+        }
+        result
+    }
+}
+
+/// Итератор по транзакциям NDJSON, см. [`NdjsonReader::into_transactions`].
+///
+/// `max_transactions` из ограничений читателя применяется к каждой выписке.
+pub struct NdjsonTransactions<R> {
+    reader: NdjsonReader<R>,
+    pending: std::vec::IntoIter<Transaction>,
+    count: Option<usize>,
+}
+
+impl<R: BufRead> NdjsonTransactions<R> {
+    fn next_transaction(&mut self) -> Result<Option<Transaction>> {
+        if let Some(tx) = self.pending.next() {
+            return Ok(Some(tx));
+        }
+
+        loop {
+            let (number, record) = match self.reader.next_record()? {
+                Some(record) => record,
+                None => return Ok(None),
+            };
+
+            match record {
+                NdjsonRecord::Statement(header) => {
+                    JsonDocument::check_version(Some(header.version.into()))?;
+                    self.count = Some(0);
+                }
+                NdjsonRecord::Transaction(tx) => match self.count.as_mut() {
+                    Some(count) => {
+                        *count += 1;
+                        self.reader.limits.check_transactions(*count)?;
+                        return Ok(Some(*tx));
+                    }
+                    None => return Err(orphan_transaction(number)),
+                },
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonTransactions<R> {
+    type Item = Result<Transaction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.finished {
+            return None;
+        }
+
+        let result = self.next_transaction().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.reader.finished = true;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_before_header() {
+        let mut reader = NdjsonReader::new(r#"{"record":"transaction"}"#.as_bytes());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_transactions_limit_per_statement() {
+        let header = concat!(
+            r#"{"record":"statement","version":1,"account":{"number":"1","currency":"RUB"},"#,
+            r#""opening_balance":{"amount":{"value":0,"currency":"RUB"},"#,
+            r#""date":"2024-01-01","is_credit":true},"#,
+            r#""closing_balance":{"amount":{"value":0,"currency":"RUB"},"#,
+            r#""date":"2024-01-31","is_credit":true}}"#
+        );
+        let tx = concat!(
+            r#"{"record":"transaction","date":"2024-01-15","#,
+            r#""amount":{"value":100,"currency":"RUB"},"is_credit":true,"description":""}"#
+        );
+        let content = [header, tx, header, tx, tx].join("\n");
+        let limits = Limits {
+            max_transactions: 1,
+            ..Limits::default()
+        };
+
+        let mut transactions = NdjsonReader::new(content.as_bytes())
+            .with_limits(limits)
+            .into_transactions();
+        assert!(transactions.next().unwrap().is_ok());
+        assert!(transactions.next().unwrap().is_ok());
+        assert!(matches!(transactions.next(), Some(Err(Error::LimitExceeded(_)))));
+        assert!(transactions.next().is_none());
+    }
+}
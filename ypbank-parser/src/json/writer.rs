@@ -0,0 +1,217 @@
+//! Сериализация выписок в JSON и NDJSON.
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::json::parser::{JSON_SCHEMA_VERSION, JsonDocument};
+use crate::types::{Account, Balance, Statement, Transaction};
+use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Строка NDJSON при записи; поля заимствуются из выписки.
+#[derive(serde::Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum NdjsonRecord<'a> {
+    Statement {
+        version: u32,
+        account: &'a Account,
+        opening_balance: &'a Balance,
+        closing_balance: &'a Balance,
+        statement_number: &'a Option<String>,
+        reference: &'a Option<String>,
+    },
+    Transaction(&'a Transaction),
+}
+
+/// Writer для JSON и NDJSON.
+///
+/// JSON записывается с отступами одним документом, NDJSON — по записи
+/// на строку без отступов.
+pub struct JsonWriter;
+
+impl JsonWriter {
+    /// Записывает документ JSON в любой приемник, реализующий трейт Write.
+    ///
+    /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
+    pub fn write_to<W: Write>(statements: &[Statement], writer: &mut W) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_to_buffered(statements, &mut buf_writer)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает документ JSON в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statements, buffer)).await
+    }
+
+    /// Записывает документ JSON в указанной кодировке.
+    ///
+    /// RFC 8259 требует UTF-8; другие кодировки нужны только для систем,
+    /// которые не умеют его читать.
+    pub fn write_to_with_encoding<W: Write>(
+        statements: &[Statement],
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(statements, buffer)
+        })
+    }
+
+    /// Асинхронно записывает документ JSON в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statements, buffer, encoding)
+        })
+        .await
+    }
+
+    /// Записывает NDJSON: для каждой выписки заголовок и транзакции по одной на строку.
+    pub fn write_ndjson_to<W: Write>(statements: &[Statement], writer: &mut W) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_ndjson_buffered(statements, &mut buf_writer)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает NDJSON: для каждой выписки заголовок и транзакции по одной на строку.
+    #[cfg(feature = "async")]
+    pub async fn write_ndjson_to_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_ndjson_to(statements, buffer)).await
+    }
+
+    /// Записывает NDJSON в указанной кодировке.
+    pub fn write_ndjson_to_with_encoding<W: Write>(
+        statements: &[Statement],
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_ndjson_buffered(statements, buffer)
+        })
+    }
+
+    /// Асинхронно записывает NDJSON в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_ndjson_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_ndjson_to_with_encoding(statements, buffer, encoding)
+        })
+        .await
+    }
+
+    /// Записывает NDJSON из итератора выписок по мере их поступления.
+    ///
+    /// Каждая выписка кодируется и сбрасывается в приемник отдельно.
+    /// При ошибке во входном итераторе запись прекращается.
+    /// Возвращает число записанных выписок.
+    pub fn write_ndjson_iter_with_encoding<W, I>(
+        statements: I,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<usize>
+    where
+        W: Write,
+        I: IntoIterator<Item = Result<Statement>>,
+    {
+        let mut written = 0;
+        for statement in statements {
+            let statement = statement?;
+            encoding::write_encoded(writer, encoding, |buffer| {
+                Self::write_ndjson_buffered(std::slice::from_ref(&statement), buffer)
+            })?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    fn write_to_buffered<W: Write>(statements: &[Statement], writer: &mut W) -> Result<()> {
+        #[derive(serde::Serialize)]
+        struct Document<'a> {
+            version: u32,
+            statements: &'a [Statement],
+        }
+
+        let document = Document {
+            version: JSON_SCHEMA_VERSION,
+            statements,
+        };
+        serde_json::to_writer_pretty(&mut *writer, &document).map_err(Self::map_error)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    fn write_ndjson_buffered<W: Write>(statements: &[Statement], writer: &mut W) -> Result<()> {
+        for statement in statements {
+            let header = NdjsonRecord::Statement {
+                version: JSON_SCHEMA_VERSION,
+                account: &statement.account,
+                opening_balance: &statement.opening_balance,
+                closing_balance: &statement.closing_balance,
+                statement_number: &statement.statement_number,
+                reference: &statement.reference,
+            };
+            Self::write_line(writer, &header)?;
+
+            for tx in &statement.transactions {
+                Self::write_line(writer, &NdjsonRecord::Transaction(tx))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_line<W: Write>(writer: &mut W, record: &NdjsonRecord) -> Result<()> {
+        serde_json::to_writer(&mut *writer, record).map_err(Self::map_error)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    fn map_error(e: serde_json::Error) -> Error {
+        if e.is_io() {
+            Error::Io(e.into())
+        } else {
+            Error::InvalidFormat(format!("Ошибка сериализации JSON: {}", e))
+        }
+    }
+}
+
+impl JsonDocument {
+    /// Записывает документ JSON в любой приемник, реализующий трейт Write.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        JsonWriter::write_to(&self.statements, writer)
+    }
+
+    /// Асинхронно записывает документ JSON в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает выписки документа в формате NDJSON.
+    pub fn write_ndjson_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        JsonWriter::write_ndjson_to(&self.statements, writer)
+    }
+
+    /// Асинхронно записывает выписки документа в формате NDJSON.
+    #[cfg(feature = "async")]
+    pub async fn write_ndjson_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_ndjson_to(buffer)).await
+    }
+}
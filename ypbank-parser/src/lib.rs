@@ -1,13 +1,24 @@
 //! # YPBank Parser
 //!
 //! Библиотека для парсинга и сериализации банковских выписок
-//! в форматах MT940, CAMT.053 (ISO 20022) и CSV.
+//! в форматах MT940, CAMT.053 (ISO 20022), CSV, 1С, OFX, BAI2, QIF, CODA, Norma 43, CFONB 120, JSON и XLSX.
 //!
 //! ## Поддерживаемые форматы
 //!
 //! - **MT940** - текстовый формат SWIFT для банковских выписок
 //! - **CAMT.053** - XML формат ISO 20022
 //! - **CSV** - формат банковских выгрузок (СберБизнес и др.)
+//! - **1С** - формат обмена 1CClientBankExchange
+//! - **OFX** - Open Financial Exchange 1.x (SGML) и 2.x (XML), включая QFX
+//! - **BAI2** - формат Cash Management Balance Reporting (банки США)
+//! - **QIF** - Quicken Interchange Format для программ учета личных финансов
+//! - **CODA** - бельгийский формат выписок с записями фиксированной длины (только чтение)
+//! - **Norma 43** - испанский формат AEB Cuaderno 43 (только чтение)
+//! - **CFONB 120** - французский формат relevé de comptes (только чтение)
+//! - **Beancount**, **Ledger** - журналы учета в простом тексте (только запись)
+//! - **JSON**, **NDJSON** - унифицированная модель с версионированной схемой (feature `serde`)
+//! - **XLSX** - книги Excel со сводкой и транзакциями (feature `xlsx`)
+//! - **Parquet** - таблица транзакций Apache Arrow (feature `parquet`, только запись)
 //!
 //! ## Пример использования
 //!
@@ -19,21 +30,71 @@
 //! let mt940 = Mt940Statement::from_read(&mut file)?;
 //! let statement: Statement = mt940.into();
 //! ```
+//!
+//! Большие файлы MT940 и NDJSON читаются по одной выписке через
+//! [`StatementReader`], а writers принимают итераторы выписок.
+//! С feature `parallel` выписки MT940 и элементы `<Stmt>` CAMT.053
+//! разбираются параллельно, с feature `async` все форматы читаются из
+//! `AsyncRead` и записываются в `AsyncWrite` (tokio).
 
 #![warn(missing_docs)]
 
 pub mod error;
+pub mod encoding;
+pub mod limits;
+pub mod amount;
+pub mod date;
+pub mod clock;
 pub mod types;
 pub mod mt940;
 pub mod csv;
 pub mod camt053;
+pub mod onec;
+pub mod ofx;
+pub mod bai2;
+pub mod qif;
+pub mod coda;
+pub mod norma43;
+pub mod cfonb;
+pub mod journal;
+#[cfg(feature = "serde")]
+pub mod json;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "parquet")]
+pub mod arrow;
 pub mod convert;
+pub mod stream;
+mod parallel;
+mod record;
 
+pub use encoding::TextEncoding;
 pub use error::{Error, Result};
+pub use limits::Limits;
+pub use clock::{Clock, FixedClock, SystemClock};
 pub use types::*;
-pub use mt940::{Mt940Statement, Mt940Writer};
-pub use csv::{CsvStatement, CsvWriter};
+pub use mt940::{Mt940Reader, Mt940Statement, Mt940StatementRef, Mt940Writer};
+pub use csv::{CsvProfile, CsvStatement, CsvWriter};
 pub use camt053::{Camt053Statement, Camt053Writer};
+pub use onec::{OneCStatement, OneCWriter};
+pub use ofx::{OfxStatement, OfxVersion, OfxWriter};
+pub use bai2::{Bai2File, Bai2Writer};
+pub use qif::{QifDateStyle, QifFile, QifWriter};
+pub use coda::CodaStatement;
+pub use norma43::Norma43Statement;
+pub use cfonb::CfonbStatement;
+pub use journal::{AccountMapping, BeancountWriter, LedgerWriter, MappingRule};
+pub use stream::StatementReader;
+#[cfg(feature = "serde")]
+pub use json::{JSON_SCHEMA_VERSION, JsonDocument, JsonWriter, NdjsonReader, NdjsonTransactions};
+#[cfg(feature = "xlsx")]
+pub use xlsx::{XlsxWorkbook, XlsxWriter};
+#[cfg(feature = "archive")]
+pub use archive::{Archive, ArchivedPeriod, ImportReport};
+#[cfg(feature = "parquet")]
+pub use arrow::ParquetWriter;
 
 /// Поддерживаемые форматы.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -44,6 +105,32 @@ pub enum Format {
     Camt053,
     /// CSV
     Csv,
+    /// 1С:Предприятие (1CClientBankExchange)
+    OneC,
+    /// OFX / QFX (Open Financial Exchange)
+    Ofx,
+    /// BAI2 (Cash Management Balance Reporting)
+    Bai2,
+    /// QIF (Quicken Interchange Format)
+    Qif,
+    /// CODA (бельгийский формат, только чтение)
+    Coda,
+    /// Norma 43 (испанский формат AEB, только чтение)
+    Norma43,
+    /// CFONB 120 (французский формат, только чтение)
+    Cfonb120,
+    /// Журнал Beancount (только запись)
+    Beancount,
+    /// Журнал Ledger / hledger (только запись)
+    Ledger,
+    /// JSON-документ с выписками (feature `serde`)
+    Json,
+    /// NDJSON: заголовок выписки и транзакции по одной на строку (feature `serde`)
+    Ndjson,
+    /// Книга Excel (feature `xlsx`)
+    Xlsx,
+    /// Таблица транзакций Parquet (feature `parquet`, только запись)
+    Parquet,
 }
 
 impl std::str::FromStr for Format {
@@ -54,6 +141,19 @@ impl std::str::FromStr for Format {
             "mt940" => Ok(Format::Mt940),
             "camt053" | "camt" | "xml" => Ok(Format::Camt053),
             "csv" => Ok(Format::Csv),
+            "1c" | "onec" | "1cclientbankexchange" => Ok(Format::OneC),
+            "ofx" | "qfx" => Ok(Format::Ofx),
+            "bai2" | "bai" => Ok(Format::Bai2),
+            "qif" => Ok(Format::Qif),
+            "coda" | "cod" => Ok(Format::Coda),
+            "norma43" | "n43" | "aeb43" => Ok(Format::Norma43),
+            "cfonb" | "cfonb120" => Ok(Format::Cfonb120),
+            "beancount" | "bean" => Ok(Format::Beancount),
+            "ledger" | "hledger" => Ok(Format::Ledger),
+            "json" => Ok(Format::Json),
+            "ndjson" | "jsonl" => Ok(Format::Ndjson),
+            "xlsx" | "excel" => Ok(Format::Xlsx),
+            "parquet" | "pq" => Ok(Format::Parquet),
             _ => Err(()),
         }
     }
@@ -69,24 +169,139 @@ impl Format {
 /// Парсит все выписки из строки в универсальный формат Statement.
 ///
 /// Для MT940 файлов, которые могут содержать несколько выписок,
-/// возвращает все найденные выписки.
+/// возвращает все найденные выписки. Применяются [`Limits::default`].
 pub fn parse_statements(content: &str, format: Format) -> Result<Vec<Statement>> {
+    parse_statements_with_limits(content, format, &Limits::default())
+}
+
+/// Парсит все выписки из строки с заданными ограничениями.
+///
+/// Размер входа проверяется до разбора, число выписок, транзакций и длины
+/// полей — парсером формата по ходу разбора (см. `parse_with_limits`
+/// каждого формата). Затем длины полей еще раз проверяются в выписках
+/// унифицированной модели, собранных из нескольких частей.
+pub fn parse_statements_with_limits(
+    content: &str,
+    format: Format,
+    limits: &Limits,
+) -> Result<Vec<Statement>> {
+    limits.check_input(content.len())?;
+
+    let statements = parse_statements_unchecked(content, format, limits)?;
+    limits.check_parsed(&statements)?;
+    Ok(statements)
+}
+
+fn parse_statements_unchecked(
+    content: &str,
+    format: Format,
+    limits: &Limits,
+) -> Result<Vec<Statement>> {
     match format {
         Format::Mt940 => {
-            let statements = Mt940Statement::parse(content)?;
+            let statements = Mt940Statement::parse_with_limits(content, limits)?;
             Ok(statements.into_iter().map(|mt940| mt940.into()).collect())
         }
         Format::Camt053 => {
-            let camt = Camt053Statement::parse(content)?;
-            Ok(vec![camt.into()])
+            let statements = Camt053Statement::parse_all_with_limits(content, limits)?;
+            Ok(statements.into_iter().map(|camt| camt.into()).collect())
         }
         Format::Csv => {
-            let csv = CsvStatement::parse(content)?;
+            let csv = CsvStatement::parse_with_limits(content, limits)?;
             Ok(vec![csv.try_into()?])
         }
+        Format::OneC => OneCStatement::parse_with_limits(content, limits)?.into_statements(),
+        Format::Ofx => {
+            let statements = OfxStatement::parse_with_limits(content, limits)?;
+            Ok(statements.into_iter().map(|ofx| ofx.into()).collect())
+        }
+        Format::Bai2 => Ok(Bai2File::parse_with_limits(content, limits)?.into_statements()),
+        Format::Qif => QifFile::parse_with_limits(content, limits)?.into_statements(),
+        Format::Coda => {
+            let statements = CodaStatement::parse_with_limits(content, limits)?;
+            Ok(statements.into_iter().map(|coda| coda.into()).collect())
+        }
+        Format::Norma43 => {
+            let statements = Norma43Statement::parse_with_limits(content, limits)?;
+            Ok(statements.into_iter().map(|n43| n43.into()).collect())
+        }
+        Format::Cfonb120 => {
+            let statements = CfonbStatement::parse_with_limits(content, limits)?;
+            Ok(statements.into_iter().map(|cfonb| cfonb.into()).collect())
+        }
+        Format::Beancount | Format::Ledger | Format::Parquet => Err(Error::InvalidFormat(format!(
+            "Формат {:?} поддерживается только для записи",
+            format
+        ))),
+        #[cfg(feature = "serde")]
+        Format::Json => Ok(JsonDocument::parse_with_limits(content, limits)?.into_statements()),
+        #[cfg(feature = "serde")]
+        Format::Ndjson => {
+            Ok(JsonDocument::parse_ndjson_with_limits(content, limits)?.into_statements())
+        }
+        #[cfg(not(feature = "serde"))]
+        Format::Json | Format::Ndjson => Err(Error::InvalidFormat(format!(
+            "Формат {:?} требует feature serde",
+            format
+        ))),
+        Format::Xlsx => Err(Error::InvalidFormat(
+            "XLSX — двоичный формат, используйте parse_statements_bytes".into(),
+        )),
     }
 }
 
+/// Парсит все выписки из байтов в универсальный формат Statement.
+///
+/// Если кодировка не указана, она определяется автоматически
+/// (BOM, XML-декларация, эвристика для Windows-1251/CP866).
+/// Книги XLSX читаются без декодирования, кодировка для них не учитывается.
+pub fn parse_statements_bytes(
+    bytes: &[u8],
+    format: Format,
+    encoding: Option<TextEncoding>,
+) -> Result<Vec<Statement>> {
+    parse_statements_bytes_with_limits(bytes, format, encoding, &Limits::default())
+}
+
+/// Парсит все выписки из байтов с заданными ограничениями.
+///
+/// Размер входа проверяется до декодирования, для XLSX — также
+/// распакованный размер книги.
+pub fn parse_statements_bytes_with_limits(
+    bytes: &[u8],
+    format: Format,
+    encoding: Option<TextEncoding>,
+    limits: &Limits,
+) -> Result<Vec<Statement>> {
+    limits.check_input(bytes.len())?;
+    if format == Format::Xlsx {
+        #[cfg(feature = "xlsx")]
+        {
+            let statements =
+                XlsxWorkbook::parse_bytes_with_limits(bytes, limits)?.into_statements();
+            limits.check_parsed(&statements)?;
+            return Ok(statements);
+        }
+        #[cfg(not(feature = "xlsx"))]
+        return Err(Error::InvalidFormat("Формат Xlsx требует feature xlsx".into()));
+    }
+    parse_statements_with_limits(&encoding::decode(bytes, encoding)?, format, limits)
+}
+
+/// Асинхронно читает все выписки из `AsyncRead` в универсальный формат Statement.
+///
+/// Вход читается целиком без блокировки потока, разбор выполняет
+/// [`parse_statements_bytes`].
+#[cfg(feature = "async")]
+pub async fn parse_statements_async<R: tokio::io::AsyncRead + Unpin>(
+    reader: &mut R,
+    format: Format,
+    encoding: Option<TextEncoding>,
+) -> Result<Vec<Statement>> {
+    let bytes = encoding::read_async(reader).await?;
+    parse_statements_bytes(&bytes, format, encoding)
+}
+
 /// Парсит первую выписку из строки в универсальный формат Statement.
 ///
 /// Для MT940 файлов возвращает только первую выписку.
@@ -0,0 +1,212 @@
+//! Ограничения на размер и сложность входных данных.
+//!
+//! Выписки из недоверенных источников (загруженные клиентами файлы) могут
+//! быть огромными или специально построенными так, чтобы разбор занимал много
+//! памяти и времени. [`Limits`] задает верхние границы, которые проверяют все
+//! парсеры; превышение возвращается как [`Error::LimitExceeded`].
+
+use crate::error::{Error, Result};
+use crate::types::{Counterparty, Statement};
+
+/// Ограничения разбора входных данных.
+///
+/// Значения по умолчанию рассчитаны на реальные банковские выгрузки
+/// с большим запасом. Для доверенных файлов используйте [`Limits::unlimited`].
+///
+/// # Пример
+///
+/// ```ignore
+/// let limits = Limits {
+///     max_input_bytes: 10 * 1024 * 1024,
+///     ..Limits::default()
+/// };
+/// let statements = parse_statements_with_limits(&content, Format::Mt940, &limits)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Максимальный размер входа в байтах. Для XLSX ограничивает также
+    /// суммарный распакованный размер частей книги.
+    pub max_input_bytes: usize,
+    /// Максимальное число выписок во входе.
+    pub max_statements: usize,
+    /// Максимальное число транзакций в одной выписке.
+    pub max_transactions: usize,
+    /// Максимальная длина текстового поля в байтах.
+    pub max_field_len: usize,
+    /// Максимальное число строк продолжения: поля `:86:` в MT940,
+    /// записей 88 в BAI2 и записей 05 у движения CFONB 120.
+    pub max_continuation_lines: usize,
+    /// Максимальная глубина вложенности элементов XML (CAMT.053, OFX 2.x).
+    pub max_xml_depth: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: 512 * 1024 * 1024,
+            max_statements: 100_000,
+            max_transactions: 1_000_000,
+            max_field_len: 64 * 1024,
+            max_continuation_lines: 100,
+            max_xml_depth: 64,
+        }
+    }
+}
+
+impl Limits {
+    /// Ограничения, которые никогда не срабатывают.
+    pub fn unlimited() -> Self {
+        Self {
+            max_input_bytes: usize::MAX,
+            max_statements: usize::MAX,
+            max_transactions: usize::MAX,
+            max_field_len: usize::MAX,
+            max_continuation_lines: usize::MAX,
+            max_xml_depth: usize::MAX,
+        }
+    }
+
+    /// Проверяет размер входа.
+    pub(crate) fn check_input(&self, len: usize) -> Result<()> {
+        Self::check("размер входа в байтах", len, self.max_input_bytes)
+    }
+
+    /// Проверяет суммарный распакованный размер архива.
+    #[cfg(feature = "xlsx")]
+    pub(crate) fn check_unpacked(&self, len: usize) -> Result<()> {
+        Self::check("распакованный размер в байтах", len, self.max_input_bytes)
+    }
+
+    /// Проверяет число выписок.
+    pub(crate) fn check_statements(&self, count: usize) -> Result<()> {
+        Self::check("число выписок", count, self.max_statements)
+    }
+
+    /// Проверяет число транзакций в выписке.
+    pub(crate) fn check_transactions(&self, count: usize) -> Result<()> {
+        Self::check("число транзакций в выписке", count, self.max_transactions)
+    }
+
+    /// Проверяет длину текстового поля.
+    pub(crate) fn check_field(&self, name: &str, value: &str) -> Result<()> {
+        self.check_field_len(name, value.len())
+    }
+
+    /// Проверяет длину поля, собираемого из нескольких частей.
+    pub(crate) fn check_field_len(&self, name: &str, len: usize) -> Result<()> {
+        if len > self.max_field_len {
+            return Err(Error::LimitExceeded(format!(
+                "длина поля {} {} > {}",
+                name, len, self.max_field_len
+            )));
+        }
+        Ok(())
+    }
+
+    /// Проверяет число строк продолжения поля `name`.
+    pub(crate) fn check_continuation_lines(&self, name: &str, count: usize) -> Result<()> {
+        if count > self.max_continuation_lines {
+            return Err(Error::LimitExceeded(format!(
+                "число строк продолжения {} {} > {}",
+                name, count, self.max_continuation_lines
+            )));
+        }
+        Ok(())
+    }
+
+    /// Проверяет глубину вложенности элементов XML за один проход.
+    ///
+    /// Комментарии, инструкции обработки и самозакрывающиеся элементы
+    /// глубину не увеличивают.
+    pub(crate) fn check_xml_depth(&self, content: &str) -> Result<()> {
+        let mut depth = 0usize;
+        let mut rest = content;
+
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            let end = rest.find('>').unwrap_or(rest.len());
+            let tag = &rest[..end];
+
+            if tag.starts_with('/') {
+                depth = depth.saturating_sub(1);
+            } else if !tag.starts_with('?') && !tag.starts_with('!') && !tag.ends_with('/') {
+                depth += 1;
+                Self::check("глубина вложенности XML", depth, self.max_xml_depth)?;
+            }
+
+            rest = &rest[end..];
+        }
+
+        Ok(())
+    }
+
+    /// Проверяет разобранные выписки в унифицированной модели: их число,
+    /// число транзакций и длины текстовых полей.
+    pub(crate) fn check_parsed(&self, statements: &[Statement]) -> Result<()> {
+        self.check_statements(statements.len())?;
+
+        for statement in statements {
+            self.check_transactions(statement.transactions.len())?;
+            self.check_field("номер счета", &statement.account.number)?;
+            self.check_optional_field("наименование счета", &statement.account.name)?;
+            self.check_optional_field("владелец счета", &statement.account.owner)?;
+            self.check_optional_field("номер выписки", &statement.statement_number)?;
+            self.check_optional_field("референс выписки", &statement.reference)?;
+
+            for tx in &statement.transactions {
+                self.check_field("назначение платежа", &tx.description)?;
+                self.check_optional_field("референс транзакции", &tx.reference)?;
+                self.check_optional_field("тип транзакции", &tx.transaction_type)?;
+                if let Some(counterparty) = &tx.counterparty {
+                    self.check_counterparty(counterparty)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_counterparty(&self, counterparty: &Counterparty) -> Result<()> {
+        self.check_optional_field("наименование контрагента", &counterparty.name)?;
+        self.check_optional_field("счет контрагента", &counterparty.account)?;
+        self.check_optional_field("банк контрагента", &counterparty.bank_name)?;
+        match &counterparty.ultimate_party {
+            Some(party) => self.check_counterparty(party),
+            None => Ok(()),
+        }
+    }
+
+    fn check_optional_field(&self, name: &str, value: &Option<String>) -> Result<()> {
+        match value {
+            Some(value) => self.check_field(name, value),
+            None => Ok(()),
+        }
+    }
+
+    fn check(what: &str, value: usize, max: usize) -> Result<()> {
+        if value > max {
+            return Err(Error::LimitExceeded(format!("{} {} > {}", what, value, max)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_depth() {
+        let limits = Limits {
+            max_xml_depth: 3,
+            ..Limits::default()
+        };
+        assert!(limits
+            .check_xml_depth("<?xml version=\"1.0\"?><a><b><c/><c>1</c></b><!-- x --></a>")
+            .is_ok());
+        assert!(matches!(
+            limits.check_xml_depth("<a><b><c><d></d></c></b></a>"),
+            Err(Error::LimitExceeded(_))
+        ));
+    }
+}
@@ -0,0 +1,394 @@
+//! Разбор MT940 без копирования полей.
+//!
+//! Строковые поля ссылаются на входной буфер через `Cow<'a, str>`;
+//! копия создается только для многострочного поля `:86:`, строки
+//! которого склеиваются через пробел.
+
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::mt940::parser::{Mt940Balance, Mt940Statement, Mt940Transaction};
+use crate::parallel;
+use crate::types::{Date, DEFAULT_CENTURY_PIVOT};
+use std::borrow::Cow;
+
+/// Выписка MT940, заимствующая поля из входного буфера.
+#[derive(Debug, Clone)]
+pub struct Mt940StatementRef<'a> {
+    /// Референс выписки (поле :20:).
+    pub reference: Cow<'a, str>,
+    /// Идентификатор счета (поле :25:).
+    pub account_id: Cow<'a, str>,
+    /// Номер выписки (поле :28C:).
+    pub statement_number: Cow<'a, str>,
+    /// Начальный баланс (поле :60F: или :60M:).
+    pub opening_balance: Mt940BalanceRef<'a>,
+    /// Конечный баланс (поле :62F: или :62M:).
+    pub closing_balance: Mt940BalanceRef<'a>,
+    /// Список транзакций (поля :61: и :86:).
+    pub transactions: Vec<Mt940TransactionRef<'a>>,
+}
+
+/// Баланс MT940, заимствующий код валюты из входного буфера.
+#[derive(Debug, Clone)]
+pub struct Mt940BalanceRef<'a> {
+    /// Индикатор кредит/дебет ('C' или 'D').
+    pub credit_debit: char,
+    /// Дата баланса.
+    pub date: Date,
+    /// Код валюты.
+    pub currency: Cow<'a, str>,
+    /// Сумма в минимальных единицах.
+    pub amount: i64,
+}
+
+/// Транзакция MT940, заимствующая поля из входного буфера.
+#[derive(Debug, Clone)]
+pub struct Mt940TransactionRef<'a> {
+    /// Дата проводки.
+    pub date: Date,
+    /// Дата валютирования.
+    pub value_date: Option<Date>,
+    /// Индикатор кредит/дебет ('C' или 'D').
+    pub credit_debit: char,
+    /// Сумма в минимальных единицах.
+    pub amount: i64,
+    /// Тип транзакции (NTRF, NMSC и т.д.).
+    pub transaction_type: Cow<'a, str>,
+    /// Референс транзакции.
+    pub reference: Option<Cow<'a, str>>,
+    /// Детали/описание транзакции.
+    pub details: Cow<'a, str>,
+}
+
+impl<'a> Mt940StatementRef<'a> {
+    /// Парсит все выписки из строки, не копируя поля.
+    ///
+    /// Блоки, которые не удалось разобрать, пропускаются с предупреждением,
+    /// как в [`Mt940Statement::parse`].
+    pub fn parse(content: &'a str) -> Result<Vec<Self>> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит все выписки из строки с заданными ограничениями.
+    ///
+    /// Превышение ограничения в любом блоке прерывает разбор.
+    pub fn parse_with_limits(content: &'a str, limits: &Limits) -> Result<Vec<Self>> {
+        Self::parse_blocks(content, limits, DEFAULT_CENTURY_PIVOT)
+    }
+
+    /// Парсит все выписки из строки, относя двузначные годы не больше
+    /// `century_pivot` к 2000-м, а остальные к 1900-м.
+    pub fn parse_with_century_pivot(content: &'a str, century_pivot: u8) -> Result<Vec<Self>> {
+        Self::parse_blocks(content, &Limits::default(), century_pivot)
+    }
+
+    fn parse_blocks(content: &'a str, limits: &Limits, century_pivot: u8) -> Result<Vec<Self>> {
+        limits.check_input(content.len())?;
+        let blocks = Mt940Statement::split_blocks(content);
+        limits.check_statements(blocks.len())?;
+
+        Mt940Statement::collect_blocks(parallel::map_ordered(&blocks, |block| {
+            Self::parse_block_with(block, limits, century_pivot)
+        }))
+    }
+
+    /// Однопроходный разбор текстового блока `{4:` одной выписки.
+    pub fn parse_block(content: &'a str) -> Result<Self> {
+        Self::parse_block_with(content, &Limits::default(), DEFAULT_CENTURY_PIVOT)
+    }
+
+    /// Разбор блока `{4:` с проверкой длины полей, числа транзакций
+    /// и строк продолжения `:86:`.
+    pub(crate) fn parse_block_with(
+        content: &'a str,
+        limits: &Limits,
+        century_pivot: u8,
+    ) -> Result<Self> {
+        let mut reference = None;
+        let mut account_id = None;
+        let mut statement_number = "";
+        let mut opening_balance = None;
+        let mut closing_balance = None;
+        let mut transactions = Vec::new();
+
+        let mut current_tx_line: Option<&'a str> = None;
+        let mut current_details: Cow<'a, str> = Cow::Borrowed("");
+        let mut in_details = false;
+        let mut continuation_lines = 0;
+
+        for line in content.lines() {
+            let line = line.trim_end();
+            limits.check_field("строка MT940", line)?;
+
+            if let Some(stripped) = line.strip_prefix(":20:") {
+                reference = Some(stripped.trim());
+                in_details = false;
+            } else if let Some(stripped) = line.strip_prefix(":25:") {
+                account_id = Some(stripped.trim());
+                in_details = false;
+            } else if let Some(stripped) = line.strip_prefix(":28C:") {
+                statement_number = stripped.trim();
+                in_details = false;
+            } else if let Some(value) =
+                line.strip_prefix(":60F:").or_else(|| line.strip_prefix(":60M:"))
+            {
+                opening_balance = Some(Self::parse_balance_value(value, century_pivot)?);
+                in_details = false;
+            } else if let Some(value) =
+                line.strip_prefix(":62F:").or_else(|| line.strip_prefix(":62M:"))
+            {
+                closing_balance = Some(Self::parse_balance_value(value, century_pivot)?);
+                in_details = false;
+            } else if let Some(stripped) = line.strip_prefix(":61:") {
+                if let Some(tx_line) = current_tx_line.take() {
+                    let details = std::mem::take(&mut current_details);
+                    Self::push_transaction(&mut transactions, tx_line, details, century_pivot);
+                    limits.check_transactions(transactions.len())?;
+                }
+                current_tx_line = Some(stripped.trim());
+                in_details = false;
+            } else if let Some(stripped) = line.strip_prefix(":86:") {
+                current_details = Cow::Borrowed(stripped.trim());
+                in_details = true;
+                continuation_lines = 0;
+            } else if line.starts_with(':') {
+                in_details = false;
+            } else if in_details && !line.is_empty() {
+                continuation_lines += 1;
+                limits.check_continuation_lines(":86:", continuation_lines)?;
+
+                // Продолжение многострочного поля :86: требует склейки
+                let details = current_details.to_mut();
+                if !details.is_empty() {
+                    details.push(' ');
+                }
+                details.push_str(line.trim());
+                limits.check_field(":86:", details)?;
+            }
+        }
+
+        if let Some(tx_line) = current_tx_line {
+            Self::push_transaction(&mut transactions, tx_line, current_details, century_pivot);
+            limits.check_transactions(transactions.len())?;
+        }
+
+        Ok(Mt940StatementRef {
+            reference: Cow::Borrowed(
+                reference.ok_or_else(|| Error::MissingField(":20:".to_string()))?,
+            ),
+            account_id: Cow::Borrowed(
+                account_id.ok_or_else(|| Error::MissingField(":25:".to_string()))?,
+            ),
+            statement_number: Cow::Borrowed(statement_number),
+            opening_balance: opening_balance
+                .ok_or_else(|| Error::MissingField(":60F: или :60M:".to_string()))?,
+            closing_balance: closing_balance
+                .ok_or_else(|| Error::MissingField(":62F: или :62M:".to_string()))?,
+            transactions,
+        })
+    }
+
+    /// Преобразует выписку во владеющую [`Mt940Statement`].
+    pub fn into_owned(self) -> Mt940Statement {
+        Mt940Statement {
+            reference: self.reference.into_owned(),
+            account_id: self.account_id.into_owned(),
+            statement_number: self.statement_number.into_owned(),
+            opening_balance: self.opening_balance.into_owned(),
+            closing_balance: self.closing_balance.into_owned(),
+            transactions: self
+                .transactions
+                .into_iter()
+                .map(Mt940TransactionRef::into_owned)
+                .collect(),
+        }
+    }
+
+    fn push_transaction(
+        transactions: &mut Vec<Mt940TransactionRef<'a>>,
+        line: &'a str,
+        details: Cow<'a, str>,
+        century_pivot: u8,
+    ) {
+        match Self::parse_transaction_line(line, details, century_pivot) {
+            Ok(tx) => transactions.push(tx),
+            Err(e) => {
+                tracing::warn!("Не удалось распарсить транзакцию: {}", e);
+            }
+        }
+    }
+
+    /// Парсит значение баланса (без тега).
+    fn parse_balance_value(value: &'a str, century_pivot: u8) -> Result<Mt940BalanceRef<'a>> {
+        let value = value.trim();
+        let invalid = || Error::Parse(format!("Некорректный формат баланса: {}", value));
+
+        // Индикатор, дата и валюта занимают первые 10 символов ASCII
+        if value.len() < 10 || !value.is_char_boundary(10) || !value[..10].is_ascii() {
+            return Err(invalid());
+        }
+
+        Ok(Mt940BalanceRef {
+            credit_debit: char::from(value.as_bytes()[0]),
+            date: Mt940Statement::parse_date(&value[1..7], century_pivot)?,
+            currency: Cow::Borrowed(&value[7..10]),
+            amount: Mt940Statement::parse_amount(&value[10..])?,
+        })
+    }
+
+    /// Парсит строку `:61:` (без тега).
+    ///
+    /// Позиции полей считаются в байтах, поэтому все срезы берутся через
+    /// `get`: строка с не-ASCII символами дает ошибку, а не панику.
+    fn parse_transaction_line(
+        line: &'a str,
+        details: Cow<'a, str>,
+        century_pivot: u8,
+    ) -> Result<Mt940TransactionRef<'a>> {
+        let line = line.trim();
+        let bytes = line.as_bytes();
+        let invalid = || Error::Parse(format!("Некорректная строка транзакции: {}", line));
+
+        if line.len() < 16 {
+            return Err(Error::Parse(format!(
+                "Строка транзакции слишком короткая: {}",
+                line
+            )));
+        }
+
+        let value_date =
+            Mt940Statement::parse_date(line.get(0..6).ok_or_else(invalid)?, century_pivot)?;
+
+        let (entry_date, cd_pos) = if bytes[6].is_ascii_digit() {
+            (Self::parse_entry_date(line.get(6..10), value_date), 10)
+        } else {
+            (None, 6)
+        };
+
+        let credit_debit = match bytes.get(cd_pos) {
+            Some(&b) if b.is_ascii() => char::from(b),
+            _ => {
+                return Err(Error::Parse(
+                    "Отсутствует индикатор кредит/дебет в транзакции".to_string(),
+                ))
+            }
+        };
+
+        let amount_start = if bytes.get(cd_pos + 1) == Some(&b'R') {
+            cd_pos + 2
+        } else {
+            cd_pos + 1
+        };
+        let amount_part = line.get(amount_start..).ok_or_else(invalid)?;
+
+        let amount_end = amount_part
+            .find(|c: char| c.is_ascii_alphabetic())
+            .map(|pos| amount_start + pos)
+            .unwrap_or(line.len());
+
+        let amount = Mt940Statement::parse_amount(&line[amount_start..amount_end])?;
+
+        let type_end = (amount_end + 4).min(line.len());
+        let transaction_type = line.get(amount_end..type_end).unwrap_or_default();
+
+        Ok(Mt940TransactionRef {
+            date: entry_date.unwrap_or(value_date),
+            value_date: Some(value_date),
+            credit_debit,
+            amount,
+            transaction_type: Cow::Borrowed(transaction_type),
+            reference: line.find("//").map(|pos| Cow::Borrowed(&line[pos + 2..])),
+            details,
+        })
+    }
+}
+
+impl Mt940StatementRef<'_> {
+    /// Дата проводки `MMDD` без года.
+    ///
+    /// Год выбирается так, чтобы дата была ближе всего к дате валютирования:
+    /// проводка 31 декабря при валютировании 2 января относится к прошлому году.
+    fn parse_entry_date(mmdd: Option<&str>, value_date: Date) -> Option<Date> {
+        let mmdd = mmdd?;
+        let month: u8 = mmdd.get(0..2)?.parse().ok()?;
+        let day: u8 = mmdd.get(2..4)?.parse().ok()?;
+        let year = value_date.year();
+
+        [year - 1, year, year + 1]
+            .into_iter()
+            .filter_map(|year| Date::from_ymd(year, month, day).ok())
+            .min_by_key(|date| date.days_since(&value_date).abs())
+    }
+}
+
+impl Mt940BalanceRef<'_> {
+    /// Преобразует баланс во владеющий [`Mt940Balance`].
+    pub fn into_owned(self) -> Mt940Balance {
+        Mt940Balance {
+            credit_debit: self.credit_debit,
+            date: self.date,
+            currency: self.currency.into_owned(),
+            amount: self.amount,
+        }
+    }
+}
+
+impl Mt940TransactionRef<'_> {
+    /// Преобразует транзакцию во владеющую [`Mt940Transaction`].
+    pub fn into_owned(self) -> Mt940Transaction {
+        Mt940Transaction {
+            date: self.date,
+            value_date: self.value_date,
+            credit_debit: self.credit_debit,
+            amount: self.amount,
+            transaction_type: self.transaction_type.into_owned(),
+            reference: self.reference.map(Cow::into_owned),
+            details: self.details.into_owned(),
+        }
+    }
+}
+
+impl<'a> From<Mt940StatementRef<'a>> for Mt940Statement {
+    fn from(statement: Mt940StatementRef<'a>) -> Self {
+        statement.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: &str = ":20:REF\n:25:NL81ASNB9999999999\n:28C:1/1\n:60F:C200101EUR444,29\n\
+        :61:2001010101D65,00NOVBNL47INGB9999999999\n:86:hr gjlm\npaulissen\n\
+        :61:2001010101C10,00NTRF//ABC\n:86:single\n:62F:C200101EUR389,29\n";
+
+    #[test]
+    fn test_fields_borrow_input() {
+        let statement = Mt940StatementRef::parse_block(BLOCK).unwrap();
+        assert!(matches!(statement.reference, Cow::Borrowed("REF")));
+        assert!(matches!(statement.opening_balance.currency, Cow::Borrowed("EUR")));
+        assert_eq!(statement.transactions.len(), 2);
+
+        let multiline = &statement.transactions[0];
+        assert!(matches!(multiline.details, Cow::Owned(_)));
+        assert_eq!(multiline.details, "hr gjlm paulissen");
+
+        let single = &statement.transactions[1];
+        assert!(matches!(single.details, Cow::Borrowed("single")));
+        assert_eq!(single.reference.as_deref(), Some("ABC"));
+        assert_eq!(single.amount, 1000);
+    }
+
+    #[test]
+    fn test_parse_matches_owned_parser() {
+        let content = format!("{{1:F01BANK}}{{4:\n{}-}}{{5:}}{{4:\n{}", BLOCK, BLOCK);
+        let borrowed: Vec<Mt940Statement> = Mt940StatementRef::parse(&content)
+            .unwrap()
+            .into_iter()
+            .map(Mt940StatementRef::into_owned)
+            .collect();
+        let owned = Mt940Statement::parse(&content).unwrap();
+        assert_eq!(borrowed.len(), 2);
+        assert_eq!(format!("{:?}", borrowed), format!("{:?}", owned));
+    }
+}
@@ -2,9 +2,13 @@
 //!
 //! MT940 - текстовый формат SWIFT для банковских выписок.
 
+pub mod borrowed;
 pub mod parser;
+pub mod reader;
 pub mod writer;
 
+pub use borrowed::{Mt940BalanceRef, Mt940StatementRef, Mt940TransactionRef};
 pub use parser::{Mt940Balance, Mt940Statement, Mt940Transaction};
+pub use reader::Mt940Reader;
 pub use writer::Mt940Writer;
 
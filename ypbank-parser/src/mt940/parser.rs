@@ -1,8 +1,16 @@
 //! Парсер формата MT940.
 
+use crate::encoding::{self, TextEncoding};
 use crate::error::{Error, Result};
-use crate::types::{Account, Amount, Balance, Counterparty, Date, Statement, Transaction};
+use crate::mt940::borrowed::Mt940StatementRef;
+use crate::limits::Limits;
+use crate::parallel;
+use crate::types::{
+    Account, Amount, Balance, Counterparty, Date, Statement, Transaction, DEFAULT_CENTURY_PIVOT,
+};
 use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
 
 /// Выписка в формате MT940.
 #[derive(Debug, Clone)]
@@ -55,25 +63,87 @@ pub struct Mt940Transaction {
 
 impl Mt940Statement {
     /// Парсит MT940 из любого источника, реализующего трейт Read.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
     pub fn from_read<R: Read>(reader: &mut R) -> Result<Vec<Self>> {
-        let mut content = String::new();
-        reader.read_to_string(&mut content)?;
+        let content = encoding::read_to_string(reader, None)?;
         Self::parse(&content)
     }
 
+    /// Асинхронно парсит MT940 из любого источника, реализующего трейт AsyncRead.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит MT940 из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Vec<Self>> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
     /// Парсит MT940 из строки.
+    ///
+    /// Блоки `{4:` независимы: с feature `parallel` они разбираются
+    /// параллельно, порядок выписок сохраняется. Для больших файлов
+    /// используйте [`Mt940Reader`](crate::mt940::Mt940Reader), который
+    /// возвращает выписки по одной.
     pub fn parse(content: &str) -> Result<Vec<Self>> {
-        let mut statements = Vec::new();
-        let blocks: Vec<&str> = content.split("{4:").collect();
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит MT940 из строки с заданными ограничениями.
+    ///
+    /// Превышение ограничения в любом блоке прерывает разбор
+    /// с [`Error::LimitExceeded`], а не пропускает блок.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Vec<Self>> {
+        Self::parse_blocks(content, limits, DEFAULT_CENTURY_PIVOT)
+    }
 
-        for block in blocks.iter().skip(1) {
-            let end_pos = block.find("-}").unwrap_or(block.len());
-            let block_content = &block[..end_pos];
+    /// Парсит MT940 из строки с заданной границей века.
+    ///
+    /// Даты MT940 содержат двузначный год: годы не больше `century_pivot`
+    /// относятся к 2000-м, остальные — к 1900-м. По умолчанию граница равна
+    /// [`DEFAULT_CENTURY_PIVOT`].
+    pub fn parse_with_century_pivot(content: &str, century_pivot: u8) -> Result<Vec<Self>> {
+        Self::parse_blocks(content, &Limits::default(), century_pivot)
+    }
+
+    fn parse_blocks(content: &str, limits: &Limits, century_pivot: u8) -> Result<Vec<Self>> {
+        limits.check_input(content.len())?;
+        let blocks = Self::split_blocks(content);
+        limits.check_statements(blocks.len())?;
+
+        Self::collect_blocks(parallel::map_ordered(&blocks, |block| {
+            Self::parse_single_statement(block, limits, century_pivot)
+        }))
+    }
+
+    /// Текстовые блоки `{4:` ... `-}` всех выписок.
+    pub(super) fn split_blocks(content: &str) -> Vec<&str> {
+        content
+            .split("{4:")
+            .skip(1)
+            .map(|block| &block[..block.find("-}").unwrap_or(block.len())])
+            .collect()
+    }
 
-            match Self::parse_single_statement(block_content) {
+    /// Отбрасывает блоки с ошибками, сообщая номер каждого.
+    ///
+    /// Превышение ограничений не пропускается, а возвращается как ошибка.
+    pub(super) fn collect_blocks<T>(results: Vec<Result<T>>) -> Result<Vec<T>> {
+        let mut statements = Vec::with_capacity(results.len());
+
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
                 Ok(stmt) => statements.push(stmt),
+                Err(e @ Error::LimitExceeded(_)) => return Err(e),
                 Err(e) => {
-                    tracing::warn!("Не удалось распарсить блок MT940: {}", e);
+                    tracing::warn!("Не удалось распарсить блок MT940 №{}: {}", index + 1, e);
                 }
             }
         }
@@ -89,135 +159,31 @@ impl Mt940Statement {
 
     /// Однопроходный парсер MT940 блока.
     ///
-    /// Итерируется по строкам контента один раз, распознавая теги
-    /// :20:, :25:, :28C:, :60F:/:60M:, :61:, :86:, :62F:/:62M:
-    /// по мере прохода. Это уменьшает сложность с O(n*m) до O(n),
-    /// где n - длина контента, m - количество тегов.
-    fn parse_single_statement(content: &str) -> Result<Self> {
-        let mut reference = None;
-        let mut account_id = None;
-        let mut statement_number = String::new();
-        let mut opening_balance = None;
-        let mut closing_balance = None;
-        let mut transactions = Vec::new();
-
-        let mut current_tx_line: Option<String> = None;
-        let mut current_details = String::new();
-        let mut in_details = false;
-
-        for line in content.lines() {
-            let line = line.trim_end();
-
-            // Распознаём тег в начале строки
-            if let Some(stripped) = line.strip_prefix(":20:") {
-                reference = Some(stripped.trim().to_string());
-                in_details = false;
-            } else if let Some(stripped) = line.strip_prefix(":25:") {
-                account_id = Some(stripped.trim().to_string());
-                in_details = false;
-            } else if let Some(stripped) = line.strip_prefix(":28C:") {
-                statement_number = stripped.trim().to_string();
-                in_details = false;
-            } else if line.starts_with(":60F:") || line.starts_with(":60M:") {
-                let value = &line[5..];
-                opening_balance = Some(Self::parse_balance_value(value)?);
-                in_details = false;
-            } else if line.starts_with(":62F:") || line.starts_with(":62M:") {
-                let value = &line[5..];
-                closing_balance = Some(Self::parse_balance_value(value)?);
-                in_details = false;
-            } else if let Some(stripped) = line.strip_prefix(":61:") {
-                // Сохраняем предыдущую транзакцию, если была
-                if let Some(tx_line) = current_tx_line.take() {
-                    match Self::parse_transaction_line(&tx_line, &current_details) {
-                        Ok(tx) => transactions.push(tx),
-                        Err(e) => {
-                            tracing::warn!("Не удалось распарсить транзакцию: {}", e);
-                        }
-                    }
-                    current_details.clear();
-                }
-                current_tx_line = Some(stripped.trim().to_string());
-                in_details = false;
-            } else if let Some(stripped) = line.strip_prefix(":86:") {
-                current_details = stripped.trim().to_string();
-                in_details = true;
-            } else if line.starts_with(':') {
-                // Другой тег - завершаем details
-                in_details = false;
-            } else if in_details && !line.is_empty() {
-                // Продолжение details (многострочное поле :86:)
-                if !current_details.is_empty() {
-                    current_details.push(' ');
-                }
-                current_details.push_str(line.trim());
-            }
-        }
-
-        // Сохраняем последнюю транзакцию
-        if let Some(tx_line) = current_tx_line {
-            match Self::parse_transaction_line(&tx_line, &current_details) {
-                Ok(tx) => transactions.push(tx),
-                Err(e) => {
-                    tracing::warn!("Не удалось распарсить транзакцию: {}", e);
-                }
-            }
-        }
-
-        Ok(Mt940Statement {
-            reference: reference.ok_or_else(|| Error::MissingField(":20:".to_string()))?,
-            account_id: account_id.ok_or_else(|| Error::MissingField(":25:".to_string()))?,
-            statement_number,
-            opening_balance: opening_balance
-                .ok_or_else(|| Error::MissingField(":60F: или :60M:".to_string()))?,
-            closing_balance: closing_balance
-                .ok_or_else(|| Error::MissingField(":62F: или :62M:".to_string()))?,
-            transactions,
-        })
-    }
-
-    /// Парсит значение баланса (без тега).
-    fn parse_balance_value(value: &str) -> Result<Mt940Balance> {
-        let value = value.trim();
-
-        if value.len() < 10 {
-            return Err(Error::Parse(format!(
-                "Некорректный формат баланса: {}",
-                value
-            )));
-        }
-
-        let credit_debit = value.chars().next().ok_or_else(|| {
-            Error::Parse("Отсутствует индикатор кредит/дебет".to_string())
-        })?;
-
-        let date_str = &value[1..7];
-        let date = Self::parse_date(date_str)?;
-        let currency = value[7..10].to_string();
-        let amount_str = value[10..].replace(',', ".");
-        let amount = Self::parse_amount(&amount_str)?;
-
-        Ok(Mt940Balance {
-            credit_debit,
-            date,
-            currency,
-            amount,
-        })
+    /// Разбор выполняет [`Mt940StatementRef::parse_block`], поля которого
+    /// затем копируются во владеющую выписку.
+    pub(crate) fn parse_single_statement(
+        content: &str,
+        limits: &Limits,
+        century_pivot: u8,
+    ) -> Result<Self> {
+        Mt940StatementRef::parse_block_with(content, limits, century_pivot)
+            .map(Mt940StatementRef::into_owned)
     }
 
-    fn parse_date(date_str: &str) -> Result<Date> {
-        if date_str.len() != 6 {
+    /// Парсит дату `YYMMDD`; век определяется по `century_pivot`.
+    pub(super) fn parse_date(date_str: &str, century_pivot: u8) -> Result<Date> {
+        if date_str.len() != 6 || !date_str.is_ascii() {
             return Err(Error::Parse(format!(
                 "Некорректный формат даты: {}",
                 date_str
             )));
         }
 
-        let year: u16 = date_str[0..2]
+        let year: u8 = date_str[0..2]
             .parse()
             .map_err(|_| Error::Parse(format!("Некорректный год: {}", &date_str[0..2])))?;
 
-        let year = if year > 50 { 1900 + year } else { 2000 + year };
+        let year = Date::expand_year(year, century_pivot);
 
         let month: u8 = date_str[2..4]
             .parse()
@@ -227,71 +193,44 @@ impl Mt940Statement {
             .parse()
             .map_err(|_| Error::Parse(format!("Некорректный день: {}", &date_str[4..6])))?;
 
-        Ok(Date::new(year, month, day))
+        Date::from_ymd(year, month, day)
     }
 
-    fn parse_amount(amount_str: &str) -> Result<i64> {
+    /// Парсит сумму с десятичной запятой или точкой.
+    ///
+    /// Обычная запись `123,45` разбирается без выделения памяти;
+    /// нестандартная — через f64, как раньше.
+    pub(super) fn parse_amount(amount_str: &str) -> Result<i64> {
+        let amount_str = amount_str.trim();
+        if let Some(amount) = Self::parse_decimal(amount_str) {
+            return Ok(amount);
+        }
+
         let amount: f64 = amount_str
-            .trim()
+            .replace(',', ".")
             .parse()
             .map_err(|_| Error::Parse(format!("Некорректная сумма: {}", amount_str)))?;
 
         Ok((amount * 100.0).round() as i64)
     }
 
-    fn parse_transaction_line(line: &str, details: &str) -> Result<Mt940Transaction> {
-        let line = line.trim();
-
-        if line.len() < 16 {
-            return Err(Error::Parse(format!(
-                "Строка транзакции слишком короткая: {}",
-                line
-            )));
-        }
-
-        let value_date = Self::parse_date(&line[0..6])?;
-
-        let (entry_date, cd_pos) = if line.chars().nth(6).map(|c| c.is_ascii_digit()).unwrap_or(false) {
-            let month: u8 = line[6..8].parse().unwrap_or(value_date.month);
-            let day: u8 = line[8..10].parse().unwrap_or(value_date.day);
-            (Some(Date::new(value_date.year, month, day)), 10)
-        } else {
-            (None, 6)
+    /// Точный разбор `цифры[,цифры]` в минимальные единицы с округлением
+    /// до сотых.
+    fn parse_decimal(s: &str) -> Option<i64> {
+        let (whole, fraction) = match s.find([',', '.']) {
+            Some(pos) => (&s[..pos], &s[pos + 1..]),
+            None => (s, ""),
         };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return None;
+        }
 
-        let credit_debit = line.chars().nth(cd_pos).ok_or_else(|| {
-            Error::Parse("Отсутствует индикатор кредит/дебет в транзакции".to_string())
-        })?;
-
-        let amount_start = if line.chars().nth(cd_pos + 1) == Some('R') {
-            cd_pos + 2
-        } else {
-            cd_pos + 1
-        };
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+        let digit = |i: usize| fraction.as_bytes().get(i).map_or(0, |b| i64::from(b - b'0'));
+        let cents = digit(0) * 10 + digit(1) + i64::from(digit(2) >= 5);
 
-        let amount_end = line[amount_start..]
-            .find(|c: char| c.is_ascii_alphabetic())
-            .map(|pos| amount_start + pos)
-            .unwrap_or(line.len());
-
-        let amount_str = line[amount_start..amount_end].replace(',', ".");
-        let amount = Self::parse_amount(&amount_str)?;
-
-        let type_start = amount_end;
-        let type_end = (type_start + 4).min(line.len());
-        let transaction_type = line[type_start..type_end].to_string();
-
-        let reference = line.find("//").map(|pos| line[pos + 2..].to_string());
-
-        Ok(Mt940Transaction {
-            date: entry_date.unwrap_or_else(|| value_date.clone()),
-            value_date: Some(value_date),
-            credit_debit,
-            amount,
-            transaction_type,
-            reference,
-            details: details.to_string(),
-        })
+        whole.checked_mul(100)?.checked_add(cents)
     }
 }
 
@@ -310,6 +249,7 @@ impl From<Mt940Statement> for Statement {
             currency: mt940.opening_balance.currency.clone(),
             name: None,
             owner: None,
+            tax_id: None,
         };
 
         let opening_balance = Balance {
@@ -346,8 +286,7 @@ impl From<Mt940Statement> for Statement {
                     Some(Counterparty {
                         name: Some(tx.details.clone()),
                         account: tx.reference.clone(),
-                        bank_code: None,
-                        bank_name: None,
+                        ..Default::default()
                     })
                 } else {
                     None
@@ -359,8 +298,14 @@ impl From<Mt940Statement> for Statement {
                     amount: Amount::new(tx.amount, &mt940.opening_balance.currency),
                     is_credit: tx.credit_debit == 'C',
                     reference: tx.reference,
+                    transaction_type: Some(tx.transaction_type).filter(|t| !t.is_empty()),
                     description: tx.details,
                     counterparty,
+                    instructed_amount: None,
+                    original_amount: None,
+                    counter_value_amount: None,
+                    exchange_rates: Vec::new(),
+                    charges: Vec::new(),
                 }
             })
             .collect();
@@ -382,15 +327,30 @@ mod tests {
 
     #[test]
     fn test_parse_date() {
-        let date = Mt940Statement::parse_date("200101").unwrap();
-        assert_eq!(date.year, 2020);
-        assert_eq!(date.month, 1);
-        assert_eq!(date.day, 1);
+        let date = Mt940Statement::parse_date("200101", DEFAULT_CENTURY_PIVOT).unwrap();
+        assert_eq!(date.year(), 2020);
+        assert_eq!(date.month(), 1);
+        assert_eq!(date.day(), 1);
+
+        assert_eq!(
+            Mt940Statement::parse_date("690101", DEFAULT_CENTURY_PIVOT).unwrap(),
+            Date::new(1969, 1, 1)
+        );
+        assert_eq!(
+            Mt940Statement::parse_date("690101", 79).unwrap(),
+            Date::new(2069, 1, 1)
+        );
+        assert!(Mt940Statement::parse_date("240230", DEFAULT_CENTURY_PIVOT).is_err());
     }
 
     #[test]
     fn test_parse_amount() {
         let amount = Mt940Statement::parse_amount("444.29").unwrap();
         assert_eq!(amount, 44429);
+        assert_eq!(Mt940Statement::parse_amount("65,").unwrap(), 6500);
+        assert_eq!(Mt940Statement::parse_amount("0,5").unwrap(), 50);
+        assert_eq!(Mt940Statement::parse_amount("1,005").unwrap(), 101);
+        assert_eq!(Mt940Statement::parse_amount("1e2").unwrap(), 10000);
+        assert!(Mt940Statement::parse_amount("abc").is_err());
     }
 }
@@ -0,0 +1,213 @@
+//! Потоковое чтение выписок MT940.
+
+use crate::encoding::{TextEncoding, TextReader};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::mt940::parser::Mt940Statement;
+use crate::types::DEFAULT_CENTURY_PIVOT;
+use std::io::BufRead;
+
+/// Начало текстового блока сообщения SWIFT.
+const BLOCK_START: &str = "{4:";
+/// Конец текстового блока сообщения SWIFT.
+const BLOCK_END: &str = "-}";
+
+/// Итератор по выпискам MT940 из любого источника `BufRead`.
+///
+/// Читает вход построчно и держит в памяти только текущий блок `{4:...-}`,
+/// поэтому объем памяти ограничен размером одной выписки, а не файла.
+/// Блоки, которые не удалось разобрать, пропускаются с предупреждением,
+/// как в [`Mt940Statement::parse`]; если во входе нет ни одной выписки,
+/// последним элементом возвращается ошибка.
+///
+/// # Пример
+///
+/// ```ignore
+/// let file = std::io::BufReader::new(std::fs::File::open("statement.sta")?);
+/// for statement in Mt940Reader::new(file) {
+///     let statement = statement?;
+///     println!("{}: {} операций", statement.account_id, statement.transactions.len());
+/// }
+/// ```
+pub struct Mt940Reader<R> {
+    lines: TextReader<R>,
+    pending: String,
+    block: String,
+    in_block: bool,
+    blocks: usize,
+    parsed: usize,
+    finished: bool,
+    limits: Limits,
+    century_pivot: u8,
+}
+
+impl<R: BufRead> Mt940Reader<R> {
+    /// Создает итератор с автоматическим определением кодировки.
+    pub fn new(reader: R) -> Self {
+        Self::with_encoding(reader, None)
+    }
+
+    /// Создает итератор для входа в заданной кодировке.
+    pub fn with_encoding(reader: R, encoding: Option<TextEncoding>) -> Self {
+        Self {
+            lines: TextReader::new(reader, encoding),
+            pending: String::new(),
+            block: String::new(),
+            in_block: false,
+            blocks: 0,
+            parsed: 0,
+            finished: false,
+            limits: Limits::default(),
+            century_pivot: DEFAULT_CENTURY_PIVOT,
+        }
+    }
+
+    /// Задает ограничения разбора.
+    ///
+    /// `max_input_bytes` ограничивает размер одного блока `{4:`, а не всего
+    /// входа, а `max_statements` не применяется: поток может быть сколь
+    /// угодно длинным.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Задает границу века для двузначных годов
+    /// (см. [`Mt940Statement::parse_with_century_pivot`]).
+    pub fn with_century_pivot(mut self, century_pivot: u8) -> Self {
+        self.century_pivot = century_pivot;
+        self
+    }
+
+    /// Возвращает содержимое следующего блока `{4:` без маркеров.
+    ///
+    /// Блок заканчивается на `-}` или на начале следующего блока.
+    fn next_block(&mut self) -> Result<Option<String>> {
+        loop {
+            if self.pending.is_empty() && self.lines.read_line(&mut self.pending)? == 0 {
+                return Ok(if self.in_block {
+                    self.in_block = false;
+                    Some(std::mem::take(&mut self.block))
+                } else {
+                    None
+                });
+            }
+
+            let line = std::mem::take(&mut self.pending);
+            if !self.in_block {
+                if let Some(pos) = line.find(BLOCK_START) {
+                    self.in_block = true;
+                    self.pending = line[pos + BLOCK_START.len()..].to_string();
+                }
+                continue;
+            }
+
+            let end = line.find(BLOCK_END);
+            let next = line.find(BLOCK_START);
+            match (end, next) {
+                (Some(end), next) if next.is_none_or(|next| end < next) => {
+                    self.block.push_str(&line[..end]);
+                    self.pending = line[end + BLOCK_END.len()..].to_string();
+                    self.in_block = false;
+                    return Ok(Some(std::mem::take(&mut self.block)));
+                }
+                (_, Some(next)) => {
+                    self.block.push_str(&line[..next]);
+                    self.pending = line[next + BLOCK_START.len()..].to_string();
+                    return Ok(Some(std::mem::take(&mut self.block)));
+                }
+                _ => self.block.push_str(&line),
+            }
+            self.limits.check_input(self.block.len())?;
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Mt940Reader<R> {
+    type Item = Result<Mt940Statement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            match self.next_block() {
+                Ok(Some(block)) => {
+                    self.blocks += 1;
+                    let statement = Mt940Statement::parse_single_statement(
+                        &block,
+                        &self.limits,
+                        self.century_pivot,
+                    );
+                    match statement {
+                        Ok(statement) => {
+                            self.parsed += 1;
+                            return Some(Ok(statement));
+                        }
+                        Err(e @ Error::LimitExceeded(_)) => {
+                            self.finished = true;
+                            return Some(Err(e));
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                "Не удалось распарсить блок MT940 №{}: {}",
+                                self.blocks,
+                                e
+                            );
+                        }
+                    }
+                }
+                Ok(None) => {
+                    self.finished = true;
+                    if self.parsed == 0 {
+                        return Some(Err(Error::InvalidFormat(
+                            "Не найдено ни одной валидной выписки MT940".to_string(),
+                        )));
+                    }
+                    return None;
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_BLOCKS: &str = "{1:F01BANK}{2:O940}{4:\n:20:A\n:25:ACC\n:28C:1\n\
+        :60F:C240101EUR1,00\n:62F:C240101EUR1,00\n-}{5:}{1:F01BANK}{4:\n:20:B\n:25:ACC\n\
+        :28C:2\n:60F:C240102EUR1,00\n:62F:C240102EUR1,00\n-}\n";
+
+    #[test]
+    fn test_blocks_on_shared_lines() {
+        let statements: Vec<_> = Mt940Reader::new(TWO_BLOCKS.as_bytes())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].reference, "A");
+        assert_eq!(statements[1].statement_number, "2");
+    }
+
+    #[test]
+    fn test_small_buffer() {
+        let reader = std::io::BufReader::with_capacity(4, TWO_BLOCKS.as_bytes());
+        let streamed: Vec<_> = Mt940Reader::new(reader).collect::<Result<_>>().unwrap();
+        let whole: Vec<_> = Mt940Reader::new(TWO_BLOCKS.as_bytes())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(format!("{:?}", streamed), format!("{:?}", whole));
+    }
+
+    #[test]
+    fn test_no_statements() {
+        let mut reader = Mt940Reader::new("no swift here\n".as_bytes());
+        assert!(matches!(reader.next(), Some(Err(Error::InvalidFormat(_)))));
+        assert!(reader.next().is_none());
+    }
+}
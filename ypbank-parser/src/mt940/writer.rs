@@ -1,8 +1,14 @@
 //! Сериализация формата MT940.
 
+use crate::encoding::{self, TextEncoding};
 use crate::error::Result;
 use crate::mt940::parser::{Mt940Balance, Mt940Statement, Mt940Transaction};
 use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Максимальная длина строки поля :86: в символах.
+const MT940_DETAILS_LINE_LEN: usize = 65;
 
 /// Writer для формата MT940.
 pub struct Mt940Writer;
@@ -18,6 +24,65 @@ impl Mt940Writer {
         Ok(())
     }
 
+    /// Асинхронно записывает выписку MT940 в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statement: &Mt940Statement,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statement, buffer)).await
+    }
+
+    /// Записывает выписку MT940 в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        statement: &Mt940Statement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(statement, buffer)
+        })
+    }
+
+    /// Асинхронно записывает выписку MT940 в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statement: &Mt940Statement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statement, buffer, encoding)
+        })
+        .await
+    }
+
+    /// Записывает выписки из итератора по мере их поступления.
+    ///
+    /// Каждая выписка кодируется и сбрасывается в приемник отдельно, поэтому
+    /// в памяти находится только текущая. При ошибке во входном итераторе
+    /// запись прекращается; уже записанные выписки остаются в приемнике.
+    /// Возвращает число записанных выписок.
+    pub fn write_iter_with_encoding<W, I, S>(
+        statements: I,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<usize>
+    where
+        W: Write,
+        I: IntoIterator<Item = Result<S>>,
+        S: Into<Mt940Statement>,
+    {
+        let mut written = 0;
+        for statement in statements {
+            Self::write_to_with_encoding(&statement?.into(), writer, encoding)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
     fn write_to_buffered<W: Write>(statement: &Mt940Statement, writer: &mut W) -> Result<()> {
         writeln!(writer, "{{1:F01BANKXXXX0000000000}}")?;
         writeln!(writer, "{{2:O940BANKXXXXN}}")?;
@@ -44,9 +109,9 @@ impl Mt940Writer {
     fn write_balance<W: Write>(writer: &mut W, tag: &str, balance: &Mt940Balance) -> Result<()> {
         let date_str = format!(
             "{:02}{:02}{:02}",
-            balance.date.year % 100,
-            balance.date.month,
-            balance.date.day
+            balance.date.year() % 100,
+            balance.date.month(),
+            balance.date.day()
         );
 
         let amount_str = Self::format_amount(balance.amount);
@@ -63,15 +128,15 @@ impl Mt940Writer {
     fn write_transaction<W: Write>(writer: &mut W, transaction: &Mt940Transaction) -> Result<()> {
         let value_date_str = format!(
             "{:02}{:02}{:02}",
-            transaction.date.year % 100,
-            transaction.date.month,
-            transaction.date.day
+            transaction.date.year() % 100,
+            transaction.date.month(),
+            transaction.date.day()
         );
 
         let entry_date_str = transaction
             .value_date
             .as_ref()
-            .map(|d| format!("{:02}{:02}", d.month, d.day))
+            .map(|d| format!("{:02}{:02}", d.month(), d.day()))
             .unwrap_or_default();
 
         let amount_str = Self::format_amount(transaction.amount);
@@ -93,21 +158,48 @@ impl Mt940Writer {
             reference_str
         )?;
 
-        if !transaction.details.is_empty() {
-            let details = &transaction.details;
-            let mut pos = 0;
-            writeln!(writer, ":86:{}", &details[pos..(pos + 65).min(details.len())])?;
-            pos += 65;
-
-            while pos < details.len() {
-                writeln!(writer, "{}", &details[pos..(pos + 65).min(details.len())])?;
-                pos += 65;
-            }
+        for (idx, line) in Self::details_lines(&transaction.details).enumerate() {
+            let prefix = if idx == 0 { ":86:" } else { "" };
+            writeln!(writer, "{}{}", prefix, line)?;
         }
 
         Ok(())
     }
 
+    /// Делит детали :86: на строки до 65 символов (не байт).
+    ///
+    /// Перенос делается по последнему пробелу, который при чтении
+    /// восстанавливается склейкой строк; слово длиннее строки режется.
+    fn details_lines(details: &str) -> impl Iterator<Item = &str> {
+        let mut rest = details;
+        std::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+            let Some((limit, _)) = rest.char_indices().nth(MT940_DETAILS_LINE_LEN) else {
+                return Some(std::mem::take(&mut rest));
+            };
+            let space = if rest[limit..].starts_with(' ') {
+                Some(limit)
+            } else {
+                rest[..limit].rfind(' ')
+            };
+            let line = match space {
+                Some(space) if space > 0 => {
+                    let line = &rest[..space];
+                    rest = &rest[space + 1..];
+                    line
+                }
+                _ => {
+                    let line = &rest[..limit];
+                    rest = &rest[limit..];
+                    line
+                }
+            };
+            Some(line)
+        })
+    }
+
     fn format_amount(amount: i64) -> String {
         let whole = amount / 100;
         let frac = (amount % 100).abs();
@@ -120,4 +212,47 @@ impl Mt940Statement {
     pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
         Mt940Writer::write_to(self, writer)
     }
+
+    /// Асинхронно записывает выписку в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает выписку MT940 в указанной кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        Mt940Writer::write_to_with_encoding(self, writer, encoding)
+    }
+
+    /// Асинхронно записывает выписку MT940 в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to_with_encoding(buffer, encoding)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_details_lines_split_by_chars() {
+        let word = "Ж".repeat(70);
+        let lines: Vec<&str> = Mt940Writer::details_lines(&word).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].chars().count(), MT940_DETAILS_LINE_LEN);
+        assert_eq!(lines[1].chars().count(), 5);
+
+        let exact = "Ж".repeat(MT940_DETAILS_LINE_LEN);
+        assert_eq!(Mt940Writer::details_lines(&exact).count(), 1);
+        assert_eq!(Mt940Writer::details_lines("").count(), 0);
+    }
 }
@@ -0,0 +1,10 @@
+//! Модуль парсинга испанского формата AEB Norma 43 (Cuaderno 43).
+//!
+//! Файл состоит из записей фиксированной длины 80 символов: заголовок счета (11),
+//! движения (22) с дополнительными описаниями (23) и исходной валютой (24),
+//! итоги по счету (33) и конец файла (88) с количеством записей.
+//! Формат поддерживается только для чтения.
+
+pub mod parser;
+
+pub use parser::{Norma43Movement, Norma43Statement};
@@ -0,0 +1,511 @@
+//! Парсер испанского формата AEB Norma 43 (Cuaderno 43).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::record::{self, RecordFormat};
+use crate::types::{
+    Account, Amount, Balance, Date, Statement, Transaction, TRANSACTION_TYPE_CASH,
+    TRANSACTION_TYPE_CHARGES, TRANSACTION_TYPE_CHEQUE, TRANSACTION_TYPE_COLLECTION,
+    TRANSACTION_TYPE_DIRECT_DEBIT, TRANSACTION_TYPE_DIVIDEND, TRANSACTION_TYPE_FOREIGN_EXCHANGE,
+    TRANSACTION_TYPE_LOAN, TRANSACTION_TYPE_MISC, TRANSACTION_TYPE_RETURNED,
+    TRANSACTION_TYPE_SECURITIES, TRANSACTION_TYPE_TRANSFER,
+};
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Длина записи Norma 43.
+pub const NORMA43_RECORD_LEN: usize = 80;
+
+/// Выписка по счету Norma 43 (записи 11 ... 33).
+///
+/// Суммы хранятся со знаком в минимальных единицах валюты.
+#[derive(Debug, Clone)]
+pub struct Norma43Statement {
+    /// Код банка (entidad).
+    pub bank_code: String,
+    /// Код отделения (oficina).
+    pub branch_code: String,
+    /// Номер счета (10 цифр).
+    pub account_number: String,
+    /// Начальная дата периода.
+    pub start_date: Date,
+    /// Конечная дата периода.
+    pub end_date: Date,
+    /// Буквенный код валюты.
+    pub currency: String,
+    /// Сокращенное наименование владельца счета.
+    pub account_name: Option<String>,
+    /// Входящий остаток (запись 11).
+    pub opening_balance: i64,
+    /// Исходящий остаток (запись 33).
+    pub closing_balance: i64,
+    /// Движения (записи 22, 23 и 24).
+    pub movements: Vec<Norma43Movement>,
+}
+
+/// Движение по счету (запись 22 с дополнительными записями 23 и 24).
+#[derive(Debug, Clone)]
+pub struct Norma43Movement {
+    /// Код отделения, в котором совершена операция.
+    pub origin_branch: String,
+    /// Дата операции.
+    pub operation_date: Date,
+    /// Дата валютирования.
+    pub value_date: Date,
+    /// Общий код операции (concepto común, 01–99).
+    pub common_concept: String,
+    /// Собственный код операции банка (concepto propio).
+    pub own_concept: String,
+    /// Сумма со знаком.
+    pub amount: i64,
+    /// Номер документа.
+    pub document_number: Option<String>,
+    /// Референс 1.
+    pub reference1: Option<String>,
+    /// Референс 2.
+    pub reference2: Option<String>,
+    /// Дополнительные описания (записи 23).
+    pub concepts: Vec<String>,
+    /// Валюта исходной суммы (запись 24).
+    pub original_currency: Option<String>,
+    /// Исходная сумма без знака (запись 24).
+    pub original_amount: Option<i64>,
+}
+
+impl Norma43Movement {
+    /// Код типа транзакции SWIFT для общего кода операции.
+    pub fn transaction_type(&self) -> &'static str {
+        match self.common_concept.as_str() {
+            // Talones, reintegros; cheques gasolina
+            "01" | "10" => TRANSACTION_TYPE_CHEQUE,
+            // Abonarés, entregas, ingresos; cajero automático
+            "02" | "11" => TRANSACTION_TYPE_CASH,
+            // Domiciliados, recibos
+            "03" => TRANSACTION_TYPE_DIRECT_DEBIT,
+            // Giros, transferencias, traspasos
+            "04" => TRANSACTION_TYPE_TRANSFER,
+            // Amortizaciones de préstamos
+            "05" => TRANSACTION_TYPE_LOAN,
+            // Remesas de efectos
+            "06" => TRANSACTION_TYPE_COLLECTION,
+            // Dividendos, cupones
+            "08" => TRANSACTION_TYPE_DIVIDEND,
+            // Suscripciones; operaciones de bolsa
+            "07" | "09" => TRANSACTION_TYPE_SECURITIES,
+            // Operaciones en el extranjero
+            "13" => TRANSACTION_TYPE_FOREIGN_EXCHANGE,
+            // Devoluciones e impagados
+            "14" => TRANSACTION_TYPE_RETURNED,
+            // Timbres, corretajes; intereses, comisiones, gastos
+            "16" | "17" => TRANSACTION_TYPE_CHARGES,
+            _ => TRANSACTION_TYPE_MISC,
+        }
+    }
+}
+
+type Record = record::Record<Norma43Statement>;
+
+impl RecordFormat for Norma43Statement {
+    const NAME: &'static str = "Norma 43";
+    const RECORD_LEN: usize = NORMA43_RECORD_LEN;
+
+    fn parse_date(value: &str) -> Result<Date> {
+        Norma43Statement::parse_date(value)
+    }
+}
+
+impl Record {
+    /// Референс: поле из одних нулей считается пустым.
+    fn reference(&self, start: usize, end: usize) -> Option<String> {
+        self.field(start, end)
+            .filter(|f| f.chars().any(|c| c != '0'))
+    }
+
+    fn count(&self, start: usize, end: usize) -> Result<usize> {
+        self.raw(start, end).trim().parse().map_err(|_| {
+            Error::Parse(format!(
+                "Строка {}: некорректное количество '{}'",
+                self.line,
+                self.raw(start, end)
+            ))
+        })
+    }
+
+    /// Сумма из 14 цифр с двумя знаками после запятой.
+    fn amount(&self, start: usize) -> Result<i64> {
+        let digits = self.raw(start, start + 13);
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(Error::Parse(format!(
+                "Строка {}: некорректная сумма '{}'",
+                self.line, digits
+            )));
+        }
+        digits
+            .parse()
+            .map_err(|_| Error::Parse("Переполнение при парсинге суммы".to_string()))
+    }
+
+    /// Сумма со знаком: признак 1 — дебет (debe), 2 — кредит (haber).
+    fn signed_amount(&self, sign_pos: usize) -> Result<i64> {
+        let amount = self.amount(sign_pos + 1)?;
+        match self.char_at(sign_pos) {
+            '1' => Ok(-amount),
+            '2' => Ok(amount),
+            other => Err(Error::Parse(format!(
+                "Строка {}: некорректный признак дебет/кредит '{}'",
+                self.line, other
+            ))),
+        }
+    }
+
+    fn currency(&self, start: usize) -> String {
+        numeric_currency(&self.raw(start, start + 2))
+    }
+}
+
+/// Буквенный код валюты по числовому коду ISO 4217.
+fn numeric_currency(code: &str) -> String {
+    match code {
+        "978" => "EUR",
+        "840" => "USD",
+        "826" => "GBP",
+        "756" => "CHF",
+        "392" => "JPY",
+        "643" => "RUB",
+        _ => {
+            tracing::warn!("Неизвестный числовой код валюты {}", code);
+            return code.to_string();
+        }
+    }
+    .to_string()
+}
+
+/// Промежуточное состояние счета при разборе.
+struct AccountState {
+    statement: Norma43Statement,
+    debits: (usize, i64),
+    credits: (usize, i64),
+}
+
+impl Norma43Statement {
+    /// Парсит Norma 43 из любого источника, реализующего трейт Read.
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит Norma 43 из любого источника, реализующего трейт AsyncRead.
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит Norma 43 из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Vec<Self>> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
+    /// Парсит Norma 43 из строки.
+    ///
+    /// Обороты и количество движений сверяются с записью 33 каждого счета,
+    /// общее количество записей — с записью 88. Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Vec<Self>> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит Norma 43 из строки с заданными ограничениями.
+    ///
+    /// Число счетов ограничено `max_statements`, число движений по счету —
+    /// `max_transactions`, суммарная длина концептов движения — `max_field_len`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Vec<Self>> {
+        limits.check_input(content.len())?;
+        let mut statements = Vec::new();
+        let mut account: Option<AccountState> = None;
+        let mut records = 0usize;
+
+        for (idx, line) in content.trim_start_matches('\u{feff}').lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = Record::new(line, idx + 1)?;
+            let kind = record.raw(1, 2);
+
+            if kind == "88" {
+                if account.is_some() {
+                    return Err(Error::InvalidFormat(
+                        "Запись 88 внутри незакрытого счета".into(),
+                    ));
+                }
+                let declared = record.count(21, 26)?;
+                if declared != records {
+                    return Err(Error::InvalidFormat(format!(
+                        "Количество записей в записи 88 не совпадает: указано {}, подсчитано {}",
+                        declared, records
+                    )));
+                }
+                if statements.is_empty() {
+                    return Err(Error::InvalidFormat(
+                        "Файл Norma 43 не содержит счетов".into(),
+                    ));
+                }
+                return Ok(statements);
+            }
+            records += 1;
+
+            match kind.as_str() {
+                "11" => {
+                    if account.is_some() {
+                        return Err(Error::InvalidFormat(format!(
+                            "Строка {}: запись 11 внутри незакрытого счета",
+                            record.line
+                        )));
+                    }
+                    account = Some(AccountState {
+                        statement: Self::parse_header(&record)?,
+                        debits: (0, 0),
+                        credits: (0, 0),
+                    });
+                }
+                "22" => {
+                    let state = Self::open_account(&mut account, &record)?;
+                    let movement = Self::parse_movement(&record)?;
+                    let totals = if movement.amount < 0 {
+                        &mut state.debits
+                    } else {
+                        &mut state.credits
+                    };
+                    totals.0 += 1;
+                    totals.1 = totals.1.saturating_add(movement.amount.abs());
+                    state.statement.movements.push(movement);
+                    limits.check_transactions(state.statement.movements.len())?;
+                }
+                "23" | "24" => {
+                    let state = Self::open_account(&mut account, &record)?;
+                    let movement = state.statement.movements.last_mut().ok_or_else(|| {
+                        Error::InvalidFormat(format!(
+                            "Строка {}: запись {} без записи 22",
+                            record.line, kind
+                        ))
+                    })?;
+                    if kind == "23" {
+                        let concepts = [record.field(5, 42), record.field(43, 80)];
+                        movement.concepts.extend(concepts.into_iter().flatten());
+                        let len = movement.concepts.iter().map(String::len).sum();
+                        limits.check_field_len("концепты Norma 43", len)?;
+                    } else {
+                        movement.original_currency = Some(record.currency(5));
+                        movement.original_amount = Some(record.amount(8)?);
+                    }
+                }
+                "33" => {
+                    let state = account.take().ok_or_else(|| {
+                        Error::InvalidFormat(format!(
+                            "Строка {}: запись 33 без записи 11",
+                            record.line
+                        ))
+                    })?;
+                    statements.push(Self::close_account(state, &record)?);
+                    limits.check_statements(statements.len())?;
+                }
+                other => {
+                    return Err(Error::InvalidFormat(format!(
+                        "Строка {}: неизвестный тип записи Norma 43: {}",
+                        record.line, other
+                    )));
+                }
+            }
+        }
+
+        Err(Error::InvalidFormat(
+            "Файл Norma 43 не завершен записью 88".into(),
+        ))
+    }
+
+    fn open_account<'a>(
+        account: &'a mut Option<AccountState>,
+        record: &Record,
+    ) -> Result<&'a mut AccountState> {
+        account.as_mut().ok_or_else(|| {
+            Error::InvalidFormat(format!(
+                "Строка {}: запись {} вне счета",
+                record.line,
+                record.raw(1, 2)
+            ))
+        })
+    }
+
+    /// Запись 11: счет, период и входящий остаток.
+    fn parse_header(record: &Record) -> Result<Self> {
+        Ok(Norma43Statement {
+            bank_code: record.raw(3, 6),
+            branch_code: record.raw(7, 10),
+            account_number: record.raw(11, 20),
+            start_date: record.date(21, "начальная дата")?,
+            end_date: record.date(27, "конечная дата")?,
+            opening_balance: record.signed_amount(33)?,
+            currency: record.currency(48),
+            account_name: record.field(52, 77),
+            closing_balance: 0,
+            movements: Vec::new(),
+        })
+    }
+
+    /// Запись 22: основная запись движения.
+    fn parse_movement(record: &Record) -> Result<Norma43Movement> {
+        Ok(Norma43Movement {
+            origin_branch: record.raw(7, 10),
+            operation_date: record.date(11, "дата операции")?,
+            value_date: record.date(17, "дата валютирования")?,
+            common_concept: record.raw(23, 24),
+            own_concept: record.raw(25, 27),
+            amount: record.signed_amount(28)?,
+            document_number: record.reference(43, 52),
+            reference1: record.reference(53, 64),
+            reference2: record.reference(65, 80),
+            concepts: Vec::new(),
+            original_currency: None,
+            original_amount: None,
+        })
+    }
+
+    /// Запись 33: сверяет обороты и исходящий остаток со значениями из файла.
+    fn close_account(state: AccountState, record: &Record) -> Result<Self> {
+        let mut statement = state.statement;
+
+        if record.raw(3, 6) != statement.bank_code
+            || record.raw(7, 10) != statement.branch_code
+            || record.raw(11, 20) != statement.account_number
+        {
+            return Err(Error::InvalidFormat(format!(
+                "Строка {}: запись 33 относится к другому счету",
+                record.line
+            )));
+        }
+
+        let declared_debits = (record.count(21, 25)?, record.amount(26)?);
+        let declared_credits = (record.count(40, 44)?, record.amount(45)?);
+        if declared_debits != state.debits || declared_credits != state.credits {
+            return Err(Error::InvalidFormat(format!(
+                "Обороты в записи 33 ({}) не совпадают: указано дебет {}/{} и кредит {}/{}, \
+                 подсчитано дебет {}/{} и кредит {}/{}",
+                statement.account_number,
+                declared_debits.0,
+                declared_debits.1,
+                declared_credits.0,
+                declared_credits.1,
+                state.debits.0,
+                state.debits.1,
+                state.credits.0,
+                state.credits.1
+            )));
+        }
+
+        statement.closing_balance = record.signed_amount(59)?;
+        let expected = statement
+            .opening_balance
+            .saturating_sub(state.debits.1)
+            .saturating_add(state.credits.1);
+        if statement.closing_balance != expected {
+            return Err(Error::InvalidFormat(format!(
+                "Исходящий остаток в записи 33 ({}) не совпадает: указано {}, подсчитано {}",
+                statement.account_number, statement.closing_balance, expected
+            )));
+        }
+
+        Ok(statement)
+    }
+
+    /// Парсит дату в формате YYMMDD.
+    fn parse_date(date_str: &str) -> Result<Date> {
+        Date::parse_from_str(date_str, "%y%m%d")
+            .map_err(|_| Error::Parse(format!("Некорректная дата Norma 43: {}", date_str)))
+    }
+}
+
+impl From<Norma43Statement> for Statement {
+    fn from(n43: Norma43Statement) -> Self {
+        let currency = n43.currency;
+
+        let transactions =
+            n43.movements
+                .into_iter()
+                .map(|m| {
+                    let description = if m.concepts.is_empty() {
+                        m.reference2.clone().unwrap_or_default()
+                    } else {
+                        m.concepts.join(" ")
+                    };
+
+                    Transaction {
+                        value_date: Some(m.value_date).filter(|d| *d != m.operation_date),
+                        date: m.operation_date,
+                        amount: Amount::new(m.amount.abs(), &currency),
+                        is_credit: m.amount >= 0,
+                        transaction_type: Some(m.transaction_type().to_string()),
+                        reference: m
+                            .reference1
+                            .clone()
+                            .or_else(|| m.reference2.clone())
+                            .or_else(|| m.document_number.clone()),
+                        description,
+                        counterparty: None,
+                        instructed_amount: None,
+                        original_amount: m.original_currency.zip(m.original_amount).map(
+                            |(original_currency, value)| Amount::new(value, original_currency),
+                        ),
+                        counter_value_amount: None,
+                        exchange_rates: Vec::new(),
+                        charges: Vec::new(),
+                    }
+                })
+                .collect();
+
+        let balance = |amount: i64, date: Date| Balance {
+            amount: Amount::new(amount.abs(), &currency),
+            date,
+            is_credit: amount >= 0,
+        };
+
+        Statement {
+            account: Account {
+                iban: None,
+                // Номер без контрольных цифр: Norma 43 их не передает
+                number: format!("{}{}{}", n43.bank_code, n43.branch_code, n43.account_number),
+                currency: currency.clone(),
+                name: None,
+                owner: n43.account_name,
+                tax_id: None,
+            },
+            opening_balance: balance(n43.opening_balance, n43.start_date),
+            closing_balance: balance(n43.closing_balance, n43.end_date),
+            transactions,
+            statement_number: None,
+            reference: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            Norma43Statement::parse_date("240131").unwrap(),
+            Date::new(2024, 1, 31)
+        );
+        assert!(Norma43Statement::parse_date("241301").is_err());
+    }
+
+    #[test]
+    fn test_signed_amount() {
+        let record = Record::new(&format!("{:27}1{:014}", "", 150050), 1).unwrap();
+        assert_eq!(record.signed_amount(28).unwrap(), -150050);
+    }
+}
@@ -0,0 +1,11 @@
+//! Модуль парсинга и сериализации формата OFX / QFX (Open Financial Exchange).
+//!
+//! Поддерживаются версии 1.x (SGML, простые элементы без закрывающих тегов)
+//! и 2.x (XML). Из файла читаются банковские и карточные выписки
+//! (`STMTTRNRS` и `CCSTMTTRNRS`).
+
+pub mod parser;
+pub mod writer;
+
+pub use parser::{OfxBalance, OfxStatement, OfxTransaction, OfxVersion};
+pub use writer::OfxWriter;
@@ -0,0 +1,429 @@
+//! Парсер формата OFX (1.x SGML и 2.x XML).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::types::{Account, Amount, Balance, Counterparty, Date, Statement, Transaction};
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Тип счета по умолчанию (ACCTTYPE).
+pub const OFX_ACCOUNT_TYPE_CHECKING: &str = "CHECKING";
+
+/// Тип транзакции поступления (TRNTYPE).
+pub const OFX_TRNTYPE_CREDIT: &str = "CREDIT";
+
+/// Тип транзакции списания (TRNTYPE).
+pub const OFX_TRNTYPE_DEBIT: &str = "DEBIT";
+
+/// Версия OFX.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OfxVersion {
+    /// OFX 1.x: SGML без закрывающих тегов у простых элементов.
+    V1,
+    /// OFX 2.x: XML.
+    #[default]
+    V2,
+}
+
+/// Выписка по одному счету (STMTRS или CCSTMTRS).
+#[derive(Debug, Clone)]
+pub struct OfxStatement {
+    /// Версия файла, из которого прочитана выписка, или в которой ее записывать.
+    pub version: OfxVersion,
+    /// Валюта выписки (CURDEF).
+    pub currency: String,
+    /// Идентификатор банка (BANKID).
+    pub bank_id: Option<String>,
+    /// Номер счета (ACCTID).
+    pub account_id: String,
+    /// Тип счета (ACCTTYPE), для карточных выписок — `CREDITCARD`.
+    pub account_type: String,
+    /// Начало периода (BANKTRANLIST/DTSTART).
+    pub date_start: Option<Date>,
+    /// Конец периода (BANKTRANLIST/DTEND).
+    pub date_end: Option<Date>,
+    /// Транзакции (STMTTRN).
+    pub transactions: Vec<OfxTransaction>,
+    /// Баланс по книгам (LEDGERBAL).
+    pub ledger_balance: OfxBalance,
+    /// Доступный остаток (AVAILBAL).
+    pub available_balance: Option<OfxBalance>,
+}
+
+/// Транзакция OFX (STMTTRN).
+#[derive(Debug, Clone)]
+pub struct OfxTransaction {
+    /// Тип транзакции (TRNTYPE): CREDIT, DEBIT, FEE и т.д.
+    pub transaction_type: String,
+    /// Дата проводки (DTPOSTED).
+    pub date_posted: Date,
+    /// Сумма со знаком в минимальных единицах (TRNAMT).
+    pub amount: i64,
+    /// Уникальный идентификатор транзакции (FITID).
+    pub fitid: String,
+    /// Номер чека или документа (CHECKNUM).
+    pub check_number: Option<String>,
+    /// Наименование контрагента (NAME).
+    pub name: Option<String>,
+    /// Примечание (MEMO).
+    pub memo: Option<String>,
+}
+
+/// Баланс OFX (LEDGERBAL, AVAILBAL).
+#[derive(Debug, Clone)]
+pub struct OfxBalance {
+    /// Сумма со знаком в минимальных единицах (BALAMT).
+    pub amount: i64,
+    /// Дата баланса (DTASOF).
+    pub date: Date,
+}
+
+impl OfxStatement {
+    /// Парсит OFX из любого источника, реализующего трейт Read.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит OFX из любого источника, реализующего трейт AsyncRead.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<Self>> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит OFX из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Vec<Self>> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
+    /// Парсит OFX из строки.
+    ///
+    /// Поддерживаются банковские (STMTRS) и карточные (CCSTMTRS) выписки
+    /// в SGML (1.x) и XML (2.x) вариантах. Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Vec<Self>> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит OFX из строки с заданными ограничениями.
+    ///
+    /// Вложенность OFX 2.x ограничена `max_xml_depth`, число выписок —
+    /// `max_statements`, число транзакций в выписке — `max_transactions`,
+    /// длина текстовых элементов транзакции — `max_field_len`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Vec<Self>> {
+        limits.check_input(content.len())?;
+        let body_start = content
+            .find("<OFX>")
+            .ok_or_else(|| Error::InvalidFormat("Не найден элемент OFX".to_string()))?;
+        let version = if content[..body_start].contains("OFXHEADER:") {
+            OfxVersion::V1
+        } else {
+            OfxVersion::V2
+        };
+        if version == OfxVersion::V2 {
+            limits.check_xml_depth(content)?;
+        }
+        let body = &content[body_start..];
+
+        let mut statements = Vec::new();
+        for block in Self::blocks_any(body, &["STMTRS", "CCSTMTRS"]) {
+            statements.push(Self::parse_statement(block, version, limits)?);
+            limits.check_statements(statements.len())?;
+        }
+
+        if statements.is_empty() {
+            return Err(Error::InvalidFormat(
+                "Не найдено ни одной выписки (STMTRS или CCSTMTRS)".to_string(),
+            ));
+        }
+
+        Ok(statements)
+    }
+
+    fn parse_statement(block: &str, version: OfxVersion, limits: &Limits) -> Result<Self> {
+        let (account_block, default_type) = match Self::blocks(block, "BANKACCTFROM").next() {
+            Some(account) => (account, OFX_ACCOUNT_TYPE_CHECKING),
+            None => (
+                Self::blocks(block, "CCACCTFROM").next().ok_or_else(|| {
+                    Error::MissingField("Отсутствует BANKACCTFROM или CCACCTFROM".to_string())
+                })?,
+                "CREDITCARD",
+            ),
+        };
+
+        let account_id = Self::leaf(account_block, "ACCTID")
+            .ok_or_else(|| Error::MissingField("Отсутствует ACCTID".to_string()))?;
+        limits.check_field("ACCTID", &account_id)?;
+
+        let tran_list = Self::blocks(block, "BANKTRANLIST").next().unwrap_or("");
+        let mut transactions = Vec::new();
+        for tx_block in Self::blocks(tran_list, "STMTTRN") {
+            match Self::parse_transaction(tx_block) {
+                Ok(tx) => {
+                    for (name, value) in [("NAME", &tx.name), ("MEMO", &tx.memo)] {
+                        limits.check_field(name, value.as_deref().unwrap_or(""))?;
+                    }
+                    limits.check_field("FITID", &tx.fitid)?;
+                    transactions.push(tx);
+                    limits.check_transactions(transactions.len())?;
+                }
+                Err(e) => {
+                    tracing::warn!("Не удалось распарсить транзакцию OFX: {}", e);
+                }
+            }
+        }
+
+        let ledger_balance = Self::blocks(block, "LEDGERBAL")
+            .next()
+            .map(Self::parse_balance)
+            .transpose()?
+            .ok_or_else(|| Error::MissingField("Отсутствует LEDGERBAL".to_string()))?;
+        let available_balance = Self::blocks(block, "AVAILBAL")
+            .next()
+            .map(Self::parse_balance)
+            .transpose()?;
+
+        Ok(OfxStatement {
+            version,
+            currency: Self::leaf(block, "CURDEF").unwrap_or_else(|| "USD".to_string()),
+            bank_id: Self::leaf(account_block, "BANKID"),
+            account_id,
+            account_type: Self::leaf(account_block, "ACCTTYPE")
+                .unwrap_or_else(|| default_type.to_string()),
+            date_start: Self::optional_date(tran_list, "DTSTART")?,
+            date_end: Self::optional_date(tran_list, "DTEND")?,
+            transactions,
+            ledger_balance,
+            available_balance,
+        })
+    }
+
+    fn parse_transaction(block: &str) -> Result<OfxTransaction> {
+        let amount = Self::leaf(block, "TRNAMT")
+            .ok_or_else(|| Error::MissingField("Отсутствует TRNAMT".to_string()))?;
+        let date = Self::leaf(block, "DTPOSTED")
+            .ok_or_else(|| Error::MissingField("Отсутствует DTPOSTED".to_string()))?;
+
+        Ok(OfxTransaction {
+            transaction_type: Self::leaf(block, "TRNTYPE").unwrap_or_else(|| "OTHER".to_string()),
+            date_posted: Self::parse_date(&date)?,
+            amount: Self::parse_amount(&amount)?,
+            fitid: Self::leaf(block, "FITID").unwrap_or_default(),
+            check_number: Self::leaf(block, "CHECKNUM"),
+            name: Self::leaf(block, "NAME"),
+            memo: Self::leaf(block, "MEMO"),
+        })
+    }
+
+    fn parse_balance(block: &str) -> Result<OfxBalance> {
+        let amount = Self::leaf(block, "BALAMT")
+            .ok_or_else(|| Error::MissingField("Отсутствует BALAMT".to_string()))?;
+        let date = Self::leaf(block, "DTASOF")
+            .ok_or_else(|| Error::MissingField("Отсутствует DTASOF".to_string()))?;
+
+        Ok(OfxBalance {
+            amount: Self::parse_amount(&amount)?,
+            date: Self::parse_date(&date)?,
+        })
+    }
+
+    fn optional_date(block: &str, tag: &str) -> Result<Option<Date>> {
+        Self::leaf(block, tag).map(|d| Self::parse_date(&d)).transpose()
+    }
+
+    /// Возвращает агрегаты `<tag>...</tag>` (закрывающие теги агрегатов есть и в SGML).
+    fn blocks<'a>(content: &'a str, tag: &str) -> impl Iterator<Item = &'a str> {
+        Self::blocks_any(content, &[tag])
+    }
+
+    /// Возвращает агрегаты с любым из тегов `tags` в порядке следования в документе.
+    fn blocks_any<'a>(content: &'a str, tags: &[&str]) -> impl Iterator<Item = &'a str> {
+        let tags: Vec<(String, String)> = tags
+            .iter()
+            .map(|tag| (format!("<{}>", tag), format!("</{}>", tag)))
+            .collect();
+        let mut pos = 0;
+
+        std::iter::from_fn(move || {
+            let (start, open_tag, close_tag) = tags
+                .iter()
+                .filter_map(|(open, close)| {
+                    content[pos..].find(open.as_str()).map(|p| (pos + p, open, close))
+                })
+                .min_by_key(|&(start, _, _)| start)?;
+            let body_start = start + open_tag.len();
+            let end = content[body_start..]
+                .find(close_tag.as_str())
+                .map_or(content.len(), |p| body_start + p);
+            pos = (end + close_tag.len()).min(content.len());
+            Some(&content[body_start..end])
+        })
+    }
+
+    /// Значение простого элемента: текст от `<tag>` до следующего тега.
+    ///
+    /// Подходит для обоих вариантов: в SGML закрывающего тега нет,
+    /// в XML он следует сразу за значением.
+    fn leaf(content: &str, tag: &str) -> Option<String> {
+        let open_tag = format!("<{}>", tag);
+        let start = content.find(&open_tag)? + open_tag.len();
+        let end = content[start..].find('<').map_or(content.len(), |p| start + p);
+        let value = Self::unescape(content[start..end].trim());
+
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    fn unescape(s: &str) -> String {
+        s.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    }
+
+    /// Парсит дату OFX: `YYYYMMDD[HHMMSS[.XXX]][[gmt offset:tz name]]`.
+    fn parse_date(s: &str) -> Result<Date> {
+        let digits = s.get(..8).filter(|d| d.chars().all(|c| c.is_ascii_digit()));
+        let digits = digits.ok_or_else(|| Error::Parse(format!("Некорректная дата OFX: {}", s)))?;
+
+        let invalid = || Error::Parse(format!("Некорректная дата OFX: {}", s));
+        let year: u16 = digits[..4].parse().map_err(|_| invalid())?;
+        let month: u8 = digits[4..6].parse().map_err(|_| invalid())?;
+        let day: u8 = digits[6..8].parse().map_err(|_| invalid())?;
+
+        Date::from_ymd(year, month, day).map_err(|_| invalid())
+    }
+
+    /// Парсит сумму без использования f64 (допускаются `.` и `,` как разделитель).
+    pub(crate) fn parse_amount(amount_str: &str) -> Result<i64> {
+        let amount_str = amount_str.trim();
+        let is_negative = amount_str.starts_with('-');
+        let unsigned = amount_str.trim_start_matches(['-', '+']).replace(',', ".");
+
+        let (whole_str, frac_str) = unsigned.split_once('.').unwrap_or((&unsigned, ""));
+        if whole_str.is_empty() && frac_str.is_empty() {
+            return Err(Error::Parse("Пустая сумма".to_string()));
+        }
+
+        let invalid = || Error::Parse(format!("Некорректная сумма: {}", amount_str));
+        let whole: i64 = if whole_str.is_empty() {
+            0
+        } else {
+            whole_str.parse().map_err(|_| invalid())?
+        };
+        let frac: i64 = match frac_str.len() {
+            0 => 0,
+            1 => frac_str.parse::<i64>().map_err(|_| invalid())? * 10,
+            _ => frac_str.get(..2).unwrap_or_default().parse().map_err(|_| invalid())?,
+        };
+
+        let amount = whole
+            .checked_mul(100)
+            .and_then(|w| w.checked_add(frac))
+            .ok_or_else(|| Error::Parse("Переполнение при парсинге суммы".to_string()))?;
+
+        Ok(if is_negative { -amount } else { amount })
+    }
+}
+
+impl From<OfxStatement> for Statement {
+    fn from(ofx: OfxStatement) -> Self {
+        let currency = ofx.currency;
+
+        // OFX не передает входящий остаток: восстанавливаем его по оборотам
+        let turnover: i64 = ofx
+            .transactions
+            .iter()
+            .fold(0i64, |sum, tx| sum.saturating_add(tx.amount));
+        let opening = ofx.ledger_balance.amount.saturating_sub(turnover);
+        let opening_date = ofx
+            .date_start
+            .or_else(|| ofx.transactions.first().map(|tx| tx.date_posted))
+            .unwrap_or(ofx.ledger_balance.date);
+
+        let transactions = ofx
+            .transactions
+            .into_iter()
+            .map(|tx| {
+                let description = tx.memo.clone().or_else(|| tx.name.clone()).unwrap_or_default();
+                let counterparty = tx.name.map(|name| Counterparty {
+                    name: Some(name),
+                    ..Default::default()
+                });
+
+                Transaction {
+                    date: tx.date_posted,
+                    value_date: None,
+                    amount: Amount::new(tx.amount.abs(), &currency),
+                    is_credit: tx.amount >= 0,
+                    reference: Some(tx.fitid).filter(|fitid| !fitid.is_empty()),
+                    transaction_type: None,
+                    description,
+                    counterparty,
+                    instructed_amount: None,
+                    original_amount: None,
+                    counter_value_amount: None,
+                    exchange_rates: Vec::new(),
+                    charges: Vec::new(),
+                }
+            })
+            .collect();
+
+        let is_iban = ofx.account_id.len() > 2
+            && ofx.account_id.chars().take(2).all(|c| c.is_ascii_uppercase());
+
+        Statement {
+            account: Account {
+                iban: is_iban.then(|| ofx.account_id.clone()),
+                number: ofx.account_id,
+                currency: currency.clone(),
+                name: None,
+                owner: None,
+                tax_id: None,
+            },
+            opening_balance: Balance {
+                amount: Amount::new(opening.abs(), &currency),
+                date: opening_date,
+                is_credit: opening >= 0,
+            },
+            closing_balance: Balance {
+                amount: Amount::new(ofx.ledger_balance.amount.abs(), &currency),
+                date: ofx.ledger_balance.date,
+                is_credit: ofx.ledger_balance.amount >= 0,
+            },
+            transactions,
+            statement_number: None,
+            reference: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_and_amount() {
+        assert_eq!(
+            OfxStatement::parse_date("20240115120000.000[-5:EST]").unwrap(),
+            Date::new(2024, 1, 15)
+        );
+        assert!(OfxStatement::parse_date("2024-01-15").is_err());
+
+        assert_eq!(OfxStatement::parse_amount("-1540.5").unwrap(), -154050);
+        assert_eq!(OfxStatement::parse_amount("+12,34").unwrap(), 1234);
+        assert!(OfxStatement::parse_amount("abc").is_err());
+    }
+}
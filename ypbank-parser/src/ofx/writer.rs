@@ -0,0 +1,361 @@
+//! Сериализация формата OFX (1.x SGML и 2.x XML).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::Result;
+use crate::ofx::parser::{OfxBalance, OfxStatement, OfxTransaction, OfxVersion};
+use crate::types::Date;
+use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Writer для формата OFX.
+///
+/// Версия определяется полем [`OfxStatement::version`]: для 1.x простые элементы
+/// пишутся без закрывающих тегов, для 2.x формируется корректный XML.
+pub struct OfxWriter;
+
+impl OfxWriter {
+    /// Записывает файл OFX с одной выпиской в любой приемник, реализующий трейт Write.
+    ///
+    /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
+    pub fn write_to<W: Write>(statement: &OfxStatement, writer: &mut W) -> Result<()> {
+        Self::write_all_to(std::slice::from_ref(statement), writer)
+    }
+
+    /// Асинхронно записывает файл OFX с одной выпиской в любой приемник, реализующий трейт
+    /// AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statement: &OfxStatement,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statement, buffer)).await
+    }
+
+    /// Записывает файл OFX с несколькими выписками (по одной STMTTRNRS на счет).
+    ///
+    /// Версия берется из первой выписки.
+    pub fn write_all_to<W: Write>(statements: &[OfxStatement], writer: &mut W) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_to_buffered(statements, &mut buf_writer, TextEncoding::Utf8)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает файл OFX с несколькими выписками (по одной STMTTRNRS на счет).
+    #[cfg(feature = "async")]
+    pub async fn write_all_to_async<W: AsyncWrite + Unpin>(
+        statements: &[OfxStatement],
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_all_to(statements, buffer)).await
+    }
+
+    /// Записывает файл OFX с одной выпиской в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        statement: &OfxStatement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        Self::write_all_to_with_encoding(std::slice::from_ref(statement), writer, encoding)
+    }
+
+    /// Асинхронно записывает файл OFX с одной выпиской в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statement: &OfxStatement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statement, buffer, encoding)
+        })
+        .await
+    }
+
+    /// Записывает файл OFX с несколькими выписками в указанной кодировке.
+    pub fn write_all_to_with_encoding<W: Write>(
+        statements: &[OfxStatement],
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(statements, buffer, encoding)
+        })
+    }
+
+    /// Асинхронно записывает файл OFX с несколькими выписками в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_all_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statements: &[OfxStatement],
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_all_to_with_encoding(statements, buffer, encoding)
+        })
+        .await
+    }
+
+    fn write_to_buffered<W: Write>(
+        statements: &[OfxStatement],
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        let version = statements.first().map(|s| s.version).unwrap_or_default();
+        let out = &mut OfxOutput { writer, version };
+
+        out.write_header(encoding)?;
+        out.open("OFX")?;
+
+        let server_date = statements
+            .iter()
+            .map(|s| &s.ledger_balance.date)
+            .max();
+        out.open("SIGNONMSGSRSV1")?;
+        out.open("SONRS")?;
+        out.write_status()?;
+        if let Some(date) = server_date {
+            out.leaf("DTSERVER", &Self::format_date_time(date))?;
+        }
+        out.leaf("LANGUAGE", "RUS")?;
+        out.close("SONRS")?;
+        out.close("SIGNONMSGSRSV1")?;
+
+        let (bank, cards): (Vec<_>, Vec<_>) =
+            statements.iter().partition(|s| s.account_type != "CREDITCARD");
+
+        if !bank.is_empty() {
+            out.open("BANKMSGSRSV1")?;
+            for (idx, statement) in bank.iter().enumerate() {
+                Self::write_statement(out, statement, idx + 1, false)?;
+            }
+            out.close("BANKMSGSRSV1")?;
+        }
+
+        if !cards.is_empty() {
+            out.open("CREDITCARDMSGSRSV1")?;
+            for (idx, statement) in cards.iter().enumerate() {
+                Self::write_statement(out, statement, idx + 1, true)?;
+            }
+            out.close("CREDITCARDMSGSRSV1")?;
+        }
+
+        out.close("OFX")?;
+
+        Ok(())
+    }
+
+    fn write_statement<W: Write>(
+        out: &mut OfxOutput<'_, W>,
+        statement: &OfxStatement,
+        trnuid: usize,
+        credit_card: bool,
+    ) -> Result<()> {
+        let (wrapper, response, account_tag) = if credit_card {
+            ("CCSTMTTRNRS", "CCSTMTRS", "CCACCTFROM")
+        } else {
+            ("STMTTRNRS", "STMTRS", "BANKACCTFROM")
+        };
+
+        out.open(wrapper)?;
+        out.leaf("TRNUID", &trnuid.to_string())?;
+        out.write_status()?;
+        out.open(response)?;
+        out.leaf("CURDEF", &statement.currency)?;
+
+        out.open(account_tag)?;
+        if !credit_card {
+            if let Some(ref bank_id) = statement.bank_id {
+                out.leaf("BANKID", bank_id)?;
+            }
+        }
+        out.leaf("ACCTID", &statement.account_id)?;
+        if !credit_card {
+            out.leaf("ACCTTYPE", &statement.account_type)?;
+        }
+        out.close(account_tag)?;
+
+        out.open("BANKTRANLIST")?;
+        if let Some(ref date) = statement.date_start {
+            out.leaf("DTSTART", &Self::format_date(date))?;
+        }
+        if let Some(ref date) = statement.date_end {
+            out.leaf("DTEND", &Self::format_date(date))?;
+        }
+        for tx in &statement.transactions {
+            Self::write_transaction(out, tx)?;
+        }
+        out.close("BANKTRANLIST")?;
+
+        Self::write_balance(out, "LEDGERBAL", &statement.ledger_balance)?;
+        if let Some(ref available) = statement.available_balance {
+            Self::write_balance(out, "AVAILBAL", available)?;
+        }
+
+        out.close(response)?;
+        out.close(wrapper)?;
+
+        Ok(())
+    }
+
+    fn write_transaction<W: Write>(out: &mut OfxOutput<'_, W>, tx: &OfxTransaction) -> Result<()> {
+        out.open("STMTTRN")?;
+        out.leaf("TRNTYPE", &tx.transaction_type)?;
+        out.leaf("DTPOSTED", &Self::format_date(&tx.date_posted))?;
+        out.leaf("TRNAMT", &Self::format_amount(tx.amount))?;
+        out.leaf("FITID", &tx.fitid)?;
+        if let Some(ref check_number) = tx.check_number {
+            out.leaf("CHECKNUM", check_number)?;
+        }
+        if let Some(ref name) = tx.name {
+            out.leaf("NAME", name)?;
+        }
+        if let Some(ref memo) = tx.memo {
+            out.leaf("MEMO", memo)?;
+        }
+        out.close("STMTTRN")?;
+
+        Ok(())
+    }
+
+    fn write_balance<W: Write>(
+        out: &mut OfxOutput<'_, W>,
+        tag: &str,
+        balance: &OfxBalance,
+    ) -> Result<()> {
+        out.open(tag)?;
+        out.leaf("BALAMT", &Self::format_amount(balance.amount))?;
+        out.leaf("DTASOF", &Self::format_date(&balance.date))?;
+        out.close(tag)?;
+
+        Ok(())
+    }
+
+    fn format_amount(amount: i64) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        let abs = amount.unsigned_abs();
+        format!("{}{}.{:02}", sign, abs / 100, abs % 100)
+    }
+
+    fn format_date(date: &Date) -> String {
+        format!("{:04}{:02}{:02}", date.year(), date.month(), date.day())
+    }
+
+    fn format_date_time(date: &Date) -> String {
+        format!("{}000000", Self::format_date(date))
+    }
+}
+
+/// Приемник с учетом синтаксиса версии OFX.
+struct OfxOutput<'a, W: Write> {
+    writer: &'a mut W,
+    version: OfxVersion,
+}
+
+impl<W: Write> OfxOutput<'_, W> {
+    fn write_header(&mut self, encoding: TextEncoding) -> Result<()> {
+        match self.version {
+            OfxVersion::V1 => {
+                let charset = match encoding {
+                    TextEncoding::Utf8 => "NONE",
+                    TextEncoding::Windows1251 => "1251",
+                    TextEncoding::Cp866 => "866",
+                };
+                let data_encoding = match encoding {
+                    TextEncoding::Utf8 => "UTF-8",
+                    _ => "USASCII",
+                };
+
+                writeln!(self.writer, "OFXHEADER:100")?;
+                writeln!(self.writer, "DATA:OFXSGML")?;
+                writeln!(self.writer, "VERSION:102")?;
+                writeln!(self.writer, "SECURITY:NONE")?;
+                writeln!(self.writer, "ENCODING:{}", data_encoding)?;
+                writeln!(self.writer, "CHARSET:{}", charset)?;
+                writeln!(self.writer, "COMPRESSION:NONE")?;
+                writeln!(self.writer, "OLDFILEUID:NONE")?;
+                writeln!(self.writer, "NEWFILEUID:NONE")?;
+                writeln!(self.writer)?;
+            }
+            OfxVersion::V2 => {
+                writeln!(
+                    self.writer,
+                    "<?xml version=\"1.0\" encoding=\"{}\" standalone=\"no\"?>",
+                    encoding.label()
+                )?;
+                writeln!(
+                    self.writer,
+                    "<?OFX OFXHEADER=\"200\" VERSION=\"220\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>"
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_status(&mut self) -> Result<()> {
+        self.open("STATUS")?;
+        self.leaf("CODE", "0")?;
+        self.leaf("SEVERITY", "INFO")?;
+        self.close("STATUS")
+    }
+
+    fn open(&mut self, tag: &str) -> Result<()> {
+        writeln!(self.writer, "<{}>", tag)?;
+        Ok(())
+    }
+
+    fn close(&mut self, tag: &str) -> Result<()> {
+        writeln!(self.writer, "</{}>", tag)?;
+        Ok(())
+    }
+
+    fn leaf(&mut self, tag: &str, value: &str) -> Result<()> {
+        // Перевод строки завершил бы значение элемента в SGML
+        let value = Self::escape(&value.replace(['\r', '\n'], " "));
+        match self.version {
+            OfxVersion::V1 => writeln!(self.writer, "<{}>{}", tag, value)?,
+            OfxVersion::V2 => writeln!(self.writer, "<{}>{}</{}>", tag, value, tag)?,
+        }
+        Ok(())
+    }
+
+    fn escape(s: &str) -> String {
+        s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    }
+}
+
+impl OfxStatement {
+    /// Записывает файл OFX с выпиской в любой приемник, реализующий трейт Write.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        OfxWriter::write_to(self, writer)
+    }
+
+    /// Асинхронно записывает файл OFX с выпиской в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает файл OFX с выпиской в указанной кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        OfxWriter::write_to_with_encoding(self, writer, encoding)
+    }
+
+    /// Асинхронно записывает файл OFX с выпиской в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to_with_encoding(buffer, encoding)).await
+    }
+}
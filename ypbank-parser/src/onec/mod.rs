@@ -0,0 +1,11 @@
+//! Модуль парсинга и сериализации формата 1С:Предприятие (1CClientBankExchange).
+//!
+//! Текстовый формат обмена с системами «Клиент-Банк»: заголовок файла,
+//! секции расчетных счетов (`СекцияРасчСчет`) и платежных документов
+//! (`СекцияДокумент`) в виде строк `Ключ=Значение`.
+
+pub mod parser;
+pub mod writer;
+
+pub use parser::{OneCAccountSection, OneCDocument, OneCParty, OneCStatement};
+pub use writer::OneCWriter;
@@ -0,0 +1,574 @@
+//! Парсер формата 1CClientBankExchange.
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::types::{Account, Amount, Balance, Counterparty, Date, Statement, Transaction};
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Признак начала файла обмена.
+pub const ONEC_SIGNATURE: &str = "1CClientBankExchange";
+
+/// Вид документа по умолчанию.
+pub const ONEC_PAYMENT_ORDER: &str = "Платежное поручение";
+
+/// Валюта расчетных счетов в формате 1С.
+const ONEC_CURRENCY: &str = "RUB";
+
+/// Файл обмена 1С:Предприятие.
+#[derive(Debug, Clone)]
+pub struct OneCStatement {
+    /// Версия формата (ВерсияФормата).
+    pub format_version: String,
+    /// Кодировка файла (Кодировка): Windows, DOS или UTF-8.
+    pub encoding: String,
+    /// Программа-отправитель (Отправитель).
+    pub sender: Option<String>,
+    /// Программа-получатель (Получатель).
+    pub receiver: Option<String>,
+    /// Дата создания файла (ДатаСоздания).
+    pub creation_date: Option<Date>,
+    /// Время создания файла (ВремяСоздания).
+    pub creation_time: Option<String>,
+    /// Начало периода (ДатаНачала).
+    pub date_from: Option<Date>,
+    /// Конец периода (ДатаКонца).
+    pub date_to: Option<Date>,
+    /// Расчетные счета, по которым выгружены документы (РасчСчет).
+    pub account_numbers: Vec<String>,
+    /// Секции остатков по расчетным счетам (СекцияРасчСчет).
+    pub accounts: Vec<OneCAccountSection>,
+    /// Платежные документы (СекцияДокумент).
+    pub documents: Vec<OneCDocument>,
+}
+
+/// Секция остатков и оборотов по расчетному счету.
+#[derive(Debug, Clone)]
+pub struct OneCAccountSection {
+    /// Начало периода.
+    pub date_from: Option<Date>,
+    /// Конец периода.
+    pub date_to: Option<Date>,
+    /// Номер расчетного счета.
+    pub account: String,
+    /// Начальный остаток в копейках.
+    pub opening_balance: i64,
+    /// Сумма поступлений в копейках.
+    pub total_credit: i64,
+    /// Сумма списаний в копейках.
+    pub total_debit: i64,
+    /// Конечный остаток в копейках.
+    pub closing_balance: i64,
+}
+
+/// Платежный документ.
+#[derive(Debug, Clone)]
+pub struct OneCDocument {
+    /// Вид документа (значение СекцияДокумент).
+    pub document_type: String,
+    /// Номер документа.
+    pub number: String,
+    /// Дата документа.
+    pub date: Date,
+    /// Сумма в копейках.
+    pub amount: i64,
+    /// Плательщик.
+    pub payer: OneCParty,
+    /// Получатель.
+    pub payee: OneCParty,
+    /// Дата списания со счета плательщика (ДатаСписано).
+    pub debited_date: Option<Date>,
+    /// Дата поступления на счет получателя (ДатаПоступило).
+    pub credited_date: Option<Date>,
+    /// Вид оплаты (ВидОплаты).
+    pub payment_kind: Option<String>,
+    /// Очередность платежа.
+    pub priority: Option<String>,
+    /// Назначение платежа (НазначениеПлатежа и НазначениеПлатежа1..6).
+    pub purpose: String,
+    /// Прочие реквизиты документа в исходном порядке.
+    pub other_fields: Vec<(String, String)>,
+}
+
+/// Реквизиты плательщика или получателя.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OneCParty {
+    /// Счет (ПлательщикСчет / ПолучательСчет).
+    pub account: Option<String>,
+    /// Полное наименование (Плательщик / Получатель).
+    pub full_name: Option<String>,
+    /// Наименование (Плательщик1 / Получатель1).
+    pub name: Option<String>,
+    /// ИНН.
+    pub inn: Option<String>,
+    /// КПП.
+    pub kpp: Option<String>,
+    /// Расчетный счет (ПлательщикРасчСчет / ПолучательРасчСчет).
+    pub settlement_account: Option<String>,
+    /// Наименование банка (Банк1).
+    pub bank_name: Option<String>,
+    /// Город банка (Банк2).
+    pub bank_city: Option<String>,
+    /// БИК банка.
+    pub bik: Option<String>,
+    /// Корреспондентский счет банка.
+    pub correspondent_account: Option<String>,
+}
+
+impl OneCStatement {
+    /// Парсит файл 1С из любого источника, реализующего трейт Read.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит файл 1С из любого источника, реализующего трейт AsyncRead.
+    ///
+    /// Кодировка определяется автоматически (UTF-8, Windows-1251, CP866).
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит файл 1С из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<Self> {
+        Self::parse(&encoding::decode(bytes, encoding)?)
+    }
+
+    /// Парсит файл 1С из строки. Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Self> {
+        Self::parse_with_limits(content, &Limits::default())
+    }
+
+    /// Парсит файл 1С из строки с заданными ограничениями.
+    ///
+    /// Длина строки ограничена `max_field_len`, число документов —
+    /// `max_transactions`, число секций счетов — `max_statements`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Self> {
+        limits.check_input(content.len())?;
+        let mut lines = content.trim_start_matches('\u{feff}').lines().map(str::trim);
+
+        if lines.next() != Some(ONEC_SIGNATURE) {
+            return Err(Error::InvalidFormat(format!(
+                "Файл не начинается с {}",
+                ONEC_SIGNATURE
+            )));
+        }
+
+        let mut statement = OneCStatement {
+            format_version: String::new(),
+            encoding: "Windows".to_string(),
+            sender: None,
+            receiver: None,
+            creation_date: None,
+            creation_time: None,
+            date_from: None,
+            date_to: None,
+            account_numbers: Vec::new(),
+            accounts: Vec::new(),
+            documents: Vec::new(),
+        };
+
+        while let Some(line) = lines.next() {
+            if line.is_empty() {
+                continue;
+            }
+            limits.check_field("строка 1С", line)?;
+
+            let (key, value) = Self::split_line(line);
+
+            match key {
+                "КонецФайла" => return Ok(statement),
+                "СекцияРасчСчет" => {
+                    let fields = Self::collect_section(&mut lines, "КонецРасчСчет", limits)?;
+                    statement.accounts.push(Self::parse_account_section(&fields)?);
+                    limits.check_statements(statement.accounts.len())?;
+                }
+                "СекцияДокумент" => {
+                    let fields = Self::collect_section(&mut lines, "КонецДокумента", limits)?;
+                    match Self::parse_document(value, &fields) {
+                        Ok(document) => {
+                            statement.documents.push(document);
+                            limits.check_transactions(statement.documents.len())?;
+                        }
+                        Err(e) => {
+                            tracing::warn!("Не удалось распарсить документ 1С: {}", e);
+                        }
+                    }
+                }
+                "ВерсияФормата" => statement.format_version = value.to_string(),
+                "Кодировка" => statement.encoding = value.to_string(),
+                "Отправитель" => statement.sender = Self::non_empty(value),
+                "Получатель" => statement.receiver = Self::non_empty(value),
+                "ДатаСоздания" => statement.creation_date = Self::parse_optional_date(value)?,
+                "ВремяСоздания" => statement.creation_time = Self::non_empty(value),
+                "ДатаНачала" => statement.date_from = Self::parse_optional_date(value)?,
+                "ДатаКонца" => statement.date_to = Self::parse_optional_date(value)?,
+                "РасчСчет" => statement.account_numbers.push(value.to_string()),
+                _ => {}
+            }
+        }
+
+        tracing::warn!("Файл 1С не завершен строкой КонецФайла");
+        Ok(statement)
+    }
+
+    fn split_line(line: &str) -> (&str, &str) {
+        match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (line, ""),
+        }
+    }
+
+    fn collect_section<'a, I>(
+        lines: &mut I,
+        end_marker: &str,
+        limits: &Limits,
+    ) -> Result<Vec<(&'a str, &'a str)>>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut fields = Vec::new();
+
+        for line in lines.by_ref() {
+            if line == end_marker {
+                return Ok(fields);
+            }
+            if !line.is_empty() {
+                limits.check_field("строка 1С", line)?;
+                fields.push(Self::split_line(line));
+            }
+        }
+
+        Err(Error::InvalidFormat(format!("Не найден конец секции {}", end_marker)))
+    }
+
+    fn field<'a>(fields: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+        fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| *v)
+            .filter(|v| !v.is_empty())
+    }
+
+    fn non_empty(value: &str) -> Option<String> {
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+
+    fn parse_account_section(fields: &[(&str, &str)]) -> Result<OneCAccountSection> {
+        let amount = |key: &str| -> Result<i64> {
+            Self::field(fields, key).map(Self::parse_amount).unwrap_or(Ok(0))
+        };
+
+        Ok(OneCAccountSection {
+            date_from: Self::parse_optional_date(Self::field(fields, "ДатаНачала").unwrap_or(""))?,
+            date_to: Self::parse_optional_date(Self::field(fields, "ДатаКонца").unwrap_or(""))?,
+            account: Self::field(fields, "РасчСчет")
+                .ok_or_else(|| Error::MissingField("РасчСчет в СекцияРасчСчет".to_string()))?
+                .to_string(),
+            opening_balance: amount("НачальныйОстаток")?,
+            total_credit: amount("ВсегоПоступило")?,
+            total_debit: amount("ВсегоСписано")?,
+            closing_balance: amount("КонечныйОстаток")?,
+        })
+    }
+
+    fn parse_document(document_type: &str, fields: &[(&str, &str)]) -> Result<OneCDocument> {
+        let number = Self::field(fields, "Номер")
+            .ok_or_else(|| Error::MissingField("Номер".to_string()))?
+            .to_string();
+        let date = Self::parse_date(
+            Self::field(fields, "Дата").ok_or_else(|| Error::MissingField("Дата".to_string()))?,
+        )?;
+        let amount = Self::parse_amount(
+            Self::field(fields, "Сумма").ok_or_else(|| Error::MissingField("Сумма".to_string()))?,
+        )?;
+
+        let mut payer = OneCParty::default();
+        let mut payee = OneCParty::default();
+        let mut purpose_parts: Vec<&str> = Vec::new();
+        let mut document = OneCDocument {
+            document_type: if document_type.is_empty() {
+                ONEC_PAYMENT_ORDER.to_string()
+            } else {
+                document_type.to_string()
+            },
+            number,
+            date,
+            amount,
+            payer: OneCParty::default(),
+            payee: OneCParty::default(),
+            debited_date: None,
+            credited_date: None,
+            payment_kind: None,
+            priority: None,
+            purpose: String::new(),
+            other_fields: Vec::new(),
+        };
+
+        for &(key, value) in fields {
+            if let Some(suffix) = key.strip_prefix("Плательщик") {
+                if Self::set_party_field(&mut payer, suffix, value) {
+                    continue;
+                }
+            } else if let Some(suffix) = key.strip_prefix("Получатель") {
+                if Self::set_party_field(&mut payee, suffix, value) {
+                    continue;
+                }
+            }
+
+            match key {
+                "Номер" | "Дата" | "Сумма" => {}
+                "ДатаСписано" => document.debited_date = Self::parse_optional_date(value)?,
+                "ДатаПоступило" => document.credited_date = Self::parse_optional_date(value)?,
+                "ВидОплаты" => document.payment_kind = Self::non_empty(value),
+                "Очередность" => document.priority = Self::non_empty(value),
+                "НазначениеПлатежа" => purpose_parts.insert(0, value),
+                k if k.starts_with("НазначениеПлатежа") => purpose_parts.push(value),
+                _ => document.other_fields.push((key.to_string(), value.to_string())),
+            }
+        }
+
+        document.payer = payer;
+        document.payee = payee;
+        document.purpose = purpose_parts
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(document)
+    }
+
+    /// Заполняет реквизит участника по суффиксу ключа. Возвращает false для неизвестных ключей.
+    fn set_party_field(party: &mut OneCParty, suffix: &str, value: &str) -> bool {
+        let value = Self::non_empty(value);
+        let slot = match suffix {
+            "" => &mut party.full_name,
+            "Счет" => &mut party.account,
+            "1" => &mut party.name,
+            "ИНН" => &mut party.inn,
+            "КПП" => &mut party.kpp,
+            "РасчСчет" => &mut party.settlement_account,
+            "Банк1" => &mut party.bank_name,
+            "Банк2" => &mut party.bank_city,
+            "БИК" => &mut party.bik,
+            "Корсчет" => &mut party.correspondent_account,
+            _ => return false,
+        };
+        *slot = value;
+        true
+    }
+
+    fn parse_optional_date(value: &str) -> Result<Option<Date>> {
+        if value.is_empty() {
+            Ok(None)
+        } else {
+            Self::parse_date(value).map(Some)
+        }
+    }
+
+    fn parse_date(date_str: &str) -> Result<Date> {
+        let parts: Vec<&str> = date_str.trim().split('.').collect();
+        if parts.len() != 3 {
+            return Err(Error::Parse(format!("Некорректный формат даты: {}", date_str)));
+        }
+
+        let day: u8 = parts[0]
+            .parse()
+            .map_err(|_| Error::Parse(format!("Некорректный день: {}", parts[0])))?;
+        let month: u8 = parts[1]
+            .parse()
+            .map_err(|_| Error::Parse(format!("Некорректный месяц: {}", parts[1])))?;
+        let year: u16 = parts[2]
+            .parse()
+            .map_err(|_| Error::Parse(format!("Некорректный год: {}", parts[2])))?;
+
+        Date::from_ymd(year, month, day)
+    }
+
+    /// Парсит сумму вида `1540.00` без использования f64.
+    fn parse_amount(amount_str: &str) -> Result<i64> {
+        let amount_str = amount_str.trim();
+        let (is_negative, digits) = match amount_str.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, amount_str),
+        };
+        let normalized = digits.replace(',', ".");
+        let (whole_str, frac_str) = normalized.split_once('.').unwrap_or((&normalized, ""));
+
+        if whole_str.is_empty() && frac_str.is_empty() {
+            return Err(Error::Parse("Пустая сумма".to_string()));
+        }
+
+        let whole: i64 = if whole_str.is_empty() {
+            0
+        } else {
+            whole_str
+                .parse()
+                .map_err(|_| Error::Parse(format!("Некорректная сумма: {}", amount_str)))?
+        };
+
+        let frac: i64 = match frac_str.len() {
+            0 => Ok(0),
+            1 => frac_str.parse::<i64>().map(|f| f * 10),
+            _ => frac_str.get(..2).unwrap_or_default().parse(),
+        }
+        .map_err(|_| Error::Parse(format!("Некорректная сумма: {}", amount_str)))?;
+
+        let amount = whole
+            .checked_mul(100)
+            .and_then(|w| w.checked_add(frac))
+            .ok_or_else(|| Error::Parse("Переполнение при парсинге суммы".to_string()))?;
+
+        Ok(if is_negative { -amount } else { amount })
+    }
+
+    /// Преобразует файл обмена в выписки: по одной на каждый расчетный счет.
+    ///
+    /// Если секций остатков нет, выписки строятся по счетам из заголовка
+    /// с нулевыми остатками. Период без дат в секции и заголовке берется
+    /// по датам документов; если нет и документов, возвращается
+    /// [`Error::MissingField`].
+    pub fn into_statements(self) -> Result<Vec<Statement>> {
+        let mut sections = self.accounts;
+
+        for number in &self.account_numbers {
+            if !sections.iter().any(|s| &s.account == number) {
+                sections.push(OneCAccountSection {
+                    date_from: self.date_from,
+                    date_to: self.date_to,
+                    account: number.clone(),
+                    opening_balance: 0,
+                    total_credit: 0,
+                    total_debit: 0,
+                    closing_balance: 0,
+                });
+            }
+        }
+
+        if sections.is_empty() {
+            return Err(Error::MissingField(
+                "Не найдено ни одного расчетного счета (СекцияРасчСчет или РасчСчет)".to_string(),
+            ));
+        }
+
+        let default_from = self.date_from.or_else(|| self.documents.iter().map(|d| d.date).min());
+        let default_to = self.date_to.or_else(|| self.documents.iter().map(|d| d.date).max());
+
+        sections
+            .into_iter()
+            .map(|section| {
+                let date_from = section.date_from.or(default_from).ok_or_else(|| {
+                    Error::MissingField(format!("ДатаНачала для счета {}", section.account))
+                })?;
+                let date_to = section.date_to.or(default_to).ok_or_else(|| {
+                    Error::MissingField(format!("ДатаКонца для счета {}", section.account))
+                })?;
+                let transactions = self
+                    .documents
+                    .iter()
+                    .filter_map(|doc| Self::document_to_transaction(doc, &section.account))
+                    .collect();
+
+                let own_party = self.documents.iter().find_map(|doc| {
+                    [&doc.payer, &doc.payee]
+                        .into_iter()
+                        .find(|p| p.account.as_deref() == Some(section.account.as_str()))
+                });
+                let owner = own_party.and_then(|p| p.name.clone().or_else(|| p.full_name.clone()));
+                let tax_id = own_party.and_then(|p| p.inn.clone());
+
+                Ok(Statement {
+                    account: Account {
+                        iban: None,
+                        number: section.account.clone(),
+                        currency: ONEC_CURRENCY.to_string(),
+                        name: None,
+                        owner,
+                        tax_id,
+                    },
+                    opening_balance: Balance {
+                        amount: Amount::new(section.opening_balance, ONEC_CURRENCY),
+                        date: date_from,
+                        is_credit: section.opening_balance >= 0,
+                    },
+                    closing_balance: Balance {
+                        amount: Amount::new(section.closing_balance, ONEC_CURRENCY),
+                        date: date_to,
+                        is_credit: section.closing_balance >= 0,
+                    },
+                    transactions,
+                    statement_number: None,
+                    reference: None,
+                })
+            })
+            .collect()
+    }
+
+    fn document_to_transaction(doc: &OneCDocument, account: &str) -> Option<Transaction> {
+        let (is_credit, counterparty, date) = if doc.payer.account.as_deref() == Some(account) {
+            (false, &doc.payee, doc.debited_date.as_ref())
+        } else if doc.payee.account.as_deref() == Some(account) {
+            (true, &doc.payer, doc.credited_date.as_ref())
+        } else {
+            return None;
+        };
+
+        Some(Transaction {
+            date: *date.unwrap_or(&doc.date),
+            value_date: None,
+            amount: Amount::new(doc.amount, ONEC_CURRENCY),
+            is_credit,
+            reference: Some(doc.number.clone()),
+            transaction_type: None,
+            description: doc.purpose.clone(),
+            counterparty: Some(counterparty.to_counterparty()),
+            instructed_amount: None,
+            original_amount: None,
+            counter_value_amount: None,
+            exchange_rates: Vec::new(),
+            charges: Vec::new(),
+        })
+    }
+}
+
+impl OneCParty {
+    fn to_counterparty(&self) -> Counterparty {
+        Counterparty {
+            name: self.name.clone().or_else(|| self.full_name.clone()),
+            account: self.account.clone(),
+            bank_code: self.bik.clone(),
+            bank_name: self.bank_name.clone(),
+            bank_correspondent_account: self.correspondent_account.clone(),
+            tax_id: self.inn.clone(),
+            tax_reason_code: self.kpp.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(OneCStatement::parse_amount("1540.00").unwrap(), 154000);
+        assert_eq!(OneCStatement::parse_amount("1540.5").unwrap(), 154050);
+        assert_eq!(OneCStatement::parse_amount("-7").unwrap(), -700);
+        assert!(OneCStatement::parse_amount("abc").is_err());
+        assert!(OneCStatement::parse_amount("5.Ж0").is_err());
+    }
+}
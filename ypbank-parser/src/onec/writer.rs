@@ -0,0 +1,242 @@
+//! Сериализация формата 1CClientBankExchange.
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::Result;
+use crate::onec::parser::{
+    OneCAccountSection, OneCDocument, OneCParty, OneCStatement, ONEC_SIGNATURE,
+};
+use crate::types::{AmountFormat, Date};
+use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Writer для формата 1CClientBankExchange.
+///
+/// Строки разделяются CRLF, как принято в файлах обмена 1С.
+/// Поле `Кодировка` всегда соответствует кодировке записанных байтов:
+/// `Windows`, `DOS` или `UTF-8`. Сама 1С принимает только первые две,
+/// поэтому для загрузки в 1С используйте `write_to_with_encoding`
+/// с `TextEncoding::Windows1251`.
+pub struct OneCWriter;
+
+impl OneCWriter {
+    /// Записывает файл обмена в UTF-8 в любой приемник, реализующий трейт Write.
+    ///
+    /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
+    pub fn write_to<W: Write>(statement: &OneCStatement, writer: &mut W) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_to_buffered(statement, &mut buf_writer, TextEncoding::Utf8)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает файл обмена в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statement: &OneCStatement,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(statement, buffer)).await
+    }
+
+    /// Записывает файл обмена в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        statement: &OneCStatement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(statement, buffer, encoding)
+        })
+    }
+
+    /// Асинхронно записывает файл обмена в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        statement: &OneCStatement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(statement, buffer, encoding)
+        })
+        .await
+    }
+
+    fn write_to_buffered<W: Write>(
+        statement: &OneCStatement,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        // Поле Кодировка должно соответствовать фактической кодировке файла
+        let encoding_name = match encoding {
+            TextEncoding::Windows1251 => "Windows",
+            TextEncoding::Cp866 => "DOS",
+            TextEncoding::Utf8 => "UTF-8",
+        };
+
+        Self::write_line(writer, ONEC_SIGNATURE)?;
+        Self::write_field(writer, "ВерсияФормата", &statement.format_version)?;
+        Self::write_field(writer, "Кодировка", encoding_name)?;
+        Self::write_field(writer, "Отправитель", statement.sender.as_deref().unwrap_or(""))?;
+        Self::write_field(writer, "Получатель", statement.receiver.as_deref().unwrap_or(""))?;
+        Self::write_date_field(writer, "ДатаСоздания", statement.creation_date.as_ref())?;
+        if let Some(ref time) = statement.creation_time {
+            Self::write_field(writer, "ВремяСоздания", time)?;
+        }
+        Self::write_date_field(writer, "ДатаНачала", statement.date_from.as_ref())?;
+        Self::write_date_field(writer, "ДатаКонца", statement.date_to.as_ref())?;
+
+        for number in &statement.account_numbers {
+            Self::write_field(writer, "РасчСчет", number)?;
+        }
+
+        let mut document_types: Vec<&str> = Vec::new();
+        for document in &statement.documents {
+            if !document_types.contains(&document.document_type.as_str()) {
+                document_types.push(&document.document_type);
+            }
+        }
+        for document_type in document_types {
+            Self::write_field(writer, "Документ", document_type)?;
+        }
+
+        for section in &statement.accounts {
+            Self::write_account_section(writer, section)?;
+        }
+
+        for document in &statement.documents {
+            Self::write_document(writer, document)?;
+        }
+
+        Self::write_line(writer, "КонецФайла")?;
+
+        Ok(())
+    }
+
+    fn write_account_section<W: Write>(writer: &mut W, section: &OneCAccountSection) -> Result<()> {
+        Self::write_line(writer, "СекцияРасчСчет")?;
+        Self::write_date_field(writer, "ДатаНачала", section.date_from.as_ref())?;
+        Self::write_date_field(writer, "ДатаКонца", section.date_to.as_ref())?;
+        Self::write_field(writer, "РасчСчет", &section.account)?;
+        Self::write_field(writer, "НачальныйОстаток", &Self::format_amount(section.opening_balance))?;
+        Self::write_field(writer, "ВсегоПоступило", &Self::format_amount(section.total_credit))?;
+        Self::write_field(writer, "ВсегоСписано", &Self::format_amount(section.total_debit))?;
+        Self::write_field(writer, "КонечныйОстаток", &Self::format_amount(section.closing_balance))?;
+        Self::write_line(writer, "КонецРасчСчет")?;
+
+        Ok(())
+    }
+
+    fn write_document<W: Write>(writer: &mut W, document: &OneCDocument) -> Result<()> {
+        Self::write_field(writer, "СекцияДокумент", &document.document_type)?;
+        Self::write_field(writer, "Номер", &document.number)?;
+        Self::write_field(writer, "Дата", &Self::format_date(&document.date))?;
+        Self::write_field(writer, "Сумма", &Self::format_amount(document.amount))?;
+
+        Self::write_party(writer, "Плательщик", &document.payer)?;
+        Self::write_date_field(writer, "ДатаСписано", document.debited_date.as_ref())?;
+        Self::write_party(writer, "Получатель", &document.payee)?;
+        Self::write_date_field(writer, "ДатаПоступило", document.credited_date.as_ref())?;
+
+        if let Some(ref payment_kind) = document.payment_kind {
+            Self::write_field(writer, "ВидОплаты", payment_kind)?;
+        }
+        if let Some(ref priority) = document.priority {
+            Self::write_field(writer, "Очередность", priority)?;
+        }
+
+        for (key, value) in &document.other_fields {
+            Self::write_field(writer, key, value)?;
+        }
+
+        Self::write_field(writer, "НазначениеПлатежа", &document.purpose)?;
+        Self::write_line(writer, "КонецДокумента")?;
+
+        Ok(())
+    }
+
+    fn write_party<W: Write>(writer: &mut W, prefix: &str, party: &OneCParty) -> Result<()> {
+        let fields = [
+            ("Счет", &party.account),
+            ("", &party.full_name),
+            ("ИНН", &party.inn),
+            ("КПП", &party.kpp),
+            ("1", &party.name),
+            ("РасчСчет", &party.settlement_account),
+            ("Банк1", &party.bank_name),
+            ("Банк2", &party.bank_city),
+            ("БИК", &party.bik),
+            ("Корсчет", &party.correspondent_account),
+        ];
+
+        for (suffix, value) in fields {
+            if let Some(value) = value {
+                Self::write_field(writer, &format!("{}{}", prefix, suffix), value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_date_field<W: Write>(writer: &mut W, key: &str, date: Option<&Date>) -> Result<()> {
+        match date {
+            Some(date) => Self::write_field(writer, key, &Self::format_date(date)),
+            None => Ok(()),
+        }
+    }
+
+    fn write_field<W: Write>(writer: &mut W, key: &str, value: &str) -> Result<()> {
+        // Значение не может содержать перевод строки
+        let value = value.replace(['\r', '\n'], " ");
+        write!(writer, "{}={}\r\n", key, value)?;
+        Ok(())
+    }
+
+    fn write_line<W: Write>(writer: &mut W, line: &str) -> Result<()> {
+        write!(writer, "{}\r\n", line)?;
+        Ok(())
+    }
+
+    fn format_amount(amount: i64) -> String {
+        AmountFormat::PLAIN.format_value(amount)
+    }
+
+    fn format_date(date: &Date) -> String {
+        format!("{:02}.{:02}.{:04}", date.day(), date.month(), date.year())
+    }
+}
+
+impl OneCStatement {
+    /// Записывает файл обмена в любой приемник, реализующий трейт Write.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        OneCWriter::write_to(self, writer)
+    }
+
+    /// Асинхронно записывает файл обмена в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает файл обмена в указанной кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        OneCWriter::write_to_with_encoding(self, writer, encoding)
+    }
+
+    /// Асинхронно записывает файл обмена в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to_with_encoding(buffer, encoding)).await
+    }
+}
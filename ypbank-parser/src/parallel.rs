@@ -0,0 +1,21 @@
+//! Обработка независимых блоков входа: выписок MT940 и элементов `<Stmt>`.
+//!
+//! С feature `parallel` блоки разбираются в пуле потоков rayon, без нее —
+//! последовательно. Результаты в обоих случаях идут в порядке блоков во входе.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Применяет `f` к каждому блоку и возвращает результаты в исходном порядке.
+pub(crate) fn map_ordered<T, U, F>(blocks: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    return blocks.par_iter().map(f).collect();
+
+    #[cfg(not(feature = "parallel"))]
+    blocks.iter().map(f).collect()
+}
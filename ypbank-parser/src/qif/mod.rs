@@ -0,0 +1,11 @@
+//! Модуль парсинга и сериализации формата QIF (Quicken Interchange Format).
+//!
+//! Текстовый формат программ учета личных финансов: списки `!Type:Bank`
+//! из записей, каждое поле которых занимает строку с однобуквенным кодом
+//! (`D` — дата, `T` — сумма, `P` — получатель), а запись завершается `^`.
+
+pub mod parser;
+pub mod writer;
+
+pub use parser::{QifAccount, QifDateStyle, QifFile, QifTransaction};
+pub use writer::QifWriter;
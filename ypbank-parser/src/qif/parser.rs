@@ -0,0 +1,542 @@
+//! Нестрогий парсер формата QIF (Quicken Interchange Format).
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::types::{
+    Account, Amount, Balance, Counterparty, Date, Statement, Transaction, DEFAULT_CENTURY_PIVOT,
+};
+use std::io::Read;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Тип списка операций по банковскому счету.
+pub const QIF_TYPE_BANK: &str = "Bank";
+
+/// Получатель в служебной операции входящего остатка (соглашение Quicken).
+pub const QIF_OPENING_BALANCE: &str = "Opening Balance";
+
+/// QIF не передает валюту; используется при преобразовании в Statement.
+const QIF_CURRENCY: &str = "RUB";
+
+/// Порядок полей даты в QIF.
+///
+/// Стандартного формата дат в QIF нет: американские программы пишут
+/// месяц первым, европейские — день.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QifDateStyle {
+    /// MM/DD/YYYY, десятичный разделитель суммы — точка.
+    #[default]
+    Us,
+    /// DD/MM/YYYY, запятая в сумме без точки считается десятичным разделителем.
+    Eu,
+}
+
+impl std::str::FromStr for QifDateStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "us" | "mdy" => Ok(QifDateStyle::Us),
+            "eu" | "dmy" => Ok(QifDateStyle::Eu),
+            other => Err(Error::InvalidFormat(format!(
+                "Неизвестный формат дат QIF: {} (ожидается us или eu)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Файл QIF: один или несколько счетов.
+#[derive(Debug, Clone)]
+pub struct QifFile {
+    /// Порядок полей даты, с которым файл прочитан или будет записан.
+    pub date_style: QifDateStyle,
+    /// Счета с операциями.
+    pub accounts: Vec<QifAccount>,
+}
+
+/// Счет QIF (блок `!Account` и следующий за ним список `!Type:`).
+#[derive(Debug, Clone)]
+pub struct QifAccount {
+    /// Наименование счета (N в блоке `!Account`).
+    pub name: Option<String>,
+    /// Тип списка операций: Bank, CCard, Cash и т.д.
+    pub account_type: String,
+    /// Описание счета (D в блоке `!Account`).
+    pub description: Option<String>,
+    /// Операции.
+    pub transactions: Vec<QifTransaction>,
+}
+
+/// Операция QIF (запись, завершаемая `^`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QifTransaction {
+    /// Дата (D).
+    pub date: Date,
+    /// Сумма со знаком в минимальных единицах (T или U).
+    pub amount: i64,
+    /// Номер документа (N).
+    pub number: Option<String>,
+    /// Получатель или плательщик (P).
+    pub payee: Option<String>,
+    /// Примечание (M).
+    pub memo: Option<String>,
+    /// Категория или счет перевода (L).
+    pub category: Option<String>,
+    /// Отметка сверки (C).
+    pub cleared: Option<String>,
+}
+
+/// Поля накапливаемой записи до символа `^`.
+#[derive(Default)]
+struct PendingRecord {
+    date: Option<String>,
+    amount: Option<String>,
+    number: Option<String>,
+    payee: Option<String>,
+    memo: Option<String>,
+    category: Option<String>,
+    cleared: Option<String>,
+}
+
+impl PendingRecord {
+    fn is_empty(&self) -> bool {
+        self.date.is_none() && self.amount.is_none() && self.payee.is_none()
+    }
+}
+
+impl QifFile {
+    /// Парсит QIF из любого источника, реализующего трейт Read.
+    ///
+    /// Даты читаются в американском порядке, см. [`QifFile::parse_with_style`].
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string(reader, None)?;
+        Self::parse(&content)
+    }
+
+    /// Асинхронно парсит QIF из любого источника, реализующего трейт AsyncRead.
+    ///
+    /// Даты читаются в американском порядке, см. [`QifFile::parse_with_style`].
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let content = encoding::read_to_string_async(reader, None).await?;
+        Self::parse(&content)
+    }
+
+    /// Парсит QIF из байтов в заданной кодировке.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn parse_bytes(
+        bytes: &[u8],
+        encoding: Option<TextEncoding>,
+        date_style: QifDateStyle,
+    ) -> Result<Self> {
+        Self::parse_with_style(&encoding::decode(bytes, encoding)?, date_style)
+    }
+
+    /// Парсит QIF из строки с американским порядком дат.
+    /// Применяются [`Limits::default`].
+    pub fn parse(content: &str) -> Result<Self> {
+        Self::parse_with_style(content, QifDateStyle::default())
+    }
+
+    /// Парсит QIF из строки с американским порядком дат и заданными ограничениями.
+    ///
+    /// Длина строки ограничена `max_field_len`, число счетов —
+    /// `max_statements`, число операций в счете — `max_transactions`.
+    pub fn parse_with_limits(content: &str, limits: &Limits) -> Result<Self> {
+        Self::parse_lines(content, QifDateStyle::default(), limits)
+    }
+
+    /// Парсит QIF из строки с заданным порядком дат.
+    ///
+    /// Разбор нестрогий: неизвестные поля и списки, отличные от операций
+    /// по счетам (категории, инвестиции), пропускаются; записи без даты
+    /// или суммы пропускаются с предупреждением.
+    pub fn parse_with_style(content: &str, date_style: QifDateStyle) -> Result<Self> {
+        Self::parse_lines(content, date_style, &Limits::default())
+    }
+
+    fn parse_lines(content: &str, date_style: QifDateStyle, limits: &Limits) -> Result<Self> {
+        limits.check_input(content.len())?;
+        let mut file = QifFile {
+            date_style,
+            accounts: Vec::new(),
+        };
+        let mut current: Option<QifAccount> = None;
+        let mut pending = PendingRecord::default();
+        let mut in_account_header = false;
+        let mut skip_section = false;
+        let mut has_content = false;
+
+        for line in content.trim_start_matches('\u{feff}').lines() {
+            let line = line.trim_end();
+            if line.trim().is_empty() {
+                continue;
+            }
+            limits.check_field("строка QIF", line)?;
+            has_content = true;
+
+            if let Some(header) = line.strip_prefix('!') {
+                let header = header.trim();
+                let (kind, value) = header.split_once(':').unwrap_or((header, ""));
+
+                match kind.to_lowercase().as_str() {
+                    "account" => {
+                        if let Some(account) = current.take() {
+                            file.push_account(account, limits)?;
+                        }
+                        current = Some(Self::new_account(QIF_TYPE_BANK));
+                        in_account_header = true;
+                        skip_section = false;
+                    }
+                    "type" => {
+                        in_account_header = false;
+                        skip_section = Self::is_non_transaction_type(value);
+                        if skip_section {
+                            tracing::warn!("Пропущен список QIF !Type:{}", value);
+                            continue;
+                        }
+                        match current {
+                            // Список операций относится к только что описанному счету
+                            Some(ref mut account) if account.transactions.is_empty() => {
+                                account.account_type = value.trim().to_string();
+                            }
+                            _ => {
+                                if let Some(account) = current.take() {
+                                    file.push_account(account, limits)?;
+                                }
+                                current = Some(Self::new_account(value.trim()));
+                            }
+                        }
+                    }
+                    // !Option:AutoSwitch, !Clear:AutoSwitch и прочие директивы
+                    _ => {}
+                }
+                continue;
+            }
+
+            if skip_section {
+                continue;
+            }
+
+            let (code, value) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+            let value = value.trim();
+
+            if in_account_header {
+                let account = current.get_or_insert_with(|| Self::new_account(QIF_TYPE_BANK));
+                match code {
+                    "N" => account.name = Self::non_empty(value),
+                    "T" => account.account_type = value.to_string(),
+                    "D" => account.description = Self::non_empty(value),
+                    "^" => in_account_header = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match code {
+                "D" => pending.date = Some(value.to_string()),
+                // U дублирует T в новых версиях Quicken; T имеет приоритет
+                "T" => pending.amount = Some(value.to_string()),
+                "U" if pending.amount.is_none() => pending.amount = Some(value.to_string()),
+                "N" => pending.number = Self::non_empty(value),
+                "P" => pending.payee = Self::non_empty(value),
+                "M" => pending.memo = Self::non_empty(value),
+                "L" => pending.category = Self::non_empty(value),
+                "C" => pending.cleared = Self::non_empty(value),
+                "^" => {
+                    let record = std::mem::take(&mut pending);
+                    let account = current.get_or_insert_with(|| Self::new_account(QIF_TYPE_BANK));
+                    Self::push_record(account, record, date_style, limits)?;
+                }
+                // Адрес (A), разбиение (S, E, $) и прочие поля не используются
+                _ => {}
+            }
+        }
+
+        // Последняя запись без завершающего ^
+        if !pending.is_empty() {
+            let account = current.get_or_insert_with(|| Self::new_account(QIF_TYPE_BANK));
+            Self::push_record(account, pending, date_style, limits)?;
+        }
+        if let Some(account) = current.take() {
+            file.push_account(account, limits)?;
+        }
+
+        if !has_content {
+            return Err(Error::InvalidFormat("Пустой файл QIF".to_string()));
+        }
+
+        Ok(file)
+    }
+
+    fn new_account(account_type: &str) -> QifAccount {
+        QifAccount {
+            name: None,
+            account_type: account_type.to_string(),
+            description: None,
+            transactions: Vec::new(),
+        }
+    }
+
+    /// Списки, не содержащие операций по счетам.
+    fn is_non_transaction_type(value: &str) -> bool {
+        matches!(
+            value.trim().to_lowercase().as_str(),
+            "cat" | "class" | "memorized" | "invst" | "security" | "prices"
+        )
+    }
+
+    fn push_account(&mut self, account: QifAccount, limits: &Limits) -> Result<()> {
+        self.accounts.push(account);
+        limits.check_statements(self.accounts.len())
+    }
+
+    fn push_record(
+        account: &mut QifAccount,
+        record: PendingRecord,
+        date_style: QifDateStyle,
+        limits: &Limits,
+    ) -> Result<()> {
+        match Self::build_transaction(record, date_style) {
+            Ok(tx) => {
+                account.transactions.push(tx);
+                limits.check_transactions(account.transactions.len())?;
+            }
+            Err(e) => {
+                tracing::warn!("Не удалось распарсить операцию QIF: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn build_transaction(record: PendingRecord, date_style: QifDateStyle) -> Result<QifTransaction> {
+        let date = record
+            .date
+            .ok_or_else(|| Error::MissingField("Отсутствует дата (D)".to_string()))?;
+        let amount = record
+            .amount
+            .ok_or_else(|| Error::MissingField("Отсутствует сумма (T)".to_string()))?;
+
+        Ok(QifTransaction {
+            date: Self::parse_date(&date, date_style)?,
+            amount: Self::parse_amount(&amount, date_style)?,
+            number: record.number,
+            payee: record.payee,
+            memo: record.memo,
+            category: record.category,
+            cleared: record.cleared,
+        })
+    }
+
+    fn non_empty(value: &str) -> Option<String> {
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+
+    /// Парсит дату QIF.
+    ///
+    /// Допускаются разделители `/`, `.`, `-` и апостроф Quicken (`1/15'24`),
+    /// двузначный год и ISO-порядок, если первое поле состоит из четырех цифр.
+    pub(crate) fn parse_date(date_str: &str, date_style: QifDateStyle) -> Result<Date> {
+        let invalid = || Error::Parse(format!("Некорректная дата QIF: {}", date_str));
+
+        let parts: Vec<&str> = date_str
+            .split(['/', '.', '-', '\''])
+            .map(str::trim)
+            .collect();
+        if parts.len() != 3 || parts.iter().any(|p| p.is_empty()) {
+            return Err(invalid());
+        }
+
+        let number = |s: &str| s.parse::<u16>().map_err(|_| invalid());
+        let (year, month, day) = if parts[0].len() == 4 {
+            (number(parts[0])?, number(parts[1])?, number(parts[2])?)
+        } else {
+            let (month, day) = match date_style {
+                QifDateStyle::Us => (number(parts[0])?, number(parts[1])?),
+                QifDateStyle::Eu => (number(parts[1])?, number(parts[0])?),
+            };
+            let year = number(parts[2])?;
+            let year = match parts[2].len() {
+                1 | 2 => Date::expand_year(year as u8, DEFAULT_CENTURY_PIVOT),
+                _ => year,
+            };
+            (year, month, day)
+        };
+
+        let month = u8::try_from(month).map_err(|_| invalid())?;
+        let day = u8::try_from(day).map_err(|_| invalid())?;
+        Date::from_ymd(year, month, day).map_err(|_| invalid())
+    }
+
+    /// Парсит сумму без использования f64.
+    ///
+    /// Если в строке есть и точка, и запятая, десятичным считается последний
+    /// разделитель. Одиночная запятая — десятичный разделитель только для
+    /// европейского стиля.
+    pub(crate) fn parse_amount(amount_str: &str, date_style: QifDateStyle) -> Result<i64> {
+        let invalid = || Error::Parse(format!("Некорректная сумма QIF: {}", amount_str));
+
+        let trimmed: String = amount_str.chars().filter(|c| !c.is_whitespace()).collect();
+        let is_negative = trimmed.starts_with('-');
+        let unsigned = trimmed.trim_start_matches(['-', '+']);
+
+        let decimal = match (unsigned.rfind('.'), unsigned.rfind(',')) {
+            (Some(dot), Some(comma)) => Some(dot.max(comma)),
+            (Some(dot), None) => Some(dot),
+            (None, Some(comma)) if date_style == QifDateStyle::Eu => Some(comma),
+            _ => None,
+        };
+        let (whole_str, frac_str) = match decimal {
+            Some(pos) => (&unsigned[..pos], &unsigned[pos + 1..]),
+            None => (unsigned, ""),
+        };
+        let whole_digits: String = whole_str.chars().filter(|c| *c != ',' && *c != '.').collect();
+
+        if whole_digits.is_empty() && frac_str.is_empty() {
+            return Err(invalid());
+        }
+        if !whole_digits.chars().all(|c| c.is_ascii_digit())
+            || !frac_str.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let whole: i64 = if whole_digits.is_empty() {
+            0
+        } else {
+            whole_digits.parse().map_err(|_| invalid())?
+        };
+        let frac: i64 = match frac_str.len() {
+            0 => 0,
+            1 => frac_str.parse::<i64>().map_err(|_| invalid())? * 10,
+            _ => frac_str.get(..2).unwrap_or_default().parse().map_err(|_| invalid())?,
+        };
+
+        let amount = whole
+            .checked_mul(100)
+            .and_then(|w| w.checked_add(frac))
+            .ok_or_else(|| Error::Parse("Переполнение при парсинге суммы".to_string()))?;
+
+        Ok(if is_negative { -amount } else { amount })
+    }
+
+    /// Преобразует файл в выписки: по одной на каждый счет.
+    ///
+    /// Операция с получателем `Opening Balance` в начале списка считается
+    /// входящим остатком; исходящий остаток рассчитывается по оборотам.
+    /// Для счета без операций дата остатков неизвестна, поэтому возвращается
+    /// [`Error::MissingField`].
+    pub fn into_statements(self) -> Result<Vec<Statement>> {
+        self.accounts
+            .into_iter()
+            .enumerate()
+            .map(|(idx, account)| {
+                let mut transactions = account.transactions;
+                let opening = match transactions.first() {
+                    Some(tx) if tx.payee.as_deref() == Some(QIF_OPENING_BALANCE) => {
+                        Some(transactions.remove(0))
+                    }
+                    _ => None,
+                };
+
+                let first_date = opening
+                    .as_ref()
+                    .or(transactions.first())
+                    .map(|tx| tx.date)
+                    .ok_or_else(|| {
+                        Error::MissingField(format!(
+                            "Операции счета {} (дата остатков неизвестна)",
+                            account.name.as_deref().unwrap_or("без имени")
+                        ))
+                    })?;
+                let last_date = transactions
+                    .last()
+                    .map(|tx| tx.date)
+                    .unwrap_or_else(|| first_date);
+
+                let opening_amount = opening.map_or(0, |tx| tx.amount);
+                let closing_amount = transactions
+                    .iter()
+                    .fold(opening_amount, |sum, tx| sum.saturating_add(tx.amount));
+
+                let balance = |amount: i64, date: Date| Balance {
+                    amount: Amount::new(amount.abs(), QIF_CURRENCY),
+                    date,
+                    is_credit: amount >= 0,
+                };
+
+                Ok(Statement {
+                    account: Account {
+                        iban: None,
+                        number: account.name.clone().unwrap_or_else(|| format!("QIF{}", idx + 1)),
+                        currency: QIF_CURRENCY.to_string(),
+                        name: account.description,
+                        owner: None,
+                        tax_id: None,
+                    },
+                    opening_balance: balance(opening_amount, first_date),
+                    closing_balance: balance(closing_amount, last_date),
+                    transactions: transactions
+                        .into_iter()
+                        .map(|tx| Transaction {
+                            date: tx.date,
+                            value_date: None,
+                            amount: Amount::new(tx.amount.abs(), QIF_CURRENCY),
+                            is_credit: tx.amount >= 0,
+                            reference: tx.number,
+                            transaction_type: None,
+                            description: tx.memo.or_else(|| tx.payee.clone()).unwrap_or_default(),
+                            counterparty: tx.payee.map(|name| Counterparty {
+                                name: Some(name),
+                                ..Default::default()
+                            }),
+                            instructed_amount: None,
+                            original_amount: None,
+                            counter_value_amount: None,
+                            exchange_rates: Vec::new(),
+                            charges: Vec::new(),
+                        })
+                        .collect(),
+                    statement_number: None,
+                    reference: None,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_styles() {
+        let us = QifDateStyle::Us;
+        let eu = QifDateStyle::Eu;
+
+        assert_eq!(QifFile::parse_date("01/15/2024", us).unwrap(), Date::new(2024, 1, 15));
+        assert_eq!(QifFile::parse_date("1/15'24", us).unwrap(), Date::new(2024, 1, 15));
+        assert_eq!(QifFile::parse_date("15.01.2024", eu).unwrap(), Date::new(2024, 1, 15));
+        assert_eq!(QifFile::parse_date("2024-01-15", eu).unwrap(), Date::new(2024, 1, 15));
+        assert!(QifFile::parse_date("15/01/2024", us).is_err());
+    }
+
+    #[test]
+    fn test_parse_amount_separators() {
+        let us = QifDateStyle::Us;
+        let eu = QifDateStyle::Eu;
+
+        assert_eq!(QifFile::parse_amount("-1,234.56", us).unwrap(), -123456);
+        assert_eq!(QifFile::parse_amount("1,234", us).unwrap(), 123400);
+        assert_eq!(QifFile::parse_amount("1.234,56", eu).unwrap(), 123456);
+        assert_eq!(QifFile::parse_amount("-65,5", eu).unwrap(), -6550);
+        assert!(QifFile::parse_amount("abc", us).is_err());
+    }
+}
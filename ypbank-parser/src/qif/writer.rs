@@ -0,0 +1,158 @@
+//! Сериализация формата QIF.
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::Result;
+use crate::qif::parser::{QifAccount, QifDateStyle, QifFile, QifTransaction};
+use crate::types::{AmountFormat, Date};
+use std::io::{BufWriter, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Writer для формата QIF.
+///
+/// Порядок полей даты определяется полем [`QifFile::date_style`],
+/// суммы всегда пишутся с точкой и без разделителей разрядов.
+pub struct QifWriter;
+
+impl QifWriter {
+    /// Записывает файл QIF в любой приемник, реализующий трейт Write.
+    ///
+    /// Использует внутреннюю буферизацию для уменьшения количества syscalls.
+    pub fn write_to<W: Write>(file: &QifFile, writer: &mut W) -> Result<()> {
+        let mut buf_writer = BufWriter::new(writer);
+        Self::write_to_buffered(file, &mut buf_writer)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает файл QIF в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        file: &QifFile,
+        writer: &mut W,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| Self::write_to(file, buffer)).await
+    }
+
+    /// Записывает файл QIF в указанной кодировке.
+    ///
+    /// Возвращает ошибку, если текст содержит символы, не представимые в кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        file: &QifFile,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_encoded(writer, encoding, |buffer| {
+            Self::write_to_buffered(file, buffer)
+        })
+    }
+
+    /// Асинхронно записывает файл QIF в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        file: &QifFile,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| {
+            Self::write_to_with_encoding(file, buffer, encoding)
+        })
+        .await
+    }
+
+    fn write_to_buffered<W: Write>(file: &QifFile, writer: &mut W) -> Result<()> {
+        for account in &file.accounts {
+            Self::write_account(writer, account, file.date_style)?;
+        }
+        Ok(())
+    }
+
+    fn write_account<W: Write>(
+        writer: &mut W,
+        account: &QifAccount,
+        date_style: QifDateStyle,
+    ) -> Result<()> {
+        // Заголовок счета нужен, чтобы несколько счетов не слились при импорте
+        if account.name.is_some() {
+            writeln!(writer, "!Account")?;
+            Self::write_field(writer, 'N', account.name.as_deref())?;
+            Self::write_field(writer, 'T', Some(&account.account_type))?;
+            Self::write_field(writer, 'D', account.description.as_deref())?;
+            writeln!(writer, "^")?;
+        }
+
+        writeln!(writer, "!Type:{}", account.account_type)?;
+        for tx in &account.transactions {
+            Self::write_transaction(writer, tx, date_style)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_transaction<W: Write>(
+        writer: &mut W,
+        tx: &QifTransaction,
+        date_style: QifDateStyle,
+    ) -> Result<()> {
+        writeln!(writer, "D{}", Self::format_date(&tx.date, date_style))?;
+        writeln!(writer, "T{}", Self::format_amount(tx.amount))?;
+        Self::write_field(writer, 'C', tx.cleared.as_deref())?;
+        Self::write_field(writer, 'N', tx.number.as_deref())?;
+        Self::write_field(writer, 'P', tx.payee.as_deref())?;
+        Self::write_field(writer, 'M', tx.memo.as_deref())?;
+        Self::write_field(writer, 'L', tx.category.as_deref())?;
+        writeln!(writer, "^")?;
+        Ok(())
+    }
+
+    fn write_field<W: Write>(writer: &mut W, code: char, value: Option<&str>) -> Result<()> {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            // Каждое поле QIF занимает ровно одну строку
+            writeln!(writer, "{}{}", code, value.replace(['\r', '\n'], " "))?;
+        }
+        Ok(())
+    }
+
+    fn format_amount(amount: i64) -> String {
+        AmountFormat::PLAIN.format_value(amount)
+    }
+
+    fn format_date(date: &Date, date_style: QifDateStyle) -> String {
+        match date_style {
+            QifDateStyle::Us => format!("{:02}/{:02}/{:04}", date.month(), date.day(), date.year()),
+            QifDateStyle::Eu => format!("{:02}/{:02}/{:04}", date.day(), date.month(), date.year()),
+        }
+    }
+}
+
+impl QifFile {
+    /// Записывает файл QIF в любой приемник, реализующий трейт Write.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        QifWriter::write_to(self, writer)
+    }
+
+    /// Асинхронно записывает файл QIF в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    /// Записывает файл QIF в указанной кодировке.
+    pub fn write_to_with_encoding<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        QifWriter::write_to_with_encoding(self, writer, encoding)
+    }
+
+    /// Асинхронно записывает файл QIF в указанной кодировке.
+    #[cfg(feature = "async")]
+    pub async fn write_to_with_encoding_async<W: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut W,
+        encoding: TextEncoding,
+    ) -> Result<()> {
+        encoding::write_async(writer, |buffer| self.write_to_with_encoding(buffer, encoding)).await
+    }
+}
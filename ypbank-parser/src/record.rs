@@ -0,0 +1,96 @@
+//! Записи фиксированной длины форматов CODA, Norma 43 и CFONB.
+//!
+//! Поля адресуются позициями из спецификаций (нумерация с 1); короткие
+//! строки дополняются пробелами до длины записи формата.
+
+use crate::error::{Error, Result};
+use crate::types::Date;
+use std::marker::PhantomData;
+
+/// Параметры формата с записями фиксированной длины.
+pub(crate) trait RecordFormat {
+    /// Название формата для сообщений об ошибках.
+    const NAME: &'static str;
+    /// Длина записи в символах.
+    const RECORD_LEN: usize;
+
+    /// Разбирает дату из шести символов записи.
+    fn parse_date(value: &str) -> Result<Date>;
+}
+
+/// Запись фиксированной длины с доступом к полям по позициям из спецификации.
+pub(crate) struct Record<F> {
+    chars: Vec<char>,
+    pub(crate) line: usize,
+    format: PhantomData<F>,
+}
+
+impl<F: RecordFormat> Record<F> {
+    pub(crate) fn new(line: &str, line_no: usize) -> Result<Self> {
+        // Лишние символы не собираются: для проверки длины достаточно одного
+        let mut chars: Vec<char> = line.chars().take(F::RECORD_LEN + 1).collect();
+        if chars.len() > F::RECORD_LEN {
+            return Err(Error::InvalidFormat(format!(
+                "Строка {}: длина записи {} {} больше {}",
+                line_no,
+                F::NAME,
+                line.chars().count(),
+                F::RECORD_LEN
+            )));
+        }
+        // Завершающие пробелы часто обрезаются при передаче файла
+        chars.resize(F::RECORD_LEN, ' ');
+        Ok(Record {
+            chars,
+            line: line_no,
+            format: PhantomData,
+        })
+    }
+
+    /// Символ в позиции `pos` (нумерация с 1).
+    pub(crate) fn char_at(&self, pos: usize) -> char {
+        self.chars[pos - 1]
+    }
+
+    /// Поле по позициям `start..=end` (нумерация с 1, как в спецификации).
+    pub(crate) fn raw(&self, start: usize, end: usize) -> String {
+        self.chars[start - 1..end].iter().collect()
+    }
+
+    pub(crate) fn field(&self, start: usize, end: usize) -> Option<String> {
+        Some(self.raw(start, end).trim().to_string()).filter(|f| !f.is_empty())
+    }
+
+    pub(crate) fn date(&self, start: usize, name: &str) -> Result<Date> {
+        F::parse_date(&self.raw(start, start + 5))
+            .map_err(|e| Error::Parse(format!("Строка {}: {}: {}", self.line, name, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Test;
+
+    impl RecordFormat for Test {
+        const NAME: &'static str = "TEST";
+        const RECORD_LEN: usize = 8;
+
+        fn parse_date(value: &str) -> Result<Date> {
+            Date::parse_from_str(value, "%y%m%d")
+        }
+    }
+
+    #[test]
+    fn test_record_fields_and_length() {
+        let record = Record::<Test>::new("ä240115", 3).unwrap();
+        assert_eq!(record.char_at(1), 'ä');
+        assert_eq!(record.raw(7, 8), "5 ");
+        assert_eq!(record.field(8, 8), None);
+        assert_eq!(record.date(2, "дата").unwrap(), Date::new(2024, 1, 15));
+
+        let err = Record::<Test>::new("ääääääääää", 4).err().unwrap().to_string();
+        assert!(err.contains("Строка 4: длина записи TEST 10 больше 8"), "{}", err);
+    }
+}
@@ -0,0 +1,149 @@
+//! Модуль потокового чтения выписок.
+//!
+//! MT940 и NDJSON читаются построчно из любого `BufRead`: в памяти находится
+//! только текущая выписка, поэтому многогигабайтные файлы обрабатываются
+//! с ограниченным расходом памяти. Остальные форматы требуют документа
+//! целиком (XML, заголовки и итоги в конце файла) и читаются полностью.
+//!
+//! При чтении транзакций NDJSON не накапливает и текущую выписку; для MT940
+//! расход памяти ограничен одной выпиской (блоком `{4:...-}`), размер
+//! которой можно ограничить через [`Limits`].
+
+use crate::encoding::{self, TextEncoding};
+use crate::error::Result;
+use crate::limits::Limits;
+#[cfg(feature = "serde")]
+use crate::json::{NdjsonReader, NdjsonTransactions};
+use crate::mt940::Mt940Reader;
+use crate::types::{Statement, Transaction};
+use crate::{parse_statements_bytes, Format};
+use std::io::BufRead;
+
+enum Source<R> {
+    Mt940(Mt940Reader<R>),
+    #[cfg(feature = "serde")]
+    Ndjson(Box<NdjsonReader<R>>),
+    Buffered(std::vec::IntoIter<Statement>),
+}
+
+/// Итератор по выпискам любого формата.
+///
+/// Для форматов с [`Format::supports_streaming`] выписки разбираются по мере
+/// чтения, для остальных вход читается целиком при создании.
+///
+/// # Пример
+///
+/// ```ignore
+/// use std::io::BufReader;
+/// use ypbank_parser::{Format, Mt940Writer, StatementReader, TextEncoding};
+///
+/// let input = BufReader::new(File::open("year.sta")?);
+/// let statements = StatementReader::new(input, Format::Mt940, None)?;
+/// Mt940Writer::write_iter_with_encoding(statements, &mut output, TextEncoding::Utf8)?;
+/// ```
+pub struct StatementReader<R> {
+    source: Source<R>,
+}
+
+impl<R: BufRead> StatementReader<R> {
+    /// Создает итератор по выпискам формата `format`.
+    ///
+    /// Если кодировка не указана, она определяется автоматически.
+    pub fn new(mut reader: R, format: Format, encoding: Option<TextEncoding>) -> Result<Self> {
+        let source = match format {
+            Format::Mt940 => Source::Mt940(Mt940Reader::with_encoding(reader, encoding)),
+            #[cfg(feature = "serde")]
+            Format::Ndjson => {
+                Source::Ndjson(Box::new(NdjsonReader::with_encoding(reader, encoding)))
+            }
+            _ => {
+                let bytes = encoding::read_limited(&mut reader, &Limits::default())?;
+                Source::Buffered(parse_statements_bytes(&bytes, format, encoding)?.into_iter())
+            }
+        };
+        Ok(Self { source })
+    }
+
+    /// Превращает итератор выписок в итератор транзакций всех выписок.
+    ///
+    /// Транзакции NDJSON возвращаются по мере чтения строк; для остальных
+    /// форматов в памяти находится не больше одной выписки.
+    pub fn into_transactions(self) -> impl Iterator<Item = Result<Transaction>> {
+        match self.source {
+            #[cfg(feature = "serde")]
+            Source::Ndjson(reader) => Transactions::Ndjson(Box::new(reader.into_transactions())),
+            source => Transactions::Statements {
+                statements: StatementReader { source },
+                current: Vec::new().into_iter(),
+            },
+        }
+    }
+}
+
+/// Итератор транзакций, см. [`StatementReader::into_transactions`].
+enum Transactions<R> {
+    Statements {
+        statements: StatementReader<R>,
+        current: std::vec::IntoIter<Transaction>,
+    },
+    #[cfg(feature = "serde")]
+    Ndjson(Box<NdjsonTransactions<R>>),
+}
+
+impl<R: BufRead> Iterator for Transactions<R> {
+    type Item = Result<Transaction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Transactions::Statements {
+                statements,
+                current,
+            } => loop {
+                if let Some(tx) = current.next() {
+                    return Some(Ok(tx));
+                }
+                match statements.next()? {
+                    Ok(statement) => *current = statement.transactions.into_iter(),
+                    Err(e) => return Some(Err(e)),
+                }
+            },
+            #[cfg(feature = "serde")]
+            Transactions::Ndjson(transactions) => transactions.next(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for StatementReader<R> {
+    type Item = Result<Statement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            Source::Mt940(reader) => reader.next().map(|r| r.map(Statement::from)),
+            #[cfg(feature = "serde")]
+            Source::Ndjson(reader) => reader.next(),
+            Source::Buffered(statements) => statements.next().map(Ok),
+        }
+    }
+}
+
+impl Format {
+    /// Поддерживает ли формат потоковое чтение по одной выписке.
+    pub fn supports_streaming(&self) -> bool {
+        matches!(self, Format::Mt940) || (cfg!(feature = "serde") && *self == Format::Ndjson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffered_fallback() {
+        let qif = "!Type:Bank\nD01/15/2024\nT-10.00\nPОплата\n^\n";
+        let reader = StatementReader::new(qif.as_bytes(), Format::Qif, None).unwrap();
+        let transactions: Vec<_> = reader.into_transactions().collect::<Result<_>>().unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert!(!Format::Qif.supports_streaming());
+        assert!(Format::Mt940.supports_streaming());
+    }
+}
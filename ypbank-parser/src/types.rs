@@ -1,5 +1,8 @@
 //! Базовые типы данных для представления банковских выписок.
 
+pub use crate::amount::{Amount, AmountFormat, MultiCurrencyTotal};
+pub use crate::date::{Date, DateTime, UtcOffset, Weekday, DEFAULT_CENTURY_PIVOT};
+
 // =============================================================================
 // Константы для CAMT.053 формата
 // =============================================================================
@@ -16,16 +19,44 @@ pub const DEBIT_INDICATOR: &str = "DBIT";
 
 /// Тип транзакции по умолчанию (перевод).
 pub const TRANSACTION_TYPE_TRANSFER: &str = "NTRF";
+/// Тип транзакции: чек.
+pub const TRANSACTION_TYPE_CHEQUE: &str = "NCHK";
+/// Тип транзакции: операция с наличными.
+pub const TRANSACTION_TYPE_CASH: &str = "NCAS";
+/// Тип транзакции: прямое дебетование.
+pub const TRANSACTION_TYPE_DIRECT_DEBIT: &str = "NDDT";
+/// Тип транзакции: инкассо (в том числе векселей).
+pub const TRANSACTION_TYPE_COLLECTION: &str = "NCOL";
+/// Тип транзакции: комиссии и сборы.
+pub const TRANSACTION_TYPE_CHARGES: &str = "NCHG";
+/// Тип транзакции: проценты.
+pub const TRANSACTION_TYPE_INTEREST: &str = "NINT";
+/// Тип транзакции: погашение кредита.
+pub const TRANSACTION_TYPE_LOAN: &str = "NLDP";
+/// Тип транзакции: дивиденды.
+pub const TRANSACTION_TYPE_DIVIDEND: &str = "NDIV";
+/// Тип транзакции: операции с ценными бумагами.
+pub const TRANSACTION_TYPE_SECURITIES: &str = "NSEC";
+/// Тип транзакции: валютная операция.
+pub const TRANSACTION_TYPE_FOREIGN_EXCHANGE: &str = "NFEX";
+/// Тип транзакции: возврат неисполненного платежа.
+pub const TRANSACTION_TYPE_RETURNED: &str = "NRTI";
+/// Тип транзакции: прочее.
+pub const TRANSACTION_TYPE_MISC: &str = "NMSC";
 
 /// End-to-end идентификатор по умолчанию.
 pub const END_TO_END_NOT_PROVIDED: &str = "NOTPROVIDED";
 
+/// Код схемы идентификации для ИНН (Othr/SchmeNm/Cd в CAMT.053).
+pub const TAX_ID_SCHEME: &str = "TXID";
+
 // =============================================================================
 // Перечисления (enums)
 // =============================================================================
 
 /// Тип баланса в банковской выписке.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BalanceType {
     /// Начальный баланс (Opening Booked).
     Opening,
@@ -57,6 +88,7 @@ impl BalanceType {
 
 /// Индикатор кредит/дебет (направление движения средств).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CreditDebit {
     /// Кредит (поступление средств).
     Credit,
@@ -107,56 +139,30 @@ impl CreditDebit {
 // Структуры данных
 // =============================================================================
 
-/// Дата в формате год-месяц-день.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Date {
-    /// Год (например, 2024).
-    pub year: u16,
-    /// Месяц (1-12).
-    pub month: u8,
-    /// День месяца (1-31).
-    pub day: u8,
-}
-
-impl Date {
-    /// Создает новую дату.
-    pub fn new(year: u16, month: u8, day: u8) -> Self {
-        Self { year, month, day }
-    }
-}
-
-impl std::fmt::Display for Date {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
-    }
-}
-
-/// Денежная сумма с валютой.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Amount {
-    /// Значение в минимальных единицах (копейки, центы).
-    pub value: i64,
-    /// Код валюты (EUR, USD, RUB и т.д.).
-    pub currency: String,
-}
-
-impl Amount {
-    /// Создает новую сумму.
-    pub fn new(value: i64, currency: impl Into<String>) -> Self {
-        Self {
-            value,
-            currency: currency.into(),
-        }
-    }
-
-    /// Возвращает значение в основных единицах (рубли, евро).
-    pub fn as_float(&self) -> f64 {
-        self.value as f64 / 100.0
-    }
+/// Почтовый адрес (PstlAdr в CAMT.053).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PostalAddress {
+    /// Улица.
+    pub street_name: Option<String>,
+    /// Номер дома.
+    pub building_number: Option<String>,
+    /// Почтовый индекс.
+    pub post_code: Option<String>,
+    /// Город.
+    pub town_name: Option<String>,
+    /// Регион.
+    pub country_subdivision: Option<String>,
+    /// Код страны (ISO 3166).
+    pub country: Option<String>,
+    /// Неструктурированные строки адреса.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub address_lines: Vec<String>,
 }
 
 /// Информация о контрагенте.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Counterparty {
     /// Название контрагента.
     pub name: Option<String>,
@@ -166,10 +172,77 @@ pub struct Counterparty {
     pub bank_code: Option<String>,
     /// Название банка.
     pub bank_name: Option<String>,
+    /// Корреспондентский счет банка.
+    pub bank_correspondent_account: Option<String>,
+    /// ИНН контрагента.
+    pub tax_id: Option<String>,
+    /// КПП контрагента.
+    pub tax_reason_code: Option<String>,
+    /// Почтовый адрес контрагента.
+    pub postal_address: Option<PostalAddress>,
+    /// Идентификатор организации (BIC/BEI или иной идентификатор).
+    pub organisation_id: Option<String>,
+    /// Конечный плательщик или получатель (UltmtDbtr/UltmtCdtr).
+    pub ultimate_party: Option<Box<Counterparty>>,
+}
+
+/// Проверяет контрольные цифры ИНН (10 цифр для организаций, 12 — для физических лиц и ИП).
+pub fn is_valid_inn(inn: &str) -> bool {
+    const WEIGHTS_10: [u32; 9] = [2, 4, 10, 3, 5, 9, 4, 6, 8];
+    const WEIGHTS_11: [u32; 10] = [7, 2, 4, 10, 3, 5, 9, 4, 6, 8];
+    const WEIGHTS_12: [u32; 11] = [3, 7, 2, 4, 10, 3, 5, 9, 4, 6, 8];
+
+    let digits: Vec<u32> = match inn.chars().map(|c| c.to_digit(10)).collect() {
+        Some(digits) => digits,
+        None => return false,
+    };
+
+    let check = |weights: &[u32]| -> u32 {
+        weights.iter().zip(&digits).map(|(w, d)| w * d).sum::<u32>() % 11 % 10
+    };
+
+    match digits.len() {
+        10 => check(&WEIGHTS_10) == digits[9],
+        12 => check(&WEIGHTS_11) == digits[10] && check(&WEIGHTS_12) == digits[11],
+        _ => false,
+    }
+}
+
+/// Курс конвертации валют (CcyXchg в CAMT.053).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExchangeRate {
+    /// Исходная валюта (SrcCcy).
+    pub source_currency: String,
+    /// Целевая валюта (TrgtCcy).
+    pub target_currency: Option<String>,
+    /// Валюта, в единицах которой выражен курс (UnitCcy).
+    pub unit_currency: Option<String>,
+    /// Курс в десятичной записи, как в исходном документе (без потери точности).
+    pub rate: String,
+    /// Идентификатор валютного контракта (CtrctId).
+    pub contract_id: Option<String>,
+}
+
+/// Комиссия, удержанная по транзакции.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Charge {
+    /// Сумма комиссии.
+    pub amount: Amount,
+    /// true = комиссия зачислена, false = списана.
+    pub is_credit: bool,
+    /// Код типа комиссии.
+    pub charge_type: Option<String>,
+    /// Плательщик комиссии (DEBT, CRED, SHAR, SLEV).
+    pub bearer: Option<String>,
+    /// Включена ли комиссия в сумму записи.
+    pub included: Option<bool>,
 }
 
 /// Банковский счет.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Account {
     /// IBAN (если есть).
     pub iban: Option<String>,
@@ -181,10 +254,13 @@ pub struct Account {
     pub name: Option<String>,
     /// Владелец счета.
     pub owner: Option<String>,
+    /// ИНН владельца счета.
+    pub tax_id: Option<String>,
 }
 
 /// Баланс счета.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Balance {
     /// Сумма баланса.
     pub amount: Amount,
@@ -194,8 +270,19 @@ pub struct Balance {
     pub is_credit: bool,
 }
 
+impl Balance {
+    /// Остаток со знаком: дебетовый остаток отрицательный.
+    ///
+    /// Знак берется из `is_credit`, поэтому результат не зависит от того,
+    /// хранит ли формат `amount` по модулю или со знаком.
+    pub fn signed_amount(&self) -> Amount {
+        signed(&self.amount, self.is_credit)
+    }
+}
+
 /// Банковская транзакция.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transaction {
     /// Дата проводки.
     pub date: Date,
@@ -207,14 +294,41 @@ pub struct Transaction {
     pub is_credit: bool,
     /// Референс/идентификатор транзакции.
     pub reference: Option<String>,
+    /// Код типа транзакции SWIFT (NTRF, NCHK, NMSC и т.д.), если известен.
+    pub transaction_type: Option<String>,
     /// Описание/назначение платежа.
     pub description: String,
     /// Информация о контрагенте.
     pub counterparty: Option<Counterparty>,
+    /// Сумма, указанная инициатором платежа (InstdAmt).
+    pub instructed_amount: Option<Amount>,
+    /// Сумма транзакции в исходной валюте (TxAmt).
+    pub original_amount: Option<Amount>,
+    /// Встречная сумма после конвертации (CntrValAmt).
+    pub counter_value_amount: Option<Amount>,
+    /// Курсы конвертации, примененные к транзакции.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub exchange_rates: Vec<ExchangeRate>,
+    /// Комиссии по транзакции.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub charges: Vec<Charge>,
+}
+
+impl Transaction {
+    /// Сумма со знаком: поступление положительное, списание отрицательное.
+    pub fn signed_amount(&self) -> Amount {
+        signed(&self.amount, self.is_credit)
+    }
+}
+
+fn signed(amount: &Amount, is_credit: bool) -> Amount {
+    let abs = amount.value.saturating_abs();
+    Amount::new(if is_credit { abs } else { -abs }, amount.currency.as_str())
 }
 
 /// Банковская выписка.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Statement {
     /// Информация о счете.
     pub account: Account,
@@ -223,6 +337,7 @@ pub struct Statement {
     /// Конечный баланс.
     pub closing_balance: Balance,
     /// Список транзакций.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub transactions: Vec<Transaction>,
     /// Номер выписки.
     pub statement_number: Option<String>,
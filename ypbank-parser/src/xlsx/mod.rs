@@ -0,0 +1,12 @@
+//! Модуль чтения и записи книг Excel (XLSX, feature `xlsx`).
+//!
+//! Книга содержит лист сводки с остатками и оборотами по выпискам, лист
+//! со всеми транзакциями и, для нескольких счетов, по листу на каждый счет.
+//! Даты записываются ячейками-датами, суммы — числами со знаком.
+//! Книги других программ читаются по первому листу с профилем CSV.
+
+pub mod parser;
+pub mod writer;
+
+pub use parser::XlsxWorkbook;
+pub use writer::{XlsxWriter, XLSX_SUMMARY_SHEET, XLSX_TRANSACTIONS_SHEET};
@@ -0,0 +1,512 @@
+//! Чтение выписок из книг Excel (XLSX).
+
+use crate::csv::{CsvProfile, CsvStatement};
+use crate::error::{Error, Result};
+use crate::limits::Limits;
+use crate::types::{Account, Amount, Balance, Counterparty, Date, Statement, Transaction};
+use crate::xlsx::writer::{
+    turnover, XlsxWriter, SUMMARY_HEADERS, TRANSACTION_HEADERS, XLSX_SUMMARY_SHEET,
+    XLSX_TRANSACTIONS_SHEET,
+};
+use calamine::{Data, Range, Reader, Xlsx};
+use std::io::{Cursor, Read, Write};
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+#[cfg(feature = "async")]
+use tokio::io::AsyncRead;
+
+/// Книга Excel с выписками.
+///
+/// Книги, записанные [`XlsxWriter`], читаются без потерь по листам сводки
+/// и транзакций. Книги другого вида разбираются по первому листу с помощью
+/// профиля CSV: строки листа превращаются в записи CSV с разделителями
+/// и форматом дат профиля.
+#[derive(Debug, Clone)]
+pub struct XlsxWorkbook {
+    /// Выписки.
+    pub statements: Vec<Statement>,
+}
+
+impl From<Vec<Statement>> for XlsxWorkbook {
+    fn from(statements: Vec<Statement>) -> Self {
+        XlsxWorkbook { statements }
+    }
+}
+
+impl XlsxWorkbook {
+    /// Читает книгу из любого источника, реализующего трейт Read.
+    pub fn from_read<R: Read>(reader: &mut R) -> Result<Self> {
+        let bytes = crate::encoding::read_limited(reader, &Limits::default())?;
+        Self::parse_bytes(&bytes)
+    }
+
+    /// Асинхронно читает книгу из любого источника, реализующего трейт AsyncRead.
+    #[cfg(feature = "async")]
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let bytes = crate::encoding::read_async(reader).await?;
+        Self::parse_bytes(&bytes)
+    }
+
+    /// Читает книгу из байтов.
+    ///
+    /// Если в книге нет листов сводки и транзакций, первый лист
+    /// разбирается по профилю [`CsvProfile::generic`]. Применяются [`Limits::default`].
+    pub fn parse_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::parse_bytes_with_limits(bytes, &Limits::default())
+    }
+
+    /// Читает книгу из байтов с заданными ограничениями.
+    ///
+    /// До распаковки проверяются размер файла и суммарный размер частей
+    /// книги, объявленный в центральном каталоге ZIP (`max_input_bytes`).
+    /// При разборе листов число строк сводки ограничено `max_statements`,
+    /// число транзакций выписки — `max_transactions`, длина текста ячейки —
+    /// `max_field_len`.
+    pub fn parse_bytes_with_limits(bytes: &[u8], limits: &Limits) -> Result<Self> {
+        let mut workbook = Self::open(bytes, limits)?;
+
+        let summary = Self::sheet(&mut workbook, XLSX_SUMMARY_SHEET);
+        let transactions = Self::sheet(&mut workbook, XLSX_TRANSACTIONS_SHEET);
+        match (summary, transactions) {
+            (Some(summary), Some(transactions))
+                if header_matches(&summary, &SUMMARY_HEADERS)
+                    && header_matches(&transactions, &TRANSACTION_HEADERS) =>
+            {
+                Self::parse_own_layout(&summary, &transactions, limits)
+            }
+            _ => Self::parse_first_sheet(&mut workbook, &CsvProfile::generic(), limits),
+        }
+    }
+
+    /// Читает первый лист книги по профилю CSV. Применяются [`Limits::default`].
+    pub fn parse_bytes_with_profile(bytes: &[u8], profile: &CsvProfile) -> Result<Self> {
+        let limits = Limits::default();
+        let mut workbook = Self::open(bytes, &limits)?;
+        Self::parse_first_sheet(&mut workbook, profile, &limits)
+    }
+
+    /// Возвращает выписки книги.
+    pub fn into_statements(self) -> Vec<Statement> {
+        self.statements
+    }
+
+    /// Записывает книгу в любой приемник, реализующий трейт Write.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        XlsxWriter::write_to(&self.statements, writer)
+    }
+
+    /// Асинхронно записывает книгу в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        crate::encoding::write_async(writer, |buffer| self.write_to(buffer)).await
+    }
+
+    fn open<'a>(bytes: &'a [u8], limits: &Limits) -> Result<Xlsx<Cursor<&'a [u8]>>> {
+        limits.check_input(bytes.len())?;
+        limits.check_unpacked(declared_unpacked_size(bytes))?;
+        calamine::open_workbook_from_rs(Cursor::new(bytes))
+            .map_err(|e| Error::InvalidFormat(format!("Некорректный файл XLSX: {}", e)))
+    }
+
+    fn sheet(workbook: &mut Xlsx<Cursor<&[u8]>>, name: &str) -> Option<Range<Data>> {
+        if !workbook.sheet_names().iter().any(|s| s == name) {
+            return None;
+        }
+        workbook.worksheet_range(name).ok()
+    }
+
+    fn parse_first_sheet(
+        workbook: &mut Xlsx<Cursor<&[u8]>>,
+        profile: &CsvProfile,
+        limits: &Limits,
+    ) -> Result<Self> {
+        let range = workbook
+            .worksheet_range_at(0)
+            .ok_or_else(|| Error::InvalidFormat("Книга XLSX не содержит листов".into()))?
+            .map_err(|e| Error::InvalidFormat(format!("Некорректный лист XLSX: {}", e)))?;
+
+        let content: String = range
+            .rows()
+            .map(|row| {
+                let fields: Vec<String> =
+                    row.iter().map(|cell| render_cell(cell, profile)).collect();
+                fields.join(&profile.delimiter.to_string()) + "\n"
+            })
+            .collect();
+
+        let csv = CsvStatement::parse_lines(&content, profile, limits)?;
+        Ok(vec![csv.try_into()?].into())
+    }
+
+    /// Разбирает листы сводки и транзакций, записанные [`XlsxWriter`].
+    fn parse_own_layout(
+        summary: &Range<Data>,
+        transactions: &Range<Data>,
+        limits: &Limits,
+    ) -> Result<Self> {
+        let mut statements = Vec::new();
+        for (idx, row) in summary.rows().enumerate().skip(1) {
+            if row.iter().all(|cell| *cell == Data::Empty) {
+                continue;
+            }
+            check_cells(row, limits)?;
+            let cell = |col: usize| row.get(col).unwrap_or(&Data::Empty);
+            let line = idx + 1;
+
+            let label = cell_string(cell(1));
+            let currency = cell_string(cell(3));
+            let balance = |col: usize, date_col: usize| -> Result<Balance> {
+                let amount = required_amount(cell(col), XLSX_SUMMARY_SHEET, line)?;
+                Ok(Balance {
+                    amount: Amount::new(amount.abs(), &currency),
+                    date: required_date(cell(date_col), XLSX_SUMMARY_SHEET, line)?,
+                    is_credit: amount >= 0,
+                })
+            };
+            let is_iban =
+                label.len() > 2 && label.chars().take(2).all(|c| c.is_ascii_uppercase());
+
+            statements.push((
+                Statement {
+                    account: Account {
+                        iban: is_iban.then(|| label.clone()),
+                        number: label.clone(),
+                        currency: currency.clone(),
+                        name: None,
+                        owner: Some(cell_string(cell(2))).filter(|o| !o.is_empty()),
+                        tax_id: None,
+                    },
+                    opening_balance: balance(6, 4)?,
+                    closing_balance: balance(9, 5)?,
+                    transactions: Vec::new(),
+                    statement_number: Some(cell_string(cell(11))).filter(|n| !n.is_empty()),
+                    reference: Some(cell_string(cell(12))).filter(|r| !r.is_empty()),
+                },
+                (cell_amount(cell(7)), cell_amount(cell(8))),
+                cell_count(cell(10)),
+            ));
+            limits.check_statements(statements.len())?;
+        }
+
+        for (idx, row) in transactions.rows().enumerate().skip(1) {
+            if row.iter().all(|cell| *cell == Data::Empty) {
+                continue;
+            }
+            check_cells(row, limits)?;
+            let cell = |col: usize| row.get(col).unwrap_or(&Data::Empty);
+            let line = idx + 1;
+
+            let number = cell_count(cell(0)).unwrap_or(0);
+            let (statement, _, _) = number
+                .checked_sub(1)
+                .and_then(|n| statements.get_mut(n))
+                .ok_or_else(|| {
+                    Error::InvalidFormat(format!(
+                        "Лист '{}', строка {}: нет выписки с номером {}",
+                        XLSX_TRANSACTIONS_SHEET, line, number
+                    ))
+                })?;
+
+            let amount = required_amount(cell(4), XLSX_TRANSACTIONS_SHEET, line)?;
+            let currency = Some(cell_string(cell(5)))
+                .filter(|c| !c.is_empty())
+                .unwrap_or_else(|| statement.account.currency.clone());
+            let optional = |col: usize| Some(cell_string(cell(col))).filter(|s| !s.is_empty());
+            let counterparty_name = optional(8);
+            let counterparty_account = optional(9);
+
+            statement.transactions.push(Transaction {
+                date: required_date(cell(2), XLSX_TRANSACTIONS_SHEET, line)?,
+                value_date: cell_date(cell(3)),
+                amount: Amount::new(amount.abs(), currency),
+                is_credit: amount >= 0,
+                reference: optional(7),
+                transaction_type: optional(6),
+                description: cell_string(cell(10)),
+                counterparty: (counterparty_name.is_some() || counterparty_account.is_some()).then(
+                    || Counterparty {
+                        name: counterparty_name,
+                        account: counterparty_account,
+                        ..Default::default()
+                    },
+                ),
+                instructed_amount: None,
+                original_amount: None,
+                counter_value_amount: None,
+                exchange_rates: Vec::new(),
+                charges: Vec::new(),
+            });
+            limits.check_transactions(statement.transactions.len())?;
+        }
+
+        let statements = statements
+            .into_iter()
+            .map(|(statement, totals, count)| {
+                if count.is_some_and(|count| count != statement.transactions.len()) {
+                    tracing::warn!(
+                        "Количество операций по счету {} в сводке не совпадает с листом транзакций",
+                        statement.account.number
+                    );
+                }
+                let (debit, credit) = turnover(&statement.transactions);
+                if totals != (Some(debit), Some(credit)) {
+                    tracing::warn!(
+                        "Обороты по счету {} в сводке не совпадают с листом транзакций",
+                        statement.account.number
+                    );
+                }
+                statement
+            })
+            .collect();
+
+        Ok(XlsxWorkbook { statements })
+    }
+}
+
+/// Суммарный распакованный размер частей книги по центральному каталогу ZIP.
+///
+/// Читаются только заголовки каталога, поэтому проверка не зависит от
+/// степени сжатия. Если каталог не найден, возвращается 0: некорректный
+/// архив отклонит calamine.
+fn declared_unpacked_size(bytes: &[u8]) -> usize {
+    const END_OF_DIRECTORY: &[u8] = b"PK\x05\x06";
+    const DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+
+    let u16_at = |pos: usize| {
+        bytes.get(pos..pos + 2).map(|b| usize::from(u16::from_le_bytes([b[0], b[1]])))
+    };
+    let u32_at = |pos: usize| {
+        bytes.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+
+    // Запись конца каталога находится в последних 22 + 65535 байтах
+    let tail = bytes.len().saturating_sub(22 + usize::from(u16::MAX));
+    let Some(end) = bytes[tail..]
+        .windows(END_OF_DIRECTORY.len())
+        .rposition(|window| window == END_OF_DIRECTORY)
+        .map(|pos| tail + pos)
+    else {
+        return 0;
+    };
+    let (Some(entries), Some(mut pos)) = (u16_at(end + 10), u32_at(end + 16)) else {
+        return 0;
+    };
+
+    let mut total = 0usize;
+    for _ in 0..entries {
+        if bytes.get(pos..pos + 4) != Some(DIRECTORY_ENTRY) {
+            break;
+        }
+        let (Some(size), Some(name), Some(extra), Some(comment)) =
+            (u32_at(pos + 24), u16_at(pos + 28), u16_at(pos + 30), u16_at(pos + 32))
+        else {
+            break;
+        };
+        total = total.saturating_add(size);
+        pos += 46 + name + extra + comment;
+    }
+    total
+}
+
+/// Проверяет длину текста в ячейках строки.
+fn check_cells(row: &[Data], limits: &Limits) -> Result<()> {
+    for cell in row {
+        if let Data::String(s) = cell {
+            limits.check_field("ячейка XLSX", s)?;
+        }
+    }
+    Ok(())
+}
+
+/// Проверяет, что первая строка листа содержит ожидаемые заголовки.
+fn header_matches(range: &Range<Data>, headers: &[&str]) -> bool {
+    range.rows().next().is_some_and(|row| {
+        headers
+            .iter()
+            .enumerate()
+            .all(|(col, header)| row.get(col).map(cell_string).as_deref() == Some(*header))
+    })
+}
+
+fn cell_string(cell: &Data) -> String {
+    match cell {
+        Data::String(s) => s.trim().to_string(),
+        Data::Int(n) => n.to_string(),
+        Data::Float(f) => f.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTimeIso(s) | Data::DurationIso(s) => s.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Сумма ячейки в минимальных единицах валюты.
+fn cell_amount(cell: &Data) -> Option<i64> {
+    match cell {
+        Data::Int(n) => n.checked_mul(100),
+        Data::Float(f) if f.is_finite() && f.abs() < 9e16 => Some((f * 100.0).round() as i64),
+        Data::String(s) => {
+            let normalized: String = s
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| if c == ',' { '.' } else { c })
+                .collect();
+            let value: f64 = normalized.parse().ok()?;
+            cell_amount(&Data::Float(value))
+        }
+        _ => None,
+    }
+}
+
+/// Неотрицательное целое число в ячейке (номер выписки, количество операций).
+fn cell_count(cell: &Data) -> Option<usize> {
+    match cell {
+        Data::Int(n) => usize::try_from(*n).ok(),
+        Data::Float(f) if f.fract() == 0.0 && *f >= 0.0 && *f < usize::MAX as f64 => {
+            Some(*f as usize)
+        }
+        Data::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Дата ячейки: типизированная дата Excel, ISO 8601 или `ДД.ММ.ГГГГ`.
+fn cell_date(cell: &Data) -> Option<Date> {
+    match cell {
+        Data::DateTime(dt) => {
+            let (year, month, day, ..) = dt.to_ymd_hms_milli();
+            Date::from_ymd(year, month, day).ok()
+        }
+        Data::DateTimeIso(s) => s.get(..10)?.parse().ok(),
+        Data::String(s) => {
+            let s = s.trim();
+            s.parse().ok().or_else(|| {
+                let mut parts = s.split('.');
+                let day = parts.next()?.parse().ok()?;
+                let month = parts.next()?.parse().ok()?;
+                let year = parts.next()?.parse().ok()?;
+                Date::from_ymd(year, month, day).ok()
+            })
+        }
+        _ => None,
+    }
+}
+
+fn required_amount(cell: &Data, sheet: &str, line: usize) -> Result<i64> {
+    cell_amount(cell).ok_or_else(|| {
+        Error::Parse(format!(
+            "Лист '{}', строка {}: некорректная сумма {:?}",
+            sheet, line, cell
+        ))
+    })
+}
+
+fn required_date(cell: &Data, sheet: &str, line: usize) -> Result<Date> {
+    cell_date(cell).ok_or_else(|| {
+        Error::Parse(format!(
+            "Лист '{}', строка {}: некорректная дата {:?}",
+            sheet, line, cell
+        ))
+    })
+}
+
+/// Представляет ячейку полем CSV в соглашениях профиля.
+fn render_cell(cell: &Data, profile: &CsvProfile) -> String {
+    let text = match cell {
+        Data::DateTime(_) | Data::DateTimeIso(_) => match cell_date(cell) {
+            Some(date) => profile.format_date(&date),
+            None => cell_string(cell),
+        },
+        Data::Float(f) => f
+            .to_string()
+            .replace('.', &profile.decimal_separator.to_string()),
+        _ => cell_string(cell),
+    };
+    // Записи CSV разбираются построчно
+    let text = text.replace(['\r', '\n'], " ");
+
+    match profile.quote {
+        Some(quote) if text.contains(profile.delimiter) || text.contains(quote) => {
+            let doubled = format!("{}{}", quote, quote);
+            format!("{}{}{}", quote, text.replace(quote, &doubled), quote)
+        }
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_amount() {
+        assert_eq!(cell_amount(&Data::Float(-1234.56)), Some(-123456));
+        assert_eq!(cell_amount(&Data::Int(15)), Some(1500));
+        assert_eq!(cell_amount(&Data::String("1 500,25".into())), Some(150025));
+        assert_eq!(cell_amount(&Data::Empty), None);
+    }
+
+    #[test]
+    fn test_unpacked_size_limit() {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet();
+        for row in 0..1000 {
+            for col in 0..20 {
+                sheet.write_number(row, col, 1.0).unwrap();
+            }
+        }
+        let bytes = workbook.save_to_buffer().unwrap();
+        let unpacked = declared_unpacked_size(&bytes);
+        assert!(unpacked > 10 * bytes.len());
+
+        let limits = Limits {
+            max_input_bytes: unpacked - 1,
+            ..Limits::default()
+        };
+        assert!(matches!(
+            XlsxWorkbook::parse_bytes_with_limits(&bytes, &limits),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert_eq!(declared_unpacked_size(b"not a zip"), 0);
+    }
+
+    #[test]
+    fn test_foreign_workbook_with_profile() {
+        use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        let date_format = Format::new().set_num_format("dd/mm/yyyy");
+        for (col, header) in ["Дата", "Сумма", "Номер", "Счет", "Назначение"]
+            .iter()
+            .enumerate()
+        {
+            sheet.write_string(0, col as u16, *header).unwrap();
+        }
+        let date = ExcelDateTime::from_ymd(2024, 2, 10).unwrap();
+        sheet
+            .write_datetime_with_format(1, 0, &date, &date_format)
+            .unwrap();
+        sheet.write_number(1, 1, -1540.5).unwrap();
+        sheet.write_number(1, 2, 17).unwrap();
+        sheet.write_string(1, 3, "40702810100000000002").unwrap();
+        sheet.write_string(1, 4, "Аренда, февраль").unwrap();
+        let bytes = workbook.save_to_buffer().unwrap();
+
+        let statements = XlsxWorkbook::parse_bytes(&bytes).unwrap().into_statements();
+        let tx = &statements[0].transactions[0];
+        assert_eq!(tx.date, Date::new(2024, 2, 10));
+        assert_eq!(tx.amount.value, 154050);
+        assert!(!tx.is_credit);
+        assert_eq!(tx.description, "Аренда, февраль");
+    }
+
+    #[test]
+    fn test_render_cell() {
+        let alfa = CsvProfile::alfa();
+        assert_eq!(render_cell(&Data::Float(1500.5), &alfa), "1500,5");
+        assert_eq!(
+            render_cell(&Data::String("ООО \"Ромашка\"; склад".into()), &alfa),
+            "\"ООО \"\"Ромашка\"\"; склад\""
+        );
+    }
+}
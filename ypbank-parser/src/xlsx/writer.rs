@@ -0,0 +1,416 @@
+//! Запись выписок в книгу Excel (XLSX).
+
+use crate::error::{Error, Result};
+use crate::types::{Date, Statement, Transaction};
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatAlign, Workbook, Worksheet, XlsxError};
+use std::io::Write;
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Название листа со сводкой по выпискам.
+pub const XLSX_SUMMARY_SHEET: &str = "Сводка";
+
+/// Название листа со всеми транзакциями.
+pub const XLSX_TRANSACTIONS_SHEET: &str = "Транзакции";
+
+/// Заголовки листа сводки.
+pub(crate) const SUMMARY_HEADERS: [&str; 13] = [
+    "№",
+    "Счет",
+    "Владелец",
+    "Валюта",
+    "Начало периода",
+    "Конец периода",
+    "Входящий остаток",
+    "Дебет",
+    "Кредит",
+    "Исходящий остаток",
+    "Операций",
+    "Номер выписки",
+    "Референс выписки",
+];
+
+/// Заголовки листов с транзакциями.
+pub(crate) const TRANSACTION_HEADERS: [&str; 11] = [
+    "№",
+    "Счет",
+    "Дата",
+    "Дата валютирования",
+    "Сумма",
+    "Валюта",
+    "Тип",
+    "Референс",
+    "Контрагент",
+    "Счет контрагента",
+    "Назначение",
+];
+
+/// Максимальная длина названия листа Excel.
+const SHEET_NAME_MAX_LEN: usize = 31;
+
+/// Форматы ячеек книги.
+struct Formats {
+    header: Format,
+    date: Format,
+    money: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        Formats {
+            header: Format::new().set_bold().set_align(FormatAlign::Center),
+            date: Format::new().set_num_format("dd.mm.yyyy"),
+            money: Format::new().set_num_format("#,##0.00;[Red]-#,##0.00"),
+        }
+    }
+}
+
+/// Writer для книг Excel.
+///
+/// Книга содержит лист сводки с остатками и оборотами по каждой выписке,
+/// лист со всеми транзакциями и, если во входных данных несколько счетов,
+/// по листу на каждый счет. Даты и суммы записываются типизированными
+/// ячейками; суммы списаний отрицательные.
+pub struct XlsxWriter;
+
+impl XlsxWriter {
+    /// Записывает книгу в любой приемник, реализующий трейт Write.
+    ///
+    /// XLSX — двоичный формат, поэтому кодировка не задается.
+    pub fn write_to<W: Write>(statements: &[Statement], writer: &mut W) -> Result<()> {
+        let buffer = Self::to_bytes(statements)?;
+        writer.write_all(&buffer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Асинхронно записывает книгу в любой приемник, реализующий трейт AsyncWrite.
+    #[cfg(feature = "async")]
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(
+        statements: &[Statement],
+        writer: &mut W,
+    ) -> Result<()> {
+        crate::encoding::write_async(writer, |buffer| Self::write_to(statements, buffer)).await
+    }
+
+    /// Собирает книгу в памяти и возвращает содержимое файла.
+    pub fn to_bytes(statements: &[Statement]) -> Result<Vec<u8>> {
+        let formats = Formats::new();
+        let mut workbook = Workbook::new();
+
+        Self::write_summary(workbook.add_worksheet(), statements, &formats)
+            .map_err(Self::map_error)?;
+
+        let all: Vec<(usize, &Statement)> = statements.iter().enumerate().collect();
+        Self::write_transactions(
+            workbook.add_worksheet(),
+            XLSX_TRANSACTIONS_SHEET,
+            &all,
+            &formats,
+        )
+        .map_err(Self::map_error)?;
+
+        let mut accounts: Vec<&str> = Vec::new();
+        for statement in statements {
+            if !accounts.contains(&account_label(statement)) {
+                accounts.push(account_label(statement));
+            }
+        }
+
+        if accounts.len() > 1 {
+            let mut used_names = vec![
+                XLSX_SUMMARY_SHEET.to_string(),
+                XLSX_TRANSACTIONS_SHEET.to_string(),
+            ];
+            for account in accounts {
+                let name = unique_sheet_name(account, &used_names);
+                let selected: Vec<(usize, &Statement)> = all
+                    .iter()
+                    .copied()
+                    .filter(|(_, s)| account_label(s) == account)
+                    .collect();
+                Self::write_transactions(workbook.add_worksheet(), &name, &selected, &formats)
+                    .map_err(Self::map_error)?;
+                used_names.push(name);
+            }
+        }
+
+        workbook.save_to_buffer().map_err(Self::map_error)
+    }
+
+    fn write_summary(
+        sheet: &mut Worksheet,
+        statements: &[Statement],
+        formats: &Formats,
+    ) -> std::result::Result<(), XlsxError> {
+        sheet.set_name(XLSX_SUMMARY_SHEET)?;
+        Self::write_headers(sheet, &SUMMARY_HEADERS, formats)?;
+
+        for (idx, statement) in statements.iter().enumerate() {
+            let row = idx as u32 + 1;
+            let (debit, credit) = turnover(&statement.transactions);
+            let owner = statement
+                .account
+                .owner
+                .as_deref()
+                .or(statement.account.name.as_deref())
+                .unwrap_or("");
+
+            sheet.write_number(row, 0, idx as f64 + 1.0)?;
+            sheet.write_string(row, 1, account_label(statement))?;
+            sheet.write_string(row, 2, owner)?;
+            sheet.write_string(row, 3, &statement.account.currency)?;
+            Self::write_date(sheet, row, 4, &statement.opening_balance.date, formats)?;
+            Self::write_date(sheet, row, 5, &statement.closing_balance.date, formats)?;
+            Self::write_money(
+                sheet,
+                row,
+                6,
+                statement.opening_balance.signed_amount().value,
+                formats,
+            )?;
+            Self::write_money(sheet, row, 7, debit, formats)?;
+            Self::write_money(sheet, row, 8, credit, formats)?;
+            Self::write_money(
+                sheet,
+                row,
+                9,
+                statement.closing_balance.signed_amount().value,
+                formats,
+            )?;
+            sheet.write_number(row, 10, statement.transactions.len() as f64)?;
+            sheet.write_string(row, 11, statement.statement_number.as_deref().unwrap_or(""))?;
+            sheet.write_string(row, 12, statement.reference.as_deref().unwrap_or(""))?;
+        }
+
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+        Ok(())
+    }
+
+    fn write_transactions(
+        sheet: &mut Worksheet,
+        name: &str,
+        statements: &[(usize, &Statement)],
+        formats: &Formats,
+    ) -> std::result::Result<(), XlsxError> {
+        sheet.set_name(name)?;
+        Self::write_headers(sheet, &TRANSACTION_HEADERS, formats)?;
+
+        let mut row = 1u32;
+        for (idx, statement) in statements {
+            for tx in &statement.transactions {
+                let counterparty = tx.counterparty.as_ref();
+                let amount = tx.signed_amount().value;
+
+                sheet.write_number(row, 0, *idx as f64 + 1.0)?;
+                sheet.write_string(row, 1, account_label(statement))?;
+                Self::write_date(sheet, row, 2, &tx.date, formats)?;
+                if let Some(value_date) = &tx.value_date {
+                    Self::write_date(sheet, row, 3, value_date, formats)?;
+                }
+                Self::write_money(sheet, row, 4, amount, formats)?;
+                sheet.write_string(row, 5, &tx.amount.currency)?;
+                sheet.write_string(row, 6, tx.transaction_type.as_deref().unwrap_or(""))?;
+                sheet.write_string(row, 7, tx.reference.as_deref().unwrap_or(""))?;
+                sheet.write_string(
+                    row,
+                    8,
+                    counterparty.and_then(|c| c.name.as_deref()).unwrap_or(""),
+                )?;
+                sheet.write_string(
+                    row,
+                    9,
+                    counterparty
+                        .and_then(|c| c.account.as_deref())
+                        .unwrap_or(""),
+                )?;
+                sheet.write_string(row, 10, &tx.description)?;
+                row += 1;
+            }
+        }
+
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+        // Назначение платежа бывает длинным: ограничиваем ширину колонки
+        sheet.set_column_width(10, 60)?;
+        Ok(())
+    }
+
+    fn write_headers(
+        sheet: &mut Worksheet,
+        headers: &[&str],
+        formats: &Formats,
+    ) -> std::result::Result<(), XlsxError> {
+        for (col, header) in headers.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, *header, &formats.header)?;
+        }
+        Ok(())
+    }
+
+    fn write_date(
+        sheet: &mut Worksheet,
+        row: u32,
+        col: u16,
+        date: &Date,
+        formats: &Formats,
+    ) -> std::result::Result<(), XlsxError> {
+        let date = ExcelDateTime::from_ymd(date.year(), date.month(), date.day())?;
+        sheet.write_datetime_with_format(row, col, &date, &formats.date)?;
+        Ok(())
+    }
+
+    fn write_money(
+        sheet: &mut Worksheet,
+        row: u32,
+        col: u16,
+        value: i64,
+        formats: &Formats,
+    ) -> std::result::Result<(), XlsxError> {
+        sheet.write_number_with_format(row, col, value as f64 / 100.0, &formats.money)?;
+        Ok(())
+    }
+
+    fn map_error(e: XlsxError) -> Error {
+        match e {
+            XlsxError::IoError(e) => Error::Io(e),
+            e => Error::InvalidFormat(format!("Ошибка записи XLSX: {}", e)),
+        }
+    }
+}
+
+/// Номер счета для книги: IBAN, если он известен.
+pub(crate) fn account_label(statement: &Statement) -> &str {
+    statement
+        .account
+        .iban
+        .as_deref()
+        .unwrap_or(&statement.account.number)
+}
+
+/// Обороты по дебету и кредиту в минимальных единицах.
+pub(crate) fn turnover(transactions: &[Transaction]) -> (i64, i64) {
+    transactions
+        .iter()
+        .fold((0i64, 0i64), |(debit, credit), tx| {
+            if tx.is_credit {
+                (debit, credit.saturating_add(tx.amount.value))
+            } else {
+                (debit.saturating_add(tx.amount.value), credit)
+            }
+        })
+}
+
+/// Название листа для счета: без запрещенных символов, не длиннее 31 символа
+/// и отличное от уже использованных.
+fn unique_sheet_name(account: &str, used: &[String]) -> String {
+    let base: String = account
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            c => c,
+        })
+        .take(SHEET_NAME_MAX_LEN)
+        .collect();
+    let base = base.trim_matches('\'').to_string();
+    let base = if base.is_empty() {
+        "Счет".to_string()
+    } else {
+        base
+    };
+
+    let is_used = |name: &str| used.iter().any(|u| u.eq_ignore_ascii_case(name));
+    if !is_used(&base) {
+        return base;
+    }
+
+    (2..)
+        .map(|n| {
+            let suffix = format!(" ({})", n);
+            let prefix: String = base
+                .chars()
+                .take(SHEET_NAME_MAX_LEN - suffix.chars().count())
+                .collect();
+            prefix + &suffix
+        })
+        .find(|name| !is_used(name))
+        .unwrap_or(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Account, Amount, Balance};
+    use calamine::{Data, Reader, Xlsx};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_typed_cells() {
+        let date = Date::new(2024, 3, 15);
+        let statement = Statement {
+            account: Account {
+                iban: None,
+                number: "40702810900000000001".to_string(),
+                currency: "RUB".to_string(),
+                name: None,
+                owner: None,
+                tax_id: None,
+            },
+            opening_balance: Balance {
+                amount: Amount::new(100000, "RUB"),
+                date,
+                is_credit: true,
+            },
+            closing_balance: Balance {
+                amount: Amount::new(87655, "RUB"),
+                date,
+                is_credit: true,
+            },
+            transactions: vec![Transaction {
+                date,
+                value_date: None,
+                amount: Amount::new(12345, "RUB"),
+                is_credit: false,
+                reference: None,
+                transaction_type: None,
+                description: "Оплата по счету".to_string(),
+                counterparty: None,
+                instructed_amount: None,
+                original_amount: None,
+                counter_value_amount: None,
+                exchange_rates: Vec::new(),
+                charges: Vec::new(),
+            }],
+            statement_number: None,
+            reference: None,
+        };
+
+        let bytes = XlsxWriter::to_bytes(&[statement]).unwrap();
+        let mut workbook: Xlsx<_> = calamine::open_workbook_from_rs(Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            workbook.sheet_names(),
+            vec![XLSX_SUMMARY_SHEET, XLSX_TRANSACTIONS_SHEET]
+        );
+
+        let sheet = workbook.worksheet_range(XLSX_TRANSACTIONS_SHEET).unwrap();
+        match sheet.get_value((1, 2)) {
+            Some(Data::DateTime(dt)) => assert_eq!(dt.to_ymd_hms_milli().0, 2024),
+            other => panic!("ожидалась дата, получено {:?}", other),
+        }
+        assert_eq!(sheet.get_value((1, 4)), Some(&Data::Float(-123.45)));
+    }
+
+    #[test]
+    fn test_unique_sheet_name() {
+        let used = vec!["Сводка".to_string(), "DE89370400440532013000".to_string()];
+        assert_eq!(unique_sheet_name("40702/810:1", &used), "40702_810_1");
+        assert_eq!(
+            unique_sheet_name("DE89370400440532013000", &used),
+            "DE89370400440532013000 (2)"
+        );
+        assert_eq!(
+            unique_sheet_name(&"9".repeat(40), &used).chars().count(),
+            31
+        );
+    }
+}
@@ -1,7 +1,15 @@
 //! Интеграционные тесты для ypbank-parser.
 
 use std::io::Cursor;
-use ypbank_parser::{BalanceType, Camt053Statement, Mt940Statement, Statement};
+use ypbank_parser::{
+    encoding, is_valid_inn, parse_statement, parse_statements, parse_statements_bytes,
+    parse_statements_bytes_with_limits, parse_statements_with_limits,
+    AccountMapping, Bai2File, BalanceType, BeancountWriter, Camt053Statement, CfonbStatement,
+    CodaStatement, CsvProfile, CsvStatement, Date, Error, Format, LedgerWriter, MappingRule, Mt940Statement,
+    Norma43Statement, OfxStatement, OfxVersion, OfxWriter, OneCStatement, QifDateStyle, QifFile, Statement,
+    TextEncoding, Limits, Mt940Reader, FixedClock, UtcOffset, Amount, AmountFormat,
+    MultiCurrencyTotal,
+};
 
 const SAMPLE_MT940: &str = r#"{1:F01ASNBNL21XXXX0000000000}{2:O940ASNBNL21XXXXN}{3:}{4:
 :20:0000000000
@@ -111,6 +119,20 @@ fn test_mt940_to_statement() {
     assert!(!statement.transactions[0].is_credit);
 }
 
+#[test]
+fn test_mt940_borrowed_parse() {
+    use ypbank_parser::Mt940StatementRef;
+
+    let borrowed = Mt940StatementRef::parse(SAMPLE_MT940).unwrap();
+    assert_eq!(borrowed.len(), 1);
+    assert_eq!(borrowed[0].account_id, "NL81ASNB9999999999");
+    assert!(matches!(borrowed[0].account_id, std::borrow::Cow::Borrowed(_)));
+
+    let owned: Vec<Mt940Statement> = borrowed.into_iter().map(Into::into).collect();
+    let expected = Mt940Statement::parse(SAMPLE_MT940).unwrap();
+    assert_eq!(format!("{:?}", owned), format!("{:?}", expected));
+}
+
 #[test]
 fn test_camt053_parse() {
     let mut cursor = Cursor::new(SAMPLE_CAMT053);
@@ -199,17 +221,2154 @@ fn test_date_parsing() {
     use ypbank_parser::Date;
 
     let date = Date::new(2024, 1, 15);
-    assert_eq!(date.year, 2024);
-    assert_eq!(date.month, 1);
-    assert_eq!(date.day, 15);
+    assert_eq!(date.year(), 2024);
+    assert_eq!(date.month(), 1);
+    assert_eq!(date.day(), 15);
 }
 
 #[test]
 fn test_amount() {
-    use ypbank_parser::Amount;
-
     let amount = Amount::new(12345, "EUR");
     assert_eq!(amount.value, 12345);
     assert_eq!(amount.currency, "EUR");
     assert!((amount.as_float() - 123.45).abs() < 0.01);
 }
+
+const SAMPLE_CAMT053_FX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+<BkToCstmrStmt>
+<GrpHdr>
+<MsgId>FX001</MsgId>
+<CreDtTm>2024-02-01T10:00:00</CreDtTm>
+</GrpHdr>
+<Stmt>
+<Id>STMT-FX</Id>
+<Acct>
+<Id>
+<IBAN>DE89370400440532013000</IBAN>
+</Id>
+<Ccy>EUR</Ccy>
+</Acct>
+<Bal>
+<Tp>
+<CdOrPrtry>
+<Cd>OPBD</Cd>
+</CdOrPrtry>
+</Tp>
+<Amt Ccy="EUR">1000.00</Amt>
+<CdtDbtInd>CRDT</CdtDbtInd>
+<Dt>
+<Dt>2024-02-01</Dt>
+</Dt>
+</Bal>
+<Ntry>
+<Amt Ccy="EUR">925.50</Amt>
+<CdtDbtInd>DBIT</CdtDbtInd>
+<BookgDt>
+<Dt>2024-02-05</Dt>
+</BookgDt>
+<AcctSvcrRef>FXREF1</AcctSvcrRef>
+<NtryDtls>
+<TxDtls>
+<AmtDtls>
+<InstdAmt>
+<Amt Ccy="USD">1000.00</Amt>
+</InstdAmt>
+<TxAmt>
+<Amt Ccy="EUR">920.50</Amt>
+<CcyXchg>
+<SrcCcy>USD</SrcCcy>
+<TrgtCcy>EUR</TrgtCcy>
+<XchgRate>0.9205</XchgRate>
+</CcyXchg>
+</TxAmt>
+</AmtDtls>
+<Chrgs>
+<Amt Ccy="EUR">5.00</Amt>
+<CdtDbtInd>DBIT</CdtDbtInd>
+<Tp>
+<Cd>COMM</Cd>
+</Tp>
+<Br>DEBT</Br>
+</Chrgs>
+<RltdPties>
+<Cdtr>
+<Nm>US Supplier Inc</Nm>
+</Cdtr>
+</RltdPties>
+</TxDtls>
+</NtryDtls>
+</Ntry>
+</Stmt>
+</BkToCstmrStmt>
+</Document>
+"#;
+
+#[test]
+fn test_camt053_fx_amounts_and_charges() {
+    let camt = Camt053Statement::parse(SAMPLE_CAMT053_FX).unwrap();
+    let details = &camt.entries[0].transaction_details[0];
+
+    assert_eq!(details.amount, Some(92050));
+    assert_eq!(details.currency.as_deref(), Some("EUR"));
+    assert_eq!(details.charges.len(), 1);
+
+    let statement: Statement = camt.into();
+    let tx = &statement.transactions[0];
+
+    assert_eq!(tx.amount.value, 92550);
+    assert_eq!(tx.instructed_amount.as_ref().map(|a| a.value), Some(100000));
+    assert_eq!(tx.instructed_amount.as_ref().map(|a| a.currency.as_str()), Some("USD"));
+    assert_eq!(tx.original_amount.as_ref().map(|a| a.value), Some(92050));
+    assert_eq!(tx.exchange_rates.len(), 1);
+    assert_eq!(tx.exchange_rates[0].source_currency, "USD");
+    assert_eq!(tx.exchange_rates[0].rate, "0.9205");
+    assert_eq!(tx.charges.len(), 1);
+    assert_eq!(tx.charges[0].amount.value, 500);
+    assert!(!tx.charges[0].is_credit);
+    assert_eq!(tx.charges[0].charge_type.as_deref(), Some("COMM"));
+    assert_eq!(tx.charges[0].bearer.as_deref(), Some("DEBT"));
+}
+
+#[test]
+fn test_camt053_direct_amount_after_several_charges() {
+    let amt_dtls_start = SAMPLE_CAMT053_FX.find("<AmtDtls>").unwrap();
+    let amt_dtls_end = SAMPLE_CAMT053_FX.find("</AmtDtls>\n").unwrap() + "</AmtDtls>\n".len();
+    let content = format!(
+        "{}{}",
+        &SAMPLE_CAMT053_FX[..amt_dtls_start],
+        &SAMPLE_CAMT053_FX[amt_dtls_end..]
+    )
+    .replace(
+        "</Chrgs>\n",
+        "</Chrgs>\n<Chrgs>\n<Amt Ccy=\"EUR\">2.00</Amt>\n</Chrgs>\n<Amt Ccy=\"EUR\">920.50</Amt>\n",
+    );
+
+    let camt = Camt053Statement::parse(&content).unwrap();
+    let details = &camt.entries[0].transaction_details[0];
+    assert_eq!(details.charges.len(), 2);
+    assert_eq!(details.amount, Some(92050));
+    assert_eq!(details.currency.as_deref(), Some("EUR"));
+}
+
+#[test]
+fn test_camt053_fx_write_roundtrip() {
+    let camt = Camt053Statement::parse(SAMPLE_CAMT053_FX).unwrap();
+
+    let mut output = Vec::new();
+    camt.write_to(&mut output).unwrap();
+    let reparsed = Camt053Statement::parse(&String::from_utf8(output).unwrap()).unwrap();
+
+    let statement: Statement = reparsed.into();
+    let tx = &statement.transactions[0];
+
+    assert_eq!(tx.instructed_amount.as_ref().map(|a| a.value), Some(100000));
+    assert_eq!(tx.exchange_rates[0].target_currency.as_deref(), Some("EUR"));
+    assert_eq!(tx.charges.len(), 1);
+    assert_eq!(tx.charges[0].amount.currency, "EUR");
+}
+
+const SAMPLE_CAMT053_PARTIES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+<BkToCstmrStmt>
+<GrpHdr>
+<MsgId>PTY001</MsgId>
+<CreDtTm>2024-03-01T09:00:00</CreDtTm>
+</GrpHdr>
+<Stmt>
+<Id>STMT-PTY</Id>
+<Acct>
+<Id>
+<IBAN>DE89370400440532013000</IBAN>
+</Id>
+<Ccy>EUR</Ccy>
+</Acct>
+<Ntry>
+<Amt Ccy="EUR">250.00</Amt>
+<CdtDbtInd>CRDT</CdtDbtInd>
+<BookgDt>
+<Dt>2024-03-04</Dt>
+</BookgDt>
+<NtryDtls>
+<TxDtls>
+<RltdPties>
+<Dbtr>
+<Nm>Muster GmbH</Nm>
+<PstlAdr>
+<StrtNm>Hauptstrasse</StrtNm>
+<BldgNb>1</BldgNb>
+<PstCd>10115</PstCd>
+<TwnNm>Berlin</TwnNm>
+<Ctry>DE</Ctry>
+</PstlAdr>
+<Id>
+<OrgId>
+<Othr>
+<Id>DE123456789</Id>
+<SchmeNm>
+<Cd>TXID</Cd>
+</SchmeNm>
+</Othr>
+</OrgId>
+</Id>
+</Dbtr>
+<DbtrAcct>
+<Id>
+<IBAN>DE02120300000000202051</IBAN>
+</Id>
+</DbtrAcct>
+<UltmtDbtr>
+<Nm>Muster Holding AG</Nm>
+</UltmtDbtr>
+<Cdtr>
+<Nm>Our Company</Nm>
+</Cdtr>
+</RltdPties>
+<RltdAgts>
+<DbtrAgt>
+<FinInstnId>
+<BIC>BYLADEM1001</BIC>
+<Nm>Deutsche Kreditbank</Nm>
+</FinInstnId>
+</DbtrAgt>
+<CdtrAgt>
+<FinInstnId>
+<BIC>COBADEFFXXX</BIC>
+</FinInstnId>
+</CdtrAgt>
+</RltdAgts>
+</TxDtls>
+</NtryDtls>
+</Ntry>
+</Stmt>
+</BkToCstmrStmt>
+</Document>
+"#;
+
+#[test]
+fn test_camt053_related_parties_and_agents() {
+    let camt = Camt053Statement::parse(SAMPLE_CAMT053_PARTIES).unwrap();
+    let details = &camt.entries[0].transaction_details[0];
+
+    let debtor = details.debtor.as_ref().unwrap();
+    assert_eq!(debtor.name.as_deref(), Some("Muster GmbH"));
+    assert_eq!(debtor.organisation_id.as_deref(), Some("DE123456789"));
+    assert_eq!(
+        debtor.postal_address.as_ref().and_then(|a| a.town_name.as_deref()),
+        Some("Berlin")
+    );
+    assert_eq!(details.debtor_account.as_deref(), Some("DE02120300000000202051"));
+    assert_eq!(
+        details.ultimate_debtor.as_ref().and_then(|p| p.name.as_deref()),
+        Some("Muster Holding AG")
+    );
+    assert_eq!(
+        details.creditor_agent.as_ref().and_then(|a| a.bic.as_deref()),
+        Some("COBADEFFXXX")
+    );
+
+    let statement: Statement = camt.into();
+    let counterparty = statement.transactions[0].counterparty.as_ref().unwrap();
+
+    assert_eq!(counterparty.name.as_deref(), Some("Muster GmbH"));
+    assert_eq!(counterparty.account.as_deref(), Some("DE02120300000000202051"));
+    assert_eq!(counterparty.bank_code.as_deref(), Some("BYLADEM1001"));
+    assert_eq!(counterparty.bank_name.as_deref(), Some("Deutsche Kreditbank"));
+    assert_eq!(counterparty.organisation_id.as_deref(), Some("DE123456789"));
+    assert_eq!(
+        counterparty.ultimate_party.as_ref().and_then(|p| p.name.as_deref()),
+        Some("Muster Holding AG")
+    );
+}
+
+#[test]
+fn test_camt053_related_parties_write_roundtrip() {
+    let camt = Camt053Statement::parse(SAMPLE_CAMT053_PARTIES).unwrap();
+
+    let mut output = Vec::new();
+    camt.write_to(&mut output).unwrap();
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(output_str.contains("<UltmtDbtr>"));
+    assert!(output_str.contains("<RltdAgts>"));
+
+    let statement: Statement = Camt053Statement::parse(&output_str).unwrap().into();
+    let counterparty = statement.transactions[0].counterparty.as_ref().unwrap();
+
+    assert_eq!(counterparty.bank_code.as_deref(), Some("BYLADEM1001"));
+    assert_eq!(counterparty.organisation_id.as_deref(), Some("DE123456789"));
+    assert_eq!(
+        counterparty.postal_address.as_ref().and_then(|a| a.country.as_deref()),
+        Some("DE")
+    );
+}
+
+#[test]
+fn test_camt053_write_non_ascii_party_account() {
+    let mut camt = Camt053Statement::parse(SAMPLE_CAMT053_PARTIES).unwrap();
+    camt.entries[0].transaction_details[0].debtor_account = Some("1Жx".to_string());
+
+    let mut output = Vec::new();
+    camt.write_to(&mut output).unwrap();
+    let output_str = String::from_utf8(output).unwrap();
+    assert!(output_str.contains("<DbtrAcct>\n<Id>\n<Othr>\n<Id>1Жx</Id>"));
+}
+
+const SAMPLE_CSV_TINKOFF: &str = "Выписка по счету 40702810900000012345
+Дата операции;Номер документа;Списание;Поступление;Счет контрагента;БИК банка контрагента;Назначение платежа
+15.01.2024;101;1540,00;;40702810600014448120;044525545;\"Оплата по счету; № 123\"
+16.01.2024;102;;25000,50;40702810100000000001;044525974;Поступление от клиента
+Итого;;1540,00;25000,50;;;
+";
+
+const SAMPLE_CSV_ALFA: &str = "Дата проводки;Номер документа;Счет дебета;Счет кредита;Дебет;Кредит;Банк контрагента;Назначение платежа
+01.02.2024;7;40702810400000000007;40702810600014448120;12 300,00;;БИК 044525593 АО АЛЬФА-БАНК;Аренда
+";
+
+#[test]
+fn test_csv_sber_profile_sample() {
+    let content = include_str!("../../examples/sample.csv");
+    let csv = CsvStatement::parse(content).unwrap();
+
+    assert_eq!(csv.transactions.len(), 1);
+    let tx = &csv.transactions[0];
+    assert_eq!(tx.debit_amount, Some(154000));
+    assert_eq!(tx.debit_account.as_deref(), Some("40702810440000030888"));
+    assert_eq!(tx.document_number, "1");
+}
+
+#[test]
+fn test_csv_sber_header_and_balances() {
+    let content = include_str!("../../examples/sample.csv");
+    let csv = CsvStatement::parse(content).unwrap();
+
+    assert_eq!(csv.account_number, "40702810440000030888");
+    assert_eq!(csv.account_name, "ООО ТЕСТОВАЯ КОМПАНИЯ");
+    assert_eq!(csv.bank_name.as_deref(), Some("АО Тест Банк"));
+    assert_eq!(csv.period_start, Some(Date::new(2024, 1, 1)));
+    assert_eq!(csv.period_end, Some(Date::new(2024, 1, 31)));
+    assert_eq!(csv.opening_balance, Some(10000000));
+    assert_eq!(csv.closing_balance, Some(9846000));
+
+    let statement: Statement = csv.try_into().unwrap();
+    assert_eq!(statement.opening_balance.amount.value, 10000000);
+    assert_eq!(statement.closing_balance.amount.value, 9846000);
+}
+
+#[test]
+fn test_csv_account_cell_tax_ids() {
+    let content = include_str!("../../examples/sample.csv");
+    let csv = CsvStatement::parse(content).unwrap();
+
+    let tx = &csv.transactions[0];
+    assert_eq!(tx.debit_details.tax_id.as_deref(), Some("7735602068"));
+    assert_eq!(tx.debit_details.name.as_deref(), Some("ООО ТЕСТОВАЯ КОМПАНИЯ"));
+    assert_eq!(tx.credit_account.as_deref(), Some("40702810600014448120"));
+    assert_eq!(tx.credit_details.tax_id.as_deref(), Some("7733573894"));
+    assert_eq!(tx.credit_details.name.as_deref(), Some("АО КОНТРАГЕНТ"));
+
+    let statement: Statement = csv.try_into().unwrap();
+    assert_eq!(statement.account.tax_id.as_deref(), Some("7735602068"));
+    let counterparty = statement.transactions[0].counterparty.as_ref().unwrap();
+    assert_eq!(counterparty.name.as_deref(), Some("АО КОНТРАГЕНТ"));
+    assert_eq!(counterparty.tax_id.as_deref(), Some("7733573894"));
+}
+
+#[test]
+fn test_csv_account_cell_inn_kpp_and_checksum() {
+    let content = include_str!("../../examples/sample.csv")
+        .replace("7733573894\nАО КОНТРАГЕНТ", "ИНН 7733573894 / КПП 773301001\nАО КОНТРАГЕНТ")
+        .replace("7735602068\n", "7735602069\n");
+    let csv = CsvStatement::parse(&content).unwrap();
+
+    let tx = &csv.transactions[0];
+    assert_eq!(tx.credit_details.tax_id.as_deref(), Some("7733573894"));
+    assert_eq!(tx.credit_details.tax_reason_code.as_deref(), Some("773301001"));
+    // ИНН с неверной контрольной суммой отбрасывается
+    assert_eq!(tx.debit_details.tax_id, None);
+
+    assert!(is_valid_inn("500100732259"));
+    assert!(!is_valid_inn("500100732250"));
+    assert!(!is_valid_inn("77356020"));
+}
+
+#[test]
+fn test_csv_tax_ids_flow_into_camt_and_mt940() {
+    let content = include_str!("../../examples/sample.csv");
+    let statement: Statement = CsvStatement::parse(content).unwrap().try_into().unwrap();
+
+    let camt: Camt053Statement = statement.clone().into();
+    let mut output = Vec::new();
+    camt.write_to(&mut output).unwrap();
+    let xml = String::from_utf8(output).unwrap();
+    assert!(xml.contains("<Cdtr>\n<Nm>АО КОНТРАГЕНТ</Nm>\n<Id>\n<OrgId>\n<Othr>\n<Id>7733573894</Id>"));
+
+    let reparsed = Camt053Statement::parse(&xml).unwrap();
+    assert_eq!(reparsed.account.owner_tax_id.as_deref(), Some("7735602068"));
+    let creditor = reparsed.entries[0].transaction_details[0].creditor.as_ref().unwrap();
+    assert_eq!(creditor.tax_id.as_deref(), Some("7733573894"));
+
+    let mt940: Mt940Statement = statement.into();
+    assert_eq!(
+        mt940.transactions[0].details,
+        "АО КОНТРАГЕНТ ИНН 7733573894 Оплата по счету № 123 от 10.01.2024"
+    );
+
+    let mut output = Vec::new();
+    mt940.write_to(&mut output).unwrap();
+    let reparsed = Mt940Statement::parse(&String::from_utf8(output).unwrap()).unwrap().remove(0);
+    assert_eq!(reparsed.transactions[0].details, mt940.transactions[0].details);
+}
+
+#[test]
+fn test_mt940_write_long_cyrillic_details() {
+    let mut mt940 = Mt940Statement::parse(SAMPLE_MT940).unwrap().remove(0);
+    let details = "Оплата по договору поставки № 15/2024, ИНН 7733573894 КПП 773301001 \
+                   ООО «Контрагент»";
+    mt940.transactions[0].details = details.to_string();
+
+    let mut output = Vec::new();
+    mt940.write_to(&mut output).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = written
+        .lines()
+        .skip_while(|line| !line.starts_with(":86:"))
+        .take(2)
+        .collect();
+    assert_eq!(lines[0], ":86:Оплата по договору поставки № 15/2024, ИНН 7733573894 КПП");
+    assert_eq!(lines[1], "773301001 ООО «Контрагент»");
+
+    let reparsed = Mt940Statement::parse(&written).unwrap().remove(0);
+    assert_eq!(reparsed.transactions[0].details, details);
+}
+
+#[test]
+fn test_csv_writer_roundtrip() {
+    let content = include_str!("../../examples/sample.csv");
+    let mut csv = CsvStatement::parse(content).unwrap();
+    csv.account_name = "ООО \"Ромашка, Лютик\"".to_string();
+    csv.transactions[0].description = "Оплата по счету, \"срочно\"\nвторая строка".to_string();
+
+    let mut output = Vec::new();
+    csv.write_to(&mut output).unwrap();
+    let written = String::from_utf8(output).unwrap();
+
+    let reparsed = CsvStatement::parse(&written).unwrap();
+    assert_eq!(reparsed, csv);
+}
+
+#[test]
+fn test_statement_to_csv_roundtrip() {
+    let statement = parse_statements(SAMPLE_1C, Format::OneC).unwrap().remove(0);
+    let csv: CsvStatement = statement.clone().into();
+
+    let mut output = Vec::new();
+    csv.write_to(&mut output).unwrap();
+    let reparsed = CsvStatement::parse(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(reparsed, csv);
+
+    let restored: Statement = reparsed.try_into().unwrap();
+    assert_eq!(restored.account.number, statement.account.number);
+    assert_eq!(restored.opening_balance.amount, statement.opening_balance.amount);
+    assert_eq!(restored.closing_balance.amount, statement.closing_balance.amount);
+    assert_eq!(restored.transactions.len(), statement.transactions.len());
+    for (restored, original) in restored.transactions.iter().zip(&statement.transactions) {
+        assert_eq!(restored.amount, original.amount);
+        assert_eq!(restored.is_credit, original.is_credit);
+        assert_eq!(restored.reference, original.reference);
+    }
+}
+
+#[test]
+fn test_csv_tinkoff_profile() {
+    let profile = CsvProfile::builtin("tinkoff").unwrap();
+    let csv = CsvStatement::parse_with_profile(SAMPLE_CSV_TINKOFF, &profile).unwrap();
+
+    assert_eq!(csv.transactions.len(), 2);
+    assert_eq!(csv.transactions[0].debit_amount, Some(154000));
+    assert_eq!(csv.transactions[0].description, "Оплата по счету; № 123");
+    assert_eq!(
+        csv.transactions[0].credit_account.as_deref(),
+        Some("40702810600014448120")
+    );
+    assert_eq!(csv.transactions[1].credit_amount, Some(2500050));
+
+    let statement: Statement = csv.try_into().unwrap();
+    let counterparty = statement.transactions[1].counterparty.as_ref().unwrap();
+    assert_eq!(counterparty.account.as_deref(), Some("40702810100000000001"));
+    assert_eq!(counterparty.bank_code.as_deref(), Some("044525974"));
+}
+
+#[test]
+fn test_csv_quoted_fields() {
+    let content = "Дата,Сумма,Номер документа,Счет контрагента,Назначение\n\
+                   2024-01-15, -10.50 ,\"7\",40702810100000000001,\" Заказ \"\"Б\"\", тест \"\n";
+    let csv = CsvStatement::parse_with_profile(content, &CsvProfile::generic()).unwrap();
+
+    assert_eq!(csv.transactions[0].debit_amount, Some(1050));
+    assert_eq!(csv.transactions[0].document_number, "7");
+    assert_eq!(csv.transactions[0].description, "Заказ \"Б\", тест");
+}
+
+#[test]
+fn test_csv_alfa_profile_thousands_separator() {
+    let profile = CsvProfile::alfa();
+    let csv = CsvStatement::parse_with_profile(SAMPLE_CSV_ALFA, &profile).unwrap();
+
+    assert_eq!(csv.transactions.len(), 1);
+    assert_eq!(csv.transactions[0].debit_amount, Some(1230000));
+}
+
+#[cfg(feature = "profile-files")]
+#[test]
+fn test_csv_profile_from_toml() {
+    let profile = CsvProfile::from_toml_str(
+        r#"
+name = "mybank"
+delimiter = "|"
+date_format = "%d/%m/%Y"
+decimal_separator = ","
+
+[header]
+lines = 1
+
+[columns]
+date = 0
+amount = 1
+description = 2
+"#,
+    )
+    .unwrap();
+
+    let content = "Дата|Сумма|Назначение\n05/03/2024|-99,90|Комиссия\n06/03/2024|100|Возврат\n";
+    let csv = CsvStatement::parse_with_profile(content, &profile).unwrap();
+
+    assert_eq!(csv.transactions.len(), 2);
+    assert_eq!(csv.transactions[0].debit_amount, Some(9990));
+    assert_eq!(csv.transactions[1].credit_amount, Some(10000));
+    assert_eq!(csv.transactions[1].description, "Возврат");
+}
+
+#[cfg(feature = "profile-files")]
+#[test]
+fn test_csv_profile_from_json_rejects_missing_amount() {
+    let result = CsvProfile::from_json_str(
+        r#"{"name": "broken", "columns": {"date": 0, "amount": null, "description": 2}}"#,
+    );
+    assert!(result.is_err());
+}
+
+// ============================================================================
+// 1C: ClientBankExchange
+// ============================================================================
+
+const SAMPLE_1C: &str = include_str!("../../examples/sample.1c.txt");
+
+#[test]
+fn test_onec_parse_sample() {
+    let onec = OneCStatement::parse(SAMPLE_1C).unwrap();
+
+    assert_eq!(onec.format_version, "1.03");
+    assert_eq!(onec.account_numbers, vec!["40702810900000012345"]);
+    assert_eq!(onec.accounts.len(), 1);
+    assert_eq!(onec.accounts[0].opening_balance, 10000000);
+    assert_eq!(onec.accounts[0].closing_balance, 13499950);
+    assert_eq!(onec.documents.len(), 2);
+    assert_eq!(onec.documents[0].payer.inn.as_deref(), Some("7707083893"));
+    assert_eq!(onec.documents[0].payer.kpp.as_deref(), Some("770701001"));
+}
+
+#[test]
+fn test_onec_into_statements() {
+    let statements = parse_statements(SAMPLE_1C, Format::OneC).unwrap();
+    assert_eq!(statements.len(), 1);
+
+    let statement = &statements[0];
+    assert_eq!(statement.account.number, "40702810900000012345");
+    assert_eq!(statement.account.owner.as_deref(), Some("ООО \"Лютик\""));
+    assert_eq!(statement.transactions.len(), 2);
+
+    let incoming = &statement.transactions[0];
+    assert!(incoming.is_credit);
+    assert_eq!(incoming.amount.value, 5000000);
+    let payer = incoming.counterparty.as_ref().unwrap();
+    assert_eq!(payer.name.as_deref(), Some("ООО \"Ромашка\""));
+    assert_eq!(payer.tax_id.as_deref(), Some("7707083893"));
+    assert_eq!(payer.tax_reason_code.as_deref(), Some("770701001"));
+    assert_eq!(payer.bank_code.as_deref(), Some("044525225"));
+    assert_eq!(
+        payer.bank_correspondent_account.as_deref(),
+        Some("30101810400000000225")
+    );
+
+    let outgoing = &statement.transactions[1];
+    assert!(!outgoing.is_credit);
+    assert_eq!(outgoing.amount.value, 1500050);
+    assert_eq!(outgoing.description, "Возврат излишне уплаченных средств");
+}
+
+#[test]
+fn test_onec_period_from_document_dates() {
+    let without_period: String = SAMPLE_1C
+        .lines()
+        .filter(|line| !line.starts_with("ДатаНачала=") && !line.starts_with("ДатаКонца="))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let statement = OneCStatement::parse(&without_period)
+        .unwrap()
+        .into_statements()
+        .unwrap()
+        .remove(0);
+    assert_eq!(statement.opening_balance.date, Date::new(2024, 1, 15));
+    assert_eq!(statement.closing_balance.date, Date::new(2024, 1, 16));
+
+    let mut without_documents = OneCStatement::parse(&without_period).unwrap();
+    without_documents.documents.clear();
+    assert!(matches!(
+        without_documents.into_statements(),
+        Err(Error::MissingField(_))
+    ));
+}
+
+#[test]
+fn test_onec_write_roundtrip() {
+    let onec = OneCStatement::parse(SAMPLE_1C).unwrap();
+
+    let mut output = Vec::new();
+    onec.write_to(&mut output).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    assert!(written.starts_with("1CClientBankExchange\r\n"));
+    assert!(written.contains("\r\nКодировка=UTF-8\r\n"));
+
+    let mut cp1251 = Vec::new();
+    onec.write_to_with_encoding(&mut cp1251, TextEncoding::Windows1251).unwrap();
+    let decoded = encoding::decode(&cp1251, None).unwrap();
+    assert!(decoded.contains("\r\nКодировка=Windows\r\n"));
+    assert!(written.ends_with("КонецФайла\r\n"));
+
+    let reparsed = OneCStatement::parse(&written).unwrap();
+    assert_eq!(reparsed.documents.len(), onec.documents.len());
+    assert_eq!(reparsed.documents[1].payee.bik.as_deref(), Some("044525974"));
+    assert_eq!(reparsed.accounts[0].total_debit, 1500050);
+}
+
+#[test]
+fn test_mt940_to_onec() {
+    let statements = parse_statements(SAMPLE_MT940, Format::Mt940).unwrap();
+    let expected = statements[0].transactions.len();
+
+    let onec: OneCStatement = statements.into();
+    assert_eq!(onec.documents.len(), expected);
+
+    let mut output = Vec::new();
+    onec.write_to(&mut output).unwrap();
+    let reparsed = parse_statements(&String::from_utf8(output).unwrap(), Format::OneC).unwrap();
+    assert_eq!(reparsed[0].transactions.len(), expected);
+}
+
+#[test]
+fn test_onec_to_mt940() {
+    let statement = parse_statement(SAMPLE_1C, Format::OneC).unwrap();
+    let mt940: Mt940Statement = statement.clone().into();
+
+    let mut output = Vec::new();
+    mt940.write_to(&mut output).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    assert!(written.lines().all(|line| line.chars().count() <= 4 + 65));
+
+    let reparsed = Mt940Statement::parse(&written).unwrap().remove(0);
+    assert_eq!(reparsed.account_id, statement.account.number);
+    assert_eq!(reparsed.transactions.len(), statement.transactions.len());
+    assert_eq!(reparsed.transactions[0].details, mt940.transactions[0].details);
+    assert!(reparsed.transactions[0].details.contains("ИНН 7707083893 КПП 770701001"));
+    assert_eq!(reparsed.closing_balance.amount, 13499950);
+}
+
+// ============================================================================
+// OFX
+// ============================================================================
+
+const SAMPLE_OFX: &str = include_str!("../../examples/sample.ofx");
+
+#[test]
+fn test_ofx_parse_sgml_sample() {
+    let ofx = OfxStatement::parse(SAMPLE_OFX).unwrap();
+    assert_eq!(ofx.len(), 1);
+
+    let ofx = &ofx[0];
+    assert_eq!(ofx.version, OfxVersion::V1);
+    assert_eq!(ofx.currency, "RUB");
+    assert_eq!(ofx.bank_id.as_deref(), Some("044525225"));
+    assert_eq!(ofx.account_id, "40702810900000012345");
+    assert_eq!(ofx.transactions.len(), 2);
+    assert_eq!(ofx.transactions[0].date_posted, Date::new(2024, 1, 15));
+    assert_eq!(ofx.transactions[1].amount, -1500050);
+    assert_eq!(ofx.transactions[1].name.as_deref(), Some("ИП Иванов & Ко"));
+    assert_eq!(ofx.ledger_balance.amount, 13499950);
+}
+
+#[test]
+fn test_ofx_into_statement() {
+    let statement = parse_statement(SAMPLE_OFX, Format::Ofx).unwrap();
+
+    assert_eq!(statement.account.number, "40702810900000012345");
+    assert_eq!(statement.opening_balance.amount.value, 10000000);
+    assert_eq!(statement.opening_balance.date, Date::new(2024, 1, 1));
+    assert_eq!(statement.closing_balance.amount.value, 13499950);
+    assert!(statement.transactions[0].is_credit);
+    assert!(!statement.transactions[1].is_credit);
+    assert_eq!(statement.transactions[1].amount.value, 1500050);
+    assert_eq!(statement.transactions[0].reference.as_deref(), Some("PP-101"));
+    assert_eq!(
+        statement.transactions[1].description,
+        "Возврат излишне уплаченных средств"
+    );
+}
+
+#[test]
+fn test_ofx_write_roundtrip_both_versions() {
+    let original = OfxStatement::parse(SAMPLE_OFX).unwrap().remove(0);
+
+    for version in [OfxVersion::V1, OfxVersion::V2] {
+        let ofx = OfxStatement {
+            version,
+            ..original.clone()
+        };
+        let mut output = Vec::new();
+        ofx.write_to(&mut output).unwrap();
+        let written = String::from_utf8(output).unwrap();
+
+        match version {
+            OfxVersion::V1 => assert!(written.starts_with("OFXHEADER:100\n")),
+            OfxVersion::V2 => {
+                assert!(written.starts_with("<?xml"));
+                assert!(written.contains("<FITID>PP-101</FITID>"));
+            }
+        }
+        assert!(written.contains("<NAME>ИП Иванов &amp; Ко"));
+
+        let reparsed = OfxStatement::parse(&written).unwrap().remove(0);
+        assert_eq!(reparsed.version, version);
+        assert_eq!(reparsed.transactions.len(), 2);
+        assert_eq!(reparsed.transactions[1].fitid, "PP-102");
+        assert_eq!(reparsed.transactions[1].name, original.transactions[1].name);
+        assert_eq!(reparsed.ledger_balance.amount, original.ledger_balance.amount);
+    }
+}
+
+#[test]
+fn test_mt940_to_ofx() {
+    let statements = parse_statements(SAMPLE_MT940, Format::Mt940).unwrap();
+    let expected = statements[0].clone();
+
+    let ofx: Vec<OfxStatement> = statements.into_iter().map(Into::into).collect();
+    assert_eq!(ofx[0].transactions[0].transaction_type, "DEBIT");
+    assert_eq!(ofx[0].transactions[0].amount, -6500);
+
+    let mut output = Vec::new();
+    OfxWriter::write_all_to(&ofx, &mut output).unwrap();
+    let reparsed = parse_statements(&String::from_utf8(output).unwrap(), Format::Ofx).unwrap();
+
+    assert_eq!(reparsed.len(), 1);
+    assert_eq!(reparsed[0].account.number, expected.account.number);
+    assert_eq!(reparsed[0].opening_balance.amount, expected.opening_balance.amount);
+    assert_eq!(reparsed[0].closing_balance.amount, expected.closing_balance.amount);
+    assert_eq!(reparsed[0].transactions.len(), expected.transactions.len());
+}
+
+#[test]
+fn test_ofx_fitids_are_unique() {
+    let mut statement = parse_statement(SAMPLE_OFX, Format::Ofx).unwrap();
+    statement.transactions[1].reference = statement.transactions[0].reference.clone();
+    statement.transactions.push(statement.transactions[0].clone());
+    statement.transactions[2].reference = None;
+
+    let ofx: OfxStatement = statement.into();
+    let fitids: Vec<&str> = ofx.transactions.iter().map(|tx| tx.fitid.as_str()).collect();
+    assert_eq!(fitids, vec!["PP-101", "PP-101-2", "202401150003"]);
+}
+
+#[test]
+fn test_ofx_statements_in_document_order() {
+    let start = SAMPLE_OFX.find("<STMTRS>").unwrap();
+    let end = SAMPLE_OFX.find("</STMTRS>").unwrap() + "</STMTRS>".len();
+    let card = SAMPLE_OFX[start..end]
+        .replace("STMTRS>", "CCSTMTRS>")
+        .replace("BANKACCTFROM>", "CCACCTFROM>")
+        .replace("40702810900000012345", "4111111111111111");
+    let content = format!("{}{}\n{}", &SAMPLE_OFX[..start], card, &SAMPLE_OFX[start..]);
+
+    let accounts: Vec<String> = OfxStatement::parse(&content)
+        .unwrap()
+        .into_iter()
+        .map(|ofx| ofx.account_id)
+        .collect();
+    assert_eq!(accounts, vec!["4111111111111111", "40702810900000012345"]);
+}
+
+#[test]
+fn test_ofx_from_statement_has_no_available_balance() {
+    let statement = parse_statement(SAMPLE_MT940, Format::Mt940).unwrap();
+    let ofx: OfxStatement = statement.into();
+    assert!(ofx.available_balance.is_none());
+
+    let mut output = Vec::new();
+    OfxWriter::write_to(&ofx, &mut output).unwrap();
+    assert!(!String::from_utf8(output).unwrap().contains("<AVAILBAL>"));
+}
+
+// ============================================================================
+// BAI2
+// ============================================================================
+
+const SAMPLE_BAI2: &str = include_str!("../../examples/sample.bai2");
+
+#[test]
+fn test_bai2_parse_sample() {
+    let bai2 = Bai2File::parse(SAMPLE_BAI2).unwrap();
+
+    assert_eq!(bai2.sender, "122099999");
+    assert_eq!(bai2.creation_date, Date::new(2024, 1, 31));
+    assert_eq!(bai2.groups.len(), 2);
+    assert_eq!(bai2.groups[0].accounts.len(), 2);
+    assert_eq!(bai2.groups[1].currency.as_deref(), Some("EUR"));
+
+    let account = &bai2.groups[0].accounts[0];
+    assert_eq!(account.summaries.len(), 4);
+    assert_eq!(account.summary("400").unwrap().item_count, Some(2));
+    assert_eq!(account.transactions.len(), 3);
+    assert_eq!(
+        account.transactions[0].text,
+        "INCOMING WIRE FROM ACME CORP INVOICE 1001 PAYMENT"
+    );
+    assert_eq!(account.transactions[2].text, "ACH DEBIT, PAYROLL JANUARY");
+    assert_eq!(account.transactions[2].customer_reference.as_deref(), Some("REF-77"));
+
+    // Остаток 015 задан в записи-продолжении 88
+    let continued = &bai2.groups[0].accounts[1];
+    assert_eq!(continued.summary("015").unwrap().amount, Some(90000));
+}
+
+#[test]
+fn test_bai2_into_statements() {
+    let statements = parse_statements(SAMPLE_BAI2, Format::Bai2).unwrap();
+    assert_eq!(statements.len(), 3);
+
+    let first = &statements[0];
+    assert_eq!(first.account.number, "0975312468");
+    assert_eq!(first.opening_balance.amount.value, 500000);
+    assert_eq!(first.closing_balance.amount.value, 475000);
+    assert!(first.transactions[0].is_credit);
+    assert!(!first.transactions[1].is_credit);
+    assert_eq!(first.transactions[1].reference.as_deref(), Some("CHK1234"));
+    assert_eq!(first.transactions[2].reference.as_deref(), Some("REF-77"));
+
+    let overdrawn = &statements[1];
+    assert_eq!(overdrawn.account.currency, "USD");
+    assert!(!overdrawn.opening_balance.is_credit);
+    assert_eq!(overdrawn.opening_balance.amount.value, 10000);
+
+    assert_eq!(statements[2].account.currency, "EUR");
+    assert_eq!(statements[2].closing_balance.date, Date::new(2024, 2, 1));
+}
+
+#[test]
+fn test_bai2_rejects_wrong_control_totals() {
+    let broken = SAMPLE_BAI2.replace("49,180000,4/", "49,180001,4/");
+    assert!(Bai2File::parse(&broken).is_err());
+
+    let broken = SAMPLE_BAI2.replace("98,400000,1,5/", "98,400000,1,6/");
+    assert!(Bai2File::parse(&broken).is_err());
+
+    let broken = SAMPLE_BAI2.replace("99,1805000,2,19/", "99,1805000,3,19/");
+    assert!(Bai2File::parse(&broken).is_err());
+}
+
+#[test]
+fn test_bai2_write_roundtrip() {
+    let bai2 = Bai2File::parse(SAMPLE_BAI2).unwrap();
+
+    let mut output = Vec::new();
+    bai2.write_to(&mut output).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    assert!(written.lines().all(|line| line.len() <= 80));
+    assert!(written.lines().last().unwrap().starts_with("99,1805000,2,"));
+
+    let reparsed = Bai2File::parse(&written).unwrap();
+    assert_eq!(reparsed.groups.len(), 2);
+    assert_eq!(
+        reparsed.groups[0].accounts[0].transactions,
+        bai2.groups[0].accounts[0].transactions
+    );
+    assert_eq!(
+        reparsed.groups[0].accounts[1].summaries,
+        bai2.groups[0].accounts[1].summaries
+    );
+}
+
+#[test]
+fn test_mt940_to_bai2_wraps_long_text() {
+    let mut statements = parse_statements(SAMPLE_MT940, Format::Mt940).unwrap();
+    let description = "Betaling sieraden ".repeat(8).trim().to_string();
+    statements[0].transactions[0].description = description.clone();
+    let expected = statements[0].clone();
+
+    let bai2: Bai2File = statements.into();
+    let mut output = Vec::new();
+    bai2.write_to(&mut output).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    assert!(written.contains("\n88,"));
+
+    let reparsed = parse_statements(&written, Format::Bai2).unwrap();
+    assert_eq!(reparsed.len(), 1);
+    assert_eq!(reparsed[0].account.number, expected.account.number);
+    assert_eq!(reparsed[0].opening_balance.amount, expected.opening_balance.amount);
+    assert_eq!(reparsed[0].closing_balance.amount, expected.closing_balance.amount);
+    assert_eq!(reparsed[0].transactions[0].description, description);
+    assert!(!reparsed[0].transactions[0].is_credit);
+}
+
+// ============================================================================
+// QIF
+// ============================================================================
+
+const SAMPLE_QIF: &str = include_str!("../../examples/sample.qif");
+
+#[test]
+fn test_qif_lenient_parse() {
+    let qif = QifFile::parse(SAMPLE_QIF).unwrap();
+
+    // Список категорий пропущен, некорректная дата пропущена,
+    // последняя запись без ^ прочитана
+    assert_eq!(qif.accounts.len(), 1);
+    let account = &qif.accounts[0];
+    assert_eq!(account.name.as_deref(), Some("40817810099910004312"));
+    assert_eq!(account.account_type, "Bank");
+    assert_eq!(account.transactions.len(), 4);
+    assert_eq!(account.transactions[1].date, Date::new(2024, 1, 15));
+    assert_eq!(account.transactions[1].amount, -154050);
+    assert_eq!(account.transactions[1].number.as_deref(), Some("101"));
+    assert_eq!(account.transactions[3].memo.as_deref(), Some("Комиссия за обслуживание"));
+}
+
+#[test]
+fn test_qif_into_statement() {
+    let statement = parse_statement(SAMPLE_QIF, Format::Qif).unwrap();
+
+    assert_eq!(statement.account.number, "40817810099910004312");
+    assert_eq!(statement.opening_balance.amount.value, 1000000);
+    assert_eq!(statement.closing_balance.amount.value, 1075950);
+    assert_eq!(statement.closing_balance.date, Date::new(2024, 1, 31));
+    assert_eq!(statement.transactions.len(), 3);
+
+    let payment = &statement.transactions[0];
+    assert!(!payment.is_credit);
+    assert_eq!(payment.reference.as_deref(), Some("101"));
+    assert_eq!(payment.description, "Оплата по договору 15");
+    assert_eq!(
+        payment.counterparty.as_ref().unwrap().name.as_deref(),
+        Some("ООО Ромашка")
+    );
+}
+
+#[test]
+fn test_qif_empty_account_has_no_invented_date() {
+    let empty = "!Account\nNEmpty\nTBank\n^\n!Type:Bank\n";
+    assert!(matches!(
+        parse_statements(empty, Format::Qif),
+        Err(Error::MissingField(_))
+    ));
+}
+
+#[test]
+fn test_qif_write_roundtrip_eu_dates() {
+    let statements = parse_statements(SAMPLE_MT940, Format::Mt940).unwrap();
+    let expected = statements[0].clone();
+
+    let qif = QifFile {
+        date_style: QifDateStyle::Eu,
+        ..statements.into()
+    };
+    let mut output = Vec::new();
+    qif.write_to(&mut output).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    assert!(written.starts_with("!Account\nNNL81ASNB9999999999\nTBank\n^\n!Type:Bank\n"));
+    assert!(written.contains("D01/01/2020\nT444.29\nCX\nPOpening Balance\n"));
+    assert!(written.contains("T-65.00\n"));
+
+    let reparsed = QifFile::parse_with_style(&written, QifDateStyle::Eu)
+        .unwrap()
+        .into_statements()
+        .unwrap();
+    assert_eq!(reparsed.len(), 1);
+    assert_eq!(reparsed[0].account.number, expected.account.number);
+    assert_eq!(reparsed[0].opening_balance.amount.value, expected.opening_balance.amount.value);
+    assert_eq!(reparsed[0].closing_balance.amount.value, expected.closing_balance.amount.value);
+    assert_eq!(reparsed[0].transactions.len(), expected.transactions.len());
+    assert_eq!(reparsed[0].transactions[0].date, expected.transactions[0].date);
+}
+
+// ============================================================================
+// Beancount / Ledger
+// ============================================================================
+
+fn sample_account_mapping() -> AccountMapping {
+    let mut mapping = AccountMapping::default();
+    mapping
+        .accounts
+        .insert("40702810900000012345".to_string(), "Assets:Bank:Sber".to_string());
+    mapping.rules = vec![
+        MappingRule::counterparty_account("40702810500000054321", "Income:Sales"),
+        MappingRule::description("(?i)возврат", "Expenses:Refunds").unwrap(),
+    ];
+    mapping
+}
+
+#[test]
+fn test_beancount_export() {
+    let statements = parse_statements(SAMPLE_1C, Format::OneC).unwrap();
+
+    let mut output = Vec::new();
+    BeancountWriter::write_to(&statements, &sample_account_mapping(), &mut output).unwrap();
+    let journal = String::from_utf8(output).unwrap();
+
+    assert!(journal.starts_with("2024-01-14 open Assets:Bank:Sber RUB\n"));
+    assert!(journal.contains("2024-01-14 open Income:Sales\n"));
+    assert!(journal.contains("2024-01-14 pad Assets:Bank:Sber Equity:Opening-Balances\n"));
+    assert!(journal.contains("2024-01-15 balance Assets:Bank:Sber 100000.00 RUB\n"));
+    assert!(journal.contains("2024-01-15 * \"ООО \\\"Ромашка\\\"\" \"Оплата по договору"));
+    assert!(journal.contains("  reference: \"101\"\n"));
+    assert!(journal.contains("-15000.50 RUB\n  Expenses:Refunds\n"));
+    // Исходящий остаток проверяется на начало следующего дня
+    assert!(journal.contains("2024-01-17 balance Assets:Bank:Sber 134999.50 RUB\n"));
+}
+
+#[test]
+fn test_ledger_export() {
+    let statements = parse_statements(SAMPLE_MT940, Format::Mt940).unwrap();
+
+    let mut output = Vec::new();
+    LedgerWriter::write_to(&statements, &AccountMapping::default(), &mut output).unwrap();
+    let journal = String::from_utf8(output).unwrap();
+
+    let lines: Vec<&str> = journal.lines().map(str::trim_end).collect();
+    assert_eq!(lines[0], "2020/01/01 * Opening balance");
+    assert!(lines[1].starts_with("    Assets:Bank") && lines[1].ends_with("= 444.29 EUR"));
+    assert_eq!(lines[2], "    Equity:Opening-Balances");
+    assert!(!journal.contains(" | "));
+    assert!(journal.contains("-65.00 EUR\n    Expenses:Uncategorized\n"));
+    assert!(journal.contains("0 EUR = 379.29 EUR"));
+}
+
+#[test]
+fn test_account_mapping_rules() {
+    let statements = parse_statements(SAMPLE_1C, Format::OneC).unwrap();
+    let mapping = sample_account_mapping();
+
+    assert_eq!(mapping.own_account("40702810900000012345", None), "Assets:Bank:Sber");
+    assert_eq!(mapping.own_account("00000000000000000000", None), "Assets:Bank");
+    assert_eq!(mapping.counter_account(&statements[0].transactions[0]), "Income:Sales");
+    assert_eq!(mapping.counter_account(&statements[0].transactions[1]), "Expenses:Refunds");
+    assert!(MappingRule::description("(unclosed", "Expenses:Misc").is_err());
+}
+
+#[cfg(feature = "profile-files")]
+#[test]
+fn test_account_mapping_from_toml() {
+    let mapping = AccountMapping::from_toml_str(include_str!("../../examples/accounts.toml")).unwrap();
+    assert_eq!(mapping.rules.len(), 2);
+    assert_eq!(mapping.own_account("40702810900000012345", None), "Assets:Bank:Sber");
+
+    let result = AccountMapping::from_toml_str("[[rules]]\naccount = \"Expenses:Misc\"\n");
+    assert!(result.is_err());
+}
+
+// ============================================================================
+// CODA
+// ============================================================================
+
+const SAMPLE_CODA: &str = include_str!("../../examples/sample.coda");
+
+#[test]
+fn test_coda_parse() {
+    let statements = CodaStatement::parse(SAMPLE_CODA).unwrap();
+    assert_eq!(statements.len(), 1);
+
+    let coda = &statements[0];
+    assert_eq!(coda.account_number, "BE68539007547034");
+    assert!(coda.is_iban);
+    assert_eq!(coda.bic.as_deref(), Some("KREDBEBB"));
+    assert_eq!(coda.opening_balance, 1250000);
+    assert_eq!(coda.closing_balance, 1368050);
+    assert_eq!(coda.movements.len(), 2);
+    assert_eq!(coda.free_communications, vec!["Nouveaux tarifs a partir du 01/02/2024"]);
+
+    let ogm = &coda.movements[0];
+    assert_eq!(ogm.ogm(), Some("+++123/4567/89002+++"));
+    assert_eq!(ogm.customer_reference.as_deref(), Some("INV-2024-0007"));
+    assert_eq!(ogm.counterparty_bic.as_deref(), Some("GEBABEBB"));
+    assert_eq!(ogm.counterparty_account.as_deref(), Some("BE71096123456769"));
+
+    let rent = &coda.movements[1];
+    assert_eq!(rent.amount, -32000);
+    assert_eq!(rent.information.len(), 1);
+    assert!(rent.information[0].starts_with("Rue de la Loi 16"));
+}
+
+#[test]
+fn test_coda_to_statement() {
+    let statement = parse_statement(SAMPLE_CODA, Format::Coda).unwrap();
+
+    assert_eq!(statement.account.iban.as_deref(), Some("BE68539007547034"));
+    assert_eq!(statement.account.owner.as_deref(), Some("ACME BELGIUM NV"));
+    assert_eq!(statement.opening_balance.date, Date::new(2024, 1, 14));
+
+    let credit = &statement.transactions[0];
+    assert!(credit.is_credit);
+    assert_eq!(credit.reference.as_deref(), Some("+++123/4567/89002+++"));
+    let counterparty = credit.counterparty.as_ref().unwrap();
+    assert_eq!(counterparty.name.as_deref(), Some("JANSSENS BVBA"));
+    assert_eq!(counterparty.bank_code.as_deref(), Some("GEBABEBB"));
+
+    let debit = &statement.transactions[1];
+    assert!(!debit.is_credit);
+    assert_eq!(
+        debit.description,
+        "Loyer janvier 2024 bureau Bruxelles, contrat 2023/17 - acompte charges communes inclus"
+    );
+
+    let camt: Camt053Statement = statement.into();
+    let mut output = Vec::new();
+    camt.write_to(&mut output).unwrap();
+    assert!(String::from_utf8(output).unwrap().contains("<Ustrd>+++123/4567/89002+++</Ustrd>"));
+}
+
+#[test]
+fn test_coda_trailer_mismatch() {
+    // Кредитовый оборот в записи 9 уменьшен на 1 евро
+    let corrupted = SAMPLE_CODA.replace(
+        "000000000320000000000001500500",
+        "000000000320000000000001490500",
+    );
+    assert!(matches!(
+        CodaStatement::parse(&corrupted),
+        Err(Error::InvalidFormat(_))
+    ));
+
+    let missing_record: String = SAMPLE_CODA
+        .lines()
+        .filter(|line| !line.starts_with('4'))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    assert!(CodaStatement::parse(&missing_record).is_err());
+}
+
+// ============================================================================
+// Norma 43 / CFONB 120
+// ============================================================================
+
+const SAMPLE_NORMA43: &str = include_str!("../../examples/sample.n43");
+const SAMPLE_CFONB: &str = include_str!("../../examples/sample.cfonb");
+
+#[test]
+fn test_norma43_parse() {
+    let statements = Norma43Statement::parse(SAMPLE_NORMA43).unwrap();
+    assert_eq!(statements.len(), 1);
+
+    let n43 = &statements[0];
+    assert_eq!(n43.bank_code, "2100");
+    assert_eq!(n43.account_number, "4502000513");
+    assert_eq!(n43.currency, "EUR");
+    assert_eq!(n43.account_name.as_deref(), Some("EMPRESA DEMO SL"));
+    assert_eq!(n43.opening_balance, 1250000);
+    assert_eq!(n43.closing_balance, 1661050);
+    assert_eq!(n43.movements.len(), 4);
+
+    let receipt = &n43.movements[1];
+    assert_eq!(receipt.amount, -45990);
+    assert_eq!(receipt.reference1.as_deref(), Some("123456789012"));
+    assert_eq!(receipt.document_number, None);
+
+    let foreign = &n43.movements[3];
+    assert_eq!(foreign.original_currency.as_deref(), Some("USD"));
+    assert_eq!(foreign.original_amount, Some(120000));
+}
+
+#[test]
+fn test_norma43_to_statement() {
+    let statement = parse_statement(SAMPLE_NORMA43, Format::Norma43).unwrap();
+
+    assert_eq!(statement.account.number, "210004184502000513");
+    assert_eq!(statement.closing_balance.date, Date::new(2024, 1, 31));
+
+    let types: Vec<_> = statement
+        .transactions
+        .iter()
+        .map(|tx| tx.transaction_type.as_deref().unwrap())
+        .collect();
+    assert_eq!(types, vec!["NTRF", "NDDT", "NCHG", "NFEX"]);
+    assert_eq!(
+        statement.transactions[0].description,
+        "TRANSFERENCIA DE ACME IBERICA SL FACTURA 2024/015"
+    );
+    assert_eq!(
+        statement.transactions[3].original_amount.as_ref().unwrap().currency,
+        "USD"
+    );
+
+    // Тип транзакции переносится в поле :61: MT940
+    let mt940: Mt940Statement = statement.into();
+    let mut output = Vec::new();
+    mt940.write_to(&mut output).unwrap();
+    assert!(String::from_utf8(output).unwrap().contains("D459,90NDDT//123456789012"));
+}
+
+#[test]
+fn test_norma43_totals_mismatch() {
+    // Количество дебетовых движений в записи 33 увеличено на одно
+    let corrupted = SAMPLE_NORMA43.replace("00051300002", "00051300003");
+    assert!(matches!(
+        Norma43Statement::parse(&corrupted),
+        Err(Error::InvalidFormat(_))
+    ));
+
+    // Количество записей в записи 88 не совпадает
+    let corrupted = SAMPLE_NORMA43.replace("999999000011", "999999000012");
+    assert!(matches!(
+        Norma43Statement::parse(&corrupted),
+        Err(Error::InvalidFormat(_))
+    ));
+}
+
+#[test]
+fn test_cfonb_parse() {
+    let statements = CfonbStatement::parse(SAMPLE_CFONB).unwrap();
+    assert_eq!(statements.len(), 1);
+
+    let cfonb = &statements[0];
+    assert_eq!(cfonb.bank_code, "30004");
+    assert_eq!(cfonb.account_number, "00010234567");
+    assert_eq!(cfonb.opening_balance, 523410);
+    assert_eq!(cfonb.closing_balance, 641361);
+    assert_eq!(cfonb.movements.len(), 3);
+
+    let transfer = &cfonb.movements[0];
+    assert_eq!(transfer.interbank_code, "05");
+    assert_eq!(transfer.complement("NPY"), Some("ACME SARL"));
+    assert_eq!(cfonb.movements[2].amount, -8999);
+}
+
+#[test]
+fn test_cfonb_to_statement() {
+    let statement = parse_statement(SAMPLE_CFONB, Format::Cfonb120).unwrap();
+
+    assert_eq!(statement.account.currency, "EUR");
+    let transfer = &statement.transactions[0];
+    assert_eq!(transfer.transaction_type.as_deref(), Some("NTRF"));
+    assert_eq!(
+        transfer.description,
+        "VIR SEPA RECU ACME SARL FACTURE 2024-015 JANVIER"
+    );
+    assert_eq!(
+        transfer.counterparty.as_ref().unwrap().name.as_deref(),
+        Some("ACME SARL")
+    );
+    assert_eq!(statement.transactions[1].transaction_type.as_deref(), Some("NCHK"));
+    assert_eq!(statement.transactions[2].transaction_type.as_deref(), Some("NMSC"));
+}
+
+#[test]
+fn test_cfonb_balance_mismatch() {
+    // Исходящий остаток увеличен на 1 цент
+    let corrupted = SAMPLE_CFONB.replace("0000000064136A", "0000000064136B");
+    assert!(matches!(
+        CfonbStatement::parse(&corrupted),
+        Err(Error::InvalidFormat(_))
+    ));
+
+    // Движение относится к другому счету
+    let corrupted = SAMPLE_CFONB.replacen("EUR2 0001023456705", "EUR2 0001023456805", 1);
+    assert!(CfonbStatement::parse(&corrupted).is_err());
+}
+
+// ============================================================================
+// JSON / NDJSON
+// ============================================================================
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_roundtrip() {
+    use ypbank_parser::{JsonDocument, JsonWriter};
+
+    let mut statements = parse_statements(SAMPLE_CAMT053_FX, Format::Camt053).unwrap();
+    statements.extend(parse_statements(SAMPLE_CAMT053_PARTIES, Format::Camt053).unwrap());
+
+    let mut output = Vec::new();
+    JsonWriter::write_to(&statements, &mut output).unwrap();
+    let json = String::from_utf8(output).unwrap();
+    assert!(json.contains("\"version\": 1"));
+
+    let reparsed = JsonDocument::parse(&json).unwrap().into_statements();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", statements));
+    assert_eq!(
+        format!("{:?}", parse_statements(&json, Format::Json).unwrap()),
+        format!("{:?}", statements)
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_ndjson_one_transaction_per_line() {
+    use ypbank_parser::{JsonDocument, JsonWriter};
+
+    let statements = OneCStatement::parse(SAMPLE_1C).unwrap().into_statements().unwrap();
+
+    let mut output = Vec::new();
+    JsonWriter::write_ndjson_to(&statements, &mut output).unwrap();
+    let ndjson = String::from_utf8(output).unwrap();
+
+    let lines: Vec<&str> = ndjson.lines().collect();
+    assert_eq!(lines.len(), 1 + statements[0].transactions.len());
+    assert!(lines[0].starts_with(r#"{"record":"statement","version":1,"#));
+    assert!(lines[1].starts_with(r#"{"record":"transaction","date":"2024-01-15","#));
+
+    let reparsed = JsonDocument::parse_ndjson(&ndjson).unwrap().into_statements();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", statements));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_input_minimal() {
+    // Необязательные поля можно опускать
+    let json = r#"{
+        "version": 1,
+        "statements": [{
+            "account": {"number": "40702810900000012345", "currency": "RUB"},
+            "opening_balance": {
+                "amount": {"value": 0, "currency": "RUB"},
+                "date": "2024-03-01",
+                "is_credit": true
+            },
+            "closing_balance": {
+                "amount": {"value": 150000, "currency": "RUB"},
+                "date": "2024-03-01",
+                "is_credit": true
+            },
+            "transactions": [{
+                "date": "2024-03-01",
+                "amount": {"value": 150000, "currency": "RUB"},
+                "is_credit": true,
+                "description": "Оплата счета 12"
+            }]
+        }]
+    }"#;
+
+    let statement = parse_statement(json, Format::Json).unwrap();
+    assert_eq!(statement.account.iban, None);
+    assert_eq!(statement.transactions[0].date, Date::new(2024, 3, 1));
+    assert!(statement.transactions[0].charges.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_rejects_invalid_input() {
+    let bad_date = r#"{"version": 1, "statements": [{
+        "account": {"number": "1", "currency": "RUB"},
+        "opening_balance": {"amount": {"value": 0, "currency": "RUB"}, "date": "2024-13-01", "is_credit": true},
+        "closing_balance": {"amount": {"value": 0, "currency": "RUB"}, "date": "2024-01-01", "is_credit": true}
+    }]}"#;
+    assert!(parse_statements(bad_date, Format::Json).is_err());
+    assert!(parse_statements(r#"{"statements": []}"#, Format::Json).is_err());
+
+    let orphan = r#"{"record":"transaction","date":"2024-01-01","amount":{"value":1,"currency":"RUB"},"is_credit":true,"description":""}"#;
+    assert!(parse_statements(orphan, Format::Ndjson).is_err());
+}
+
+// ============================================================================
+// XLSX
+// ============================================================================
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_xlsx_roundtrip_multiple_accounts() {
+    use ypbank_parser::{XlsxWorkbook, XlsxWriter};
+
+    let statements = parse_statements(SAMPLE_BAI2, Format::Bai2).unwrap();
+    assert!(statements.len() > 1);
+
+    let bytes = XlsxWriter::to_bytes(&statements).unwrap();
+    assert!(bytes.starts_with(b"PK"));
+
+    let reparsed = XlsxWorkbook::parse_bytes(&bytes).unwrap().into_statements();
+    assert_eq!(format!("{:?}", reparsed), format!("{:?}", statements));
+    assert_eq!(
+        format!("{:?}", parse_statements_bytes(&bytes, Format::Xlsx, None).unwrap()),
+        format!("{:?}", statements)
+    );
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_xlsx_preserves_cyrillic_and_amounts() {
+    use ypbank_parser::{XlsxWorkbook, XlsxWriter};
+
+    let statements = OneCStatement::parse(SAMPLE_1C).unwrap().into_statements().unwrap();
+    let mut output = Vec::new();
+    XlsxWriter::write_to(&statements, &mut output).unwrap();
+
+    let reparsed = XlsxWorkbook::parse_bytes(&output).unwrap().into_statements();
+    let (original, restored) = (&statements[0], &reparsed[0]);
+    let owner = original.account.owner.clone().or(original.account.name.clone());
+    assert_eq!(restored.account.owner, owner);
+    assert_eq!(restored.closing_balance.amount, original.closing_balance.amount);
+    for (a, b) in original.transactions.iter().zip(&restored.transactions) {
+        assert_eq!(a.description, b.description);
+        assert_eq!(a.amount, b.amount);
+        assert_eq!(a.is_credit, b.is_credit);
+    }
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_xlsx_non_ascii_account_label() {
+    use ypbank_parser::{XlsxWorkbook, XlsxWriter};
+
+    let mut statements = parse_statements(SAMPLE_BAI2, Format::Bai2).unwrap();
+    statements.truncate(1);
+    statements[0].account.number = "1Жx".to_string();
+    statements[0].account.iban = None;
+
+    let bytes = XlsxWriter::to_bytes(&statements).unwrap();
+    let reparsed = XlsxWorkbook::parse_bytes(&bytes).unwrap().into_statements();
+    assert_eq!(reparsed[0].account.number, "1Жx");
+    assert_eq!(reparsed[0].account.iban, None);
+}
+
+#[test]
+fn test_xlsx_requires_bytes() {
+    assert!(matches!(
+        parse_statements("PK", Format::Xlsx),
+        Err(Error::InvalidFormat(_))
+    ));
+}
+
+// ============================================================================
+// Потоковое чтение
+// ============================================================================
+
+#[test]
+fn test_mt940_reader_streams_statements() {
+    use std::io::BufReader;
+    use ypbank_parser::Mt940Reader;
+
+    let content = SAMPLE_MT940.repeat(3);
+    let reader = BufReader::with_capacity(16, content.as_bytes());
+    let streamed: Vec<Mt940Statement> = Mt940Reader::new(reader)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(
+        format!("{:?}", streamed),
+        format!("{:?}", Mt940Statement::parse(&content).unwrap())
+    );
+}
+
+#[test]
+fn test_statement_reader_to_mt940_writer() {
+    use ypbank_parser::{Mt940Writer, StatementReader};
+
+    let content = SAMPLE_MT940.repeat(2);
+    let statements = StatementReader::new(content.as_bytes(), Format::Mt940, None).unwrap();
+
+    let mut output = Vec::new();
+    let written =
+        Mt940Writer::write_iter_with_encoding(statements, &mut output, TextEncoding::Utf8).unwrap();
+    assert_eq!(written, 2);
+
+    let reparsed = Mt940Statement::parse(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(reparsed.len(), 2);
+    assert_eq!(reparsed[1].transactions.len(), 1);
+}
+
+#[test]
+fn test_statement_reader_into_transactions() {
+    use ypbank_parser::StatementReader;
+
+    let content = SAMPLE_MT940.repeat(2);
+    let transactions: Vec<_> = StatementReader::new(content.as_bytes(), Format::Mt940, None)
+        .unwrap()
+        .into_transactions()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert!(!transactions[0].is_credit);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_statement_reader_ndjson_transactions() {
+    use ypbank_parser::{JsonWriter, StatementReader};
+
+    let statements = parse_statements(SAMPLE_BAI2, Format::Bai2).unwrap();
+    let expected: usize = statements.iter().map(|s| s.transactions.len()).sum();
+    let mut output = Vec::new();
+    JsonWriter::write_ndjson_to(&statements, &mut output).unwrap();
+
+    let transactions: Vec<_> = StatementReader::new(output.as_slice(), Format::Ndjson, None)
+        .unwrap()
+        .into_transactions()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(transactions.len(), expected);
+    assert_eq!(transactions[0], statements[0].transactions[0]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_ndjson_stream_roundtrip() {
+    use ypbank_parser::{JsonWriter, NdjsonReader};
+
+    let statements = parse_statements(SAMPLE_BAI2, Format::Bai2).unwrap();
+    let mut output = Vec::new();
+    let written = JsonWriter::write_ndjson_iter_with_encoding(
+        statements.iter().cloned().map(Ok),
+        &mut output,
+        TextEncoding::Windows1251,
+    )
+    .unwrap();
+    assert_eq!(written, statements.len());
+
+    let restored: Vec<Statement> = NdjsonReader::with_encoding(output.as_slice(), None)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(format!("{:?}", restored), format!("{:?}", statements));
+}
+
+// ============================================================================
+// Параллельный разбор
+// ============================================================================
+
+/// Документ CAMT.053 из `count` копий выписки SAMPLE_CAMT053 с Id STMT001...
+fn multi_stmt_camt053(count: usize) -> String {
+    let start = SAMPLE_CAMT053.find("<Stmt>").unwrap();
+    let end = SAMPLE_CAMT053.find("</BkToCstmrStmt>").unwrap();
+    let stmt = &SAMPLE_CAMT053[start..end];
+
+    let statements: String = (1..=count)
+        .map(|i| stmt.replace("STMT001", &format!("STMT{:03}", i)))
+        .collect();
+    format!("{}{}{}", &SAMPLE_CAMT053[..start], statements, &SAMPLE_CAMT053[end..])
+}
+
+#[test]
+fn test_mt940_parse_preserves_block_order() {
+    let block = SAMPLE_MT940.replace(":20:0000000000", ":20:REF{}");
+    let content: String = (0..64)
+        .map(|i| {
+            let block = block.replace("REF{}", &format!("REF{:03}", i));
+            // Блок без :25: пропускается, остальные сохраняют порядок
+            if i == 10 {
+                block.replace(":25:NL81ASNB9999999999\n", "")
+            } else {
+                block
+            }
+        })
+        .collect();
+
+    let statements = Mt940Statement::parse(&content).unwrap();
+    assert_eq!(statements.len(), 63);
+    let expected: Vec<String> = (0..64)
+        .filter(|&i| i != 10)
+        .map(|i| format!("REF{:03}", i))
+        .collect();
+    let references: Vec<String> = statements.into_iter().map(|s| s.reference).collect();
+    assert_eq!(references, expected);
+}
+
+#[test]
+fn test_camt053_parse_all_statements() {
+    let content = multi_stmt_camt053(12);
+
+    let statements = Camt053Statement::parse_all(&content).unwrap();
+    let ids: Vec<&str> = statements.iter().map(|s| s.statement_id.as_str()).collect();
+    let expected: Vec<String> = (1..=12).map(|i| format!("STMT{:03}", i)).collect();
+    assert_eq!(ids, expected);
+    assert!(statements.iter().all(|s| s.message_id == "SAMPLE001"));
+
+    assert_eq!(Camt053Statement::parse(&content).unwrap().statement_id, "STMT001");
+    assert_eq!(parse_statements(&content, Format::Camt053).unwrap().len(), 12);
+}
+
+#[test]
+fn test_camt053_parse_all_reports_failed_stmt() {
+    // У пятой выписки нет ни одного элемента Id
+    let content = multi_stmt_camt053(8);
+    let start = content.find("<Id>STMT005").unwrap();
+    let end = start + content[start..].find("</Stmt>").unwrap();
+    let broken = content[start..end].replace("Id>", "Ident>");
+    let content = format!("{}{}{}", &content[..start], broken, &content[end..]);
+
+    let err = Camt053Statement::parse_all(&content).unwrap_err();
+    assert!(err.to_string().contains("Stmt №5"), "{}", err);
+}
+
+// ============================================================================
+// Лимиты
+// ============================================================================
+
+#[test]
+fn test_limits_input_size() {
+    let limits = Limits {
+        max_input_bytes: 100,
+        ..Limits::default()
+    };
+    assert!(matches!(
+        parse_statements_with_limits(SAMPLE_MT940, Format::Mt940, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+    assert!(matches!(
+        parse_statements_bytes_with_limits(SAMPLE_1C.as_bytes(), Format::OneC, None, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+    let unlimited = Limits::unlimited();
+    assert!(parse_statements_with_limits(SAMPLE_MT940, Format::Mt940, &unlimited).is_ok());
+}
+
+#[test]
+fn test_limits_mt940_not_skipped_as_bad_block() {
+    let limits = Limits {
+        max_continuation_lines: 3,
+        ..Limits::default()
+    };
+    let block = SAMPLE_MT940.replace("Betaling sieraden", "строка 1\nстрока 2\nстрока 3\nстрока 4");
+    let content = format!("{}{}", block, SAMPLE_MT940);
+    let err = parse_statements_with_limits(&content, Format::Mt940, &limits).unwrap_err();
+    assert!(matches!(err, Error::LimitExceeded(_)));
+    assert!(err.to_string().contains(":86:"));
+
+    let mut reader = Mt940Reader::new(content.as_bytes()).with_limits(limits);
+    assert!(matches!(reader.next(), Some(Err(Error::LimitExceeded(_)))));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_limits_transactions_and_statements() {
+    let limits = Limits {
+        max_transactions: 1,
+        ..Limits::default()
+    };
+    let start = SAMPLE_CAMT053.find("<Ntry>").unwrap();
+    let end = SAMPLE_CAMT053.find("</Ntry>").unwrap() + "</Ntry>".len();
+    let entry = &SAMPLE_CAMT053[start..end];
+    let content = SAMPLE_CAMT053.replacen(entry, &entry.repeat(2), 1);
+    assert!(matches!(
+        parse_statements_with_limits(&content, Format::Camt053, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+    assert!(parse_statements_with_limits(SAMPLE_CAMT053, Format::Camt053, &limits).is_ok());
+    assert!(matches!(
+        parse_statements_with_limits(SAMPLE_1C, Format::OneC, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+
+    let limits = Limits {
+        max_statements: 3,
+        ..Limits::default()
+    };
+    assert!(matches!(
+        parse_statements_with_limits(&multi_stmt_camt053(4), Format::Camt053, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+}
+
+#[test]
+fn test_limits_enforced_by_each_parser() {
+    let limits = Limits {
+        max_transactions: 0,
+        ..Limits::default()
+    };
+    let samples = [
+        (include_str!("../../examples/sample.csv"), Format::Csv),
+        (SAMPLE_OFX, Format::Ofx),
+        (SAMPLE_BAI2, Format::Bai2),
+        (SAMPLE_QIF, Format::Qif),
+        (SAMPLE_CODA, Format::Coda),
+        (SAMPLE_NORMA43, Format::Norma43),
+        (SAMPLE_CFONB, Format::Cfonb120),
+    ];
+    for (content, format) in samples {
+        let parsed = parse_statements(content, format).unwrap();
+        assert!(parsed.iter().any(|s| !s.transactions.is_empty()), "{:?}", format);
+        assert!(
+            matches!(
+                parse_statements_with_limits(content, format, &limits),
+                Err(Error::LimitExceeded(_))
+            ),
+            "{:?}",
+            format
+        );
+    }
+    assert!(matches!(
+        OneCStatement::parse_with_limits(SAMPLE_1C, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+    assert!(matches!(
+        QifFile::parse_with_limits(SAMPLE_QIF, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+
+    let limits = Limits {
+        max_field_len: 16,
+        ..Limits::default()
+    };
+    let long_memo = SAMPLE_QIF.replacen("\nP", &format!("\nP{}", "x".repeat(32)), 1);
+    assert!(matches!(
+        QifFile::parse_with_limits(&long_memo, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+    assert!(matches!(
+        Bai2File::parse_with_limits(SAMPLE_BAI2, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+}
+
+#[test]
+fn test_limits_stream_statement_count() {
+    let limits = Limits {
+        max_statements: 1,
+        ..Limits::default()
+    };
+    let content = SAMPLE_MT940.repeat(3);
+    assert!(matches!(
+        parse_statements_with_limits(&content, Format::Mt940, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+    // Поток может быть сколь угодно длинным: число выписок не ограничивается
+    let streamed = Mt940Reader::new(content.as_bytes())
+        .with_limits(limits)
+        .collect::<ypbank_parser::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(streamed.len(), 3);
+}
+
+#[test]
+fn test_limits_xml_depth() {
+    let limits = Limits {
+        max_xml_depth: 16,
+        ..Limits::default()
+    };
+    let nested = format!("{}{}", "<Ustrd>".repeat(32), "</Ustrd>".repeat(32));
+    let content = SAMPLE_CAMT053.replacen("<Ntry>", &format!("<Ntry>{}", nested), 1);
+    assert!(matches!(
+        parse_statements_with_limits(&content, Format::Camt053, &limits),
+        Err(Error::LimitExceeded(_))
+    ));
+    assert!(parse_statements_with_limits(SAMPLE_CAMT053, Format::Camt053, &limits).is_ok());
+}
+
+#[test]
+fn test_malformed_input_is_error_not_panic() {
+    // Многобайтовые символы на месте даты и суммы в :60F: и :61:
+    let balance = SAMPLE_MT940.replace(":60F:C200101EUR444,29", ":60F:C2001ЖЖ");
+    assert!(Mt940Statement::parse(&balance).is_err());
+    let line = SAMPLE_MT940.replace(":61:2001010101D65,00", ":61:200101Ж");
+    assert!(Mt940Statement::parse(&line).unwrap()[0].transactions.is_empty());
+
+    // Незакрытые и перепутанные элементы CAMT.053
+    let unclosed = SAMPLE_CAMT053.replacen("</Bal>", "", 1);
+    let _ = Camt053Statement::parse(&unclosed);
+    let swapped = SAMPLE_CAMT053.replacen("<Acct>", "</Acct><Acct>", 1);
+    let _ = Camt053Statement::parse(&swapped);
+
+    let ofx_amount = parse_statements(
+        "<OFX><STMTTRN><TRNAMT>5.Жx</TRNAMT></STMTTRN></OFX>",
+        Format::Ofx,
+    );
+    assert!(ofx_amount.is_err() || ofx_amount.unwrap().iter().all(|s| s.transactions.is_empty()));
+}
+
+// ============================================================================
+// Даты
+// ============================================================================
+
+#[test]
+fn test_mt940_century_pivot() {
+    let content = SAMPLE_MT940.replace("C200101EUR", "C690101EUR");
+    let statements = Mt940Statement::parse(&content).unwrap();
+    assert_eq!(statements[0].opening_balance.date, Date::new(1969, 1, 1));
+
+    let statements = Mt940Statement::parse_with_century_pivot(&content, 79).unwrap();
+    assert_eq!(statements[0].opening_balance.date, Date::new(2069, 1, 1));
+
+    let mut reader = Mt940Reader::new(content.as_bytes()).with_century_pivot(79);
+    assert_eq!(reader.next().unwrap().unwrap().opening_balance.date.year(), 2069);
+}
+
+#[test]
+fn test_mt940_entry_date_across_new_year() {
+    // Валютирование 2 января 2020, проводка 31 декабря предыдущего года
+    let content = SAMPLE_MT940.replace(":61:2001010101D65,00", ":61:2001021231D65,00");
+    let tx = &Mt940Statement::parse(&content).unwrap()[0].transactions[0];
+    assert_eq!(tx.value_date, Some(Date::new(2020, 1, 2)));
+    assert_eq!(tx.date, Date::new(2019, 12, 31));
+}
+
+#[test]
+fn test_parsers_reject_impossible_dates() {
+    let content = SAMPLE_MT940.replace(":60F:C200101EUR", ":60F:C200230EUR");
+    assert!(Mt940Statement::parse(&content).is_err());
+
+    let content = SAMPLE_CAMT053.replacen("2024-01-15", "2024-02-30", 1);
+    let camt = Camt053Statement::parse(&content).unwrap();
+    assert!(camt.entries.is_empty());
+
+    assert!(QifFile::parse("!Type:Bank\nD02/30/2023\nT-1.00\n^\n").unwrap().accounts[0]
+        .transactions
+        .is_empty());
+    assert!("2023-02-29".parse::<Date>().is_err());
+}
+
+#[test]
+fn test_date_business_days() {
+    let statement = &parse_statements(SAMPLE_CAMT053, Format::Camt053).unwrap()[0];
+    let booking = statement.transactions[0].date;
+    assert_eq!(booking, Date::new(2024, 1, 15));
+    assert_eq!(booking.weekday(), ypbank_parser::Weekday::Monday);
+    assert_eq!(booking.add_business_days(-1, &[]), Date::new(2024, 1, 12));
+    assert!(statement.opening_balance.date < statement.closing_balance.date);
+    assert_eq!(statement.closing_balance.date.days_since(&statement.opening_balance.date), 30);
+}
+
+#[test]
+fn test_camt053_date_times() {
+    let content = SAMPLE_CAMT053
+        .replace(
+            "<CreDtTm>2024-01-01T00:00:00</CreDtTm>",
+            "<CreDtTm>2024-02-01T08:15:00.125+01:00</CreDtTm>",
+        )
+        .replace("<Dt>2024-01-31</Dt>", "<DtTm>2024-01-31T23:59:59+01:00</DtTm>")
+        .replace("<Dt>2024-01-15</Dt>", "<DtTm>2024-01-15T10:30:00Z</DtTm>");
+    let camt = Camt053Statement::parse(&content).unwrap();
+
+    let created = camt.creation_date_time;
+    assert_eq!(created.date(), Date::new(2024, 2, 1));
+    assert_eq!(created.offset().map(|o| o.minutes()), Some(60));
+    assert_eq!(created.unix_timestamp(), 1_706_771_700);
+    assert_eq!(camt.balances[1].date, Date::new(2024, 1, 31));
+    assert_eq!(camt.entries[0].booking_date, Date::new(2024, 1, 15));
+
+    let mut output = Vec::new();
+    camt.write_to(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("<CreDtTm>2024-02-01T08:15:00.125+01:00</CreDtTm>"));
+
+    let bad = SAMPLE_CAMT053.replace("2024-01-01T00:00:00", "2024-01-01 00:00");
+    assert!(Camt053Statement::parse(&bad).is_err());
+}
+
+#[test]
+fn test_camt053_entry_without_booking_date() {
+    let content = SAMPLE_CAMT053.replace(
+        "<BookgDt>\n<Dt>2024-01-15</Dt>\n</BookgDt>",
+        "<ValDt>\n<Dt>2024-01-16</Dt>\n</ValDt>",
+    );
+    let camt = Camt053Statement::parse(&content).unwrap();
+    assert_eq!(camt.entries[0].booking_date, Date::new(2024, 1, 16));
+
+    let content = SAMPLE_CAMT053.replace("<BookgDt>\n<Dt>2024-01-15</Dt>\n</BookgDt>", "");
+    let camt = Camt053Statement::parse(&content).unwrap();
+    assert_eq!(camt.entries[0].booking_date, camt.creation_date_time.date());
+}
+
+#[test]
+fn test_conversion_creation_time_from_clock() {
+    let clock = FixedClock("2024-03-05T12:00:00Z".parse().unwrap());
+    let statement = parse_statement(SAMPLE_MT940, Format::Mt940).unwrap();
+
+    let camt = Camt053Statement::from_statement_with_clock(statement.clone(), &clock);
+    assert_eq!(camt.creation_date_time.to_string(), "2024-03-05T12:00:00Z");
+
+    let mt940 = Mt940Statement::parse(SAMPLE_MT940).unwrap().remove(0);
+    let camt = Camt053Statement::from_mt940_with_clock(mt940, &clock);
+    assert_eq!(camt.creation_date_time, clock.0);
+
+    let camt: Camt053Statement = statement.into();
+    assert_eq!(camt.creation_date_time.offset(), Some(UtcOffset::UTC));
+    assert!(camt.creation_date_time.date().year() >= 2024);
+}
+
+// ============================================================================
+// Денежные суммы
+// ============================================================================
+
+#[test]
+fn test_statement_turnover_matches_balances() {
+    for (content, format) in [
+        (SAMPLE_CAMT053, Format::Camt053),
+        (SAMPLE_MT940, Format::Mt940),
+        (SAMPLE_1C, Format::OneC),
+    ] {
+        let statement = parse_statement(content, format).unwrap();
+        let mut total = MultiCurrencyTotal::new();
+        for tx in &statement.transactions {
+            total.add(&tx.signed_amount()).unwrap();
+        }
+
+        let opening = statement.opening_balance.signed_amount();
+        let closing = statement.closing_balance.signed_amount();
+        let turnover = total
+            .get(&opening.currency)
+            .unwrap_or_else(|| Amount::zero(opening.currency.as_str()));
+        assert_eq!((&closing - &opening).unwrap(), turnover, "{:?}", format);
+    }
+}
+
+#[test]
+fn test_amount_text_formats() {
+    let amount: Amount = "-1234567.89 RUB".parse().unwrap();
+    assert_eq!(amount, Amount::new(-123_456_789, "RUB"));
+    assert_eq!(amount.to_string(), "-1234567.89 RUB");
+    assert_eq!(amount.format_with(&AmountFormat::RU), "-1 234 567,89 RUB");
+    assert_eq!(
+        Amount::parse_with("1.234,50 EUR", &AmountFormat::EU).unwrap(),
+        Amount::new(123_450, "EUR")
+    );
+
+    let mixed = &Amount::new(100, "RUB") + &Amount::new(100, "EUR");
+    assert!(matches!(mixed, Err(Error::Arithmetic(_))));
+}
+
+// ============================================================================
+// Асинхронный ввод-вывод
+// ============================================================================
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_read_matches_blocking() {
+    let mut reader = SAMPLE_MT940.as_bytes();
+    let statements = Mt940Statement::from_async_read(&mut reader).await.unwrap();
+    let expected = Mt940Statement::from_read(&mut Cursor::new(SAMPLE_MT940)).unwrap();
+    assert_eq!(format!("{:?}", statements), format!("{:?}", expected));
+
+    let mut reader = SAMPLE_CAMT053.as_bytes();
+    let camt = Camt053Statement::from_async_read(&mut reader).await.unwrap();
+    assert_eq!(camt.statement_id, "STMT001");
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_write_matches_blocking() {
+    use ypbank_parser::{Camt053Writer, Mt940Writer};
+
+    let mt940 = Mt940Statement::parse(SAMPLE_MT940).unwrap().remove(0);
+    let mut expected = Vec::new();
+    Mt940Writer::write_to(&mt940, &mut expected).unwrap();
+    let mut output = Vec::new();
+    Mt940Writer::write_to_async(&mt940, &mut output).await.unwrap();
+    assert_eq!(output, expected);
+
+    let camt: Camt053Statement = mt940.into();
+    let mut output = Vec::new();
+    Camt053Writer::write_to_with_encoding_async(&camt, &mut output, TextEncoding::Windows1251)
+        .await
+        .unwrap();
+    assert!(output.starts_with(b"<?xml version=\"1.0\" encoding=\"windows-1251\"?>"));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_parse_statements_async_detects_encoding() {
+    let bytes = encoding::encode(SAMPLE_1C, TextEncoding::Windows1251).unwrap();
+    let mut reader = bytes.as_slice();
+    let statements = ypbank_parser::parse_statements_async(&mut reader, Format::OneC, None)
+        .await
+        .unwrap();
+    let expected = parse_statements(SAMPLE_1C, Format::OneC).unwrap();
+    assert_eq!(format!("{:?}", statements), format!("{:?}", expected));
+}
+
+// ============================================================================
+// Parquet
+// ============================================================================
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_parquet_transactions_batch() {
+    use ypbank_parser::arrow::{statements_batch, transactions_batch};
+    use ypbank_parser::ParquetWriter;
+
+    let statements = parse_statements(SAMPLE_BAI2, Format::Bai2).unwrap();
+    let total: usize = statements.iter().map(|s| s.transactions.len()).sum();
+
+    let batch = transactions_batch(&statements).unwrap();
+    assert_eq!(batch.num_rows(), total);
+    let schema = batch.schema();
+    let amount = schema.field_with_name("amount").unwrap();
+    assert_eq!(amount.data_type().to_string(), "Decimal128(18, 2)");
+    assert_eq!(statements_batch(&statements).unwrap().num_rows(), statements.len());
+
+    let bytes = ParquetWriter::to_bytes(&statements).unwrap();
+    assert!(bytes.starts_with(b"PAR1") && bytes.ends_with(b"PAR1"));
+}
+
+#[test]
+fn test_parquet_is_write_only() {
+    assert_eq!(Format::parse("parquet"), Some(Format::Parquet));
+    assert!(matches!(
+        parse_statements("PAR1", Format::Parquet),
+        Err(Error::InvalidFormat(_))
+    ));
+}
+
+// ============================================================================
+// Архив
+// ============================================================================
+
+#[cfg(feature = "archive")]
+#[test]
+fn test_archive_import_deduplicates_and_reconstructs() {
+    use ypbank_parser::Archive;
+
+    let statement = parse_statement(SAMPLE_CAMT053, Format::Camt053).unwrap();
+    let mut archive = Archive::open_in_memory().unwrap();
+
+    let first = archive.import(&statement, Some("camt.xml")).unwrap();
+    assert!(first.new_statement);
+    assert_eq!(first.inserted, statement.transactions.len());
+    assert!(first.overlaps.is_empty());
+
+    let again = archive.import(&statement, Some("camt.xml")).unwrap();
+    assert!(!again.new_statement);
+    assert_eq!(again.inserted, 0);
+
+    let restored = archive.statements(&statement.account.number).unwrap();
+    assert_eq!(restored.len(), 1);
+    assert_eq!(restored[0].opening_balance, statement.opening_balance);
+    assert_eq!(restored[0].closing_balance, statement.closing_balance);
+    for (a, b) in statement.transactions.iter().zip(&restored[0].transactions) {
+        assert_eq!(a.date, b.date);
+        assert_eq!(a.amount, b.amount);
+        assert_eq!(a.is_credit, b.is_credit);
+        assert_eq!(a.reference, b.reference);
+        assert_eq!(a.description, b.description);
+    }
+}
+
+#[cfg(feature = "archive")]
+#[test]
+fn test_archive_detects_overlapping_periods() {
+    use ypbank_parser::Archive;
+
+    let mut statement = parse_statement(SAMPLE_CAMT053, Format::Camt053).unwrap();
+    for (i, tx) in statement.transactions.iter_mut().enumerate() {
+        tx.reference = Some(format!("REF{}", i));
+    }
+    let mut unreferenced = statement.transactions[0].clone();
+    unreferenced.reference = None;
+    statement.transactions.push(unreferenced);
+    let mut archive = Archive::open_in_memory().unwrap();
+    let first = archive.import(&statement, Some("january.xml")).unwrap();
+
+    // Та же выписка с другим исходящим остатком: период пересекается,
+    // транзакции с референсом уже есть в архиве, а без референса
+    // не дедуплицируются.
+    let mut corrected = statement.clone();
+    corrected.closing_balance.amount.value += 1;
+    let report = archive.import(&corrected, Some("january-v2.xml")).unwrap();
+    let referenced = statement.transactions.iter().filter(|t| t.reference.is_some()).count();
+    assert!(report.new_statement);
+    assert_eq!(report.duplicates, referenced);
+    assert_eq!(report.inserted, 1);
+    assert_eq!(report.overlaps.len(), 1);
+    assert_eq!(report.overlaps[0].statement_id, first.statement_id);
+    assert_eq!(report.overlaps[0].source.as_deref(), Some("january.xml"));
+}
+
+#[cfg(feature = "archive")]
+#[test]
+fn test_archive_persists_to_file() {
+    use ypbank_parser::Archive;
+
+    let path = std::env::temp_dir().join(format!("ypbank-archive-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let statements = parse_statements(SAMPLE_BAI2, Format::Bai2).unwrap();
+    {
+        let mut archive = Archive::open(&path).unwrap();
+        for statement in &statements {
+            archive.import(statement, None).unwrap();
+        }
+    }
+
+    let archive = Archive::open(&path).unwrap();
+    for statement in &statements {
+        let restored = archive.statements(&statement.account.number).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].transactions.len(), statement.transactions.len());
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+// ============================================================================
+// Кодировки
+// ============================================================================
+
+#[test]
+fn test_csv_from_read_windows1251() {
+    let content = include_str!("../../examples/sample.csv");
+    let bytes = encoding::encode(content, TextEncoding::Windows1251).unwrap();
+
+    let from_bytes = CsvStatement::from_read(&mut Cursor::new(bytes)).unwrap();
+    let from_str = CsvStatement::parse(content).unwrap();
+    assert_eq!(from_bytes.transactions.len(), from_str.transactions.len());
+    assert_eq!(
+        from_bytes.transactions[0].description,
+        from_str.transactions[0].description
+    );
+}
+
+#[test]
+fn test_onec_cp866_roundtrip() {
+    let onec = OneCStatement::parse(SAMPLE_1C).unwrap();
+
+    let mut output = Vec::new();
+    onec.write_to_with_encoding(&mut output, TextEncoding::Cp866)
+        .unwrap();
+    assert_eq!(TextEncoding::detect(&output), TextEncoding::Cp866);
+
+    let reparsed = OneCStatement::parse_bytes(&output, None).unwrap();
+    assert_eq!(reparsed.encoding, "DOS");
+    assert_eq!(reparsed.documents[0].purpose, onec.documents[0].purpose);
+}
+
+#[test]
+fn test_camt053_output_encoding_declaration() {
+    let statements = parse_statements_bytes(SAMPLE_1C.as_bytes(), Format::OneC, None).unwrap();
+    let camt: Camt053Statement = statements.into_iter().next().unwrap().into();
+
+    let mut output = Vec::new();
+    camt.write_to_with_encoding(&mut output, TextEncoding::Windows1251)
+        .unwrap();
+    assert!(output.starts_with(b"<?xml version=\"1.0\" encoding=\"windows-1251\"?>"));
+
+    let reparsed = Camt053Statement::parse_bytes(&output, None).unwrap();
+    assert_eq!(reparsed.entries.len(), 2);
+    assert_eq!(
+        reparsed.entries[1].transaction_details[0].remittance_info,
+        vec!["Возврат излишне уплаченных средств".to_string()]
+    );
+}
+
+#[test]
+fn test_output_encoding_rejects_unmappable() {
+    let mt940 = Mt940Statement::parse(SAMPLE_MT940).unwrap().remove(0);
+    let mut statement: Statement = mt940.into();
+    statement.transactions[0].description = "Комиссия €5".to_string();
+    let mt940: Mt940Statement = statement.into();
+
+    let mut output = Vec::new();
+    assert!(mt940
+        .write_to_with_encoding(&mut output, TextEncoding::Cp866)
+        .is_err());
+}